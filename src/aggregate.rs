@@ -0,0 +1,549 @@
+// Copyright 2019 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A quadtree which keeps a summary of every subtree, behind the `aggregate` feature.
+//!
+//! An [`AggregatedQuadtree`] caches an [`Aggregate`] of everything held in and beneath each of
+//! its nodes, such as a count, or a total mass and centre of mass. The summaries along the path
+//! to an entry are kept up to date as it comes and goes, and [`.traverse()`] lets a walk over the
+//! tree take a whole subtree's summary in place of visiting everything in it, as a Barnes–Hut
+//! simulation does with distant groups of bodies.
+//!
+//! ```
+//! use quadtree_rs::{
+//!     aggregate::{AggregatedQuadtree, Count, Visit, Visited},
+//!     area::AreaBuilder,
+//!     point::Point,
+//! };
+//!
+//! let mut qt = AggregatedQuadtree::<u32, char, Count>::new(4);
+//! for (x, y) in [(1, 1), (2, 2), (3, 1), (12, 12)] {
+//!     qt.insert_pt(Point { x, y }, 'x');
+//! }
+//! assert_eq!(qt.aggregate(), &Count(4));
+//!
+//! // Count everything in the western half without visiting it entry by entry.
+//! let west = AreaBuilder::default().anchor((0, 0).into()).dimensions((8, 16)).build().unwrap();
+//! let mut count = 0;
+//! for visited in qt.traverse(|bounds, _| {
+//!     if west.contains(bounds) {
+//!         Visit::Accept
+//!     } else if west.intersects(bounds) {
+//!         Visit::Descend
+//!     } else {
+//!         Visit::Reject
+//!     }
+//! }) {
+//!     match visited {
+//!         Visited::Subtree { aggregate, .. } => count += aggregate.0,
+//!         Visited::Entry(entry) if west.intersects(entry.area()) => count += 1,
+//!         Visited::Entry(_) => {}
+//!     }
+//! }
+//! assert_eq!(count, 3);
+//! ```
+//!
+//! [`AggregatedQuadtree`]: struct.AggregatedQuadtree.html
+//! [`Aggregate`]: trait.Aggregate.html
+//! [`.traverse()`]: struct.AggregatedQuadtree.html#method.traverse
+
+use {
+    crate::{area::Area, entry::Entry, iter::IntoIter, point::Point, qtinner::NodeId, Quadtree},
+    num::PrimInt,
+    std::{collections::hash_map::RandomState, default::Default, hash::BuildHasher},
+};
+
+/// A summary of a set of entries, which can be built up one entry at a time.
+///
+/// The summaries form a monoid: [`empty()`] is the summary of nothing, and combining it with any
+/// other summary leaves that summary unchanged. Summaries are combined in an unspecified order, so
+/// [`combine()`] should be associative and commutative.
+///
+/// ```
+/// use quadtree_rs::{
+///     aggregate::{Aggregate, AggregatedQuadtree},
+///     area::Area,
+///     point::Point,
+/// };
+///
+/// // The total mass of some bodies, and the sum of their positions weighted by mass.
+/// #[derive(Clone, Debug, PartialEq)]
+/// struct Mass {
+///     mass: f64,
+///     moment: (f64, f64),
+/// }
+///
+/// impl Aggregate<u32, f64> for Mass {
+///     fn empty() -> Self {
+///         Mass { mass: 0.0, moment: (0.0, 0.0) }
+///     }
+///
+///     fn of(region: Area<u32>, mass: &f64) -> Self {
+///         let (x, y) = (f64::from(region.anchor().x()), f64::from(region.anchor().y()));
+///         Mass { mass: *mass, moment: (x * mass, y * mass) }
+///     }
+///
+///     fn combine(&self, other: &Self) -> Self {
+///         Mass {
+///             mass: self.mass + other.mass,
+///             moment: (self.moment.0 + other.moment.0, self.moment.1 + other.moment.1),
+///         }
+///     }
+/// }
+///
+/// let mut qt = AggregatedQuadtree::<u32, f64, Mass>::new(4);
+/// qt.insert_pt(Point { x: 2, y: 2 }, 1.0);
+/// qt.insert_pt(Point { x: 8, y: 2 }, 3.0);
+///
+/// // The centre of mass.
+/// let Mass { mass, moment } = qt.aggregate();
+/// assert_eq!((moment.0 / mass, moment.1 / mass), (6.5, 2.0));
+/// ```
+///
+/// [`empty()`]: #tymethod.empty
+/// [`combine()`]: #tymethod.combine
+pub trait Aggregate<U, V>: Clone
+where
+    U: PrimInt + Default,
+{
+    /// The summary of no entries at all.
+    fn empty() -> Self;
+
+    /// The summary of a single entry.
+    fn of(region: Area<U>, value: &V) -> Self;
+
+    /// The summary of everything summarized by either `self` or `other`.
+    fn combine(&self, other: &Self) -> Self;
+}
+
+/// The simplest [`Aggregate`]: the number of entries.
+///
+/// [`Aggregate`]: trait.Aggregate.html
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Count(pub usize);
+
+impl<U, V> Aggregate<U, V> for Count
+where
+    U: PrimInt + Default,
+{
+    fn empty() -> Self {
+        Count(0)
+    }
+
+    fn of(_: Area<U>, _: &V) -> Self {
+        Count(1)
+    }
+
+    fn combine(&self, other: &Self) -> Self {
+        Count(self.0 + other.0)
+    }
+}
+
+/// What [`.traverse()`] should do with a subtree, given its bounds and its summary.
+///
+/// [`.traverse()`]: struct.AggregatedQuadtree.html#method.traverse
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Visit {
+    /// Take the subtree's summary as a whole, as a [`Visited::Subtree`].
+    ///
+    /// [`Visited::Subtree`]: enum.Visited.html#variant.Subtree
+    Accept,
+    /// Visit the entries held at the top of the subtree, then decide on each of its subquadrants
+    /// in turn.
+    Descend,
+    /// Skip the subtree altogether.
+    Reject,
+}
+
+/// Something visited by [`.traverse()`].
+///
+/// [`.traverse()`]: struct.AggregatedQuadtree.html#method.traverse
+#[derive(Debug, PartialEq)]
+pub enum Visited<'a, U, V, A>
+where
+    U: PrimInt + Default,
+{
+    /// A subtree which was accepted whole.
+    Subtree {
+        /// The region which everything in the subtree lies within.
+        bounds: Area<U>,
+        /// The summary of everything in the subtree.
+        aggregate: &'a A,
+    },
+    /// An entry held by a subtree which was descended into.
+    Entry(&'a Entry<U, V>),
+}
+
+/// A quadtree with a cached [`Aggregate`] of every subtree.
+///
+/// Every change goes through the `AggregatedQuadtree`, so that the summaries can follow it. For
+/// everything which only reads the quadtree, use [`.as_quadtree()`]. See the [module-level
+/// documentation] for more.
+///
+/// [`Aggregate`]: trait.Aggregate.html
+/// [`.as_quadtree()`]: #method.as_quadtree
+/// [module-level documentation]: index.html
+#[derive(Clone, Debug)]
+pub struct AggregatedQuadtree<U, V, A, S = RandomState>
+where
+    U: PrimInt + Default,
+{
+    tree: Quadtree<U, V, S>,
+    // The summary of everything held by and beneath each node, indexed by the node's id.
+    summaries: Vec<A>,
+}
+
+impl<U, V, A> AggregatedQuadtree<U, V, A>
+where
+    U: PrimInt + Default,
+    A: Aggregate<U, V>,
+{
+    // pub
+
+    /// Creates a new, empty quadtree with some depth.
+    /// See [`Quadtree::new()`].
+    ///
+    /// [`Quadtree::new()`]: ../struct.Quadtree.html#method.new
+    pub fn new(depth: usize) -> Self {
+        Self::from(Quadtree::new(depth))
+    }
+
+    /// Creates a new, empty quadtree with some depth and an explicit anchor.
+    /// See [`Quadtree::new_with_anchor()`].
+    ///
+    /// [`Quadtree::new_with_anchor()`]: ../struct.Quadtree.html#method.new_with_anchor
+    pub fn new_with_anchor(anchor: Point<U>, depth: usize) -> Self {
+        Self::from(Quadtree::new_with_anchor(anchor, depth))
+    }
+}
+
+impl<U, V, A, S> AggregatedQuadtree<U, V, A, S>
+where
+    U: PrimInt + Default,
+    A: Aggregate<U, V>,
+    S: BuildHasher,
+{
+    // pub
+
+    /// The summary of every entry in the quadtree.
+    pub fn aggregate(&self) -> &A {
+        &self.summaries[0]
+    }
+
+    /// The summary of every entry whose region overlaps `area`.
+    ///
+    /// Subtrees which lie wholly within `area` contribute their cached summaries, so only the
+    /// subtrees which cross its boundary are looked into entry by entry.
+    /// ```
+    /// use quadtree_rs::{
+    ///     aggregate::{AggregatedQuadtree, Count},
+    ///     area::AreaBuilder,
+    ///     point::Point,
+    /// };
+    ///
+    /// let mut qt = AggregatedQuadtree::<u32, (), Count>::new(6);
+    /// for i in 0..100 {
+    ///     qt.insert_pt(Point { x: i * 7 % 64, y: i * 3 % 64 }, ());
+    /// }
+    ///
+    /// let region = AreaBuilder::default()
+    ///     .anchor((0, 0).into())
+    ///     .dimensions((32, 40))
+    ///     .build().unwrap();
+    /// let count = qt.aggregate_in_region(region);
+    /// assert_eq!(count, Count(qt.as_quadtree().view(region).len()));
+    /// ```
+    pub fn aggregate_in_region(&self, area: Area<U>) -> A {
+        self.aggregate_below(0, area)
+    }
+
+    /// Walks the quadtree from the root down, letting `decide` choose for each non-empty subtree
+    /// whether to [accept] its summary whole, [descend] into it, or [reject] it, given the bounds
+    /// which everything in it lies within and its summary.
+    ///
+    /// Accepted subtrees are returned as [`Visited::Subtree`]s, and the entries held by those
+    /// descended into as [`Visited::Entry`]s, depth-first. Descending into a leaf returns all of
+    /// its entries.
+    ///
+    /// [accept]: enum.Visit.html#variant.Accept
+    /// [descend]: enum.Visit.html#variant.Descend
+    /// [reject]: enum.Visit.html#variant.Reject
+    /// [`Visited::Subtree`]: enum.Visited.html#variant.Subtree
+    /// [`Visited::Entry`]: enum.Visited.html#variant.Entry
+    pub fn traverse<F>(&self, mut decide: F) -> Vec<Visited<'_, U, V, A>>
+    where
+        F: FnMut(Area<U>, &A) -> Visit,
+    {
+        let inner = &self.tree.inner;
+        let mut visited = vec![];
+        let mut stack: Vec<NodeId> = vec![0];
+        while let Some(id) = stack.pop() {
+            let node = inner.node(id);
+            let aggregate = &self.summaries[id];
+            if node.len() == 0 {
+                continue;
+            }
+            match decide(node.bounds(), aggregate) {
+                Visit::Accept => visited.push(Visited::Subtree {
+                    bounds: node.bounds(),
+                    aggregate,
+                }),
+                Visit::Descend => {
+                    visited.extend(
+                        node.handles()
+                            .iter()
+                            .filter_map(|&handle| self.tree.get(handle))
+                            .map(Visited::Entry),
+                    );
+                    if let Some(sqs) = node.subquadrants() {
+                        stack.extend(sqs.iter().rev());
+                    }
+                }
+                Visit::Reject => {}
+            }
+        }
+        visited
+    }
+
+    /// Associate some value with a region in the quadtree, and summarize it.
+    /// See [`Quadtree::insert()`].
+    ///
+    /// [`Quadtree::insert()`]: ../struct.Quadtree.html#method.insert
+    pub fn insert(&mut self, region: Area<U>, val: V) -> Option<u64> {
+        let (allocated, root) = (self.tree.inner.node_count(), self.tree.inner.region());
+        let handle = self.tree.insert(region, val)?;
+        if self.tree.inner.region() != root {
+            // The tree grew to fit the region, and was rebuilt.
+            self.summaries = Self::summarize_all(&self.tree);
+        } else {
+            let region = self.region_of(handle);
+            self.refresh(allocated, &[region]);
+        }
+        Some(handle)
+    }
+
+    /// Alias for [`.insert()`] which expects a [`Point`] instead of an [`Area`].
+    ///
+    /// [`.insert()`]: #method.insert
+    /// [`Area`]: ../area/struct.Area.html
+    /// [`Point`]: ../point/struct.Point.html
+    pub fn insert_pt(&mut self, point: Point<U>, val: V) -> Option<u64> {
+        self.insert(Quadtree::<U, V, S>::area_at_pt(point), val)
+    }
+
+    /// Moves the entry with the given handle to a new region.
+    /// See [`Quadtree::relocate()`].
+    ///
+    /// [`Quadtree::relocate()`]: ../struct.Quadtree.html#method.relocate
+    pub fn relocate(&mut self, handle: u64, region: Area<U>) -> bool {
+        let from = match self.tree.get(handle) {
+            Some(entry) => entry.area(),
+            None => return false,
+        };
+        let (allocated, root) = (self.tree.inner.node_count(), self.tree.inner.region());
+        if !self.tree.relocate(handle, region) {
+            return false;
+        }
+        if self.tree.inner.region() != root {
+            self.summaries = Self::summarize_all(&self.tree);
+        } else {
+            let to = self.region_of(handle);
+            self.refresh(allocated, &[from, to]);
+        }
+        true
+    }
+
+    /// Applies `f` to every value which overlaps `area`, re-summarizes them, and returns how many
+    /// there were.
+    /// See [`Quadtree::modify()`].
+    ///
+    /// [`Quadtree::modify()`]: ../struct.Quadtree.html#method.modify
+    pub fn modify<F>(&mut self, area: Area<U>, f: F) -> usize
+    where
+        F: FnMut(&Area<U>, &mut V),
+    {
+        let regions: Vec<Area<U>> = self.tree.query(area).map(Entry::area).collect();
+        let modified = self.tree.modify(area, f);
+        self.refresh(self.tree.inner.node_count(), &regions);
+        modified
+    }
+
+    /// Applies `f` to every value in the quadtree, re-summarizes them, and returns how many there
+    /// were.
+    /// See [`Quadtree::modify_all()`].
+    ///
+    /// [`Quadtree::modify_all()`]: ../struct.Quadtree.html#method.modify_all
+    pub fn modify_all<F>(&mut self, f: F) -> usize
+    where
+        F: FnMut(&Area<U>, &mut V),
+    {
+        let modified = self.tree.modify_all(f);
+        self.summaries = Self::summarize_all(&self.tree);
+        modified
+    }
+
+    /// Deletes all value associations which overlap a region in the tree, and drops them from the
+    /// summaries. See [`Quadtree::delete()`].
+    ///
+    /// [`Quadtree::delete()`]: ../struct.Quadtree.html#method.delete
+    pub fn delete(&mut self, area: Area<U>) -> IntoIter<U, V> {
+        let entries: Vec<Entry<U, V>> = self.tree.delete(area).collect();
+        let regions: Vec<Area<U>> = entries.iter().map(Entry::area).collect();
+        self.refresh(self.tree.inner.node_count(), &regions);
+        IntoIter {
+            entries: entries.into_iter(),
+        }
+    }
+
+    /// Deletes a single item from the quadtree, and drops it from the summaries.
+    /// See [`Quadtree::delete_by_handle()`].
+    ///
+    /// [`Quadtree::delete_by_handle()`]: ../struct.Quadtree.html#method.delete_by_handle
+    pub fn delete_by_handle(&mut self, handle: u64) -> Option<Entry<U, V>> {
+        let entry = self.tree.delete_by_handle(handle)?;
+        self.refresh(self.tree.inner.node_count(), &[entry.area()]);
+        Some(entry)
+    }
+
+    /// Removes a single item from the quadtree, and drops it from the summaries.
+    /// See [`Quadtree::remove()`].
+    ///
+    /// [`Quadtree::remove()`]: ../struct.Quadtree.html#method.remove
+    pub fn remove(&mut self, handle: u64) -> Option<(Area<U>, V)> {
+        self.delete_by_handle(handle).map(Entry::into_parts)
+    }
+
+    /// Retains only the values which satisfy `f`, and re-summarizes the rest.
+    /// See [`Quadtree::retain()`].
+    ///
+    /// Since `f` may change the values it retains, every summary is rebuilt afterwards.
+    ///
+    /// [`Quadtree::retain()`]: ../struct.Quadtree.html#method.retain
+    pub fn retain<F>(&mut self, f: F) -> IntoIter<U, V>
+    where
+        F: FnMut(&mut V) -> bool,
+    {
+        let removed = self.tree.retain(f);
+        self.summaries = Self::summarize_all(&self.tree);
+        removed
+    }
+
+    /// Resets the quadtree, and its summaries, to a totally empty state.
+    pub fn reset(&mut self) {
+        self.tree.reset();
+        self.summaries = vec![A::empty()];
+    }
+
+    /// The quadtree, for everything which doesn't change it.
+    pub fn as_quadtree(&self) -> &Quadtree<U, V, S> {
+        &self.tree
+    }
+
+    /// Consumes the aggregated quadtree, returning the quadtree and discarding the summaries.
+    pub fn into_quadtree(self) -> Quadtree<U, V, S> {
+        self.tree
+    }
+
+    // fn
+
+    fn region_of(&self, handle: u64) -> Area<U> {
+        self.tree
+            .get(handle)
+            .map(Entry::area)
+            .expect("A handle which was just stored should have a region.")
+    }
+
+    // The summary of everything at or beneath the node @id which intersects @req. Depth-first, as
+    // with counting, so the only bookkeeping is the call stack.
+    fn aggregate_below(&self, id: NodeId, req: Area<U>) -> A {
+        let node = self.tree.inner.node(id);
+        if node.len() == 0 || !node.bounds().intersects(req) {
+            return A::empty();
+        }
+        if req.contains(node.bounds()) {
+            return self.summaries[id].clone();
+        }
+
+        let here = node
+            .handles()
+            .iter()
+            .filter_map(|&handle| self.tree.get(handle))
+            .filter(|entry| entry.area().intersects(req))
+            .fold(A::empty(), |acc, entry| {
+                acc.combine(&A::of(entry.area(), entry.value_ref()))
+            });
+        node.subquadrants().map_or(here.clone(), |sqs| {
+            sqs.iter()
+                .fold(here, |acc, &sq| acc.combine(&self.aggregate_below(sq, req)))
+        })
+    }
+
+    // Summarizes every node of @tree.
+    fn summarize_all(tree: &Quadtree<U, V, S>) -> Vec<A> {
+        let mut summaries = vec![A::empty(); tree.inner.node_count()];
+        // Subquadrants are always allocated after their parent, so walking the arena backwards
+        // summarizes every node after everything beneath it.
+        for id in (0..summaries.len()).rev() {
+            summaries[id] = Self::summarize(tree, &summaries, id);
+        }
+        summaries
+    }
+
+    // The summary of the node @id, from the entries it holds and the summaries of its
+    // subquadrants.
+    fn summarize(tree: &Quadtree<U, V, S>, summaries: &[A], id: NodeId) -> A {
+        let node = tree.inner.node(id);
+        let here = node
+            .handles()
+            .iter()
+            .filter_map(|&handle| tree.get(handle))
+            .fold(A::empty(), |acc, entry| {
+                acc.combine(&A::of(entry.area(), entry.value_ref()))
+            });
+        node.subquadrants().map_or(here.clone(), |sqs| {
+            sqs.iter()
+                .fold(here, |acc, &sq| acc.combine(&summaries[sq]))
+        })
+    }
+
+    // Brings the summaries up to date after a change which only touched the nodes on the paths to
+    // @regions, and any nodes allocated since the arena held @allocated of them.
+    fn refresh(&mut self, allocated: usize, regions: &[Area<U>]) {
+        let inner = &self.tree.inner;
+        let mut stale: Vec<NodeId> = (allocated..inner.node_count()).collect();
+        for &region in regions {
+            stale.extend(inner.path_to(region));
+        }
+        stale.sort_unstable();
+        stale.dedup();
+
+        self.summaries.resize(inner.node_count(), A::empty());
+        for &id in stale.iter().rev() {
+            self.summaries[id] = Self::summarize(&self.tree, &self.summaries, id);
+        }
+    }
+}
+
+impl<U, V, A, S> From<Quadtree<U, V, S>> for AggregatedQuadtree<U, V, A, S>
+where
+    U: PrimInt + Default,
+    A: Aggregate<U, V>,
+    S: BuildHasher,
+{
+    /// Wraps a quadtree, summarizing everything already in it.
+    fn from(tree: Quadtree<U, V, S>) -> Self {
+        let summaries = Self::summarize_all(&tree);
+        Self { tree, summaries }
+    }
+}
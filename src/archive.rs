@@ -0,0 +1,391 @@
+// Copyright 2019 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Zero-copy archives of quadtrees, behind the `rkyv` feature.
+//!
+//! A [`Quadtree`] can be converted into a [`FrozenQuadtree`], a flattened copy of its nodes and
+//! entries which [`rkyv`] can archive. The resulting bytes can be written to disk, memory-mapped
+//! back in, and queried in place through [`ArchivedFrozenQuadtree`] without a deserialization
+//! pass.
+//!
+//! ```
+//! use quadtree_rs::{archive::FrozenQuadtree, area::AreaBuilder, Quadtree};
+//!
+//! let mut qt = Quadtree::<u32, u64>::new(8);
+//! let region = AreaBuilder::default()
+//!     .anchor((10, 20).into())
+//!     .dimensions((5, 5))
+//!     .build().unwrap();
+//! qt.insert(region, 42);
+//!
+//! let bytes = rkyv::to_bytes::<_, 256>(&FrozenQuadtree::from(qt)).unwrap();
+//!
+//! // Later, perhaps from a memory-mapped file:
+//! let archived = rkyv::check_archived_root::<FrozenQuadtree<u32, u64>>(&bytes[..]).unwrap();
+//!
+//! let query = AreaBuilder::default()
+//!     .anchor((12, 22).into())
+//!     .build().unwrap();
+//! let entry = archived.query(query).next().unwrap();
+//! assert_eq!(entry.area(), region);
+//! assert_eq!(*entry.value(), 42);
+//! ```
+//!
+//! Archived coordinates are read in place, so `U` has to be a type which rkyv archives as itself
+//! (any primitive integer, with rkyv's default native-endian configuration).
+//!
+//! `rkyv::check_archived_root()` validates the layout of an archive, but not that it was produced
+//! from a real quadtree. Archives with nonsensical regions may cause queries to panic, so only
+//! read archives from sources you trust.
+//!
+//! [`Quadtree`]: ../struct.Quadtree.html
+//! [`FrozenQuadtree`]: struct.FrozenQuadtree.html
+//! [`ArchivedFrozenQuadtree`]: struct.ArchivedFrozenQuadtree.html
+//! [`rkyv`]: https://docs.rs/rkyv
+
+use {
+    crate::{
+        area::{Area, AreaBuilder},
+        entry::Entry,
+        point::Point,
+        qtinner::Node,
+        Quadtree,
+    },
+    num::PrimInt,
+    rkyv::{Archive, Archived, Deserialize, Serialize},
+    std::{
+        collections::{HashMap, HashSet},
+        convert::TryFrom,
+        default::Default,
+        hash::BuildHasher,
+        iter::FusedIterator,
+    },
+};
+
+// Index zero is always the root, so no node can have its children there.
+const NO_CHILDREN: u32 = 0;
+
+/// A flattened copy of a [`Quadtree`], ready to be archived with [`rkyv`].
+///
+/// See the [module documentation] for an example.
+///
+/// [`Quadtree`]: ../struct.Quadtree.html
+/// [`rkyv`]: https://docs.rs/rkyv
+/// [module documentation]: index.html
+#[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+#[archive(check_bytes)]
+pub struct FrozenQuadtree<U, V> {
+    anchor: [U; 2],
+    depth: u32,
+    // The width and height of the bounds, if they've been clipped by `new_with_bounds()`.
+    dimensions: Option<[U; 2]>,
+    next_handle: u64,
+    // Nodes in breadth-first order, so that every node's four subquadrants are contiguous.
+    nodes: Vec<FrozenNode<U>>,
+    // Entries sorted by handle.
+    entries: Vec<FrozenEntry<U, V>>,
+}
+
+#[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+#[archive(check_bytes)]
+struct FrozenNode<U> {
+    // The bounds of the node, which everything held by it and beneath it lies within, as
+    // [x, y, width, height].
+    region: [U; 4],
+    // The index of the first of four subquadrants, or NO_CHILDREN.
+    children: u32,
+    // Indices into the entries.
+    entries: Vec<u32>,
+}
+
+/// A region/value association held in a [`FrozenQuadtree`].
+///
+/// [`FrozenQuadtree`]: struct.FrozenQuadtree.html
+#[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+#[archive(check_bytes)]
+pub struct FrozenEntry<U, V> {
+    handle: u64,
+    region: [U; 4],
+    value: V,
+}
+
+fn to_array<U>(area: Area<U>) -> [U; 4]
+where
+    U: PrimInt + Default,
+{
+    [
+        area.anchor().x(),
+        area.anchor().y(),
+        area.width(),
+        area.height(),
+    ]
+}
+
+fn from_array<U>([x, y, w, h]: [U; 4]) -> Area<U>
+where
+    U: PrimInt + Default,
+{
+    AreaBuilder::default()
+        .anchor(Point { x, y })
+        .dimensions((w, h))
+        .build()
+        .expect("Archived regions should be valid.")
+}
+
+impl<U, V, S> From<Quadtree<U, V, S>> for FrozenQuadtree<U, V>
+where
+    U: PrimInt + Default,
+    S: BuildHasher,
+{
+    fn from(mut qt: Quadtree<U, V, S>) -> Self {
+        let anchor = qt.anchor();
+        let depth = u32::try_from(qt.depth()).expect("Depths should fit in a u32.");
+        let dimensions = qt.clipped_dimensions().map(|(w, h)| [w, h]);
+        let next_handle = qt.next_handle;
+
+        let handles: Vec<u64> = qt.entries_by_handle().iter().map(|e| e.handle()).collect();
+        let index: HashMap<u64, u32> = handles
+            .iter()
+            .enumerate()
+            .map(|(i, &handle)| (handle, i as u32))
+            .collect();
+
+        // Lay the nodes out breadth-first, handing each node's subquadrants the next four slots.
+        let inner = &qt.inner;
+        let mut order: Vec<&Node<U>> = vec![inner.root()];
+        let mut nodes = vec![];
+        let mut i = 0;
+        while let Some(node) = order.get(i).copied() {
+            let children = match node.subquadrants() {
+                Some(sqs) => {
+                    let first = order.len() as u32;
+                    order.extend(sqs.iter().map(|&sq| inner.node(sq)));
+                    first
+                }
+                None => NO_CHILDREN,
+            };
+            nodes.push(FrozenNode {
+                region: to_array(node.bounds()),
+                children,
+                entries: node.handles().iter().map(|h| index[h]).collect(),
+            });
+            i += 1;
+        }
+
+        FrozenQuadtree {
+            anchor: [anchor.x(), anchor.y()],
+            depth,
+            dimensions,
+            next_handle,
+            nodes,
+            entries: handles
+                .iter()
+                .filter_map(|handle| qt.store.remove(handle))
+                .map(|e| {
+                    let handle = e.handle();
+                    let (region, value) = e.into_parts();
+                    FrozenEntry {
+                        handle,
+                        region: to_array(region),
+                        value,
+                    }
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Thawing a [`FrozenQuadtree`] rebuilds its node structure from the entries, validating them
+/// along the way.
+///
+/// [`FrozenQuadtree`]: struct.FrozenQuadtree.html
+impl<U, V, S> TryFrom<FrozenQuadtree<U, V>> for Quadtree<U, V, S>
+where
+    U: PrimInt + Default,
+    S: BuildHasher + Default,
+{
+    type Error = String;
+
+    fn try_from(frozen: FrozenQuadtree<U, V>) -> Result<Self, Self::Error> {
+        let [x, y] = frozen.anchor;
+        let mut entries = vec![];
+        for e in frozen.entries {
+            let [ex, ey, w, h] = e.region;
+            let region = AreaBuilder::default()
+                .anchor(Point { x: ex, y: ey })
+                .dimensions((w, h))
+                .build()?;
+            entries.push(Entry::new((region, e.value), e.handle));
+        }
+        Quadtree::restore(
+            Point { x, y },
+            frozen.depth as usize,
+            frozen.dimensions.map(|[w, h]| (w, h)),
+            frozen.next_handle,
+            entries,
+        )
+    }
+}
+
+impl<U, V> ArchivedFrozenQuadtree<U, V>
+where
+    U: PrimInt + Default + Archive<Archived = U>,
+    V: Archive,
+{
+    /// The top-left corner (anchor) of the region which this quadtree represents.
+    pub fn anchor(&self) -> Point<U> {
+        Point {
+            x: self.anchor[0],
+            y: self.anchor[1],
+        }
+    }
+
+    /// The depth of the quadtree.
+    pub fn depth(&self) -> usize {
+        self.depth as usize
+    }
+
+    /// The number of elements in the quadtree.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether or not the quadtree is empty.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Looks up an entry by the handle it was given when inserted into the original quadtree.
+    pub fn get(&self, handle: u64) -> Option<&ArchivedFrozenEntry<U, V>> {
+        self.entries
+            .binary_search_by_key(&handle, |e| e.handle)
+            .ok()
+            .map(|i| &self.entries[i])
+    }
+
+    /// Returns an iterator over every entry, in order of handle.
+    pub fn iter(&self) -> std::slice::Iter<'_, ArchivedFrozenEntry<U, V>> {
+        self.entries.iter()
+    }
+
+    /// Returns an iterator over the entries whose regions intersect the query region, without
+    /// deserializing anything. See [`Quadtree::query()`].
+    ///
+    /// [`Quadtree::query()`]: ../struct.Quadtree.html#method.query
+    pub fn query(&self, area: Area<U>) -> ArchivedQuery<'_, U, V> {
+        ArchivedQuery {
+            tree: self,
+            area,
+            node_stack: vec![0],
+            entry_stack: vec![],
+            visited: HashSet::new(),
+        }
+    }
+
+    /// Alias for [`.query()`] which expects a [`Point`] instead of an [`Area`].
+    ///
+    /// [`.query()`]: #method.query
+    /// [`Area`]: ../area/struct.Area.html
+    /// [`Point`]: ../point/struct.Point.html
+    pub fn query_pt(&self, point: Point<U>) -> ArchivedQuery<'_, U, V> {
+        self.query(
+            AreaBuilder::default()
+                .anchor(point)
+                .build()
+                .expect("Unexpected error in ArchivedFrozenQuadtree::query_pt()."),
+        )
+    }
+}
+
+impl<U, V> ArchivedFrozenEntry<U, V>
+where
+    U: PrimInt + Default + Archive<Archived = U>,
+    V: Archive,
+{
+    /// The handle this entry was given when inserted into the original quadtree.
+    pub fn handle(&self) -> u64 {
+        self.handle
+    }
+
+    /// The entry's region.
+    pub fn area(&self) -> Area<U> {
+        from_array(self.region)
+    }
+
+    /// A reference to the archived value.
+    pub fn value(&self) -> &Archived<V> {
+        &self.value
+    }
+}
+
+/// An iterator over the entries of an [`ArchivedFrozenQuadtree`] which intersect some region.
+///
+/// This struct is created by the [`query`] method on [`ArchivedFrozenQuadtree`].
+///
+/// [`query`]: struct.ArchivedFrozenQuadtree.html#method.query
+/// [`ArchivedFrozenQuadtree`]: struct.ArchivedFrozenQuadtree.html
+pub struct ArchivedQuery<'a, U, V>
+where
+    U: PrimInt + Default + Archive<Archived = U>,
+    V: Archive,
+{
+    tree: &'a ArchivedFrozenQuadtree<U, V>,
+    area: Area<U>,
+    node_stack: Vec<u32>,
+    entry_stack: Vec<u32>,
+    visited: HashSet<u32>,
+}
+
+impl<'a, U, V> Iterator for ArchivedQuery<'a, U, V>
+where
+    U: PrimInt + Default + Archive<Archived = U>,
+    V: Archive,
+{
+    type Item = &'a ArchivedFrozenEntry<U, V>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            while let Some(i) = self.entry_stack.pop() {
+                if !self.visited.insert(i) {
+                    continue;
+                }
+                if let Some(entry) = self.tree.entries.get(i as usize) {
+                    if entry.area().intersects(self.area) {
+                        return Some(entry);
+                    }
+                }
+            }
+
+            let node = match self.tree.nodes.get(self.node_stack.pop()? as usize) {
+                Some(node) => node,
+                None => continue,
+            };
+            if !from_array(node.region).intersects(self.area) {
+                continue;
+            }
+            if node.children != NO_CHILDREN {
+                self.node_stack
+                    .extend(node.children..node.children.saturating_add(4));
+            }
+            self.entry_stack.extend(node.entries.iter());
+        }
+    }
+}
+
+impl<U, V> FusedIterator for ArchivedQuery<'_, U, V>
+where
+    U: PrimInt + Default + Archive<Archived = U>,
+    V: Archive,
+{
+}
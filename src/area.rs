@@ -15,9 +15,9 @@
 //! A rectangular region in the tree.
 
 use {
-    crate::point,
+    crate::{cursor::Quadrant, geometry, point},
     num::PrimInt,
-    std::{cmp::PartialOrd, default::Default, fmt::Debug},
+    std::{cmp::PartialOrd, convert::TryFrom, default::Default, fmt::Debug},
 };
 
 /// A rectangular region in 2d space.
@@ -26,10 +26,15 @@ use {
 ///
 /// **NB:**
 ///   - The top-left anchor can be any valid `(U, U)` coordinate, positive or negative, in any
-///   quadrant.
+///     quadrant.
 ///   - The width and height must both be positive and nonzero.
 #[derive(PartialEq, Eq, Clone, Copy, Hash, Builder)]
 #[builder(build_fn(validate = "Self::validate"))]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "((U, U), (U, U))", into = "((U, U), (U, U))")
+)]
 pub struct Area<U>
 where
     U: PrimInt + Default + PartialOrd,
@@ -93,10 +98,57 @@ where
     }
 }
 
+/// The inverse of the `From<Area<U>>` conversion above, which validates the dimensions just as
+/// [`AreaBuilder`] does.
+///
+/// ```
+/// use {quadtree_rs::area::Area, std::convert::TryFrom};
+///
+/// let area = Area::try_from(((1, 2), (3, 4))).unwrap();
+/// assert_eq!(area.width(), 3);
+///
+/// assert!(Area::try_from(((1, 2), (0, 4))).is_err());
+/// ```
+///
+/// [`AreaBuilder`]: struct.AreaBuilder.html
+impl<U> TryFrom<((U, U), (U, U))> for Area<U>
+where
+    U: PrimInt + Default,
+{
+    type Error = String;
+
+    fn try_from((anchor, dimensions): ((U, U), (U, U))) -> Result<Self, Self::Error> {
+        Self::new(anchor.into(), dimensions)
+    }
+}
+
 impl<U> Area<U>
 where
     U: PrimInt + Default,
 {
+    /// Creates a region from its anchor and its `(width, height)`, or explains why it can't.
+    ///
+    /// Both dimensions must be positive: with a signed `U`, a zero or negative width or height is
+    /// an error rather than a degenerate region. This is shorthand for the equivalent
+    /// [`AreaBuilder`], which validates in just the same way.
+    /// ```
+    /// use quadtree_rs::{area::Area, point::Point};
+    ///
+    /// let area = Area::new(Point {x: -3, y: 2}, (4, 1)).unwrap();
+    /// assert_eq!(area.width(), 4);
+    ///
+    /// assert!(Area::new(Point {x: -3, y: 2}, (4, 0)).is_err());
+    /// assert!(Area::new(Point {x: -3, y: 2}, (-4, 1)).is_err());
+    /// ```
+    ///
+    /// [`AreaBuilder`]: struct.AreaBuilder.html
+    pub fn new(anchor: point::Point<U>, dimensions: (U, U)) -> Result<Self, String> {
+        AreaBuilder::default()
+            .anchor(anchor)
+            .dimensions(dimensions)
+            .build()
+    }
+
     /// The top-left coordinate (anchor) of the region.
     pub fn anchor(&self) -> point::Point<U> {
         self.anchor
@@ -118,8 +170,12 @@ where
     }
 
     /// The coordinate of the bottom edge of the region.
+    ///
+    /// The bottom edge lies just past the region's last row, so a region which reaches the very
+    /// end of the range of `U` has no representable bottom edge. It saturates at `U::max_value()`
+    /// instead.
     pub fn bottom_edge(&self) -> U {
-        self.anchor().y() + self.height()
+        self.anchor().y().saturating_add(self.height())
     }
 
     /// The coordinate of the left edge of the region.
@@ -128,24 +184,76 @@ where
     }
 
     /// The coordinate of the right edge of the region.
+    ///
+    /// Like the [`.bottom_edge()`], this saturates at `U::max_value()`.
+    ///
+    /// [`.bottom_edge()`]: #method.bottom_edge
     pub fn right_edge(&self) -> U {
-        self.anchor().x() + self.width()
+        self.anchor().x().saturating_add(self.width())
     }
 
     /// Whether or not an area intersects another area.
     pub fn intersects(self, other: Self) -> bool {
-        self.left_edge() < other.right_edge()
-            && self.right_edge() > other.left_edge()
-            && self.top_edge() < other.bottom_edge()
-            && self.bottom_edge() > other.top_edge()
+        geometry::intersects(self.bounds(), other.bounds())
     }
 
     /// Whether or not an area wholly contains another area.
     pub fn contains(self, other: Self) -> bool {
-        other.right_edge() <= self.right_edge()
-            && other.left_edge() >= self.left_edge()
-            && other.top_edge() >= self.top_edge()
-            && other.bottom_edge() <= self.bottom_edge()
+        geometry::contains(self.bounds(), other.bounds())
+    }
+
+    /// The area which two areas have in common, or `None` if they don't intersect.
+    /// ```
+    /// use quadtree_rs::area::AreaBuilder;
+    ///
+    /// let a = AreaBuilder::default().anchor((0, 0).into()).dimensions((4, 4)).build().unwrap();
+    /// let b = AreaBuilder::default().anchor((2, 3).into()).dimensions((4, 4)).build().unwrap();
+    ///
+    /// let c = a.intersection(b).unwrap();
+    /// assert_eq!(c.anchor(), (2, 3).into());
+    /// assert_eq!((c.width(), c.height()), (2, 1));
+    /// ```
+    pub fn intersection(self, other: Self) -> Option<Self> {
+        if !self.intersects(other) {
+            return None;
+        }
+        let (left, top) = (
+            self.left_edge().max(other.left_edge()),
+            self.top_edge().max(other.top_edge()),
+        );
+        // Measured from each area's own anchor, so that neither far edge has to be representable.
+        let (width, height) = (
+            (self.width() - (left - self.left_edge()))
+                .min(other.width() - (left - other.left_edge())),
+            (self.height() - (top - self.top_edge()))
+                .min(other.height() - (top - other.top_edge())),
+        );
+        AreaBuilder::default()
+            .anchor(point::Point { x: left, y: top })
+            .dimensions((width, height))
+            .build()
+            .ok()
+    }
+
+    /// The number of cells which two areas have in common, or zero if they don't intersect.
+    /// Saturates at the largest value of `U`.
+    /// ```
+    /// use quadtree_rs::area::AreaBuilder;
+    ///
+    /// let a = AreaBuilder::default().anchor((0, 0).into()).dimensions((4, 4)).build().unwrap();
+    /// let b = AreaBuilder::default().anchor((2, 3).into()).dimensions((4, 4)).build().unwrap();
+    /// let c = AreaBuilder::default().anchor((8, 8).into()).dimensions((1, 1)).build().unwrap();
+    ///
+    /// assert_eq!(a.overlap(b), 2);
+    /// assert_eq!(a.overlap(c), 0);
+    /// ```
+    pub fn overlap(self, other: Self) -> U {
+        self.intersection(other).map_or(U::zero(), |common| {
+            common
+                .width()
+                .checked_mul(&common.height())
+                .unwrap_or_else(U::max_value)
+        })
     }
 
     /// Whether or not an area contains a point.
@@ -173,6 +281,43 @@ where
         self.dimensions
     }
 
+    // The given quarter of this area, which should have even sides, as the cells of a quadtree
+    // above the bottom depth do.
+    pub(crate) fn quarter(self, quadrant: Quadrant) -> Self {
+        let ([x, y], [width, height]) =
+            geometry::orthant(self.bounds(), [quadrant.is_east(), quadrant.is_south()]);
+        AreaBuilder::default()
+            .anchor(point::Point { x, y })
+            .dimensions((width, height))
+            .build()
+            .expect("Unexpected error in Area::quarter.")
+    }
+
+    // The four quarters of this area, in the order of a node's subquadrants: north-east,
+    // north-west, south-east, south-west.
+    pub(crate) fn quarters(self) -> [Self; 4] {
+        Quadrant::ALL.map(|quadrant| self.quarter(quadrant))
+    }
+
+    // The squared euclidean distance from @pt to the nearest cell of this area. Zero if the area
+    // contains @pt.
+    pub(crate) fn distance_sq_to_pt(self, pt: point::Point<U>) -> u128 {
+        geometry::distance_sq_to_pt(self.bounds(), [pt.x(), pt.y()])
+    }
+
+    // The squared euclidean distance from @pt to the furthest cell of this area.
+    pub(crate) fn farthest_distance_sq_to_pt(self, pt: point::Point<U>) -> u128 {
+        geometry::farthest_distance_sq_to_pt(self.bounds(), [pt.x(), pt.y()])
+    }
+
+    // The anchor and dimensions of the area, for the shared geometry.
+    fn bounds(self) -> geometry::Bounds<U, 2> {
+        (
+            [self.anchor().x(), self.anchor().y()],
+            [self.width(), self.height()],
+        )
+    }
+
     // Strongly-typed alias for U::one() + U::One()
     fn two() -> U {
         U::one() + U::one()
@@ -0,0 +1,212 @@
+// Copyright 2019 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use {
+    crate::{entry::Entry, Quadtree},
+    num::PrimInt,
+    std::{collections::HashMap, default::Default, hash::BuildHasher},
+};
+
+// A union-find over the indices 0..n, for merging entries into groups.
+struct DisjointSets {
+    parents: Vec<usize>,
+}
+
+impl DisjointSets {
+    fn new(n: usize) -> Self {
+        Self {
+            parents: (0..n).collect(),
+        }
+    }
+
+    fn find(&mut self, mut i: usize) -> usize {
+        while self.parents[i] != i {
+            // Path halving.
+            self.parents[i] = self.parents[self.parents[i]];
+            i = self.parents[i];
+        }
+        i
+    }
+
+    fn union(&mut self, a: usize, b: usize) {
+        let (a, b) = (self.find(a), self.find(b));
+        // Always keep the smaller root, so that each group is named after its first member.
+        self.parents[a.max(b)] = a.min(b);
+    }
+}
+
+impl<U, V, S> Quadtree<U, V, S>
+where
+    U: PrimInt + Default,
+    S: BuildHasher,
+{
+    // pub
+
+    /// Groups the entries into connected components, where two entries are connected if their
+    /// regions lie within `gap` cells of each other, directly or through a chain of other
+    /// entries.
+    ///
+    /// Gaps are measured as in [`.neighbors()`]: with a `gap` of zero regions which overlap or
+    /// touch at an edge or a corner are connected, with a `gap` of one also those which are a cell
+    /// apart, and so on. A negative `gap` is treated as zero.
+    ///
+    /// Each group is labelled by its index in the returned list. The groups are ordered by the
+    /// smallest handle in each, and the entries within a group by handle, so the labels are stable
+    /// for a given quadtree. Entries which aren't connected to anything form groups of their own.
+    /// ```
+    /// use quadtree_rs::{area::AreaBuilder, Quadtree};
+    ///
+    /// let mut qt = Quadtree::<u32, char>::new(4);
+    /// let blob = |x, y| AreaBuilder::default()
+    ///     .anchor((x, y).into())
+    ///     .dimensions((2, 2))
+    ///     .build().unwrap();
+    /// qt.insert(blob(0, 0), 'a');
+    /// qt.insert(blob(1, 1), 'b');
+    /// qt.insert(blob(4, 4), 'c');
+    /// qt.insert(blob(10, 10), 'd');
+    ///
+    /// let labels = |gap| -> Vec<Vec<char>> {
+    ///     qt.connected_components(gap)
+    ///         .iter()
+    ///         .map(|group| group.iter().map(|e| *e.value_ref()).collect())
+    ///         .collect()
+    /// };
+    /// assert_eq!(labels(0), vec![vec!['a', 'b'], vec!['c'], vec!['d']]);
+    /// // 'c' is a cell away from 'b', diagonally.
+    /// assert_eq!(labels(1), vec![vec!['a', 'b', 'c'], vec!['d']]);
+    /// ```
+    ///
+    /// [`.neighbors()`]: #method.neighbors
+    pub fn connected_components(&self, gap: U) -> Vec<Vec<&Entry<U, V>>> {
+        let entries = self.entries_by_handle();
+        let index: HashMap<u64, usize> = entries
+            .iter()
+            .enumerate()
+            .map(|(i, entry)| (entry.handle(), i))
+            .collect();
+
+        let mut sets = DisjointSets::new(entries.len());
+        for (i, entry) in entries.iter().enumerate() {
+            for other in self.neighbors(entry.handle(), gap) {
+                sets.union(i, index[&other.handle()]);
+            }
+        }
+
+        // Roots are the smallest index in their group, so groups come out in order of their first
+        // members.
+        let mut labels: HashMap<usize, usize> = HashMap::new();
+        let mut groups: Vec<Vec<&Entry<U, V>>> = vec![];
+        for (i, entry) in entries.into_iter().enumerate() {
+            let root = sets.find(i);
+            let label = *labels.entry(root).or_insert_with(|| {
+                groups.push(vec![]);
+                groups.len() - 1
+            });
+            groups[label].push(entry);
+        }
+        groups
+    }
+
+    /// Clusters the entries by density, as in DBSCAN, returning the label of the cluster which
+    /// each handle was assigned to, or `None` for noise.
+    ///
+    /// Each entry is treated as the point at the anchor of its region, so this is meant for
+    /// quadtrees of points such as those filled with [`.insert_pt()`]. An entry's neighbours are
+    /// the entries within `eps` (euclidean) of it, itself included, and are found with
+    /// [`.query_within_radius()`]. With a negative `eps` nothing has any neighbours. Entries with
+    /// at least `min_pts` neighbours are core points; every core point within `eps` of another is
+    /// in the same cluster as it, along with the neighbours of both. Entries which are in no core
+    /// point's neighbourhood are noise.
+    ///
+    /// Clusters are labelled from zero in the order they're found, starting from the core points
+    /// with the smallest handles. An entry which borders more than one cluster goes to the first
+    /// of them.
+    /// ```
+    /// use quadtree_rs::{point::Point, Quadtree};
+    ///
+    /// let mut qt = Quadtree::<u32, ()>::new(5);
+    /// let a: Vec<u64> = [(1, 1), (2, 1), (1, 2), (2, 2)]
+    ///     .iter()
+    ///     .map(|&(x, y)| qt.insert_pt(Point { x, y }, ()).unwrap())
+    ///     .collect();
+    /// let b: Vec<u64> = [(20, 20), (21, 21), (22, 20)]
+    ///     .iter()
+    ///     .map(|&(x, y)| qt.insert_pt(Point { x, y }, ()).unwrap())
+    ///     .collect();
+    /// let lonely = qt.insert_pt(Point { x: 10, y: 30 }, ()).unwrap();
+    ///
+    /// let labels = qt.cluster(2, 3);
+    /// assert!(a.iter().all(|h| labels[h] == Some(0)));
+    /// assert!(b.iter().all(|h| labels[h] == Some(1)));
+    /// assert_eq!(labels[&lonely], None);
+    /// ```
+    ///
+    /// [`.insert_pt()`]: #method.insert_pt
+    /// [`.query_within_radius()`]: #method.query_within_radius
+    pub fn cluster(&self, eps: U, min_pts: usize) -> HashMap<u64, Option<usize>> {
+        let mut handles: Vec<u64> = self.iter().map(Entry::handle).collect();
+        handles.sort_unstable();
+
+        let mut labels: HashMap<u64, Option<usize>> = HashMap::with_capacity(handles.len());
+        let mut clusters = 0;
+        for handle in handles {
+            if labels.contains_key(&handle) {
+                continue;
+            }
+            let within_eps = self.points_within_eps(handle, eps);
+            if within_eps.len() < min_pts {
+                labels.insert(handle, None);
+                continue;
+            }
+
+            let label = Some(clusters);
+            clusters += 1;
+            labels.insert(handle, label);
+            let mut seeds = within_eps;
+            while let Some(seed) = seeds.pop() {
+                match labels.get(&seed) {
+                    // Noise until now, but it borders this cluster.
+                    Some(None) => {
+                        labels.insert(seed, label);
+                    }
+                    Some(Some(_)) => {}
+                    None => {
+                        labels.insert(seed, label);
+                        let theirs = self.points_within_eps(seed, eps);
+                        if theirs.len() >= min_pts {
+                            seeds.extend(theirs);
+                        }
+                    }
+                }
+            }
+        }
+        labels
+    }
+
+    // fn
+
+    // The handles of the entries whose anchors lie within @eps of the anchor of @handle's entry,
+    // including @handle itself.
+    fn points_within_eps(&self, handle: u64, eps: U) -> Vec<u64> {
+        let (center, eps_sq) = match (self.get(handle), eps.to_u128()) {
+            (Some(entry), Some(eps)) => (entry.anchor(), eps.saturating_mul(eps)),
+            _ => return vec![],
+        };
+        self.query_within_radius(center, eps)
+            .filter(|entry| Self::area_at_pt(entry.anchor()).distance_sq_to_pt(center) <= eps_sq)
+            .map(Entry::handle)
+            .collect()
+    }
+}
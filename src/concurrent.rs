@@ -0,0 +1,583 @@
+// Copyright 2019 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Quadtrees which can be shared between threads, behind the `concurrent` feature.
+//!
+//! ## `ConcurrentQuadtree`
+//!
+//! Wrapping a [`Quadtree`] in a single `RwLock` means that every insertion has to wait for every
+//! query, anywhere in the tree, to finish. A [`ConcurrentQuadtree`] instead splits its region into
+//! four quadrants, each with its own lock, so that work in one quadrant never waits on work in
+//! another. Regions which straddle more than one quadrant are kept in a fifth, shared shard.
+//!
+//! ```
+//! use quadtree_rs::{area::AreaBuilder, concurrent::ConcurrentQuadtree};
+//! use std::{sync::Arc, thread};
+//!
+//! let qt = Arc::new(ConcurrentQuadtree::<u32, u32>::new(8));
+//!
+//! let writers: Vec<_> = (0..4)
+//!     .map(|t| {
+//!         let qt = Arc::clone(&qt);
+//!         thread::spawn(move || {
+//!             for i in 0..64 {
+//!                 qt.insert_pt((t * 64 + i, t * 64 + i).into(), t).unwrap();
+//!             }
+//!         })
+//!     })
+//!     .collect();
+//! for writer in writers {
+//!     writer.join().unwrap();
+//! }
+//!
+//! let region = AreaBuilder::default()
+//!     .anchor((0, 0).into())
+//!     .dimensions((128, 128))
+//!     .build().unwrap();
+//! assert_eq!(qt.len(), 256);
+//! assert_eq!(qt.query(region).len(), 128);
+//! ```
+//!
+//! Each operation locks one shard at a time, so an operation spanning several quadrants is not
+//! atomic: a concurrent query may observe some but not all of the effects of a [`.modify()`] or
+//! [`.delete()`] which is still in progress.
+//!
+//! [`Quadtree`]: ../struct.Quadtree.html
+//! [`ConcurrentQuadtree`]: struct.ConcurrentQuadtree.html
+//! [`.modify()`]: struct.ConcurrentQuadtree.html#method.modify
+//! [`.delete()`]: struct.ConcurrentQuadtree.html#method.delete
+//!
+//! ## `SnapshotQuadtree`
+//!
+//! Where there is one writer and many readers, such as a game loop which rebuilds its spatial index
+//! once per frame, a [`SnapshotQuadtree`] lets readers hold on to an immutable snapshot for as long
+//! as they like. The writer changes the next version in private, and then [`.publish()`]es it;
+//! readers which already hold a snapshot carry on seeing the old version, and nobody ever waits on
+//! a long-running query.
+//!
+//! ```
+//! use quadtree_rs::concurrent::SnapshotQuadtree;
+//! use std::{sync::Arc, thread};
+//!
+//! let qt = Arc::new(SnapshotQuadtree::<u32, u32>::new(4));
+//! qt.write(|next| next.insert_pt((1, 1).into(), 1));
+//! qt.publish();
+//!
+//! let before = qt.snapshot();
+//! let writer = {
+//!     let qt = Arc::clone(&qt);
+//!     thread::spawn(move || {
+//!         qt.write(|next| next.insert_pt((2, 2).into(), 2));
+//!         qt.publish();
+//!     })
+//! };
+//! writer.join().unwrap();
+//!
+//! // The old snapshot is unchanged; a fresh one sees the insertion.
+//! assert_eq!(before.len(), 1);
+//! assert_eq!(qt.snapshot().len(), 2);
+//! ```
+//!
+//! [`SnapshotQuadtree`]: struct.SnapshotQuadtree.html
+//! [`.publish()`]: struct.SnapshotQuadtree.html#method.publish
+
+use {
+    crate::{
+        area::{Area, AreaBuilder},
+        entry::Entry,
+        point::Point,
+        qtinner::Layout,
+        BoundsPolicy, Quadtree,
+    },
+    num::PrimInt,
+    std::{
+        default::Default,
+        mem,
+        sync::{
+            atomic::{AtomicU64, Ordering},
+            Arc, Mutex, RwLock, RwLockReadGuard, RwLockWriteGuard,
+        },
+    },
+};
+
+/// A quadtree with one lock per top-level quadrant, which can be shared between threads.
+///
+/// Since no reference into the tree can outlive the lock guarding it, queries hand back copies of
+/// the matching entries, or visit them in place with a callback. See the [module-level
+/// documentation] for more.
+///
+/// [module-level documentation]: index.html
+#[derive(Debug)]
+pub struct ConcurrentQuadtree<U, V>
+where
+    U: PrimInt + Default,
+{
+    // The region which the quadtree accepts insertions into. This may be smaller than the region
+    // covered by the shards if it was converted from a quadtree built with `new_with_bounds()`.
+    region: Area<U>,
+    depth: usize,
+    // One shard per quadrant, in the order [ne, nw, se, sw]. A quadtree of depth zero has no
+    // quadrants, and so no shards.
+    shards: Vec<Shard<U, V>>,
+    // Regions which don't fit within a single quadrant. This covers the whole region.
+    spanning: Shard<U, V>,
+    // What happens to insertions which don't fit within the region. The tree can't grow without
+    // resharding it, so an expanding policy rejects them instead.
+    bounds_policy: BoundsPolicy,
+    next_handle: AtomicU64,
+}
+
+// A quadtree behind its own lock. The region is kept outside the lock, since it never changes and
+// routing an operation shouldn't have to wait on anything.
+#[derive(Debug)]
+struct Shard<U, V>
+where
+    U: PrimInt + Default,
+{
+    region: Area<U>,
+    tree: RwLock<Quadtree<U, V>>,
+}
+
+impl<U, V> Shard<U, V>
+where
+    U: PrimInt + Default,
+{
+    fn new(anchor: Point<U>, depth: usize, layout: &Layout) -> Self {
+        let mut tree = Quadtree::new_with_anchor(anchor, depth);
+        tree.relayout(layout.clone());
+        Self {
+            region: tree.inner.region(),
+            tree: RwLock::new(tree),
+        }
+    }
+
+    // A poisoned lock means that some other thread panicked partway through changing this shard,
+    // which may have left its tree and store disagreeing with one another. There's no recovering
+    // from that.
+    fn read(&self) -> RwLockReadGuard<'_, Quadtree<U, V>> {
+        self.tree.read().expect(POISONED)
+    }
+
+    fn write(&self) -> RwLockWriteGuard<'_, Quadtree<U, V>> {
+        self.tree.write().expect(POISONED)
+    }
+
+    fn into_inner(self) -> Quadtree<U, V> {
+        self.tree.into_inner().expect(POISONED)
+    }
+}
+
+const POISONED: &str = "A lock within the quadtree was poisoned by a panicking thread.";
+
+impl<U, V> ConcurrentQuadtree<U, V>
+where
+    U: PrimInt + Default,
+{
+    // pub
+
+    /// Creates a new, empty quadtree with some depth.
+    /// See [`Quadtree::new()`].
+    ///
+    /// [`Quadtree::new()`]: ../struct.Quadtree.html#method.new
+    pub fn new(depth: usize) -> Self {
+        Self::new_with_anchor(
+            Point {
+                x: U::zero(),
+                y: U::zero(),
+            },
+            depth,
+        )
+    }
+
+    /// Creates a new, empty quadtree with some depth and an explicit anchor.
+    /// See [`Quadtree::new_with_anchor()`].
+    ///
+    /// [`Quadtree::new_with_anchor()`]: ../struct.Quadtree.html#method.new_with_anchor
+    pub fn new_with_anchor(anchor: Point<U>, depth: usize) -> Self {
+        Self::with_layout(anchor, depth, &Layout::default())
+    }
+
+    /// The top-left corner (anchor) of the region which this quadtree represents.
+    pub fn anchor(&self) -> Point<U> {
+        self.region.anchor()
+    }
+
+    /// The width of the region which this quadtree represents.
+    pub fn width(&self) -> usize {
+        self.region.width().to_usize().unwrap()
+    }
+
+    /// The height of the region which this quadtree represents.
+    pub fn height(&self) -> usize {
+        self.region.height().to_usize().unwrap()
+    }
+
+    /// The depth of the quadtree.
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// The number of elements in the quadtree.
+    pub fn len(&self) -> usize {
+        self.all_shards().map(|shard| shard.read().len()).sum()
+    }
+
+    /// Whether or not the quadtree is empty.
+    pub fn is_empty(&self) -> bool {
+        self.all_shards().all(|shard| shard.read().is_empty())
+    }
+
+    /// Whether or not some trial region could fit in the region which this quadtree represents.
+    pub fn contains(&self, area: Area<U>) -> bool {
+        self.region.contains(area)
+    }
+
+    /// Inserts the value into the quadtree at the given region. See [`Quadtree::insert()`].
+    ///
+    /// Only the shard which the region falls into is locked. A region which doesn't fit is
+    /// rejected, unless this was converted from a quadtree which clips such regions.
+    ///
+    /// [`Quadtree::insert()`]: ../struct.Quadtree.html#method.insert
+    pub fn insert(&self, region: Area<U>, val: V) -> Option<u64> {
+        let region = self.fit(region)?;
+        let handle = self.next_handle.fetch_add(1, Ordering::Relaxed);
+        self.shard_for(region)
+            .write()
+            .insert_with_handle(region, val, handle);
+        Some(handle)
+    }
+
+    /// Alias for [`.insert()`] which expects a [`Point`] instead of an [`Area`].
+    ///
+    /// [`.insert()`]: #method.insert
+    /// [`Point`]: ../point/struct.Point.html
+    pub fn insert_pt(&self, point: Point<U>, val: V) -> Option<u64> {
+        self.insert(
+            AreaBuilder::default()
+                .anchor(point)
+                .build()
+                .expect("Unexpected error in ConcurrentQuadtree::insert_pt()."),
+            val,
+        )
+    }
+
+    /// Calls `f` on every [`&Entry<U, V>`] whose region intersects the query region, as
+    /// [`Quadtree::query()`] would return them, while holding the lock of each shard in turn.
+    ///
+    /// [`&Entry<U, V>`]: ../entry/struct.Entry.html
+    /// [`Quadtree::query()`]: ../struct.Quadtree.html#method.query
+    pub fn query_with<F>(&self, area: Area<U>, mut f: F)
+    where
+        F: FnMut(&Entry<U, V>),
+    {
+        for shard in self.shards_intersecting(area) {
+            shard.read().query(area).for_each(&mut f);
+        }
+    }
+
+    /// Counts the regions which intersect the query region.
+    /// See [`Quadtree::count_in_region()`].
+    ///
+    /// [`Quadtree::count_in_region()`]: ../struct.Quadtree.html#method.count_in_region
+    pub fn count_in_region(&self, area: Area<U>) -> usize {
+        self.shards_intersecting(area)
+            .map(|shard| shard.read().count_in_region(area))
+            .sum()
+    }
+
+    /// Runs `f` over every value whose region intersects the query region, and returns how many
+    /// there were.
+    /// See [`Quadtree::modify()`].
+    ///
+    /// [`Quadtree::modify()`]: ../struct.Quadtree.html#method.modify
+    pub fn modify<F>(&self, area: Area<U>, mut f: F) -> usize
+    where
+        F: FnMut(&Area<U>, &mut V),
+    {
+        self.shards_intersecting(area)
+            .map(|shard| shard.write().modify(area, &mut f))
+            .sum()
+    }
+
+    /// Deletes and returns every entry whose region intersects the query region.
+    /// See [`Quadtree::delete()`].
+    ///
+    /// [`Quadtree::delete()`]: ../struct.Quadtree.html#method.delete
+    pub fn delete(&self, area: Area<U>) -> Vec<Entry<U, V>> {
+        self.shards_intersecting(area)
+            .flat_map(|shard| shard.write().delete(area).collect::<Vec<_>>())
+            .collect()
+    }
+
+    /// Deletes and returns the entry with the given handle, if there is one.
+    /// See [`Quadtree::delete_by_handle()`].
+    ///
+    /// [`Quadtree::delete_by_handle()`]: ../struct.Quadtree.html#method.delete_by_handle
+    pub fn delete_by_handle(&self, handle: u64) -> Option<Entry<U, V>> {
+        self.all_shards()
+            .find_map(|shard| shard.write().delete_by_handle(handle))
+    }
+
+    /// Consumes the concurrent quadtree, returning an ordinary [`Quadtree`] which holds the same
+    /// entries under the same handles.
+    ///
+    /// [`Quadtree`]: ../struct.Quadtree.html
+    pub fn into_quadtree(self) -> Quadtree<U, V> {
+        let mut qt = self.spanning.into_inner();
+        for shard in self.shards {
+            for entry in shard.into_inner() {
+                let handle = entry.handle();
+                let (region, val) = entry.into_parts();
+                qt.insert_with_handle(region, val, handle);
+            }
+        }
+        qt.next_handle = self.next_handle.into_inner();
+        qt.bounds = self.region;
+        qt.bounds_policy = self.bounds_policy;
+        qt
+    }
+
+    // fn
+
+    // An empty quadtree whose shards all subdivide their cells as @layout has it.
+    fn with_layout(anchor: Point<U>, depth: usize, layout: &Layout) -> Self {
+        let spanning = Shard::new(anchor, depth, layout);
+        let region = spanning.region;
+        let shards = if depth == 0 {
+            vec![]
+        } else {
+            region
+                .quarters()
+                .iter()
+                .map(|quarter| Shard::new(quarter.anchor(), depth - 1, layout))
+                .collect()
+        };
+        Self {
+            region,
+            depth,
+            shards,
+            spanning,
+            bounds_policy: BoundsPolicy::default(),
+            next_handle: AtomicU64::new(0),
+        }
+    }
+
+    // The region to store in place of @region, as the bounds policy has it: @region itself if it
+    // fits, its clipped remainder if it's clipping, or None otherwise.
+    fn fit(&self, region: Area<U>) -> Option<Area<U>> {
+        if self.contains(region) {
+            return Some(region);
+        }
+        match self.bounds_policy {
+            BoundsPolicy::Clip => self.region.intersection(region),
+            BoundsPolicy::Reject | BoundsPolicy::Expand => None,
+        }
+    }
+
+    // The shard which @region belongs in: the quadrant which totally contains it, if any.
+    fn shard_for(&self, region: Area<U>) -> &Shard<U, V> {
+        self.shards
+            .iter()
+            .find(|shard| shard.region.contains(region))
+            .unwrap_or(&self.spanning)
+    }
+
+    fn shards_intersecting(&self, area: Area<U>) -> impl Iterator<Item = &Shard<U, V>> {
+        self.all_shards()
+            .filter(move |shard| shard.region.intersects(area))
+    }
+
+    fn all_shards(&self) -> impl Iterator<Item = &Shard<U, V>> {
+        std::iter::once(&self.spanning).chain(self.shards.iter())
+    }
+}
+
+impl<U, V> ConcurrentQuadtree<U, V>
+where
+    U: PrimInt + Default,
+    V: Clone,
+{
+    /// Returns a copy of every [`Entry<U, V>`] whose region intersects the query region.
+    /// See [`Quadtree::query()`].
+    ///
+    /// [`Entry<U, V>`]: ../entry/struct.Entry.html
+    /// [`Quadtree::query()`]: ../struct.Quadtree.html#method.query
+    pub fn query(&self, area: Area<U>) -> Vec<Entry<U, V>> {
+        let mut entries = vec![];
+        self.query_with(area, |entry| entries.push(entry.clone()));
+        entries
+    }
+
+    /// Returns a copy of the entry with the given handle, if there is one.
+    /// See [`Quadtree::get()`].
+    ///
+    /// [`Quadtree::get()`]: ../struct.Quadtree.html#method.get
+    pub fn get(&self, handle: u64) -> Option<Entry<U, V>> {
+        self.all_shards()
+            .find_map(|shard| shard.read().get(handle).cloned())
+    }
+}
+
+impl<U, V> From<Quadtree<U, V>> for ConcurrentQuadtree<U, V>
+where
+    U: PrimInt + Default,
+{
+    /// Shards a quadtree, keeping its entries, handles and bounds. Every shard subdivides its cells
+    /// with the quadtree's split policy and looseness, and regions which don't fit are clipped or
+    /// rejected as the quadtree's bounds policy has it. A concurrent quadtree can't grow, though,
+    /// so under [`BoundsPolicy::Expand`] they're rejected.
+    ///
+    /// [`BoundsPolicy::Expand`]: ../enum.BoundsPolicy.html#variant.Expand
+    fn from(qt: Quadtree<U, V>) -> Self {
+        let mut concurrent = Self::with_layout(qt.anchor(), qt.depth(), qt.inner.layout());
+        concurrent.region = qt.bounds;
+        concurrent.bounds_policy = qt.bounds_policy;
+        concurrent
+            .next_handle
+            .store(qt.next_handle, Ordering::Relaxed);
+        for entry in qt {
+            let handle = entry.handle();
+            let (region, val) = entry.into_parts();
+            concurrent
+                .shard_for(region)
+                .write()
+                .insert_with_handle(region, val, handle);
+        }
+        concurrent
+    }
+}
+
+/// A quadtree which readers take immutable snapshots of, while a single writer prepares the next
+/// version.
+///
+/// Taking a [`.snapshot()`] only bumps a reference count, and the snapshot is an ordinary
+/// [`Quadtree`] which can be queried for as long as the reader likes without holding up anyone
+/// else. Changes made through [`.write()`] aren't visible to readers until the next
+/// [`.publish()`], which copies the pending version and swaps it in. See the [module-level
+/// documentation] for more.
+///
+/// [`.snapshot()`]: #method.snapshot
+/// [`.write()`]: #method.write
+/// [`.publish()`]: #method.publish
+/// [`Quadtree`]: ../struct.Quadtree.html
+/// [module-level documentation]: index.html
+#[derive(Debug)]
+pub struct SnapshotQuadtree<U, V>
+where
+    U: PrimInt + Default,
+{
+    // The most recently published version. The lock is only ever held long enough to clone or
+    // replace the Arc.
+    published: RwLock<Arc<Quadtree<U, V>>>,
+    // The next version, which only the writer sees.
+    pending: Mutex<Quadtree<U, V>>,
+}
+
+impl<U, V> SnapshotQuadtree<U, V>
+where
+    U: PrimInt + Default,
+    V: Clone,
+{
+    // pub
+
+    /// Creates a new, empty quadtree with some depth.
+    /// See [`Quadtree::new()`].
+    ///
+    /// [`Quadtree::new()`]: ../struct.Quadtree.html#method.new
+    pub fn new(depth: usize) -> Self {
+        Self::from(Quadtree::new(depth))
+    }
+
+    /// Creates a new, empty quadtree with some depth and an explicit anchor.
+    /// See [`Quadtree::new_with_anchor()`].
+    ///
+    /// [`Quadtree::new_with_anchor()`]: ../struct.Quadtree.html#method.new_with_anchor
+    pub fn new_with_anchor(anchor: Point<U>, depth: usize) -> Self {
+        Self::from(Quadtree::new_with_anchor(anchor, depth))
+    }
+
+    /// Returns the most recently published version of the quadtree.
+    ///
+    /// The snapshot never changes, however long it is kept around, and holding on to it doesn't
+    /// block the writer.
+    pub fn snapshot(&self) -> Arc<Quadtree<U, V>> {
+        Arc::clone(&self.published.read().expect(POISONED))
+    }
+
+    /// Makes changes to the next version of the quadtree, returning whatever `f` returns.
+    ///
+    /// The changes aren't visible to [`.snapshot()`] until the next [`.publish()`]. Calls from
+    /// several threads at once are serialized.
+    ///
+    /// ```
+    /// use quadtree_rs::concurrent::SnapshotQuadtree;
+    ///
+    /// let qt = SnapshotQuadtree::<u32, char>::new(2);
+    ///
+    /// let handle = qt.write(|next| next.insert_pt((1, 1).into(), 'a')).unwrap();
+    /// assert!(qt.snapshot().get(handle).is_none());
+    ///
+    /// qt.publish();
+    /// assert_eq!(qt.snapshot().get(handle).unwrap().value_ref(), &'a');
+    /// ```
+    ///
+    /// [`.snapshot()`]: #method.snapshot
+    /// [`.publish()`]: #method.publish
+    pub fn write<F, R>(&self, f: F) -> R
+    where
+        F: FnOnce(&mut Quadtree<U, V>) -> R,
+    {
+        f(&mut self.pending.lock().expect(POISONED))
+    }
+
+    /// Publishes the pending version of the quadtree, so that every later [`.snapshot()`] sees
+    /// the changes made by [`.write()`] so far. Returns the newly published snapshot.
+    ///
+    /// This copies the whole pending version, so it's best called once per batch of changes
+    /// rather than after every one.
+    ///
+    /// [`.snapshot()`]: #method.snapshot
+    /// [`.write()`]: #method.write
+    pub fn publish(&self) -> Arc<Quadtree<U, V>> {
+        let next = Arc::new(self.pending.lock().expect(POISONED).clone());
+        let previous = mem::replace(
+            &mut *self.published.write().expect(POISONED),
+            Arc::clone(&next),
+        );
+        // If this was the last reference to the previous version, it's freed here, after the
+        // lock has been released rather than while readers are waiting on it.
+        drop(previous);
+        next
+    }
+
+    /// Consumes the snapshot quadtree, returning the pending version, including any changes
+    /// which were never published.
+    pub fn into_quadtree(self) -> Quadtree<U, V> {
+        self.pending.into_inner().expect(POISONED)
+    }
+}
+
+impl<U, V> From<Quadtree<U, V>> for SnapshotQuadtree<U, V>
+where
+    U: PrimInt + Default,
+    V: Clone,
+{
+    /// Wraps a quadtree, publishing it as the first snapshot.
+    fn from(qt: Quadtree<U, V>) -> Self {
+        Self {
+            published: RwLock::new(Arc::new(qt.clone())),
+            pending: Mutex::new(qt),
+        }
+    }
+}
@@ -0,0 +1,262 @@
+// Copyright 2019 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use {
+    crate::{
+        area::{Area, AreaBuilder},
+        point::Point,
+        Quadtree,
+    },
+    num::PrimInt,
+    std::{default::Default, hash::BuildHasher},
+};
+
+// How the coverage of two sets of regions is combined.
+#[derive(Clone, Copy)]
+pub(crate) enum SetOperation {
+    Union,
+    Intersection,
+    Difference,
+}
+
+impl SetOperation {
+    fn keeps(self, ours: bool, theirs: bool) -> bool {
+        match self {
+            SetOperation::Union => ours || theirs,
+            SetOperation::Intersection => ours && theirs,
+            SetOperation::Difference => ours && !theirs,
+        }
+    }
+}
+
+impl<U, V, S> Quadtree<U, V, S>
+where
+    U: PrimInt + Default,
+    S: BuildHasher,
+{
+    // pub
+
+    /// The area covered by this quadtree's regions, or by any of `other`, as a new quadtree of
+    /// disjoint regions.
+    ///
+    /// The new quadtree has the same anchor, depth and bounds as this one, and the parts of
+    /// `other` which lie outside those bounds are ignored. `other` can be the
+    /// [`.regions()`] of another quadtree, or any list of regions.
+    /// ```
+    /// use quadtree_rs::{area::AreaBuilder, Quadtree};
+    ///
+    /// let mut roads = Quadtree::<u32, &str>::new(4);
+    /// let road = AreaBuilder::default()
+    ///     .anchor((0, 4).into())
+    ///     .dimensions((16, 2))
+    ///     .build().unwrap();
+    /// roads.insert(road, "high street");
+    ///
+    /// let mut rivers = Quadtree::<u32, &str>::new(4);
+    /// let river = AreaBuilder::default()
+    ///     .anchor((6, 0).into())
+    ///     .dimensions((2, 16))
+    ///     .build().unwrap();
+    /// rivers.insert(river, "the river");
+    ///
+    /// let covered = roads.coverage_union(rivers.regions());
+    /// let area: u32 = covered.regions().map(|r| r.width() * r.height()).sum();
+    /// assert_eq!(area, 32 + 32 - 4);
+    /// ```
+    ///
+    /// [`.regions()`]: #method.regions
+    pub fn coverage_union<I>(&self, other: I) -> Quadtree<U, ()>
+    where
+        I: IntoIterator<Item = Area<U>>,
+    {
+        self.combine_coverage(other, SetOperation::Union)
+    }
+
+    /// The area covered both by this quadtree's regions and by `other`, as a new quadtree of
+    /// disjoint regions. See [`.coverage_union()`].
+    /// ```
+    /// use quadtree_rs::{area::AreaBuilder, Quadtree};
+    ///
+    /// let mut roads = Quadtree::<u32, &str>::new(4);
+    /// let road = AreaBuilder::default()
+    ///     .anchor((0, 4).into())
+    ///     .dimensions((16, 2))
+    ///     .build().unwrap();
+    /// roads.insert(road, "high street");
+    ///
+    /// let river = AreaBuilder::default()
+    ///     .anchor((6, 0).into())
+    ///     .dimensions((2, 16))
+    ///     .build().unwrap();
+    ///
+    /// // The bridge.
+    /// let bridges = roads.coverage_intersection(vec![river]);
+    /// let bridge = AreaBuilder::default()
+    ///     .anchor((6, 4).into())
+    ///     .dimensions((2, 2))
+    ///     .build().unwrap();
+    /// assert_eq!(bridges.regions().collect::<Vec<_>>(), vec![bridge]);
+    /// ```
+    ///
+    /// [`.coverage_union()`]: #method.coverage_union
+    pub fn coverage_intersection<I>(&self, other: I) -> Quadtree<U, ()>
+    where
+        I: IntoIterator<Item = Area<U>>,
+    {
+        self.combine_coverage(other, SetOperation::Intersection)
+    }
+
+    /// The area covered by this quadtree's regions but not by `other`, as a new quadtree of
+    /// disjoint regions. See [`.coverage_union()`].
+    /// ```
+    /// use quadtree_rs::{area::AreaBuilder, Quadtree};
+    ///
+    /// let mut map = Quadtree::<u32, ()>::new(4);
+    /// let land = AreaBuilder::default().anchor((0, 0).into()).dimensions((8, 8)).build().unwrap();
+    /// map.insert(land, ());
+    ///
+    /// let lake = AreaBuilder::default().anchor((2, 2).into()).dimensions((2, 2)).build().unwrap();
+    /// let dry = map.coverage_difference(vec![lake]);
+    ///
+    /// assert!(dry.query(lake).next().is_none());
+    /// assert_eq!(dry.regions().map(|r| r.width() * r.height()).sum::<u32>(), 64 - 4);
+    /// ```
+    ///
+    /// [`.coverage_union()`]: #method.coverage_union
+    pub fn coverage_difference<I>(&self, other: I) -> Quadtree<U, ()>
+    where
+        I: IntoIterator<Item = Area<U>>,
+    {
+        self.combine_coverage(other, SetOperation::Difference)
+    }
+
+    // fn
+
+    // Sweeps across the quadtree from west to east, cutting it into columns at every vertical edge
+    // of either set of regions. Within a column each region covers the full width or none of it, so
+    // the coverage of each set is a list of vertical spans, and the result is one more. Rectangles
+    // are only emitted once their spans stop, so that they grow as wide as they can.
+    pub(crate) fn combine_coverage<I>(&self, other: I, op: SetOperation) -> Quadtree<U, ()>
+    where
+        I: IntoIterator<Item = Area<U>>,
+    {
+        let mut theirs = Quadtree::<U, ()>::new_with_anchor(self.anchor(), self.depth());
+        theirs.bounds = self.bounds;
+        for region in other {
+            if let Some(region) = self.bounds.intersection(region) {
+                theirs.insert(region, ());
+            }
+        }
+
+        let mut xs: Vec<U> = self
+            .regions()
+            .chain(theirs.regions())
+            .flat_map(|region| [region.left_edge(), region.right_edge()])
+            .collect();
+        xs.sort_unstable();
+        xs.dedup();
+
+        let mut combined = Quadtree::<U, ()>::new_with_anchor(self.anchor(), self.depth());
+        combined.bounds = self.bounds;
+        // The spans of the previous column, and the column where each of them started.
+        let mut open: Vec<((U, U), U)> = vec![];
+        for column in xs.windows(2) {
+            let (west, east) = (column[0], column[1]);
+            let strip = AreaBuilder::default()
+                .anchor(Point {
+                    x: west,
+                    y: self.bounds.top_edge(),
+                })
+                .dimensions((east - west, self.bounds.height()))
+                .build()
+                .expect("Unexpected error in Quadtree::combine_coverage().");
+            let spans = Self::combine_spans(
+                &Self::spans(self.query(strip).map(|e| e.area())),
+                &Self::spans(theirs.query(strip).map(|e| e.area())),
+                op,
+            );
+
+            let (continuing, stopped) =
+                open.into_iter().partition(|(span, _)| spans.contains(span));
+            for (span, start) in stopped {
+                Self::emit(&mut combined, span, start, west);
+            }
+            open = continuing;
+            for span in spans {
+                if !open.iter().any(|(open_span, _)| *open_span == span) {
+                    open.push((span, west));
+                }
+            }
+        }
+        if let Some(&last) = xs.last() {
+            for (span, start) in open {
+                Self::emit(&mut combined, span, start, last);
+            }
+        }
+        combined
+    }
+
+    // The vertical spans which @regions cover, sorted and merged.
+    fn spans<R>(regions: R) -> Vec<(U, U)>
+    where
+        R: Iterator<Item = Area<U>>,
+    {
+        let mut spans: Vec<(U, U)> = regions
+            .map(|region| (region.top_edge(), region.bottom_edge()))
+            .collect();
+        spans.sort_unstable();
+        let mut merged: Vec<(U, U)> = vec![];
+        for (top, bottom) in spans {
+            match merged.last_mut() {
+                Some(last) if top <= last.1 => last.1 = last.1.max(bottom),
+                _ => merged.push((top, bottom)),
+            }
+        }
+        merged
+    }
+
+    // The spans covered by @ours and @theirs together, as @op has it.
+    fn combine_spans(ours: &[(U, U)], theirs: &[(U, U)], op: SetOperation) -> Vec<(U, U)> {
+        let mut ys: Vec<U> = ours
+            .iter()
+            .chain(theirs)
+            .flat_map(|&(top, bottom)| [top, bottom])
+            .collect();
+        ys.sort_unstable();
+        ys.dedup();
+
+        let covers =
+            |spans: &[(U, U)], y: U| spans.iter().any(|&(top, bottom)| top <= y && y < bottom);
+        let mut combined: Vec<(U, U)> = vec![];
+        for band in ys.windows(2) {
+            if !op.keeps(covers(ours, band[0]), covers(theirs, band[0])) {
+                continue;
+            }
+            match combined.last_mut() {
+                Some(last) if last.1 == band[0] => last.1 = band[1],
+                _ => combined.push((band[0], band[1])),
+            }
+        }
+        combined
+    }
+
+    fn emit(qt: &mut Quadtree<U, ()>, (top, bottom): (U, U), west: U, east: U) {
+        let region = AreaBuilder::default()
+            .anchor(Point { x: west, y: top })
+            .dimensions((east - west, bottom - top))
+            .build()
+            .expect("Unexpected error in Quadtree::combine_coverage().");
+        qt.insert(region, ());
+    }
+}
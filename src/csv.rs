@@ -0,0 +1,166 @@
+// Copyright 2019 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use {
+    crate::{
+        area::{Area, AreaBuilder},
+        error::CsvRowError,
+        point::Point,
+        quantize, Quadtree,
+    },
+    num::PrimInt,
+    std::{
+        default::Default,
+        io::{self, BufRead, BufReader, Read},
+        str::FromStr,
+    },
+};
+
+impl<U, V> Quadtree<U, V>
+where
+    U: PrimInt + Default,
+    V: FromStr,
+{
+    // pub
+
+    /// Reads rows of `x,y,value` or `x,y,w,h,value` from `reader` into a new quadtree just large
+    /// enough to hold them, along with the rows which couldn't be loaded.
+    ///
+    /// Coordinates and dimensions may be fractional. They're divided by `cell_size` and each row
+    /// covers the smallest run of whole cells which holds it, so a row with no width or height,
+    /// such as every `x,y,value` row, covers a single cell. The value is parsed from the last
+    /// field, which may be double-quoted to hold commas, with `""` standing for a quote. Fields are
+    /// trimmed, blank lines are skipped, and so is a first row whose `x` isn't a number, taking it
+    /// to be a header. Fields can't span lines.
+    ///
+    /// Rows which don't parse, or whose cells fall outside the coordinates `U` can represent, are
+    /// left out and reported, each as a [`CsvRowError`] naming its line. The `n`th row loaded is
+    /// given the handle `n`, just as with [`.from_regions()`]. Only a failure to read from
+    /// `reader` is returned as an error.
+    /// ```
+    /// use quadtree_rs::Quadtree;
+    ///
+    /// let csv = "x,y,w,h,name\n\
+    ///            0.5,1.5,tree\n\
+    ///            2,2,3.5,1,\"pond, small\"\n\
+    ///            4,oops,well\n";
+    /// let (qt, errors) = Quadtree::<u32, String>::from_csv(csv.as_bytes(), 1.0).unwrap();
+    ///
+    /// assert_eq!(qt.len(), 2);
+    /// let pond = qt.get(1).unwrap();
+    /// assert_eq!(pond.value_ref(), "pond, small");
+    /// assert_eq!((pond.width(), pond.height()), (4, 1));
+    ///
+    /// assert_eq!(errors.len(), 1);
+    /// assert_eq!(errors[0].line(), 4);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `cell_size` isn't finite and positive, or if the rows are too spread out for a
+    /// square covering them to be representable in `U`.
+    ///
+    /// [`CsvRowError`]: error/struct.CsvRowError.html
+    /// [`.from_regions()`]: #method.from_regions
+    pub fn from_csv<R: Read>(reader: R, cell_size: f64) -> io::Result<(Self, Vec<CsvRowError>)> {
+        assert!(
+            cell_size.is_finite() && cell_size > 0.0,
+            "The cells must have a finite, positive size."
+        );
+        let (mut items, mut errors) = (vec![], vec![]);
+        let mut first = true;
+        for (i, line) in BufReader::new(reader).lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let fields = Self::fields(&line);
+            // Every line has at least one field.
+            let is_header = first && fields.as_ref().is_ok_and(|f| f[0].parse::<f64>().is_err());
+            first = false;
+            if is_header {
+                continue;
+            }
+            match fields.and_then(|fields| Self::row(&fields, cell_size)) {
+                Ok(item) => items.push(item),
+                Err(reason) => errors.push(CsvRowError::new(i + 1, reason)),
+            }
+        }
+        Ok((Self::from_regions(items), errors))
+    }
+
+    // fn
+
+    fn row(fields: &[String], cell_size: f64) -> Result<(Area<U>, V), String> {
+        let number = |field: &String| {
+            field
+                .parse::<f64>()
+                .ok()
+                .filter(|f| f.is_finite())
+                .ok_or_else(|| format!("{:?} is not a number.", field))
+        };
+        let (x, y, w, h, value) = match fields {
+            [x, y, value] => (number(x)?, number(y)?, 0.0, 0.0, value),
+            [x, y, w, h, value] => (number(x)?, number(y)?, number(w)?, number(h)?, value),
+            _ => {
+                return Err(format!(
+                    "Expected 3 or 5 fields, but found {}.",
+                    fields.len()
+                ))
+            }
+        };
+        if w < 0.0 || h < 0.0 {
+            return Err("Widths and heights may not be negative.".to_string());
+        }
+
+        let (left, right) = quantize::cover(x / cell_size, (x + w) / cell_size);
+        let (top, bottom) = quantize::cover(y / cell_size, (y + h) / cell_size);
+        let coord = |v: f64| {
+            U::from(v).ok_or_else(|| "The row doesn't fit in the coordinate type.".to_string())
+        };
+        let region = AreaBuilder::default()
+            .anchor(Point {
+                x: coord(left)?,
+                y: coord(top)?,
+            })
+            .dimensions((coord(right - left)?, coord(bottom - top)?))
+            .build()?;
+        let value = value
+            .parse()
+            .map_err(|_| format!("{:?} is not a value.", value))?;
+        Ok((region, value))
+    }
+
+    // Splits @line at the commas which aren't within double quotes, and trims each field.
+    fn fields(line: &str) -> Result<Vec<String>, String> {
+        let (mut fields, mut field, mut quoted) = (vec![], String::new(), false);
+        let mut chars = line.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '"' if quoted && chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                '"' => quoted = !quoted,
+                ',' if !quoted => fields.push(std::mem::take(&mut field)),
+                _ => field.push(c),
+            }
+        }
+        if quoted {
+            return Err("A quoted field isn't closed.".to_string());
+        }
+        fields.push(field);
+        Ok(fields.iter().map(|f| f.trim().to_string()).collect())
+    }
+}
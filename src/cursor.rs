@@ -0,0 +1,300 @@
+// Copyright 2019 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Moving around a quadtree's cells by hand.
+//!
+//! A [`Cursor`] starts at the root cell of a [`Quadtree`] and can be moved down into any of the
+//! four cells beneath the one it's on, and back up again. At each cell it shows the cell's region
+//! and the entries held there, so that algorithms which need to steer their own way through the
+//! tree, such as streaming in levels of detail, can be written outside the crate.
+//!
+//! ```
+//! use quadtree_rs::{cursor::Quadrant, Quadtree};
+//!
+//! let mut qt = Quadtree::<u32, char>::new(3);
+//! qt.insert_pt((1, 1).into(), 'a');
+//!
+//! let mut cursor = qt.cursor();
+//! assert_eq!(cursor.region().width(), 8);
+//! assert_eq!(cursor.len(), 1);
+//!
+//! // Follow 'a' down to the cell which holds it.
+//! for quadrant in [Quadrant::NorthWest, Quadrant::NorthWest, Quadrant::SouthEast] {
+//!     assert!(cursor.descend(quadrant));
+//! }
+//! assert_eq!(cursor.depth(), 3);
+//! assert!(cursor.is_leaf());
+//! assert_eq!(cursor.items().map(|e| *e.value_ref()).collect::<Vec<_>>(), vec!['a']);
+//!
+//! assert!(cursor.ascend());
+//! assert_eq!(cursor.region().width(), 2);
+//! ```
+//!
+//! [`Cursor`]: struct.Cursor.html
+//! [`Quadtree`]: ../struct.Quadtree.html
+
+use {
+    crate::{
+        area::Area,
+        entry::Entry,
+        qtinner::{Node, NodeId, QTInner},
+        Quadtree,
+    },
+    num::PrimInt,
+    std::{collections::hash_map::RandomState, default::Default, hash::BuildHasher},
+};
+
+/// One of the four cells which a cell is split into.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Quadrant {
+    /// The top-right quarter.
+    NorthEast,
+    /// The top-left quarter.
+    NorthWest,
+    /// The bottom-right quarter.
+    SouthEast,
+    /// The bottom-left quarter.
+    SouthWest,
+}
+
+impl Quadrant {
+    // Every quadrant, in the order of a node's subquadrants.
+    pub(crate) const ALL: [Quadrant; 4] = [
+        Quadrant::NorthEast,
+        Quadrant::NorthWest,
+        Quadrant::SouthEast,
+        Quadrant::SouthWest,
+    ];
+
+    // The position of this quadrant among a node's subquadrants.
+    pub(crate) fn index(self) -> usize {
+        match self {
+            Quadrant::NorthEast => 0,
+            Quadrant::NorthWest => 1,
+            Quadrant::SouthEast => 2,
+            Quadrant::SouthWest => 3,
+        }
+    }
+
+    pub(crate) fn from_index(index: usize) -> Self {
+        match index {
+            0 => Quadrant::NorthEast,
+            1 => Quadrant::NorthWest,
+            2 => Quadrant::SouthEast,
+            _ => Quadrant::SouthWest,
+        }
+    }
+
+    // The quadrant of a cell which lies east and/or south of its center.
+    pub(crate) fn at(east: bool, south: bool) -> Self {
+        match (east, south) {
+            (true, false) => Quadrant::NorthEast,
+            (false, false) => Quadrant::NorthWest,
+            (true, true) => Quadrant::SouthEast,
+            (false, true) => Quadrant::SouthWest,
+        }
+    }
+
+    pub(crate) fn is_east(self) -> bool {
+        matches!(self, Quadrant::NorthEast | Quadrant::SouthEast)
+    }
+
+    pub(crate) fn is_south(self) -> bool {
+        matches!(self, Quadrant::SouthEast | Quadrant::SouthWest)
+    }
+
+    // The digit which stands for this quadrant in a quadkey.
+    pub(crate) fn quadkey_digit(self) -> char {
+        match self {
+            Quadrant::NorthWest => '0',
+            Quadrant::NorthEast => '1',
+            Quadrant::SouthWest => '2',
+            Quadrant::SouthEast => '3',
+        }
+    }
+
+    pub(crate) fn from_quadkey_digit(digit: char) -> Option<Self> {
+        match digit {
+            '0' => Some(Quadrant::NorthWest),
+            '1' => Some(Quadrant::NorthEast),
+            '2' => Some(Quadrant::SouthWest),
+            '3' => Some(Quadrant::SouthEast),
+            _ => None,
+        }
+    }
+}
+
+/// A position within the cells of a [`Quadtree`], which can be moved up and down the tree.
+///
+/// This struct is created by the [`cursor`] method on [`Quadtree`]. See the [module-level
+/// documentation] for more.
+///
+/// [`cursor`]: ../struct.Quadtree.html#method.cursor
+/// [`Quadtree`]: ../struct.Quadtree.html
+/// [module-level documentation]: index.html
+#[derive(Debug)]
+pub struct Cursor<'a, U, V, S = RandomState>
+where
+    U: PrimInt + Default,
+{
+    tree: &'a Quadtree<U, V, S>,
+    // The nodes from the root down to the one the cursor is on.
+    path: Vec<NodeId>,
+}
+
+impl<'a, U, V, S> Cursor<'a, U, V, S>
+where
+    U: PrimInt + Default,
+    S: BuildHasher,
+{
+    // pub
+
+    /// The area which the current cell covers.
+    pub fn region(&self) -> Area<U> {
+        self.node().region()
+    }
+
+    /// The area which every region held at or beneath the current cell lies within. This is the
+    /// cell's region, unless the quadtree is [loose].
+    ///
+    /// [loose]: ../struct.Quadtree.html#method.set_looseness
+    pub fn bounds(&self) -> Area<U> {
+        self.node().bounds()
+    }
+
+    /// How many levels below the root the current cell is. The root's cell is at depth zero.
+    pub fn depth(&self) -> usize {
+        self.path.len() - 1
+    }
+
+    /// The [quadkey] of the current cell: a digit for each step down from the root, `0` for the
+    /// north-west quarter, `1` for the north-east, `2` for the south-west and `3` for the
+    /// south-east. The root's is empty.
+    ///
+    /// [quadkey]: ../struct.Quadtree.html#method.quadkey
+    pub fn quadkey(&self) -> String {
+        let inner = &self.tree.inner;
+        self.path
+            .windows(2)
+            .map(|step| {
+                let sqs = inner
+                    .node(step[0])
+                    .subquadrants()
+                    .expect("A cursor only steps into subquadrants.");
+                let index = sqs.iter().position(|&id| id == step[1]).unwrap();
+                Quadrant::from_index(index).quadkey_digit()
+            })
+            .collect()
+    }
+
+    /// The number of entries held at or beneath the current cell.
+    pub fn len(&self) -> usize {
+        self.node().len()
+    }
+
+    /// Whether there are no entries at or beneath the current cell.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Whether the current cell is the root's.
+    pub fn is_root(&self) -> bool {
+        self.path.len() == 1
+    }
+
+    /// Whether the current cell hasn't been split, so that there's nowhere to descend to.
+    pub fn is_leaf(&self) -> bool {
+        self.node().subquadrants().is_none()
+    }
+
+    /// The entries held by the current cell itself, in no particular order. Entries held beneath
+    /// it aren't included.
+    pub fn items(&self) -> impl Iterator<Item = &'a Entry<U, V>> + 'a {
+        let store = &self.tree.store;
+        self.tree
+            .inner
+            .node(self.id())
+            .handles()
+            .iter()
+            .filter_map(move |handle| store.get(handle))
+    }
+
+    /// Moves the cursor down into the given quarter of the current cell. Returns whether it
+    /// moved; it doesn't if the current cell is a leaf.
+    pub fn descend(&mut self, quadrant: Quadrant) -> bool {
+        match self.node().subquadrants() {
+            Some(sqs) => {
+                self.path.push(sqs[quadrant.index()]);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Moves the cursor up to the cell which the current cell is part of. Returns whether it
+    /// moved; it doesn't if the cursor is at the root.
+    pub fn ascend(&mut self) -> bool {
+        if self.is_root() {
+            return false;
+        }
+        self.path.pop();
+        true
+    }
+
+    /// Moves the cursor back up to the root.
+    pub fn reset(&mut self) {
+        self.path.truncate(1);
+    }
+
+    // fn
+
+    fn id(&self) -> NodeId {
+        *self.path.last().expect("A cursor always has a root.")
+    }
+
+    fn node(&self) -> &'a Node<U> {
+        self.tree.inner.node(self.id())
+    }
+}
+
+// A cursor only holds a reference and its path, so it can be cloned whatever it's a cursor into.
+impl<U, V, S> Clone for Cursor<'_, U, V, S>
+where
+    U: PrimInt + Default,
+{
+    fn clone(&self) -> Self {
+        Cursor {
+            tree: self.tree,
+            path: self.path.clone(),
+        }
+    }
+}
+
+impl<U, V, S> Quadtree<U, V, S>
+where
+    U: PrimInt + Default,
+    S: BuildHasher,
+{
+    // pub
+
+    /// A [`Cursor`] on the root cell of the quadtree, for moving around its cells by hand.
+    ///
+    /// [`Cursor`]: cursor/struct.Cursor.html
+    pub fn cursor(&self) -> Cursor<'_, U, V, S> {
+        Cursor {
+            tree: self,
+            path: vec![QTInner::<U>::ROOT],
+        }
+    }
+}
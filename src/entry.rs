@@ -64,7 +64,8 @@ use {
 ///
 /// [`Quadtree`]: ../struct.Quadtree.html
 // TODO(ambuc): Entry should hold Box<V> for better return-by-value semantics.
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Entry<U, V>
 where
     U: PrimInt + Default,
@@ -109,6 +110,11 @@ where
         &self.value
     }
 
+    /// Consumes the entry, returning its region and value.
+    pub fn into_parts(self) -> (Area<U>, V) {
+        (self.region, self.value)
+    }
+
     // pub(crate)
 
     pub(crate) fn new((region, value): (Area<U>, V), handle: u64) -> Self {
@@ -119,6 +125,14 @@ where
         }
     }
 
+    pub(crate) fn area_ref(&self) -> &Area<U> {
+        &self.region
+    }
+
+    pub(crate) fn set_area(&mut self, region: Area<U>) {
+        self.region = region;
+    }
+
     pub(crate) fn dimensions(&self) -> (U, U) {
         self.area().dimensions()
     }
@@ -0,0 +1,76 @@
+// Copyright 2019 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Errors which can be returned by quadtree operations.
+
+use std::{error::Error, fmt};
+
+/// The reason a [`Quadtree::try_insert()`] was refused.
+///
+/// [`Quadtree::try_insert()`]: ../struct.Quadtree.html#method.try_insert
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InsertError {
+    /// The region doesn't fit within the quadtree's bounds, and the quadtree couldn't grow to fit
+    /// it.
+    OutOfBounds,
+    /// The region has a zero (or, for signed coordinates, negative) width or height.
+    ZeroSize,
+    /// The far edge of the region isn't representable in the coordinate type.
+    Overflow,
+}
+
+impl fmt::Display for InsertError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            InsertError::OutOfBounds => "The region doesn't fit within the quadtree.",
+            InsertError::ZeroSize => "Regions may not have nonpositive widths or heights.",
+            InsertError::Overflow => "The region's far edge overflows its coordinate type.",
+        })
+    }
+}
+
+impl Error for InsertError {}
+
+/// A row which [`Quadtree::from_csv()`] couldn't load, and why.
+///
+/// [`Quadtree::from_csv()`]: ../struct.Quadtree.html#method.from_csv
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CsvRowError {
+    line: usize,
+    reason: String,
+}
+
+impl CsvRowError {
+    pub(crate) fn new(line: usize, reason: String) -> Self {
+        CsvRowError { line, reason }
+    }
+
+    /// The line the row is on, counting from one.
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// Why the row couldn't be loaded.
+    pub fn reason(&self) -> &str {
+        &self.reason
+    }
+}
+
+impl fmt::Display for CsvRowError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Line {}: {}", self.line, self.reason)
+    }
+}
+
+impl Error for CsvRowError {}
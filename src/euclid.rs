@@ -0,0 +1,187 @@
+// Copyright 2019 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Conversions to and from the types of [`euclid`], behind the `euclid` feature.
+//!
+//! A [`Point2D`] converts into a [`Point`], and a [`Rect`] into an [`Area`], so they can be
+//! handed straight to the quadtree. Neither of this crate's types has a unit, so converting back
+//! gives an [`UnknownUnit`]. To keep track of the unit, convert into a [`Typed`] instead, which
+//! carries it alongside the point or area and converts back into the euclid type it came from.
+//!
+//! ```
+//! use {
+//!     euclid::{Point2D, Rect, Size2D},
+//!     quadtree_rs::{area::Area, euclid::Typed, Quadtree},
+//!     std::convert::TryFrom,
+//! };
+//!
+//! enum ScreenSpace {}
+//!
+//! let button: Rect<u32, ScreenSpace> = Rect::new(Point2D::new(10, 20), Size2D::new(30, 8));
+//!
+//! let mut qt = Quadtree::<u32, &str>::new(6);
+//! qt.insert(Area::try_from(button).unwrap(), "ok");
+//!
+//! let click: Point2D<u32, ScreenSpace> = Point2D::new(12, 25);
+//! let hit = qt.query_pt(click.into()).next().unwrap();
+//!
+//! let hit_rect: Rect<u32, ScreenSpace> = Typed::new(hit.area()).into();
+//! assert_eq!(hit_rect, button);
+//! ```
+//!
+//! [`euclid`]: https://docs.rs/euclid
+//! [`Point2D`]: https://docs.rs/euclid/0.22/euclid/struct.Point2D.html
+//! [`Rect`]: https://docs.rs/euclid/0.22/euclid/struct.Rect.html
+//! [`UnknownUnit`]: https://docs.rs/euclid/0.22/euclid/struct.UnknownUnit.html
+//! [`Point`]: ../point/struct.Point.html
+//! [`Area`]: ../area/struct.Area.html
+//! [`Typed`]: struct.Typed.html
+
+use {
+    crate::{area::Area, point::Point},
+    ::euclid::{Point2D, Rect, Size2D, UnknownUnit},
+    num::PrimInt,
+    std::{convert::TryFrom, default::Default, fmt, marker::PhantomData},
+};
+
+/// A [`Point`] or [`Area`] tagged with the euclid unit it's measured in.
+///
+/// [`Point`]: ../point/struct.Point.html
+/// [`Area`]: ../area/struct.Area.html
+pub struct Typed<T, Unit> {
+    value: T,
+    unit: PhantomData<Unit>,
+}
+
+impl<T, Unit> Typed<T, Unit> {
+    /// Tags `value` with the unit `Unit`.
+    pub fn new(value: T) -> Self {
+        Typed {
+            value,
+            unit: PhantomData,
+        }
+    }
+
+    /// The untagged value.
+    pub fn get(self) -> T {
+        self.value
+    }
+}
+
+// Units are usually uninhabited types which derive nothing, so none of these can be derived.
+impl<T: Clone, Unit> Clone for Typed<T, Unit> {
+    fn clone(&self) -> Self {
+        Self::new(self.value.clone())
+    }
+}
+
+impl<T: Copy, Unit> Copy for Typed<T, Unit> {}
+
+impl<T: PartialEq, Unit> PartialEq for Typed<T, Unit> {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
+impl<T: Eq, Unit> Eq for Typed<T, Unit> {}
+
+impl<T: fmt::Debug, Unit> fmt::Debug for Typed<T, Unit> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Typed").field(&self.value).finish()
+    }
+}
+
+impl<U, Unit> From<Point2D<U, Unit>> for Point<U>
+where
+    U: PrimInt,
+{
+    fn from(point: Point2D<U, Unit>) -> Self {
+        Self {
+            x: point.x,
+            y: point.y,
+        }
+    }
+}
+
+impl<U> From<Point<U>> for Point2D<U, UnknownUnit>
+where
+    U: PrimInt,
+{
+    fn from(point: Point<U>) -> Self {
+        Point2D::new(point.x, point.y)
+    }
+}
+
+impl<U, Unit> From<Point2D<U, Unit>> for Typed<Point<U>, Unit>
+where
+    U: PrimInt,
+{
+    fn from(point: Point2D<U, Unit>) -> Self {
+        Self::new(point.into())
+    }
+}
+
+impl<U, Unit> From<Typed<Point<U>, Unit>> for Point2D<U, Unit>
+where
+    U: PrimInt,
+{
+    fn from(point: Typed<Point<U>, Unit>) -> Self {
+        Point2D::new(point.value.x, point.value.y)
+    }
+}
+
+/// A rectangle with no width or height has no region.
+impl<U, Unit> TryFrom<Rect<U, Unit>> for Area<U>
+where
+    U: PrimInt + Default,
+{
+    type Error = String;
+
+    fn try_from(rect: Rect<U, Unit>) -> Result<Self, Self::Error> {
+        Self::new(rect.origin.into(), (rect.size.width, rect.size.height))
+    }
+}
+
+impl<U> From<Area<U>> for Rect<U, UnknownUnit>
+where
+    U: PrimInt + Default,
+{
+    fn from(area: Area<U>) -> Self {
+        Typed::new(area).into()
+    }
+}
+
+impl<U, Unit> TryFrom<Rect<U, Unit>> for Typed<Area<U>, Unit>
+where
+    U: PrimInt + Default,
+{
+    type Error = String;
+
+    fn try_from(rect: Rect<U, Unit>) -> Result<Self, Self::Error> {
+        Area::try_from(rect).map(Self::new)
+    }
+}
+
+impl<U, Unit> From<Typed<Area<U>, Unit>> for Rect<U, Unit>
+where
+    U: PrimInt + Default,
+{
+    fn from(area: Typed<Area<U>, Unit>) -> Self {
+        let area = area.value;
+        Rect::new(
+            Typed::new(area.anchor()).into(),
+            Size2D::new(area.width(), area.height()),
+        )
+    }
+}
@@ -0,0 +1,114 @@
+// Copyright 2019 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// Conversions to and from the types of `geo-types`, behind the `geo` feature.
+
+use {
+    crate::{area::Area, point::Point},
+    geo_types::{Coord, CoordNum, Rect},
+    num::PrimInt,
+    std::{convert::TryFrom, default::Default},
+};
+
+impl<U> From<Coord<U>> for Point<U>
+where
+    U: PrimInt + CoordNum,
+{
+    fn from(coord: Coord<U>) -> Self {
+        Self {
+            x: coord.x,
+            y: coord.y,
+        }
+    }
+}
+
+impl<U> From<Point<U>> for Coord<U>
+where
+    U: PrimInt + CoordNum,
+{
+    fn from(point: Point<U>) -> Self {
+        Self {
+            x: point.x,
+            y: point.y,
+        }
+    }
+}
+
+impl<U> From<geo_types::Point<U>> for Point<U>
+where
+    U: PrimInt + CoordNum,
+{
+    fn from(point: geo_types::Point<U>) -> Self {
+        point.0.into()
+    }
+}
+
+impl<U> From<Point<U>> for geo_types::Point<U>
+where
+    U: PrimInt + CoordNum,
+{
+    fn from(point: Point<U>) -> Self {
+        Self(point.into())
+    }
+}
+
+/// A region covers the cells from the rectangle's minimum corner up to, but not including, its
+/// maximum corner, so a rectangle with no width or height has no region.
+///
+/// ```
+/// use {
+///     geo_types::{coord, Rect},
+///     quadtree_rs::{area::Area, Quadtree},
+///     std::convert::TryFrom,
+/// };
+///
+/// let mut qt = Quadtree::<i32, &str>::new_with_anchor((-8, -8).into(), 4);
+/// let rect = Rect::new(coord! {x: 3, y: -1}, coord! {x: 1, y: 2});
+/// qt.insert(Area::try_from(rect).unwrap(), "rect");
+///
+/// let found = qt.query_pt(coord! {x: 2, y: 1}.into()).next().unwrap();
+/// assert_eq!(Rect::from(found.area()), rect);
+///
+/// assert!(Area::try_from(Rect::new(coord! {x: 3, y: 1}, coord! {x: 3, y: 4})).is_err());
+/// ```
+impl<U> TryFrom<Rect<U>> for Area<U>
+where
+    U: PrimInt + Default + CoordNum,
+{
+    type Error = String;
+
+    fn try_from(rect: Rect<U>) -> Result<Self, Self::Error> {
+        Self::new(rect.min().into(), (rect.width(), rect.height()))
+    }
+}
+
+/// The rectangle's maximum corner is the far corner of the region, or as near to it as `U` can
+/// represent.
+impl<U> From<Area<U>> for Rect<U>
+where
+    U: PrimInt + Default + CoordNum,
+{
+    fn from(area: Area<U>) -> Self {
+        Rect::new(
+            Coord {
+                x: area.left_edge(),
+                y: area.top_edge(),
+            },
+            Coord {
+                x: area.right_edge(),
+                y: area.bottom_edge(),
+            },
+        )
+    }
+}
@@ -0,0 +1,316 @@
+// Copyright 2019 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Reading and writing quadtrees as [GeoJSON], behind the `geojson` feature.
+//!
+//! See [`Quadtree::from_geojson()`] and [`Quadtree::to_geojson()`]. Each feature becomes one
+//! entry, whose region is the feature's bounding box and whose value is the feature's
+//! `properties`. A [`Quantizer`] lays a grid over the plane to turn the bounding boxes, which are
+//! in floating-point coordinates such as longitude and latitude, into regions of whole cells.
+//!
+//! ```
+//! use quadtree_rs::{geojson::Quantizer, Quadtree};
+//!
+//! let json = r#"{
+//!     "type": "FeatureCollection",
+//!     "features": [{
+//!         "type": "Feature",
+//!         "geometry": {"type": "Point", "coordinates": [-0.12, 51.5]},
+//!         "properties": {"name": "London"}
+//!     }]
+//! }"#;
+//!
+//! // Cells a tenth of a degree across, counted from (-180, 90).
+//! let quantizer = Quantizer::new(-180.0, 90.0, 0.1);
+//! let qt = Quadtree::<u32, serde_json::Value>::from_geojson(json.as_bytes(), quantizer).unwrap();
+//!
+//! let entry = qt.iter().next().unwrap();
+//! assert_eq!(entry.value_ref()["name"], "London");
+//! assert_eq!((entry.anchor().x(), entry.anchor().y()), (1798, 385));
+//!
+//! let mut out: Vec<u8> = vec![];
+//! qt.to_geojson(&mut out, quantizer).unwrap();
+//! ```
+//!
+//! [GeoJSON]: https://tools.ietf.org/html/rfc7946
+//! [`Quadtree::from_geojson()`]: ../struct.Quadtree.html#method.from_geojson
+//! [`Quadtree::to_geojson()`]: ../struct.Quadtree.html#method.to_geojson
+//! [`Quantizer`]: struct.Quantizer.html
+
+use {
+    crate::{
+        area::{Area, AreaBuilder},
+        point::Point,
+        quantize, Quadtree,
+    },
+    num::PrimInt,
+    serde_json::{json, Value},
+    std::{
+        default::Default,
+        hash::BuildHasher,
+        io::{self, Read, Write},
+    },
+};
+
+/// A grid of square cells laid over the plane, for converting between GeoJSON coordinates and
+/// the coordinates of a [`Quadtree`].
+///
+/// Columns are counted east from the grid's west edge, and rows south from its north edge, so
+/// that the north-west corner of the grid is the quadtree's `(0, 0)` and the quadtree's `y` grows
+/// as the GeoJSON `y` (the latitude) shrinks.
+///
+/// [`Quadtree`]: ../struct.Quadtree.html
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Quantizer {
+    west: f64,
+    north: f64,
+    cell_size: f64,
+}
+
+impl Quantizer {
+    /// A grid whose north-west corner is at `(west, north)`, with cells `cell_size` across.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `west` or `north` isn't finite, or if `cell_size` isn't finite and positive.
+    pub fn new(west: f64, north: f64, cell_size: f64) -> Self {
+        assert!(
+            west.is_finite() && north.is_finite(),
+            "The grid's corner must be finite."
+        );
+        assert!(
+            cell_size.is_finite() && cell_size > 0.0,
+            "The grid's cells must have a finite, positive size."
+        );
+        Quantizer {
+            west,
+            north,
+            cell_size,
+        }
+    }
+
+    // The smallest region of whole cells which covers the bounding box [west, south, east,
+    // north]. Even a bounding box of no size covers a cell.
+    fn region<U>(&self, bbox: [f64; 4]) -> io::Result<Area<U>>
+    where
+        U: PrimInt + Default,
+    {
+        let [west, south, east, north] = bbox;
+        let (left, right) = quantize::cover(self.column(west), self.column(east));
+        let (top, bottom) = quantize::cover(self.row(north), self.row(south));
+        let coord = |v: f64| {
+            U::from(v).ok_or_else(|| {
+                invalid_data(format!(
+                    "The bounding box {:?} doesn't fit in the coordinate type.",
+                    bbox
+                ))
+            })
+        };
+        AreaBuilder::default()
+            .anchor(Point {
+                x: coord(left)?,
+                y: coord(top)?,
+            })
+            .dimensions((coord(right - left)?, coord(bottom - top)?))
+            .build()
+            .map_err(invalid_data)
+    }
+
+    // The bounding box [west, south, east, north] of @region.
+    fn bbox<U>(&self, region: Area<U>) -> [f64; 4]
+    where
+        U: PrimInt + Default,
+    {
+        let f = |u: U| u.to_f64().expect("Coordinates should fit in an f64.");
+        let (x, y) = (f(region.anchor().x()), f(region.anchor().y()));
+        let (w, h) = (f(region.width()), f(region.height()));
+        [
+            self.west + x * self.cell_size,
+            self.north - (y + h) * self.cell_size,
+            self.west + (x + w) * self.cell_size,
+            self.north - y * self.cell_size,
+        ]
+    }
+
+    fn column(&self, x: f64) -> f64 {
+        (x - self.west) / self.cell_size
+    }
+
+    fn row(&self, y: f64) -> f64 {
+        (self.north - y) / self.cell_size
+    }
+}
+
+fn invalid_data(msg: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+// The bounding box [west, south, east, north] of a feature: its "bbox" if it has one, or else
+// that of every position in its geometry.
+fn bbox_of(feature: &Value) -> io::Result<[f64; 4]> {
+    if let Some(bbox) = feature.get("bbox") {
+        let numbers: Vec<f64> = bbox
+            .as_array()
+            .map(|a| a.iter().filter_map(Value::as_f64).collect())
+            .unwrap_or_default();
+        return match numbers[..] {
+            [west, south, east, north] => Ok([west, south, east, north]),
+            // With elevations.
+            [west, south, _, east, north, _] => Ok([west, south, east, north]),
+            _ => Err(invalid_data(format!("{} is not a bounding box.", bbox))),
+        };
+    }
+    let mut bbox: Option<[f64; 4]> = None;
+    extend_bbox(feature.get("geometry").unwrap_or(&Value::Null), &mut bbox);
+    bbox.ok_or_else(|| {
+        invalid_data("A feature has neither a bbox nor any coordinates.".to_string())
+    })
+}
+
+fn extend_bbox(geometry: &Value, bbox: &mut Option<[f64; 4]>) {
+    if let Some(geometries) = geometry.get("geometries").and_then(Value::as_array) {
+        for g in geometries {
+            extend_bbox(g, bbox);
+        }
+    } else if let Some(coordinates) = geometry.get("coordinates") {
+        extend_bbox_with_positions(coordinates, bbox);
+    }
+}
+
+// @coordinates is a position, or nested arrays of them.
+fn extend_bbox_with_positions(coordinates: &Value, bbox: &mut Option<[f64; 4]>) {
+    let items = match coordinates.as_array() {
+        Some(items) => items,
+        None => return,
+    };
+    match (
+        items.first().and_then(Value::as_f64),
+        items.get(1).and_then(Value::as_f64),
+    ) {
+        (Some(x), Some(y)) => {
+            let [w, s, e, n] = bbox.unwrap_or([x, y, x, y]);
+            *bbox = Some([w.min(x), s.min(y), e.max(x), n.max(y)]);
+        }
+        _ => {
+            for item in items {
+                extend_bbox_with_positions(item, bbox);
+            }
+        }
+    }
+}
+
+impl<U> Quadtree<U, Value>
+where
+    U: PrimInt + Default,
+{
+    // pub
+
+    /// Reads a GeoJSON `FeatureCollection`, or a single `Feature`, from `reader` into a new
+    /// quadtree just large enough to hold it. See the [`geojson`] module.
+    ///
+    /// Each feature's region is the smallest run of `quantizer`'s cells which covers its `bbox`,
+    /// or, if it has none, every position in its geometry. Its value is its `properties`, or
+    /// `null` if it has none. The `n`th feature is given the handle `n`, just as with
+    /// [`.from_regions()`].
+    ///
+    /// Input which isn't GeoJSON, features with no coordinates, and bounding boxes which fall
+    /// outside the coordinates `U` can represent are rejected with an error of kind
+    /// [`InvalidData`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the features are too spread out for a square covering them to be representable
+    /// in `U`.
+    ///
+    /// [`geojson`]: geojson/index.html
+    /// [`.from_regions()`]: #method.from_regions
+    /// [`InvalidData`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.InvalidData
+    pub fn from_geojson<R: Read>(reader: R, quantizer: Quantizer) -> io::Result<Self> {
+        let document: Value = serde_json::from_reader(reader)?;
+        let features = match document.get("type").and_then(Value::as_str) {
+            Some("FeatureCollection") => document
+                .get("features")
+                .and_then(Value::as_array)
+                .ok_or_else(|| invalid_data("A FeatureCollection has no features.".to_string()))?
+                .iter()
+                .collect(),
+            Some("Feature") => vec![&document],
+            _ => {
+                return Err(invalid_data(
+                    "Not a GeoJSON Feature or FeatureCollection.".to_string(),
+                ))
+            }
+        };
+
+        let items = features
+            .into_iter()
+            .map(|feature| {
+                let region = quantizer.region(bbox_of(feature)?)?;
+                let properties = feature.get("properties").cloned().unwrap_or(Value::Null);
+                Ok((region, properties))
+            })
+            .collect::<io::Result<Vec<(Area<U>, Value)>>>()?;
+        Ok(Self::from_regions(items))
+    }
+}
+
+impl<U, S> Quadtree<U, Value, S>
+where
+    U: PrimInt + Default,
+    S: BuildHasher,
+{
+    // pub
+
+    /// Writes the quadtree to `writer` as a GeoJSON `FeatureCollection`. See the [`geojson`]
+    /// module.
+    ///
+    /// Each entry becomes a feature whose geometry is the rectangle `quantizer` maps its region
+    /// to, with a matching `bbox`, and whose `properties` are its value. Features are written in
+    /// the order of their handles, so the output is stable across runs. Reading the output back
+    /// with [`.from_geojson()`] and the same quantizer gives back the same regions and values.
+    ///
+    /// [`geojson`]: geojson/index.html
+    /// [`.from_geojson()`]: #method.from_geojson
+    pub fn to_geojson<W: Write>(&self, mut writer: W, quantizer: Quantizer) -> io::Result<()> {
+        let entries = self.entries_by_handle();
+
+        let features: Vec<Value> = entries
+            .into_iter()
+            .map(|entry| {
+                let [west, south, east, north] = quantizer.bbox(entry.area());
+                json!({
+                    "type": "Feature",
+                    "bbox": [west, south, east, north],
+                    "geometry": {
+                        "type": "Polygon",
+                        "coordinates": [[
+                            [west, south],
+                            [east, south],
+                            [east, north],
+                            [west, north],
+                            [west, south],
+                        ]],
+                    },
+                    "properties": entry.value_ref(),
+                })
+            })
+            .collect();
+        let collection = json!({
+            "type": "FeatureCollection",
+            "features": features,
+        });
+        serde_json::to_writer(&mut writer, &collection)?;
+        writer.flush()
+    }
+}
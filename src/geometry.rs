@@ -0,0 +1,144 @@
+// Copyright 2019 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// The geometry of boxes of whole cells, written once, axis by axis, for any number of dimensions.
+// Both the 2d Area and the ndtree's D-dimensional Region are built on it.
+//
+// A box is given by its anchor, the corner with the smallest coordinates, and its extent along
+// each axis. The cell at c covers [c, c + 1) along each axis. Far edges may lie past the end of
+// @U, so they're kept as Options, where None is past the end.
+
+use num::PrimInt;
+
+pub(crate) type Bounds<U, const D: usize> = ([U; D], [U; D]);
+
+// Whether the boxes @a and @b have any cell in common.
+pub(crate) fn intersects<U, const D: usize>(a: Bounds<U, D>, b: Bounds<U, D>) -> bool
+where
+    U: PrimInt,
+{
+    (0..D).all(|axis| {
+        let (a_lo, a_far) = span(a, axis);
+        let (b_lo, b_far) = span(b, axis);
+        before(a_lo, b_far) && before(b_lo, a_far)
+    })
+}
+
+// Whether every cell of @inner lies within @outer.
+pub(crate) fn contains<U, const D: usize>(outer: Bounds<U, D>, inner: Bounds<U, D>) -> bool
+where
+    U: PrimInt,
+{
+    (0..D).all(|axis| {
+        let (outer_lo, outer_far) = span(outer, axis);
+        let (inner_lo, inner_far) = span(inner, axis);
+        inner_lo >= outer_lo && within(inner_far, outer_far)
+    })
+}
+
+// The squared euclidean distance from @pt to the nearest cell of @b. Zero if @b contains @pt.
+// Computed on the unsigned gaps along each axis, saturating at u128::MAX, so that no coordinates
+// of any type can overflow.
+pub(crate) fn distance_sq_to_pt<U, const D: usize>(b: Bounds<U, D>, pt: [U; D]) -> u128
+where
+    U: PrimInt,
+{
+    (0..D)
+        .map(|axis| {
+            let (c, (lo, far)) = (pt[axis], span(b, axis));
+            let gap = if c < lo {
+                distance(c, lo)
+            } else {
+                match far {
+                    // The last cell of @b is at far - 1.
+                    Some(far) if c >= far => distance(c, far - U::one()),
+                    _ => 0,
+                }
+            };
+            gap.saturating_mul(gap)
+        })
+        .fold(0, u128::saturating_add)
+}
+
+// The squared euclidean distance from @pt to the furthest cell of @b.
+pub(crate) fn farthest_distance_sq_to_pt<U, const D: usize>(b: Bounds<U, D>, pt: [U; D]) -> u128
+where
+    U: PrimInt,
+{
+    let (anchor, dimensions) = b;
+    (0..D)
+        .map(|axis| {
+            let (c, lo) = (pt[axis], anchor[axis]);
+            let hi = lo.saturating_add(dimensions[axis] - U::one());
+            let gap = distance(c, lo).max(distance(c, hi));
+            gap.saturating_mul(gap)
+        })
+        .fold(0, u128::saturating_add)
+}
+
+// One of the 2^D boxes which @b is split into by halving it along every axis: the far half along
+// each axis for which @far is set, and the near half otherwise. @b should have even extents, as
+// the cells of a tree above its bottom depth do.
+pub(crate) fn orthant<U, const D: usize>(b: Bounds<U, D>, far: [bool; D]) -> Bounds<U, D>
+where
+    U: PrimInt,
+{
+    let (anchor, dimensions) = b;
+    let half: [U; D] = std::array::from_fn(|axis| dimensions[axis] / (U::one() + U::one()));
+    let anchor = std::array::from_fn(|axis| {
+        if far[axis] {
+            anchor[axis] + half[axis]
+        } else {
+            anchor[axis]
+        }
+    });
+    (anchor, half)
+}
+
+// The distance between @a and @b, which for a signed U might not fit in U itself.
+fn distance<U: PrimInt>(a: U, b: U) -> u128 {
+    let (lo, hi) = if a < b { (a, b) } else { (b, a) };
+    match hi.checked_sub(&lo).and_then(|gap| gap.to_u128()) {
+        Some(gap) => gap,
+        // Only a signed U can overflow, when @lo is negative and @hi isn't: go via zero.
+        None => {
+            let below = (U::zero() - (lo + U::one())).to_u128().unwrap_or(u128::MAX);
+            let above = hi.to_u128().unwrap_or(u128::MAX);
+            above.saturating_add(below).saturating_add(1)
+        }
+    }
+}
+
+// The near and far edges of @b along @axis.
+fn span<U, const D: usize>(b: Bounds<U, D>, axis: usize) -> (U, Option<U>)
+where
+    U: PrimInt,
+{
+    let (anchor, dimensions) = b;
+    (anchor[axis], anchor[axis].checked_add(&dimensions[axis]))
+}
+
+// Whether @edge lies before @far.
+fn before<U: PrimInt>(edge: U, far: Option<U>) -> bool {
+    far.is_none_or(|far| edge < far)
+}
+
+// Whether @a lies no further out than @b.
+fn within<U: PrimInt>(a: Option<U>, b: Option<U>) -> bool {
+    match (a, b) {
+        (_, None) => true,
+        (None, Some(_)) => false,
+        (Some(a), Some(b)) => a <= b,
+    }
+}
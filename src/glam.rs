@@ -0,0 +1,47 @@
+// Copyright 2019 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// Conversions to and from the integer vectors of `glam`, behind the `glam` feature.
+
+use {
+    crate::point::Point,
+    ::glam::{I16Vec2, I64Vec2, I8Vec2, IVec2, U16Vec2, U64Vec2, U8Vec2, UVec2},
+};
+
+macro_rules! vec2_conversions {
+    ($($vec:ident => $u:ty),*) => {$(
+        impl From<$vec> for Point<$u> {
+            fn from(v: $vec) -> Self {
+                Self { x: v.x, y: v.y }
+            }
+        }
+
+        impl From<Point<$u>> for $vec {
+            fn from(point: Point<$u>) -> Self {
+                Self::new(point.x, point.y)
+            }
+        }
+    )*};
+}
+
+vec2_conversions!(
+    I8Vec2 => i8,
+    I16Vec2 => i16,
+    IVec2 => i32,
+    I64Vec2 => i64,
+    U8Vec2 => u8,
+    U16Vec2 => u16,
+    UVec2 => u32,
+    U64Vec2 => u64
+);
@@ -0,0 +1,150 @@
+// Copyright 2019 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use {
+    crate::{area::Area, point::Point, Quadtree},
+    num::PrimInt,
+    std::default::Default,
+};
+
+impl<U> Quadtree<U, ()>
+where
+    U: PrimInt + Default,
+{
+    // pub
+
+    /// Builds an occupancy quadtree from a `width` by `height` grid, in which the cell `(x, y)` is
+    /// occupied if `occupied(x, y)` is true.
+    ///
+    /// Uniform blocks are merged bottom-up: every aligned square of the tree whose cells are all
+    /// occupied is held as a single entry, unless it lies within a larger such square. So a fully
+    /// occupied grid becomes a handful of entries rather than one per cell. The quadtree is
+    /// bounded just as with [`.new_with_bounds()`].
+    /// ```
+    /// use quadtree_rs::Quadtree;
+    ///
+    /// // A 6x4 room with a wall down its middle.
+    /// let qt = Quadtree::<u32, ()>::from_grid(6, 4, |x, _| x == 3);
+    ///
+    /// assert_eq!((qt.width(), qt.height()), (6, 4));
+    /// assert!(qt.query_pt((3, 2).into()).next().is_some());
+    /// assert!(qt.query_pt((2, 2).into()).next().is_none());
+    ///
+    /// // With no wall at all, the room is one 4x4 block and two 2x2 blocks.
+    /// let open = Quadtree::<u32, ()>::from_grid(6, 4, |_, _| true);
+    /// assert_eq!(open.len(), 3);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if either dimension is zero, or if no square covering the grid is representable in
+    /// `U`.
+    ///
+    /// [`.new_with_bounds()`]: #method.new_with_bounds
+    pub fn from_grid<F>(width: U, height: U, mut occupied: F) -> Self
+    where
+        F: FnMut(U, U) -> bool,
+    {
+        let origin = Point {
+            x: U::zero(),
+            y: U::zero(),
+        };
+        let mut qt = Self::new_with_bounds(origin, width, height);
+        let root = qt.inner.region();
+        let mut blocks = vec![];
+        if Self::merge_blocks(root, (width, height), &mut occupied, &mut blocks) {
+            blocks.push(root);
+        }
+        for block in blocks {
+            qt.insert(block, ());
+        }
+        qt
+    }
+
+    /// Builds an occupancy quadtree from a row-major `width` by `height` mask, in which a cell is
+    /// occupied if its byte is nonzero. See [`.from_grid()`].
+    /// ```
+    /// use quadtree_rs::Quadtree;
+    ///
+    /// let mask = [
+    ///     1, 1, 0,
+    ///     1, 1, 0,
+    /// ];
+    /// let qt = Quadtree::<u8, ()>::from_mask(3, 2, &mask);
+    ///
+    /// assert_eq!(qt.len(), 1);
+    /// assert_eq!(qt.iter().next().unwrap().width(), 2);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `mask` doesn't hold exactly `width * height` bytes, or as [`.from_grid()`]
+    /// does.
+    ///
+    /// [`.from_grid()`]: #method.from_grid
+    pub fn from_mask(width: U, height: U, mask: &[u8]) -> Self {
+        let to_usize = |u: U| {
+            u.to_usize()
+                .expect("The mask's dimensions should fit in a usize.")
+        };
+        let (w, h) = (to_usize(width), to_usize(height));
+        assert_eq!(
+            Some(mask.len()),
+            w.checked_mul(h),
+            "The mask must hold exactly width * height bytes."
+        );
+        Self::from_grid(width, height, |x, y| {
+            mask[to_usize(y) * w + to_usize(x)] != 0
+        })
+    }
+
+    // fn
+
+    // Whether every cell of the square @cell of the tree is occupied. If not, the largest wholly
+    // occupied squares within it are added to @blocks.
+    fn merge_blocks<F>(
+        cell: Area<U>,
+        (width, height): (U, U),
+        occupied: &mut F,
+        blocks: &mut Vec<Area<U>>,
+    ) -> bool
+    where
+        F: FnMut(U, U) -> bool,
+    {
+        let anchor = cell.anchor();
+        if anchor.x() >= width || anchor.y() >= height {
+            return false;
+        }
+        if cell.width() == U::one() {
+            return occupied(anchor.x(), anchor.y());
+        }
+
+        let quarters = cell.quarters();
+        let full: Vec<bool> = quarters
+            .iter()
+            .map(|&q| Self::merge_blocks(q, (width, height), occupied, blocks))
+            .collect();
+        if full.iter().all(|&f| f) {
+            return true;
+        }
+        blocks.extend(
+            quarters
+                .iter()
+                .zip(full)
+                .filter(|&(_, f)| f)
+                .map(|(&q, _)| q),
+        );
+        false
+    }
+}
@@ -13,9 +13,14 @@
 // limitations under the License.
 
 use {
-    crate::{area::Area, qtinner::QTInner, traversal::Traversal},
+    crate::{
+        area::Area,
+        qtinner::{NodeId, QTInner},
+        shape::Shape,
+        traversal::Traversal,
+    },
     num::PrimInt,
-    std::{collections::HashSet, default::Default, iter::FusedIterator},
+    std::{default::Default, iter::FusedIterator},
 };
 
 #[derive(Clone, Debug)]
@@ -23,22 +28,22 @@ pub(crate) struct HandleIter<'a, U>
 where
     U: PrimInt + Default,
 {
-    search_area: Area<U>,
+    qt: &'a QTInner<U>,
+    search_shape: Shape<U>,
     handle_stack: Vec<u64>,
-    qt_stack: Vec<&'a QTInner<U>>,
-    visited: HashSet<u64>,
+    qt_stack: Vec<NodeId>,
 }
 
 impl<'a, U> HandleIter<'a, U>
 where
     U: PrimInt + Default,
 {
-    pub(crate) fn new(qt: &'a QTInner<U>, search_area: Area<U>) -> HandleIter<'a, U> {
+    pub(crate) fn new(qt: &'a QTInner<U>, search_shape: Shape<U>) -> HandleIter<'a, U> {
         HandleIter {
-            search_area,
+            qt,
+            search_shape,
             handle_stack: vec![],
-            qt_stack: vec![qt],
-            visited: HashSet::new(),
+            qt_stack: vec![QTInner::<U>::ROOT],
         }
     }
 
@@ -48,41 +53,71 @@ where
     // Instead, we can make a beeline for the lowest region which totally contains the @req (but no
     // lower). We then have to actually evaluate every handle below that node.
     //
-    // Along the way, if our query is meant to be of type Traversal::Overlapping, we collect the
-    // handles we meet along the way. They are guaranteed to intersect @req.
+    // Along the way, unless our query is meant to be of type Traversal::Strict, we collect the
+    // handles we meet along the way. Their regions straddle the node we descend into, so they may
+    // or may not intersect @req.
     pub(crate) fn query_optimization(&mut self, req: Area<U>, traversal_method: Traversal) {
         // This method expects to be called at a point in time when the HandleIter has just been
         // created but has not yet been called.
         assert!(self.qt_stack.len() == 1);
         assert!(self.handle_stack.is_empty());
-        assert!(self.visited.is_empty());
 
+        // The bounds of a loose tree's subquadrants overlap, so there's no one subquadrant to make
+        // a beeline for. We search from the root instead.
+        if self.qt.is_loose() {
+            return;
+        }
         self.descend_recurse_step(req, traversal_method);
     }
 
+    // Splits a HandleIter which hasn't been called yet into the handles held at (and, after
+    // descent, above) its starting node, and one HandleIter for each subquadrant of that node which
+    // is worth searching. Between them they yield exactly what this HandleIter would have.
+    #[cfg(feature = "rayon")]
+    pub(crate) fn split(mut self) -> (Vec<u64>, Vec<HandleIter<'a, U>>) {
+        assert!(self.qt_stack.len() == 1);
+        let node = self.qt.node(self.qt_stack[0]);
+        self.handle_stack.extend(node.handles());
+
+        let (qt, search_shape) = (self.qt, self.search_shape);
+        let subtrees = node
+            .subquadrants()
+            .iter()
+            .flatten()
+            .filter(|&&sq| search_shape.intersects(qt.node(sq).bounds()))
+            .map(|&sq| HandleIter {
+                qt,
+                search_shape,
+                handle_stack: vec![],
+                qt_stack: vec![sq],
+            })
+            .collect();
+        (self.handle_stack, subtrees)
+    }
+
     fn descend_recurse_step(&mut self, req: Area<U>, traversal_method: Traversal) {
         assert!(self.qt_stack.len() == 1);
         // Peek into the stack. We have to peek rather than pop, because if we are about to go too
         // far down we'd rather stop and return the HandleIter as-is.
-        if let Some(qt) = self.qt_stack.last() {
+        if let Some(&id) = self.qt_stack.last() {
+            let qt = self.qt.node(id);
             // If the region doesn't contain our @req, we're already too far down. Return here.
             if !qt.region().contains(req) {
                 return;
             }
             assert!(qt.region().contains(req));
 
-            if let Some(subquadrants) = qt.subquadrants().as_ref() {
-                for subquadrant in subquadrants.iter() {
+            if let Some(subquadrants) = qt.subquadrants() {
+                for subquadrant in subquadrants {
                     // If we find a subquadrant which totally contains the @req, we want to make
                     // that our new sole qt.
-                    if subquadrant.region().contains(req) {
-                        if traversal_method == Traversal::Overlapping {
+                    if self.qt.node(subquadrant).region().contains(req) {
+                        if traversal_method != Traversal::Strict {
                             self.handle_stack.extend(qt.handles());
                         }
 
-                        // TODO(ambuc): Could this be done with Vec::swap() or std::mem::replace()?
                         assert!(self.qt_stack.len() == 1);
-                        self.qt_stack = vec![subquadrant];
+                        self.qt_stack[0] = subquadrant;
 
                         // Recurse on this step. It will naturally return, but we want to propogate
                         // that return rather than continue to search the other subquadrants.
@@ -104,18 +139,21 @@ where
     #[inline]
     fn next(&mut self) -> Option<Self::Item> {
         loop {
-            while let Some(handle) = self.handle_stack.pop() {
-                if self.visited.insert(handle) {
-                    return Some(handle);
-                }
+            // Every handle is held by exactly one node, so there's no need to deduplicate.
+            if let Some(handle) = self.handle_stack.pop() {
+                return Some(handle);
             }
 
             // Then check the qt_stack.
-            if let Some(qt) = self.qt_stack.pop() {
+            if let Some(id) = self.qt_stack.pop() {
+                let qt = self.qt.node(id);
                 // Push my sub quadrants onto the qt_stack too.
-                if let Some(sub_quadrants) = qt.subquadrants().as_ref() {
+                if let Some(sub_quadrants) = qt.subquadrants() {
                     for sub_quadrant in sub_quadrants {
-                        if sub_quadrant.region().intersects(self.search_area) {
+                        if self
+                            .search_shape
+                            .intersects(self.qt.node(sub_quadrant).bounds())
+                        {
                             self.qt_stack.push(sub_quadrant)
                         }
                     }
@@ -124,11 +162,7 @@ where
                 // Push my regions onto the region stack
                 match qt.handles().len() {
                     0 => (),
-                    1 => {
-                        if self.visited.insert(qt.handles()[0]) {
-                            return Some(qt.handles()[0]);
-                        }
-                    }
+                    1 => return Some(qt.handles()[0]),
                     _ => self.handle_stack.extend(qt.handles()),
                 }
 
@@ -140,9 +174,15 @@ where
         }
     }
 
+    // Everything on the handle stack is still to come, and at most everything held beneath the
+    // nodes still to be searched.
     #[inline]
     fn size_hint(&self) -> (usize, Option<usize>) {
-        (0, None)
+        let below: usize = self.qt_stack.iter().map(|&id| self.qt.node(id).len()).sum();
+        (
+            self.handle_stack.len(),
+            Some(self.handle_stack.len() + below),
+        )
     }
 }
 
@@ -0,0 +1,217 @@
+// Copyright 2019 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use {
+    crate::{area::AreaBuilder, point::Point, Quadtree},
+    num::PrimInt,
+    std::{default::Default, hash::BuildHasher},
+};
+
+// A row-major image with @N bytes per pixel.
+struct Image<'a, const N: usize> {
+    width: usize,
+    height: usize,
+    pixels: &'a [u8],
+}
+
+impl<const N: usize> Image<'_, N> {
+    // The average color of the pixels in [@x0, @x1) x [@y0, @y1), and the root-mean-square
+    // difference between them and it over every channel.
+    fn average(&self, (x0, x1): (usize, usize), (y0, y1): (usize, usize)) -> ([u8; N], f64) {
+        let (mut sums, mut squares) = ([0_u64; N], [0_u64; N]);
+        for y in y0..y1 {
+            let row = &self.pixels[(y * self.width + x0) * N..(y * self.width + x1) * N];
+            for pixel in row.chunks_exact(N) {
+                for (c, &v) in pixel.iter().enumerate() {
+                    sums[c] += u64::from(v);
+                    squares[c] += u64::from(v) * u64::from(v);
+                }
+            }
+        }
+
+        let count = ((x1 - x0) * (y1 - y0)) as f64;
+        let mut variance = 0.0;
+        let mut color = [0; N];
+        for c in 0..N {
+            let mean = sums[c] as f64 / count;
+            let mean_sq = squares[c] as f64 / count;
+            variance += (mean_sq - mean * mean).max(0.0);
+            color[c] = mean.round() as u8;
+        }
+        let error = (variance / N as f64).sqrt();
+        (color, error)
+    }
+}
+
+impl<U, const N: usize> Quadtree<U, [u8; N]>
+where
+    U: PrimInt + Default,
+{
+    // pub
+
+    /// Compresses a `width` by `height` image into a region quadtree whose entries are blocks of
+    /// a single, averaged color.
+    ///
+    /// `pixels` is row-major, with `N` bytes per pixel: one for grayscale, four for RGBA, and so
+    /// on. Starting from the whole image, each square of the tree is kept as one block if the
+    /// root-mean-square difference between its pixels and their average color, over every
+    /// channel, is at most `threshold`; otherwise it's split into its four quadrants. Blocks at
+    /// the edge of the image are clipped to it. A `threshold` of zero only merges blocks of a
+    /// single color, so the image is reproduced exactly.
+    ///
+    /// Use [`.render_to_buffer()`] to reconstruct the image.
+    /// ```
+    /// use quadtree_rs::Quadtree;
+    ///
+    /// // An 8x8 grayscale image: black, with a soft white square in its lower right.
+    /// let mut pixels = vec![0_u8; 64];
+    /// for y in 4..8 {
+    ///     for x in 4..8 {
+    ///         pixels[y * 8 + x] = 250 + (x + y) as u8 % 2;
+    ///     }
+    /// }
+    ///
+    /// let lossless = Quadtree::<u32, [u8; 1]>::from_image(8, 8, &pixels, 0.0);
+    /// let lossy = Quadtree::<u32, [u8; 1]>::from_image(8, 8, &pixels, 1.0);
+    /// assert_eq!(lossless.len(), 19);
+    /// assert_eq!(lossy.len(), 4);
+    ///
+    /// let mut rendered = vec![0_u8; 64];
+    /// lossless.render_to_buffer(&mut rendered);
+    /// assert_eq!(rendered, pixels);
+    ///
+    /// lossy.render_to_buffer(&mut rendered);
+    /// assert_eq!(rendered[63], 251);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `N` is zero, if `pixels` doesn't hold exactly `width * height * N` bytes, or if
+    /// either dimension is zero or too large for a square covering the image to be representable
+    /// in `U`.
+    ///
+    /// [`.render_to_buffer()`]: #method.render_to_buffer
+    pub fn from_image(width: U, height: U, pixels: &[u8], threshold: f64) -> Self {
+        assert!(N > 0, "Pixels must have at least one channel.");
+        let to_usize = |u: U| {
+            u.to_usize()
+                .expect("The image's dimensions should fit in a usize.")
+        };
+        let image = Image::<N> {
+            width: to_usize(width),
+            height: to_usize(height),
+            pixels,
+        };
+        assert_eq!(
+            Some(pixels.len()),
+            image
+                .width
+                .checked_mul(image.height)
+                .and_then(|area| area.checked_mul(N)),
+            "The image must hold exactly width * height * N bytes."
+        );
+
+        let origin = Point {
+            x: U::zero(),
+            y: U::zero(),
+        };
+        let mut qt = Self::new_with_bounds(origin, width, height);
+        let depth = qt.depth();
+        qt.compress(&image, (0, 0), depth, threshold);
+        qt
+    }
+
+    // fn
+
+    // Compresses the part of @image within the square at (@x, @y) with a side of 2^@depth.
+    fn compress(
+        &mut self,
+        image: &Image<'_, N>,
+        (x, y): (usize, usize),
+        depth: usize,
+        threshold: f64,
+    ) {
+        if x >= image.width || y >= image.height {
+            return;
+        }
+        let side = 1 << depth;
+        let (x1, y1) = ((x + side).min(image.width), (y + side).min(image.height));
+        let (color, error) = image.average((x, x1), (y, y1));
+        if depth == 0 || error <= threshold {
+            let coord = |v: usize| U::from(v).expect("The image's cells should fit in U.");
+            let block = AreaBuilder::default()
+                .anchor(Point {
+                    x: coord(x),
+                    y: coord(y),
+                })
+                .dimensions((coord(x1 - x), coord(y1 - y)))
+                .build()
+                .expect("Unexpected error in Quadtree::from_image().");
+            self.insert(block, color);
+            return;
+        }
+        let half = side / 2;
+        for &(dx, dy) in &[(0, 0), (half, 0), (0, half), (half, half)] {
+            self.compress(image, (x + dx, y + dy), depth - 1, threshold);
+        }
+    }
+}
+
+impl<U, S, const N: usize> Quadtree<U, [u8; N], S>
+where
+    U: PrimInt + Default,
+    S: BuildHasher,
+{
+    // pub
+
+    /// Paints every block of the quadtree into a row-major image the size of the quadtree, with
+    /// `N` bytes per pixel, reconstructing an image compressed with [`.from_image()`]. Pixels
+    /// which no block covers are left as they were.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `buffer` doesn't hold exactly [`.width()`] `*` [`.height()`] `* N` bytes.
+    ///
+    /// [`.from_image()`]: #method.from_image
+    /// [`.width()`]: #method.width
+    /// [`.height()`]: #method.height
+    pub fn render_to_buffer(&self, buffer: &mut [u8]) {
+        let (width, height) = (self.width(), self.height());
+        assert_eq!(
+            buffer.len(),
+            width * height * N,
+            "The buffer must hold exactly width * height * N bytes."
+        );
+        let offset = |c: U, origin: U| {
+            (c - origin)
+                .to_usize()
+                .expect("Blocks should lie within the quadtree.")
+        };
+        let anchor = self.anchor();
+        for entry in self.iter() {
+            let (x0, y0) = (
+                offset(entry.anchor().x(), anchor.x()),
+                offset(entry.anchor().y(), anchor.y()),
+            );
+            let x1 = (x0 + offset(entry.width(), U::zero())).min(width);
+            let y1 = (y0 + offset(entry.height(), U::zero())).min(height);
+            for y in y0..y1 {
+                for pixel in buffer[(y * width + x0) * N..(y * width + x1) * N].chunks_exact_mut(N)
+                {
+                    pixel.copy_from_slice(entry.value_ref());
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,326 @@
+// Copyright 2019 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A quadtree which can look its entries up by value, behind the `index` feature.
+//!
+//! Finding where a value is stored in a plain [`Quadtree`] means scanning every entry. An
+//! [`IndexedQuadtree`] also keeps a reverse index from values to the handles they're stored
+//! under, which is kept up to date as entries come and go, so that [`.find_value()`] only looks
+//! at the entries which share the value's hash.
+//!
+//! ```
+//! use quadtree_rs::{area::AreaBuilder, index::IndexedQuadtree};
+//!
+//! // Entity IDs, stored where the entities are.
+//! let mut qt = IndexedQuadtree::<u32, u64>::new(8);
+//! qt.insert_pt((3, 4).into(), 42);
+//!
+//! let hitbox = AreaBuilder::default()
+//!     .anchor((10, 10).into())
+//!     .dimensions((2, 3))
+//!     .build().unwrap();
+//! qt.insert(hitbox, 42);
+//!
+//! assert_eq!(qt.find_value(&42).count(), 2);
+//! assert!(qt.find_value(&42).any(|region| region == hitbox));
+//! assert_eq!(qt.find_value(&7).next(), None);
+//! ```
+//!
+//! [`Quadtree`]: ../struct.Quadtree.html
+//! [`IndexedQuadtree`]: struct.IndexedQuadtree.html
+//! [`.find_value()`]: struct.IndexedQuadtree.html#method.find_value
+
+use {
+    crate::{area::Area, entry::Entry, iter::IntoIter, point::Point, Quadtree},
+    num::PrimInt,
+    std::{
+        collections::{hash_map::RandomState, HashMap},
+        default::Default,
+        hash::{BuildHasher, Hash},
+    },
+};
+
+/// A quadtree with a reverse index from its values to their regions.
+///
+/// Every change goes through the `IndexedQuadtree`, so that the index can follow it. For
+/// everything which only reads the quadtree, use [`.as_quadtree()`]. See the [module-level
+/// documentation] for more.
+///
+/// [`.as_quadtree()`]: #method.as_quadtree
+/// [module-level documentation]: index.html
+#[derive(Clone, Debug)]
+pub struct IndexedQuadtree<U, V, S = RandomState>
+where
+    U: PrimInt + Default,
+{
+    tree: Quadtree<U, V, S>,
+    // The handles of the entries holding each value, keyed by the value's hash under the tree's
+    // hasher. Values whose hashes collide share a bucket, so lookups compare the values too.
+    index: HashMap<u64, Vec<u64>>,
+}
+
+impl<U, V> IndexedQuadtree<U, V>
+where
+    U: PrimInt + Default,
+    V: Eq + Hash,
+{
+    // pub
+
+    /// Creates a new, empty quadtree with some depth.
+    /// See [`Quadtree::new()`].
+    ///
+    /// [`Quadtree::new()`]: ../struct.Quadtree.html#method.new
+    pub fn new(depth: usize) -> Self {
+        Self::from(Quadtree::new(depth))
+    }
+
+    /// Creates a new, empty quadtree with some depth and an explicit anchor.
+    /// See [`Quadtree::new_with_anchor()`].
+    ///
+    /// [`Quadtree::new_with_anchor()`]: ../struct.Quadtree.html#method.new_with_anchor
+    pub fn new_with_anchor(anchor: Point<U>, depth: usize) -> Self {
+        Self::from(Quadtree::new_with_anchor(anchor, depth))
+    }
+}
+
+impl<U, V, S> IndexedQuadtree<U, V, S>
+where
+    U: PrimInt + Default,
+    V: Eq + Hash,
+    S: BuildHasher,
+{
+    // pub
+
+    /// The regions of every entry holding `val`, in no particular order.
+    ///
+    /// This only looks at the entries whose values hash the same as `val`, rather than scanning
+    /// the whole quadtree.
+    pub fn find_value<'a>(&'a self, val: &'a V) -> impl Iterator<Item = Area<U>> + 'a {
+        self.handles_of(val)
+            .filter_map(move |handle| self.tree.get(handle))
+            .filter(move |entry| entry.value_ref() == val)
+            .map(Entry::area)
+    }
+
+    /// Whether any region in the quadtree is associated with `val`.
+    /// See [`Quadtree::contains_value()`].
+    ///
+    /// Unlike its namesake, this doesn't scan the whole quadtree.
+    ///
+    /// [`Quadtree::contains_value()`]: ../struct.Quadtree.html#method.contains_value
+    pub fn contains_value(&self, val: &V) -> bool {
+        self.find_value(val).next().is_some()
+    }
+
+    /// Associate some value with a region in the quadtree, and index it.
+    /// See [`Quadtree::insert()`].
+    ///
+    /// [`Quadtree::insert()`]: ../struct.Quadtree.html#method.insert
+    pub fn insert(&mut self, region: Area<U>, val: V) -> Option<u64> {
+        let hash = self.hash(&val);
+        let handle = self.tree.insert(region, val)?;
+        self.index.entry(hash).or_default().push(handle);
+        Some(handle)
+    }
+
+    /// Alias for [`.insert()`] which expects a [`Point`] instead of an [`Area`].
+    ///
+    /// [`.insert()`]: #method.insert
+    /// [`Area`]: ../area/struct.Area.html
+    /// [`Point`]: ../point/struct.Point.html
+    pub fn insert_pt(&mut self, point: Point<U>, val: V) -> Option<u64> {
+        let hash = self.hash(&val);
+        let handle = self.tree.insert_pt(point, val)?;
+        self.index.entry(hash).or_default().push(handle);
+        Some(handle)
+    }
+
+    /// Moves the entry with the given handle to a new region. Its value, and so its place in the
+    /// index, doesn't change.
+    /// See [`Quadtree::relocate()`].
+    ///
+    /// [`Quadtree::relocate()`]: ../struct.Quadtree.html#method.relocate
+    pub fn relocate(&mut self, handle: u64, region: Area<U>) -> bool {
+        self.tree.relocate(handle, region)
+    }
+
+    /// Applies `f` to every value which overlaps `area`, re-indexes them, and returns how many
+    /// there were.
+    /// See [`Quadtree::modify()`].
+    ///
+    /// [`Quadtree::modify()`]: ../struct.Quadtree.html#method.modify
+    pub fn modify<F>(&mut self, area: Area<U>, mut f: F) -> usize
+    where
+        F: FnMut(&Area<U>, &mut V),
+    {
+        let handles: Vec<u64> = self.tree.query(area).map(Entry::handle).collect();
+        for handle in &handles {
+            self.modify_handle(*handle, &mut f);
+        }
+        handles.len()
+    }
+
+    /// Applies `f` to every value in the quadtree, re-indexes them, and returns how many there
+    /// were.
+    /// See [`Quadtree::modify_all()`].
+    ///
+    /// [`Quadtree::modify_all()`]: ../struct.Quadtree.html#method.modify_all
+    pub fn modify_all<F>(&mut self, mut f: F) -> usize
+    where
+        F: FnMut(&Area<U>, &mut V),
+    {
+        let handles: Vec<u64> = self.tree.iter().map(Entry::handle).collect();
+        for handle in &handles {
+            self.modify_handle(*handle, &mut f);
+        }
+        handles.len()
+    }
+
+    /// Deletes all value associations which overlap a region in the tree, and drops them from the
+    /// index. See [`Quadtree::delete()`].
+    ///
+    /// [`Quadtree::delete()`]: ../struct.Quadtree.html#method.delete
+    pub fn delete(&mut self, area: Area<U>) -> IntoIter<U, V> {
+        let entries: Vec<Entry<U, V>> = self.tree.delete(area).collect();
+        for entry in &entries {
+            self.unindex(entry);
+        }
+        IntoIter {
+            entries: entries.into_iter(),
+        }
+    }
+
+    /// Deletes a single item from the quadtree, and drops it from the index.
+    /// See [`Quadtree::delete_by_handle()`].
+    ///
+    /// [`Quadtree::delete_by_handle()`]: ../struct.Quadtree.html#method.delete_by_handle
+    pub fn delete_by_handle(&mut self, handle: u64) -> Option<Entry<U, V>> {
+        let entry = self.tree.delete_by_handle(handle)?;
+        self.unindex(&entry);
+        Some(entry)
+    }
+
+    /// Removes a single item from the quadtree, and drops it from the index.
+    /// See [`Quadtree::remove()`].
+    ///
+    /// [`Quadtree::remove()`]: ../struct.Quadtree.html#method.remove
+    pub fn remove(&mut self, handle: u64) -> Option<(Area<U>, V)> {
+        self.delete_by_handle(handle).map(Entry::into_parts)
+    }
+
+    /// Retains only the values which satisfy `f`, and re-indexes the rest.
+    /// See [`Quadtree::retain()`].
+    ///
+    /// Since `f` may change the values it retains, the whole index is rebuilt afterwards.
+    ///
+    /// [`Quadtree::retain()`]: ../struct.Quadtree.html#method.retain
+    pub fn retain<F>(&mut self, f: F) -> IntoIter<U, V>
+    where
+        F: FnMut(&mut V) -> bool,
+    {
+        let removed = self.tree.retain(f);
+        self.index = Self::build_index(&self.tree);
+        removed
+    }
+
+    /// Resets the quadtree, and its index, to a totally empty state.
+    pub fn reset(&mut self) {
+        self.tree.reset();
+        self.index.clear();
+    }
+
+    /// The quadtree, for everything which doesn't change it.
+    pub fn as_quadtree(&self) -> &Quadtree<U, V, S> {
+        &self.tree
+    }
+
+    /// Consumes the indexed quadtree, returning the quadtree and discarding the index.
+    pub fn into_quadtree(self) -> Quadtree<U, V, S> {
+        self.tree
+    }
+
+    // fn
+
+    fn build_index(tree: &Quadtree<U, V, S>) -> HashMap<u64, Vec<u64>> {
+        let mut index: HashMap<u64, Vec<u64>> = HashMap::new();
+        for entry in tree.iter() {
+            let hash = tree.hasher().hash_one(entry.value_ref());
+            index.entry(hash).or_default().push(entry.handle());
+        }
+        index
+    }
+
+    fn hash(&self, val: &V) -> u64 {
+        self.tree.hasher().hash_one(val)
+    }
+
+    fn handles_of(&self, val: &V) -> impl Iterator<Item = u64> + '_ {
+        self.index
+            .get(&self.hash(val))
+            .into_iter()
+            .flatten()
+            .copied()
+    }
+
+    // Applies @f to the value under @handle, moving the handle to another bucket if its hash
+    // changed.
+    fn modify_handle<F>(&mut self, handle: u64, f: &mut F)
+    where
+        F: FnMut(&Area<U>, &mut V),
+    {
+        let old = match self.tree.get(handle) {
+            Some(entry) => self.hash(entry.value_ref()),
+            None => return,
+        };
+        if let Some(entry) = self.tree.get_mut(handle) {
+            let region = entry.area();
+            f(&region, entry.value_mut());
+        }
+        let new = match self.tree.get(handle) {
+            Some(entry) => self.hash(entry.value_ref()),
+            None => return,
+        };
+        if old != new {
+            self.remove_from_bucket(old, handle);
+            self.index.entry(new).or_default().push(handle);
+        }
+    }
+
+    fn unindex(&mut self, entry: &Entry<U, V>) {
+        let hash = self.hash(entry.value_ref());
+        self.remove_from_bucket(hash, entry.handle());
+    }
+
+    fn remove_from_bucket(&mut self, hash: u64, handle: u64) {
+        if let Some(bucket) = self.index.get_mut(&hash) {
+            bucket.retain(|h| *h != handle);
+            if bucket.is_empty() {
+                self.index.remove(&hash);
+            }
+        }
+    }
+}
+
+impl<U, V, S> From<Quadtree<U, V, S>> for IndexedQuadtree<U, V, S>
+where
+    U: PrimInt + Default,
+    V: Eq + Hash,
+    S: BuildHasher,
+{
+    /// Wraps a quadtree, indexing everything already in it.
+    fn from(tree: Quadtree<U, V, S>) -> Self {
+        let index = Self::build_index(&tree);
+        Self { tree, index }
+    }
+}
@@ -14,11 +14,22 @@
 
 use {
     crate::{
-        area::Area, entry::Entry, handle_iter::HandleIter, qtinner::QTInner, traversal::Traversal,
-        types::StoreType,
+        area::Area,
+        cursor::Quadrant,
+        entry::Entry,
+        handle_iter::HandleIter,
+        qtinner::{Node, NodeId, QTInner},
+        shape::Shape,
+        traversal::Traversal,
+        types::{EntriesMut, StoreType},
+        Quadtree,
     },
     num::PrimInt,
-    std::iter::FusedIterator,
+    std::{
+        collections::{hash_map::RandomState, VecDeque},
+        hash::BuildHasher,
+        iter::FusedIterator,
+    },
 };
 
 /// An iterator over all regions and values of a [`Quadtree`].
@@ -28,42 +39,63 @@ use {
 /// [`iter`]: ../struct.Quadtree.html#method.iter
 /// [`Quadtree`]: ../struct.Quadtree.html
 #[derive(Clone, Debug)]
-pub struct Iter<'a, U, V>
+pub struct Iter<'a, U, V, S = RandomState>
 where
     U: PrimInt + Default,
 {
-    store: &'a StoreType<U, V>,
-    handle_iter: HandleIter<'a, U>,
+    store: &'a StoreType<U, V, S>,
+    qt: &'a QTInner<U>,
+    // The handles still to come are those from @front up to (but not including) @back, in arena
+    // order: by node id, then by position among the node's handles. Every handle is held by
+    // exactly one node, and the nodes are already in an arena, so the two ends can be walked
+    // towards each other without a stack.
+    front: (NodeId, usize),
+    back: (NodeId, usize),
 }
 
-impl<'a, U, V> Iter<'a, U, V>
+impl<'a, U, V, S> Iter<'a, U, V, S>
 where
     U: PrimInt + Default,
 {
-    pub(crate) fn new(qt: &'a QTInner<U>, store: &'a StoreType<U, V>) -> Iter<'a, U, V> {
+    pub(crate) fn new(qt: &'a QTInner<U>, store: &'a StoreType<U, V, S>) -> Iter<'a, U, V, S> {
         Iter {
             store,
-            handle_iter: HandleIter::new(qt, qt.region()),
+            qt,
+            front: (QTInner::<U>::ROOT, 0),
+            back: (qt.node_count(), 0),
         }
     }
+
+    fn entry(&self, handle: u64) -> &'a Entry<U, V>
+    where
+        S: BuildHasher,
+    {
+        self.store
+            .get(&handle)
+            .expect("Shouldn't have an handle in the tree which isn't in the store.")
+    }
 }
 
-impl<'a, U, V> Iterator for Iter<'a, U, V>
+impl<'a, U, V, S> Iterator for Iter<'a, U, V, S>
 where
     U: PrimInt + Default,
+    S: BuildHasher,
 {
     type Item = &'a Entry<U, V>;
 
     #[inline]
     fn next(&mut self) -> Option<Self::Item> {
-        match self.handle_iter.next() {
-            Some(handle) => Some(
-                self.store
-                    .get(&handle)
-                    .expect("Shouldn't have an handle in the tree which isn't in the store."),
-            ),
-            None => None,
+        while self.front < self.back {
+            let (id, i) = self.front;
+            match self.qt.node(id).handles().get(i) {
+                Some(&handle) => {
+                    self.front = (id, i + 1);
+                    return Some(self.entry(handle));
+                }
+                None => self.front = (id + 1, 0),
+            }
         }
+        None
     }
 
     #[inline]
@@ -72,7 +104,32 @@ where
     }
 }
 
-impl<U, V> FusedIterator for Iter<'_, U, V> where U: PrimInt + Default {}
+impl<U, V, S> DoubleEndedIterator for Iter<'_, U, V, S>
+where
+    U: PrimInt + Default,
+    S: BuildHasher,
+{
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        while self.front < self.back {
+            match self.back {
+                (id, 0) => self.back = (id - 1, self.qt.node(id - 1).handles().len()),
+                (id, i) => {
+                    self.back = (id, i - 1);
+                    return Some(self.entry(self.qt.node(id).handles()[i - 1]));
+                }
+            }
+        }
+        None
+    }
+}
+
+impl<U, V, S> FusedIterator for Iter<'_, U, V, S>
+where
+    U: PrimInt + Default,
+    S: BuildHasher,
+{
+}
 
 /// A consuming iterator over all region/value associations held in a [`Quadtree`].
 ///
@@ -85,7 +142,7 @@ pub struct IntoIter<U, V>
 where
     U: PrimInt + Default,
 {
-    pub(crate) entries: Vec<Entry<U, V>>,
+    pub(crate) entries: std::vec::IntoIter<Entry<U, V>>,
 }
 
 impl<U, V> Iterator for IntoIter<U, V>
@@ -96,17 +153,97 @@ where
 
     #[inline]
     fn next(&mut self) -> Option<Self::Item> {
-        self.entries.pop()
+        self.entries.next()
     }
 
     #[inline]
     fn size_hint(&self) -> (usize, Option<usize>) {
-        (0, None)
+        self.entries.size_hint()
     }
 }
 
 impl<U, V> FusedIterator for IntoIter<U, V> where U: PrimInt + Default {}
 
+/// A draining iterator over region/value associations removed from a [`Quadtree`].
+///
+/// This struct is created by the [`drain`] and [`drain_region`] methods on [`Quadtree`]. Each
+/// entry is removed from the quadtree as it's yielded, and whatever hasn't been yielded by the
+/// time the iterator is dropped is removed then.
+///
+/// [`drain`]: ../struct.Quadtree.html#method.drain
+/// [`drain_region`]: ../struct.Quadtree.html#method.drain_region
+/// [`Quadtree`]: ../struct.Quadtree.html
+#[derive(Debug)]
+pub struct Drain<'a, U, V, S = RandomState>
+where
+    U: PrimInt + Default,
+    S: BuildHasher,
+{
+    qt: &'a mut Quadtree<U, V, S>,
+    handles: std::vec::IntoIter<u64>,
+    // Whether every entry is being drained, in which case the rest can be dropped wholesale.
+    everything: bool,
+}
+
+impl<'a, U, V, S> Drain<'a, U, V, S>
+where
+    U: PrimInt + Default,
+    S: BuildHasher,
+{
+    pub(crate) fn new(qt: &'a mut Quadtree<U, V, S>, handles: Vec<u64>, everything: bool) -> Self {
+        Drain {
+            qt,
+            handles: handles.into_iter(),
+            everything,
+        }
+    }
+}
+
+impl<U, V, S> Iterator for Drain<'_, U, V, S>
+where
+    U: PrimInt + Default,
+    S: BuildHasher,
+{
+    type Item = Entry<U, V>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let qt = &mut *self.qt;
+        self.handles
+            .by_ref()
+            .find_map(|handle| qt.delete_by_handle(handle))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.handles.len()))
+    }
+}
+
+impl<U, V, S> FusedIterator for Drain<'_, U, V, S>
+where
+    U: PrimInt + Default,
+    S: BuildHasher,
+{
+}
+
+impl<U, V, S> Drop for Drain<'_, U, V, S>
+where
+    U: PrimInt + Default,
+    S: BuildHasher,
+{
+    fn drop(&mut self) {
+        if self.everything {
+            self.qt.store.clear();
+            self.qt.inner.reset();
+        } else {
+            for handle in self.handles.by_ref() {
+                self.qt.delete_by_handle(handle);
+            }
+        }
+    }
+}
+
 /// An iterator over the regions and values of a [`Quadtree`].
 ///
 /// This struct is created by the [`query`] method on [`Quadtree`].
@@ -114,70 +251,172 @@ impl<U, V> FusedIterator for IntoIter<U, V> where U: PrimInt + Default {}
 /// [`query`]: ../struct.Quadtree.html#method.query
 /// [`Quadtree`]: ../struct.Quadtree.html
 #[derive(Clone, Debug)]
-pub struct Query<'a, U, V>
+pub struct Query<'a, U, V, S = RandomState>
 where
     U: PrimInt + Default,
 {
-    query_region: Area<U>,
-    handle_iter: HandleIter<'a, U>,
-    store: &'a StoreType<U, V>,
-    traversal_method: Traversal,
+    pub(crate) query_shape: Shape<U>,
+    pub(crate) handle_iter: HandleIter<'a, U>,
+    pub(crate) store: &'a StoreType<U, V, S>,
+    pub(crate) traversal_method: Traversal,
+    // How many more entries the query may produce, if it's been limited.
+    pub(crate) remaining: Option<usize>,
 }
 
-impl<'a, U, V> Query<'a, U, V>
+impl<'a, U, V, S> Query<'a, U, V, S>
 where
     U: PrimInt + Default,
 {
+    /// Stops the query once it has produced at most `n` entries.
+    ///
+    /// Unlike `.take(n)`, this is part of the query itself, so a `Query` which has been limited is
+    /// still a `Query`. Once the last entry has been produced, nothing more of the quadtree is
+    /// searched.
+    /// ```
+    /// use quadtree_rs::{area::AreaBuilder, Quadtree};
+    ///
+    /// let mut qt = Quadtree::<u32, u32>::new(4);
+    /// qt.extend((0..16).map(|i| ((i, i), i)));
+    ///
+    /// let region = AreaBuilder::default()
+    ///     .anchor((0, 0).into())
+    ///     .dimensions((16, 16))
+    ///     .build().unwrap();
+    /// assert_eq!(qt.query(region).limit(3).count(), 3);
+    /// assert_eq!(qt.query(region).limit(100).count(), 16);
+    /// ```
+    pub fn limit(mut self, n: usize) -> Self {
+        self.remaining = Some(self.remaining.map_or(n, |remaining| remaining.min(n)));
+        self
+    }
+
     pub(crate) fn new(
-        query_region: Area<U>,
+        query_shape: Shape<U>,
         qt: &'a QTInner<U>,
-        store: &'a StoreType<U, V>,
+        store: &'a StoreType<U, V, S>,
         traversal_method: Traversal,
-    ) -> Query<'a, U, V>
+    ) -> Query<'a, U, V, S>
     where
         U: PrimInt + Default,
     {
         // Construct the HandleIter first...
-        let mut handle_iter = HandleIter::new(qt, query_region);
+        let search_shape = traversal_method.search_shape(query_shape);
+        let mut handle_iter = HandleIter::new(qt, search_shape);
 
         // ...and descend it to the appropriate level. Depending on the type of @traversal_method,
         // this will potentially collect intersecting regions along the way. Avoiding combing the
         // entire Quadtree is essential for the efficiency of a query.
-        handle_iter.query_optimization(query_region, traversal_method);
+        if let Some(bounding_box) = search_shape.bounding_box() {
+            handle_iter.query_optimization(bounding_box, traversal_method);
+        }
 
         Query {
-            query_region,
+            query_shape,
             handle_iter,
             store,
             traversal_method,
+            remaining: None,
         }
     }
-}
 
-impl<'a, U, V> Iterator for Query<'a, U, V>
-where
-    U: PrimInt + Default,
-{
-    type Item = &'a Entry<U, V>;
+    // The next entry which matches the query and whose value satisfies @accept. @accept is only
+    // called for entries which match the query, and only accepted entries count towards a limit.
     #[inline]
-    fn next(&mut self) -> Option<Self::Item> {
+    pub(crate) fn next_matching<F>(&mut self, mut accept: F) -> Option<&'a Entry<U, V>>
+    where
+        F: FnMut(&V) -> bool,
+        S: BuildHasher,
+    {
+        if self.remaining == Some(0) {
+            return None;
+        }
         for handle in self.handle_iter.by_ref() {
             if let Some(entry) = self.store.get(&handle) {
-                if self.traversal_method.eval(entry.area(), self.query_region) {
+                if self.traversal_method.eval(entry.area(), self.query_shape)
+                    && accept(entry.value_ref())
+                {
+                    if let Some(remaining) = self.remaining.as_mut() {
+                        *remaining -= 1;
+                    }
                     return Some(entry);
                 }
             }
         }
         None
     }
+}
 
+impl<'a, U, V, S> Iterator for Query<'a, U, V, S>
+where
+    U: PrimInt + Default,
+    S: BuildHasher,
+{
+    type Item = &'a Entry<U, V>;
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_matching(|_| true)
+    }
+
+    // Any of the handles still to come might not match, but there can't be more matches than
+    // there are handles.
     #[inline]
     fn size_hint(&self) -> (usize, Option<usize>) {
-        (0, Some(self.store.len()))
+        let most = self.handle_iter.size_hint().1.unwrap_or(self.store.len());
+        (
+            0,
+            Some(self.remaining.map_or(most, |remaining| remaining.min(most))),
+        )
     }
 }
 
-impl<U, V> FusedIterator for Query<'_, U, V> where U: PrimInt + Default {}
+impl<U, V, S> FusedIterator for Query<'_, U, V, S>
+where
+    U: PrimInt + Default,
+    S: BuildHasher,
+{
+}
+
+/// An iterator over the regions and values of a [`Quadtree`] which filters values as it goes.
+///
+/// This struct is created by the [`query_filter`] method on [`Quadtree`].
+///
+/// [`query_filter`]: ../struct.Quadtree.html#method.query_filter
+/// [`Quadtree`]: ../struct.Quadtree.html
+#[derive(Clone, Debug)]
+pub struct QueryFilter<'a, U, V, F, S = RandomState>
+where
+    U: PrimInt + Default,
+{
+    pub(crate) query: Query<'a, U, V, S>,
+    pub(crate) pred: F,
+}
+
+impl<'a, U, V, F, S> Iterator for QueryFilter<'a, U, V, F, S>
+where
+    U: PrimInt + Default,
+    F: FnMut(&V) -> bool,
+    S: BuildHasher,
+{
+    type Item = &'a Entry<U, V>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.query.next_matching(&mut self.pred)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, self.query.size_hint().1)
+    }
+}
+
+impl<U, V, F, S> FusedIterator for QueryFilter<'_, U, V, F, S>
+where
+    U: PrimInt + Default,
+    F: FnMut(&V) -> bool,
+    S: BuildHasher,
+{
+}
 
 /// An iterator over the values held within a [`Quadtree`].
 ///
@@ -186,16 +425,17 @@ impl<U, V> FusedIterator for Query<'_, U, V> where U: PrimInt + Default {}
 /// [`values`]: ../struct.Quadtree.html#method.values
 /// [`Quadtree`]: ../struct.Quadtree.html
 #[derive(Clone, Debug)]
-pub struct Values<'a, U, V>
+pub struct Values<'a, U, V, S = RandomState>
 where
     U: PrimInt + Default,
 {
-    pub(crate) inner: Iter<'a, U, V>,
+    pub(crate) inner: Iter<'a, U, V, S>,
 }
 
-impl<'a, U, V> Iterator for Values<'a, U, V>
+impl<'a, U, V, S> Iterator for Values<'a, U, V, S>
 where
     U: PrimInt + Default,
+    S: BuildHasher,
 {
     type Item = &'a V;
 
@@ -206,11 +446,27 @@ where
 
     #[inline]
     fn size_hint(&self) -> (usize, Option<usize>) {
-        (0, None)
+        self.inner.size_hint()
     }
 }
 
-impl<U, V> FusedIterator for Values<'_, U, V> where U: PrimInt + Default {}
+impl<U, V, S> DoubleEndedIterator for Values<'_, U, V, S>
+where
+    U: PrimInt + Default,
+    S: BuildHasher,
+{
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back().map(|e| e.value_ref())
+    }
+}
+
+impl<U, V, S> FusedIterator for Values<'_, U, V, S>
+where
+    U: PrimInt + Default,
+    S: BuildHasher,
+{
+}
 
 /// An iterator over the regions held within a [`Quadtree`].
 ///
@@ -219,16 +475,17 @@ impl<U, V> FusedIterator for Values<'_, U, V> where U: PrimInt + Default {}
 /// [`regions`]: ../struct.Quadtree.html#method.regions
 /// [`Quadtree`]: ../struct.Quadtree.html
 #[derive(Clone, Debug)]
-pub struct Regions<'a, U, V>
+pub struct Regions<'a, U, V, S = RandomState>
 where
     U: PrimInt + Default,
 {
-    pub(crate) inner: Iter<'a, U, V>,
+    pub(crate) inner: Iter<'a, U, V, S>,
 }
 
-impl<'a, U, V> Iterator for Regions<'a, U, V>
+impl<'a, U, V, S> Iterator for Regions<'a, U, V, S>
 where
     U: PrimInt + Default,
+    S: BuildHasher,
 {
     type Item = Area<U>;
 
@@ -237,10 +494,584 @@ where
         self.inner.next().map(|e| e.area())
     }
 
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<U, V, S> DoubleEndedIterator for Regions<'_, U, V, S>
+where
+    U: PrimInt + Default,
+    S: BuildHasher,
+{
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back().map(|e| e.area())
+    }
+}
+
+impl<U, V, S> FusedIterator for Regions<'_, U, V, S>
+where
+    U: PrimInt + Default,
+    S: BuildHasher,
+{
+}
+
+/// An iterator over the handles of the values held within a [`Quadtree`].
+///
+/// This struct is created by the [`handles`] method on [`Quadtree`].
+///
+/// [`handles`]: ../struct.Quadtree.html#method.handles
+/// [`Quadtree`]: ../struct.Quadtree.html
+#[derive(Clone, Debug)]
+pub struct Handles<'a, U, V, S = RandomState>
+where
+    U: PrimInt + Default,
+{
+    pub(crate) inner: Iter<'a, U, V, S>,
+}
+
+impl<U, V, S> Iterator for Handles<'_, U, V, S>
+where
+    U: PrimInt + Default,
+    S: BuildHasher,
+{
+    type Item = u64;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|e| e.handle())
+    }
+
     #[inline]
     fn size_hint(&self) -> (usize, Option<usize>) {
         (0, None)
     }
 }
 
-impl<U, V> FusedIterator for Regions<'_, U, V> where U: PrimInt + Default {}
+impl<U, V, S> FusedIterator for Handles<'_, U, V, S>
+where
+    U: PrimInt + Default,
+    S: BuildHasher,
+{
+}
+
+/// An iterator over the handles, regions and values held within a [`Quadtree`].
+///
+/// This struct is created by the [`iter_with_handles`] method on [`Quadtree`].
+///
+/// [`iter_with_handles`]: ../struct.Quadtree.html#method.iter_with_handles
+/// [`Quadtree`]: ../struct.Quadtree.html
+#[derive(Clone, Debug)]
+pub struct IterWithHandles<'a, U, V, S = RandomState>
+where
+    U: PrimInt + Default,
+{
+    pub(crate) inner: Iter<'a, U, V, S>,
+}
+
+impl<'a, U, V, S> Iterator for IterWithHandles<'a, U, V, S>
+where
+    U: PrimInt + Default,
+    S: BuildHasher,
+{
+    type Item = (u64, Area<U>, &'a V);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner
+            .next()
+            .map(|e| (e.handle(), e.area(), e.value_ref()))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, None)
+    }
+}
+
+impl<U, V, S> FusedIterator for IterWithHandles<'_, U, V, S>
+where
+    U: PrimInt + Default,
+    S: BuildHasher,
+{
+}
+
+/// A mutable iterator over all regions and values of a [`Quadtree`].
+///
+/// The regions are handed out by value, so only the values can be changed through it.
+///
+/// This struct is created by the [`iter_mut`] method on [`Quadtree`].
+///
+/// [`iter_mut`]: ../struct.Quadtree.html#method.iter_mut
+/// [`Quadtree`]: ../struct.Quadtree.html
+#[derive(Debug)]
+pub struct IterMut<'a, U, V>
+where
+    U: PrimInt + Default,
+{
+    pub(crate) entries: EntriesMut<'a, U, V>,
+}
+
+impl<'a, U, V> Iterator for IterMut<'a, U, V>
+where
+    U: PrimInt + Default,
+{
+    type Item = (Area<U>, &'a mut V);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.entries.next().map(|e| (e.area(), e.value_mut()))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.entries.size_hint()
+    }
+}
+
+impl<U, V> FusedIterator for IterMut<'_, U, V> where U: PrimInt + Default {}
+
+/// A mutable iterator over the values held within a [`Quadtree`].
+///
+/// This struct is created by the [`values_mut`] method on [`Quadtree`].
+///
+/// [`values_mut`]: ../struct.Quadtree.html#method.values_mut
+/// [`Quadtree`]: ../struct.Quadtree.html
+#[derive(Debug)]
+pub struct ValuesMut<'a, U, V>
+where
+    U: PrimInt + Default,
+{
+    pub(crate) inner: IterMut<'a, U, V>,
+}
+
+impl<'a, U, V> Iterator for ValuesMut<'a, U, V>
+where
+    U: PrimInt + Default,
+{
+    type Item = &'a mut V;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(_, v)| v)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<U, V> FusedIterator for ValuesMut<'_, U, V> where U: PrimInt + Default {}
+
+/// An iterator over every pair of entries in a [`Quadtree`] whose regions intersect.
+///
+/// This struct is created by the [`collisions`] method on [`Quadtree`].
+///
+/// [`collisions`]: ../struct.Quadtree.html#method.collisions
+/// [`Quadtree`]: ../struct.Quadtree.html
+#[derive(Clone, Debug)]
+pub struct Collisions<'a, U, V, S = RandomState>
+where
+    U: PrimInt + Default,
+{
+    pub(crate) pairs: std::vec::IntoIter<(u64, u64)>,
+    pub(crate) store: &'a StoreType<U, V, S>,
+}
+
+impl<'a, U, V, S> Iterator for Collisions<'a, U, V, S>
+where
+    U: PrimInt + Default,
+    S: BuildHasher,
+{
+    type Item = (&'a Entry<U, V>, &'a Entry<U, V>);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let store = self.store;
+        self.pairs
+            .find_map(|(a, b)| Some((store.get(&a)?, store.get(&b)?)))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.pairs.len()))
+    }
+}
+
+impl<U, V, S> FusedIterator for Collisions<'_, U, V, S>
+where
+    U: PrimInt + Default,
+    S: BuildHasher,
+{
+}
+
+/// An iterator over the parts of a region which no region in a [`Quadtree`] covers.
+///
+/// This struct is created by the [`free_space`] method on [`Quadtree`].
+///
+/// [`free_space`]: ../struct.Quadtree.html#method.free_space
+/// [`Quadtree`]: ../struct.Quadtree.html
+#[derive(Clone, Debug)]
+pub struct FreeSpace<'a, U, V, S = RandomState>
+where
+    U: PrimInt + Default,
+{
+    tree: &'a Quadtree<U, V, S>,
+    area: Area<U>,
+    // The cells still to look at, as a stack. The next one to look at is on top.
+    cells: Vec<Area<U>>,
+}
+
+impl<'a, U, V, S> FreeSpace<'a, U, V, S>
+where
+    U: PrimInt + Default,
+    S: BuildHasher,
+{
+    // Starts from the root cell @root, or yields nothing if there's no @area to look in.
+    pub(crate) fn new(tree: &'a Quadtree<U, V, S>, root: Area<U>, area: Option<Area<U>>) -> Self {
+        FreeSpace {
+            tree,
+            area: area.unwrap_or(root),
+            cells: area.map(|_| root).into_iter().collect(),
+        }
+    }
+
+    // The four quadrants of the square @cell, in reverse reading order so that they come off the
+    // stack in reading order.
+    fn quadrants(cell: Area<U>) -> [Area<U>; 4] {
+        [
+            Quadrant::SouthEast,
+            Quadrant::SouthWest,
+            Quadrant::NorthEast,
+            Quadrant::NorthWest,
+        ]
+        .map(|quadrant| cell.quarter(quadrant))
+    }
+}
+
+impl<U, V, S> Iterator for FreeSpace<'_, U, V, S>
+where
+    U: PrimInt + Default,
+    S: BuildHasher,
+{
+    type Item = Area<U>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(cell) = self.cells.pop() {
+            let part = match cell.intersection(self.area) {
+                Some(part) => part,
+                None => continue,
+            };
+            if !self.tree.intersects_any(part) {
+                return Some(part);
+            }
+            // Nothing in a cell which a single region covers is free, and a unit cell which
+            // anything touches is covered.
+            if cell.width() > U::one() && self.tree.query_containing(part).next().is_none() {
+                self.cells.extend(Self::quadrants(cell));
+            }
+        }
+        None
+    }
+}
+
+impl<U, V, S> FusedIterator for FreeSpace<'_, U, V, S>
+where
+    U: PrimInt + Default,
+    S: BuildHasher,
+{
+}
+
+/// An iterator over the tiles of a [`Quadtree`] at some zoom level, each with the entries whose
+/// regions intersect it.
+///
+/// This struct is created by the [`tiles`] method on [`Quadtree`].
+///
+/// [`tiles`]: ../struct.Quadtree.html#method.tiles
+/// [`Quadtree`]: ../struct.Quadtree.html
+#[derive(Clone, Debug)]
+pub struct Tiles<'a, U, V, S = RandomState>
+where
+    U: PrimInt + Default,
+{
+    tree: &'a Quadtree<U, V, S>,
+    zoom: usize,
+    // The cells still to look at, with their (z, x, y), as a stack. The next one to look at is on
+    // top.
+    cells: Vec<(Area<U>, (usize, u64, u64))>,
+}
+
+impl<'a, U, V, S> Tiles<'a, U, V, S>
+where
+    U: PrimInt + Default,
+    S: BuildHasher,
+{
+    // Starts from the root cell @root, or yields nothing if there's no @zoom to cut it at.
+    pub(crate) fn new(tree: &'a Quadtree<U, V, S>, root: Area<U>, zoom: Option<usize>) -> Self {
+        Tiles {
+            tree,
+            zoom: zoom.unwrap_or(0),
+            cells: zoom.map(|_| (root, (0, 0, 0))).into_iter().collect(),
+        }
+    }
+}
+
+impl<'a, U, V, S> Iterator for Tiles<'a, U, V, S>
+where
+    U: PrimInt + Default,
+    S: BuildHasher,
+{
+    type Item = ((usize, u64, u64), Vec<&'a Entry<U, V>>);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((cell, (z, x, y))) = self.cells.pop() {
+            if z == self.zoom {
+                let entries: Vec<&'a Entry<U, V>> = self.tree.query(cell).collect();
+                if entries.is_empty() {
+                    continue;
+                }
+                return Some(((z, x, y), entries));
+            }
+            if !self.tree.intersects_any(cell) {
+                continue;
+            }
+            // Pushed in reverse reading order, so that they come off the stack in reading order.
+            for quadrant in [
+                Quadrant::SouthEast,
+                Quadrant::SouthWest,
+                Quadrant::NorthEast,
+                Quadrant::NorthWest,
+            ] {
+                let id = (
+                    z + 1,
+                    2 * x + u64::from(quadrant.is_east()),
+                    2 * y + u64::from(quadrant.is_south()),
+                );
+                self.cells.push((cell.quarter(quadrant), id));
+            }
+        }
+        None
+    }
+}
+
+impl<U, V, S> FusedIterator for Tiles<'_, U, V, S>
+where
+    U: PrimInt + Default,
+    S: BuildHasher,
+{
+}
+
+/// The order in which [`Quadtree::nodes_in()`] and [`Quadtree::iter_in()`] walk the cells of a
+/// quadtree.
+///
+/// In every order a cell comes before the cells beneath it.
+///
+/// [`Quadtree::nodes_in()`]: ../struct.Quadtree.html#method.nodes_in
+/// [`Quadtree::iter_in()`]: ../struct.Quadtree.html#method.iter_in
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Order {
+    /// Each cell is followed by everything beneath it before its next sibling, and the cells
+    /// beneath a cell come in the order north-east, north-west, south-east, south-west.
+    DepthFirst,
+    /// A level at a time, from the root down: coarse cells before fine ones.
+    BreadthFirst,
+    /// Z-order, as in a depth-first walk, but taking the cells beneath a cell in the order
+    /// north-west, north-east, south-west, south-east. Cells which are near each other in the
+    /// plane tend to be near each other in this order.
+    Morton,
+    /// Along a Hilbert curve, as in a depth-first walk, but turning the order of the cells
+    /// beneath each cell so that every cell is followed by one which shares an edge with it.
+    /// Nearby cells stay together even more than in Morton order, at the cost of a little work
+    /// per cell.
+    Hilbert,
+}
+
+// The orientation of the Hilbert curve through a cell, as the symmetry of the square which maps
+// the curve's basic shape onto it: the identity, a flip about either diagonal, or a half turn.
+// Combining two of them is an xor.
+const TRANSPOSED: u8 = 1;
+const ANTI_TRANSPOSED: u8 = 2;
+
+// The subquadrants of a cell through which the Hilbert curve runs with @orientation, in the order
+// the curve visits them, each with the orientation of the curve through it.
+//
+// The basic shape runs north-west, south-west, south-east, north-east. The curve runs through the
+// first of those flipped about the main diagonal, so that it leaves towards the second, and
+// through the last flipped about the other diagonal.
+fn hilbert_quadrants(orientation: u8) -> [(usize, u8); 4] {
+    let basic = [
+        ((0, 0), TRANSPOSED),
+        ((0, 1), 0),
+        ((1, 1), 0),
+        ((1, 0), ANTI_TRANSPOSED),
+    ];
+    basic.map(|((x, y), turn)| {
+        // As (east, south) bits.
+        let (x, y) = match orientation {
+            0 => (x, y),
+            TRANSPOSED => (y, x),
+            ANTI_TRANSPOSED => (1 - y, 1 - x),
+            _ => (1 - x, 1 - y),
+        };
+        let quadrant = match (x, y) {
+            (1, 0) => 0,
+            (0, 0) => 1,
+            (1, 1) => 2,
+            _ => 3,
+        };
+        (quadrant, orientation ^ turn)
+    })
+}
+
+/// An iterator over the cells which a [`Quadtree`] is divided into, as `(region, depth,
+/// item_count)` triples.
+///
+/// This struct is created by the [`nodes`] and [`nodes_in`] methods on [`Quadtree`].
+///
+/// [`nodes`]: ../struct.Quadtree.html#method.nodes
+/// [`nodes_in`]: ../struct.Quadtree.html#method.nodes_in
+/// [`Quadtree`]: ../struct.Quadtree.html
+#[derive(Clone, Debug)]
+pub struct Nodes<'a, U>
+where
+    U: PrimInt + Default,
+{
+    qt: &'a QTInner<U>,
+    order: Order,
+    // The nodes found but not yet visited, each with the orientation of the Hilbert curve through
+    // it. Breadth-first walks take them from the front, as a queue; the others take them from the
+    // back, as a stack.
+    pending: VecDeque<(NodeId, u8)>,
+}
+
+impl<'a, U> Nodes<'a, U>
+where
+    U: PrimInt + Default,
+{
+    pub(crate) fn new(qt: &'a QTInner<U>, order: Order) -> Self {
+        Nodes {
+            qt,
+            order,
+            pending: VecDeque::from([(QTInner::<U>::ROOT, 0)]),
+        }
+    }
+
+    fn next_node(&mut self) -> Option<&'a Node<U>> {
+        let qt = self.qt;
+        let (id, orientation) = match self.order {
+            Order::BreadthFirst => self.pending.pop_front()?,
+            _ => self.pending.pop_back()?,
+        };
+        let node = qt.node(id);
+        if let Some(sqs) = node.subquadrants() {
+            let [ne, nw, se, sw] = sqs.map(|id| (id, 0));
+            // Stacks are pushed in reverse, so that the first comes off first.
+            self.pending.extend(match self.order {
+                Order::BreadthFirst => [ne, nw, se, sw],
+                Order::DepthFirst => [sw, se, nw, ne],
+                Order::Morton => [se, sw, ne, nw],
+                Order::Hilbert => {
+                    let mut next = hilbert_quadrants(orientation)
+                        .map(|(quadrant, orientation)| (sqs[quadrant], orientation));
+                    next.reverse();
+                    next
+                }
+            });
+        }
+        Some(node)
+    }
+}
+
+impl<U> Iterator for Nodes<'_, U>
+where
+    U: PrimInt + Default,
+{
+    type Item = (Area<U>, usize, usize);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.next_node()?;
+        // Nodes count their depth down towards the leaves; the root's is the tree's.
+        let depth = self.qt.root().depth() - node.depth();
+        Some((node.region(), depth, node.handles().len()))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.pending.len(), Some(self.qt.node_count()))
+    }
+}
+
+impl<U> FusedIterator for Nodes<'_, U> where U: PrimInt + Default {}
+
+/// An iterator over all regions and values of a [`Quadtree`], taking its cells in a chosen
+/// [`Order`].
+///
+/// This struct is created by the [`iter_in`] method on [`Quadtree`].
+///
+/// [`Order`]: enum.Order.html
+/// [`iter_in`]: ../struct.Quadtree.html#method.iter_in
+/// [`Quadtree`]: ../struct.Quadtree.html
+#[derive(Clone, Debug)]
+pub struct OrderedIter<'a, U, V, S = RandomState>
+where
+    U: PrimInt + Default,
+{
+    store: &'a StoreType<U, V, S>,
+    nodes: Nodes<'a, U>,
+    // The handles still to come from the node visited last.
+    handles: &'a [u64],
+}
+
+impl<'a, U, V, S> OrderedIter<'a, U, V, S>
+where
+    U: PrimInt + Default,
+{
+    pub(crate) fn new(qt: &'a QTInner<U>, store: &'a StoreType<U, V, S>, order: Order) -> Self {
+        OrderedIter {
+            store,
+            nodes: Nodes::new(qt, order),
+            handles: &[],
+        }
+    }
+}
+
+impl<'a, U, V, S> Iterator for OrderedIter<'a, U, V, S>
+where
+    U: PrimInt + Default,
+    S: BuildHasher,
+{
+    type Item = &'a Entry<U, V>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some((&handle, rest)) = self.handles.split_first() {
+                self.handles = rest;
+                return Some(
+                    self.store
+                        .get(&handle)
+                        .expect("Shouldn't have an handle in the tree which isn't in the store."),
+                );
+            }
+            self.handles = self.nodes.next_node()?.handles();
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.handles.len(), Some(self.store.len()))
+    }
+}
+
+impl<U, V, S> FusedIterator for OrderedIter<'_, U, V, S>
+where
+    U: PrimInt + Default,
+    S: BuildHasher,
+{
+}
@@ -81,28 +81,28 @@
 //! //                           |       |       |
 //! //                           +---+---+---+---+
 //!
-//! // If a region cannot be represented by one node in the tree, a handle type is inserted in multiple places.
+//! // A region which straddles the subquadrants of a node is held by that node, however large it
+//! // is.
 //! let region_c = AreaBuilder::default()
 //!     .anchor(Point {x: 0, y: 0})
 //!     .dimensions((3, 3))
 //!     .build().unwrap();
 //! qt.insert(region_c, 'c');
 //!
-//! // (0,0)->4x4                +---+---+---+---+
-//! //   (0,0)->2x2 ['b', 'c']   | a |   | c |   |
-//! //     (0,0)->1x1 ['a']      +---+   +---+---+
-//! //   (0,2)->2x2              |   b,c | c |   |
-//! //     (0,2)->1x1 ['c']      +---+---+---+---+
-//! //     (1,2)->1x1 ['c']      | c | c | c |   |
-//! //   (2,0)->2x2              +---+---+---+---+
-//! //     (2,0)->1x1 ['c']      |   |   |   |   |
-//! //     (2,1)->1x1 ['c']      +---+---+---+---+
-//! //   (2,2)->2x2
-//! //     (2,2)->1x1 ['c']
+//! // (0,0)->4x4 ['c']          +---+---+---+---+
+//! //   (0,0)->2x2 ['b']        | a |   |       |
+//! //     (0,0)->1x1 ['a']      +---+   +       +
+//! //                           |     b |   c   |
+//! //                           +---+---+---+---+
+//! //                           |       |       |
+//! //                           +       +       +
+//! //                           |       |       |
+//! //                           +---+---+---+---+
 //! ```
 //!
-//! Duplicating the storage handle allows for fast lookup and insertion at the cost of slow
-//! deletion. `quadtree_rs` is well-suited for scenarios with low churn but frequent read access.
+//! Every handle is held by exactly one node, the smallest whose cell totally contains its region,
+//! so inserting or deleting a region of any size touches a single node. In exchange, a query has
+//! to look at the regions held by every node above the cells it covers, not just those below.
 //!
 //! # Usage
 //!
@@ -117,32 +117,90 @@
 extern crate derive_builder;
 extern crate num;
 
+#[cfg(feature = "aggregate")]
+pub mod aggregate;
+#[cfg(feature = "rkyv")]
+pub mod archive;
 pub mod area;
+#[cfg(feature = "concurrent")]
+pub mod concurrent;
+pub mod cursor;
 pub mod entry;
+pub mod error;
+#[cfg(feature = "euclid")]
+pub mod euclid;
+#[cfg(feature = "geojson")]
+pub mod geojson;
+#[cfg(feature = "index")]
+pub mod index;
 pub mod iter;
+#[cfg(feature = "ndtree")]
+pub mod ndtree;
+#[cfg(feature = "octree")]
+pub mod octree;
+#[cfg(feature = "rayon")]
+pub mod par_iter;
 pub mod point;
+pub mod point_quadtree;
+pub mod snapshot;
+pub mod split;
+pub mod view;
+pub mod visit;
+#[cfg(feature = "wkt")]
+pub mod wkt;
 
+mod clustering;
+mod coverage;
+mod csv;
+#[cfg(feature = "geo")]
+mod geo;
+mod geometry;
+#[cfg(feature = "glam")]
+mod glam;
+mod grid;
 mod handle_iter;
+mod image;
+mod morton;
+#[cfg(feature = "nalgebra")]
+mod nalgebra;
+mod nearest;
+mod occupancy;
 mod qtinner;
+mod quadkey;
+mod quantize;
+mod raycast;
+#[cfg(feature = "serde")]
+mod serialization;
+mod shape;
 mod traversal;
 mod types;
 
 use {
     crate::{
         area::{Area, AreaBuilder},
+        cursor::Quadrant,
         entry::Entry,
-        handle_iter::HandleIter,
-        iter::{IntoIter, Iter, Query, Regions, Values},
+        error::InsertError,
+        iter::{
+            Collisions, Drain, FreeSpace, Handles, IntoIter, Iter, IterMut, IterWithHandles, Nodes,
+            Order, OrderedIter, Query, QueryFilter, Regions, Values, ValuesMut,
+        },
+        nearest::NearestIter,
         point::Point,
-        qtinner::QTInner,
+        qtinner::{Layout, QTInner},
+        shape::Shape,
+        split::{CapacityLimited, DepthLimited, SplitPolicy},
         traversal::Traversal,
         types::StoreType,
+        view::QuadtreeView,
     },
     num::PrimInt,
     std::{
-        collections::{HashMap, HashSet},
+        collections::{hash_map::RandomState, HashMap},
         default::Default,
-        hash::Hash,
+        fmt,
+        hash::BuildHasher,
+        sync::Arc,
     },
 };
 
@@ -154,14 +212,19 @@ use {
 ///
 /// ## Parameterization
 ///
-/// `Quadtree<U, V>` is parameterized over
-///  - `U`, the type of the coordinate, and
-///  - `V`, the value being stored.
+/// `Quadtree<U, V, S>` is parameterized over
+///  - `U`, the type of the coordinate,
+///  - `V`, the value being stored, and
+///  - `S`, the hasher used by the internal store. This defaults to `RandomState`.
 ///
 /// `U` must implement `num::PrimInt` and a set of arithmetic operations necessary for coordinate
 /// insertion and comparison. `U` must also implement `std::default` for [`derive_builder`]
 /// semantics.
 ///
+/// Every lookup of an entry by its handle goes through `S`. Handles are small sequential integers
+/// which don't need a DoS-resistant hash, so for large trees a faster hasher can be supplied
+/// through [`.with_hasher()`].
+///
 /// ## Strictness
 ///
 /// Some methods ([`.query()`], [`.modify()`], and [`.delete()`]) have strict variants. While the
@@ -169,19 +232,95 @@ use {
 /// operational region, the strict behavior is for the operation to apply only to those regions
 /// which are _totally contained by_ the operational region.
 ///
+/// The inverse, [`.query_containing()`], applies only to those regions which _totally contain_
+/// the operational region, and [`.query_exact()`] only to those which _are_ the operational
+/// region.
+///
+/// ## Storage
+///
+/// Each entry is held by exactly one cell of the tree, however large its region is. With the
+/// default [`DepthLimited`] split policy that's the smallest cell which totally contains the
+/// region, as in an MX-CIF quadtree: a region which straddles the borders of a cell's quadrants
+/// stays at that cell. Inserting or deleting a large region therefore touches a single cell, and
+/// queries never see the same entry twice. Other [split policies] may keep an entry in a leaf
+/// above that cell until the leaf fills up, and a [loose] quadtree files entries by their center.
+///
+/// ## Points
+///
+/// When every entry is a point, a [`PointQuadtree`] does the same job without keeping a region
+/// per entry, packing the points of each leaf together.
+///
+/// ## Serialization
+///
+/// With the `serde` feature enabled, `Quadtree<U, V>` implements `Serialize` and `Deserialize`.
+/// Only the tree's bounds and its entries (including their handles) are written out; on
+/// deserialization the node structure is rebuilt from scratch, and input describing entries which
+/// lie outside the tree or which reuse a handle is rejected.
+///
+/// ## Parallelism
+///
+/// With the `rayon` feature enabled, the entries and values of a quadtree can be walked in
+/// parallel with [`.par_iter()`], [`.par_values()`] and [`.par_values_mut()`], and queried in
+/// parallel with [`.par_query()`]. See the [`par_iter`] module.
+///
+/// ## Indexing
+///
+/// With the `index` feature enabled, an [`IndexedQuadtree`] keeps a reverse index from values to
+/// the regions they're stored at, for values which implement `Eq + Hash`.
+///
 /// [`derive_builder`]: https://docs.rs/derive_builder/0.7.0/derive_builder/
 /// [`.query()`]: #method.query
 /// [`.modify()`]: #method.modify
 /// [`.delete()`]: #method.delete
+/// [`.query_containing()`]: #method.query_containing
+/// [`.query_exact()`]: #method.query_exact
+/// [`DepthLimited`]: split/struct.DepthLimited.html
+/// [split policies]: #method.set_split_policy
+/// [loose]: #method.set_looseness
+/// [`.with_hasher()`]: #method.with_hasher
+/// [`.par_iter()`]: #method.par_iter
+/// [`.par_values()`]: #method.par_values
+/// [`.par_values_mut()`]: #method.par_values_mut
+/// [`.par_query()`]: #method.par_query
+/// [`par_iter`]: par_iter/index.html
+/// [`IndexedQuadtree`]: index/struct.IndexedQuadtree.html
+/// [`PointQuadtree`]: point_quadtree/struct.PointQuadtree.html
 // TODO(ambuc): Implement `.delete_by(anchor, dimensions, fn)`: `.retain()` is the inverse.
 // TODO(ambuc): Implement `FromIterator<(K, V)>` for `Quadtree`.
-#[derive(Debug, PartialEq, Eq)]
-pub struct Quadtree<U, V>
+#[derive(Clone)]
+pub struct Quadtree<U, V, S = RandomState>
 where
     U: PrimInt + Default,
 {
     inner: QTInner<U>,
-    store: StoreType<U, V>,
+    store: StoreType<U, V, S>,
+    // The region which the quadtree accepts insertions into. This is usually the root's region,
+    // but a quadtree built with `new_with_bounds()` is clipped to some smaller rectangle anchored
+    // at the same point.
+    bounds: Area<U>,
+    // What happens to an insertion outside @bounds.
+    bounds_policy: BoundsPolicy,
+    // The handle which the next insertion will be assigned. Handles are sequential and never
+    // re-used.
+    next_handle: u64,
+}
+
+/// What a quadtree does with an insertion whose region doesn't fit within it. See
+/// [`Quadtree::set_bounds_policy()`].
+///
+/// [`Quadtree::set_bounds_policy()`]: struct.Quadtree.html#method.set_bounds_policy
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BoundsPolicy {
+    /// The insertion fails. This is the default.
+    #[default]
+    Reject,
+    /// The region is clipped to the part of it which lies within the quadtree, and that's what is
+    /// stored. Regions which don't overlap the quadtree at all are still rejected.
+    Clip,
+    /// The quadtree grows to fit the region. See [`Quadtree::set_auto_expand()`].
+    ///
+    /// [`Quadtree::set_auto_expand()`]: struct.Quadtree.html#method.set_auto_expand
+    Expand,
 }
 
 impl<U, V> Quadtree<U, V>
@@ -204,6 +343,11 @@ where
     /// assert_eq!(qt.width(), 4);
     /// assert_eq!(qt.height(), 4);
     /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `2^depth` isn't representable in `U`: a `Quadtree<u8, _>` can be at most 7
+    /// deep, for example.
     pub fn new(depth: usize) -> Self {
         Self::new_with_anchor(
             point::Point {
@@ -233,13 +377,532 @@ where
     /// assert_eq!(qt.width(), 8);
     /// assert_eq!(qt.height(), 8);
     /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the far edges of the quadtree, `anchor + 2^depth`, aren't representable in `U`.
     pub fn new_with_anchor(anchor: point::Point<U>, depth: usize) -> Self {
+        Self::with_anchor_and_hasher(anchor, depth, RandomState::new())
+    }
+
+    /// Creates a new, empty quadtree covering an arbitrary rectangle.
+    ///
+    /// The depth is chosen automatically: it's the smallest which fits both the width and the
+    /// height. The quadtree only accepts insertions within the given rectangle, and its
+    /// [`.width()`] and [`.height()`] are those of the rectangle rather than of the underlying
+    /// square.
+    /// ```
+    /// use quadtree_rs::Quadtree;
+    ///
+    /// let mut qt = Quadtree::<u32, u8>::new_with_bounds((0, 0).into(), 1920, 1080);
+    ///
+    /// assert_eq!(qt.depth(), 11);
+    /// assert_eq!(qt.width(), 1920);
+    /// assert_eq!(qt.height(), 1080);
+    ///
+    /// assert!(qt.insert_pt((1919, 1079).into(), 1).is_some());
+    /// // Inside the underlying 2048x2048 square, but outside the bounds.
+    /// assert!(qt.insert_pt((1919, 1080).into(), 2).is_none());
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if either dimension is zero, or if no square of side `2^depth` is representable in
+    /// `U`.
+    ///
+    /// [`.width()`]: #method.width
+    /// [`.height()`]: #method.height
+    pub fn new_with_bounds(anchor: point::Point<U>, width: U, height: U) -> Self {
+        let bounds = AreaBuilder::default()
+            .anchor(anchor)
+            .dimensions((width, height))
+            .build()
+            .expect("The bounds of a quadtree must have a nonzero width and height.");
+
+        let mut qt = Self::new_with_anchor(anchor, Self::square_to_fit(width.max(height)).0);
+        qt.bounds = bounds;
+        qt
+    }
+
+    /// Creates a new quadtree holding every one of `items`, built in a single pass.
+    ///
+    /// This is equivalent to creating an empty quadtree with [`.new_with_anchor()`] and inserting
+    /// each item in turn, but faster for large inputs: the items are sorted once by their
+    /// position in the tree and every node is built exactly once, rather than being walked from
+    /// the root for each insertion.
+    ///
+    /// The `n`th item is given the handle `n`. Items which don't fit in the quadtree are dropped,
+    /// but still use up their handle, so that the handles of the others are predictable.
+    /// ```
+    /// use quadtree_rs::{area::AreaBuilder, Quadtree};
+    ///
+    /// let items = (0..4_u32).map(|i| {
+    ///     let region = AreaBuilder::default().anchor((i, i).into()).build().unwrap();
+    ///     (region, i * 10)
+    /// });
+    /// let qt = Quadtree::<u32, u32>::bulk_load((0, 0).into(), 2, items);
+    ///
+    /// assert_eq!(qt.len(), 4);
+    /// assert_eq!(qt.get(2).unwrap().value_ref(), &20);
+    /// ```
+    ///
+    /// [`.new_with_anchor()`]: #method.new_with_anchor
+    pub fn bulk_load<I>(anchor: point::Point<U>, depth: usize, items: I) -> Self
+    where
+        I: IntoIterator<Item = (Area<U>, V)>,
+    {
+        let mut qt = Self::new_with_anchor(anchor, depth);
+        let mut placed = Vec::new();
+        for (region, val) in items {
+            let handle = qt.next_handle;
+            qt.next_handle += 1;
+            if qt.contains(region) {
+                qt.store.insert(handle, Entry::new((region, val), handle));
+                placed.push((handle, region));
+            }
+        }
+        qt.inner = QTInner::bulk_load(anchor, depth, &placed);
+        qt
+    }
+
+    /// Creates a new quadtree just large enough to hold every one of `items`, and fills it.
+    ///
+    /// The quadtree is anchored at the upper-left corner of the items' bounding box, and its depth
+    /// is the smallest whose square covers the whole box. The `n`th item is given the handle `n`,
+    /// just as with [`.bulk_load()`]. An empty iterator gives an empty quadtree of depth 0 at the
+    /// origin.
+    /// ```
+    /// use quadtree_rs::{area::AreaBuilder, point::Point, Quadtree};
+    ///
+    /// let items = vec![
+    ///     (AreaBuilder::default().anchor((10, 20).into()).build().unwrap(), 'a'),
+    ///     (
+    ///         AreaBuilder::default().anchor((13, 14).into()).dimensions((6, 2)).build().unwrap(),
+    ///         'b',
+    ///     ),
+    /// ];
+    /// let qt = Quadtree::<u32, char>::from_regions(items);
+    ///
+    /// // The bounding box is 9 wide and 7 tall, so the quadtree is 16x16.
+    /// assert_eq!(qt.anchor(), Point {x: 10, y: 14});
+    /// assert_eq!(qt.depth(), 4);
+    /// assert_eq!(qt.len(), 2);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the bounding box, or the square which covers it, isn't representable in `U`.
+    ///
+    /// [`.bulk_load()`]: #method.bulk_load
+    pub fn from_regions<I>(items: I) -> Self
+    where
+        I: IntoIterator<Item = (Area<U>, V)>,
+    {
+        let items: Vec<(Area<U>, V)> = items.into_iter().collect();
+        let mut corners = items.iter().map(|(region, _)| {
+            let far = |edge: U, len: U| {
+                edge.checked_add(&len)
+                    .expect("The items are too large for this coordinate type.")
+            };
+            (
+                region.anchor(),
+                Point {
+                    x: far(region.anchor().x(), region.width()),
+                    y: far(region.anchor().y(), region.height()),
+                },
+            )
+        });
+        let (near, far) = match corners.next() {
+            Some(first) => corners.fold(first, |(near, far), (a, b)| {
+                (
+                    Point {
+                        x: near.x().min(a.x()),
+                        y: near.y().min(a.y()),
+                    },
+                    Point {
+                        x: far.x().max(b.x()),
+                        y: far.y().max(b.y()),
+                    },
+                )
+            }),
+            None => return Self::new(0),
+        };
+
+        let span = |lo: U, hi: U| {
+            hi.checked_sub(&lo)
+                .expect("The items are too spread out for this coordinate type.")
+        };
+        let side = span(near.x(), far.x()).max(span(near.y(), far.y()));
+        let (depth, edge) = Self::square_to_fit(side);
+        // The far edges of the quadtree's square have to be representable too.
+        near.x()
+            .checked_add(&edge)
+            .and_then(|_| near.y().checked_add(&edge))
+            .expect("The items are too spread out for this coordinate type.");
+        Self::bulk_load(near, depth, items)
+    }
+
+    /// Creates a new quadtree just large enough to hold every one of `items`, which are points,
+    /// and fills it. See [`.from_regions()`].
+    /// ```
+    /// use quadtree_rs::{point::Point, Quadtree};
+    ///
+    /// let qt = Quadtree::<i32, u8>::from_points(vec![((-3, 4).into(), 1), ((2, -1).into(), 2)]);
+    ///
+    /// assert_eq!(qt.anchor(), Point {x: -3, y: -1});
+    /// assert_eq!(qt.depth(), 3);
+    /// assert_eq!(qt.query_pt((2, -1).into()).next().unwrap().value_ref(), &2);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the bounding box, or the square which covers it, isn't representable in `U`.
+    ///
+    /// [`.from_regions()`]: #method.from_regions
+    pub fn from_points<I>(items: I) -> Self
+    where
+        I: IntoIterator<Item = (Point<U>, V)>,
+    {
+        Self::from_regions(
+            items
+                .into_iter()
+                .map(|(pt, val)| (Self::area_at_pt(pt), val)),
+        )
+    }
+
+    /// Creates a new, empty quadtree with some depth, with room for at least `capacity` elements
+    /// before its internal store has to reallocate.
+    /// ```
+    /// use quadtree_rs::Quadtree;
+    ///
+    /// let qt = Quadtree::<u32, u8>::with_capacity(/*depth=*/ 4, 100);
+    /// assert!(qt.capacity() >= 100);
+    /// assert!(qt.is_empty());
+    /// ```
+    pub fn with_capacity(depth: usize, capacity: usize) -> Self {
+        let mut qt = Self::new(depth);
+        qt.reserve(capacity);
+        qt
+    }
+
+    /// Creates a new, empty quadtree with some depth, whose entries are kept in insertion order.
+    ///
+    /// The entries of an ordered quadtree are stored in a `BTreeMap` rather than a `HashMap`.
+    /// Lookups by handle are a little slower, but every operation which walks the whole store
+    /// ([`.modify_all()`], [`.retain()`], [`.delete()`] and consuming the quadtree with
+    /// `into_iter()`) visits its entries in the order they were inserted. This is useful for
+    /// snapshot tests and for replaying a sequence of operations.
+    /// ```
+    /// use quadtree_rs::Quadtree;
+    ///
+    /// let mut qt = Quadtree::<u32, char>::new_ordered(/*depth=*/ 4);
+    /// qt.extend(vec![((9, 9), 'a'), ((1, 1), 'b'), ((5, 5), 'c')]);
+    ///
+    /// let values: Vec<char> = qt.into_iter().map(|e| *e.value_ref()).collect();
+    /// assert_eq!(values, vec!['a', 'b', 'c']);
+    /// ```
+    ///
+    /// [`.modify_all()`]: #method.modify_all
+    /// [`.retain()`]: #method.retain
+    /// [`.delete()`]: #method.delete
+    pub fn new_ordered(depth: usize) -> Self {
+        Self::new_ordered_with_anchor(
+            point::Point {
+                x: U::zero(),
+                y: U::zero(),
+            },
+            depth,
+        )
+    }
+
+    /// Creates a new, empty quadtree with some depth and an explicit anchor, whose entries are
+    /// kept in insertion order. See [`.new_ordered()`].
+    ///
+    /// [`.new_ordered()`]: #method.new_ordered
+    pub fn new_ordered_with_anchor(anchor: point::Point<U>, depth: usize) -> Self {
+        Self::check_root(anchor, depth).unwrap_or_else(|msg| panic!("{}", msg));
+        let inner = QTInner::new(anchor, depth, Layout::default());
+        Self {
+            bounds: inner.region(),
+            inner,
+            bounds_policy: BoundsPolicy::default(),
+            store: StoreType::ordered(RandomState::new()),
+            next_handle: 0,
+        }
+    }
+}
+
+impl<U, V, S> Quadtree<U, V, S>
+where
+    U: PrimInt + Default,
+    S: BuildHasher,
+{
+    // pub
+
+    /// Creates a new, empty quadtree with some depth, which will use the given hash builder to
+    /// hash handles in its internal store.
+    /// ```
+    /// use quadtree_rs::{area::AreaBuilder, Quadtree};
+    /// use std::{collections::hash_map::DefaultHasher, hash::BuildHasherDefault};
+    ///
+    /// let mut qt = Quadtree::<u32, u8, _>::with_hasher(
+    ///     /*depth=*/ 2,
+    ///     BuildHasherDefault::<DefaultHasher>::default(),
+    /// );
+    ///
+    /// let region = AreaBuilder::default().anchor((1, 1).into()).build().unwrap();
+    /// let handle = qt.insert(region, 5).unwrap();
+    /// assert_eq!(qt.get(handle).unwrap().value_ref(), &5);
+    /// ```
+    pub fn with_hasher(depth: usize, hash_builder: S) -> Self {
+        Self::with_anchor_and_hasher(
+            point::Point {
+                x: U::zero(),
+                y: U::zero(),
+            },
+            depth,
+            hash_builder,
+        )
+    }
+
+    /// Creates a new, empty quadtree with some depth and an explicit anchor, which will use the
+    /// given hash builder to hash handles in its internal store.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the far edges of the quadtree, `anchor + 2^depth`, aren't representable in `U`.
+    pub fn with_anchor_and_hasher(anchor: point::Point<U>, depth: usize, hash_builder: S) -> Self {
+        Self::check_root(anchor, depth).unwrap_or_else(|msg| panic!("{}", msg));
+        let inner = QTInner::new(anchor, depth, Layout::default());
         Self {
-            inner: QTInner::new(anchor, depth),
-            store: HashMap::new(),
+            bounds: inner.region(),
+            inner,
+            bounds_policy: BoundsPolicy::default(),
+            store: StoreType::hashed(hash_builder),
+            next_handle: 0,
+        }
+    }
+
+    /// The hash builder used by the quadtree's internal store.
+    pub fn hasher(&self) -> &S {
+        self.store.hasher()
+    }
+
+    /// Whether or not the quadtree keeps its entries in insertion order. See [`.new_ordered()`].
+    ///
+    /// [`.new_ordered()`]: #method.new_ordered
+    pub fn is_ordered(&self) -> bool {
+        self.store.is_ordered()
+    }
+
+    /// Turns auto-expansion on or off. It's off by default.
+    ///
+    /// While it's on, an insertion which doesn't fit in the quadtree grows the quadtree to fit
+    /// instead of failing. Each step of growth doubles the width and height of the region toward
+    /// the new item, so that the old region becomes one quadrant of the new one and the depth goes
+    /// up by one. Existing handles stay valid throughout.
+    ///
+    /// A quadtree built with [`.new_with_bounds()`] loses its clipping the first time it grows.
+    /// Insertions which would need a region not representable in `U` still fail.
+    ///
+    /// Auto-expansion is a property of this particular quadtree. It isn't compared by `==`, and
+    /// it isn't serialized.
+    /// ```
+    /// use quadtree_rs::{point::Point, Quadtree};
+    ///
+    /// let mut qt = Quadtree::<i32, char>::new_with_anchor((0, 0).into(), 2);
+    /// assert!(qt.insert_pt((5, -1).into(), 'a').is_none());
+    ///
+    /// qt.set_auto_expand(true);
+    /// let handle = qt.insert_pt((5, -1).into(), 'a').unwrap();
+    ///
+    /// // Grown rightward and upward from a 4x4 region to 8x8.
+    /// assert_eq!(qt.anchor(), Point {x: 0, y: -4});
+    /// assert_eq!(qt.depth(), 3);
+    /// assert_eq!(qt.get(handle).unwrap().value_ref(), &'a');
+    /// ```
+    ///
+    /// [`.new_with_bounds()`]: #method.new_with_bounds
+    pub fn set_auto_expand(&mut self, auto_expand: bool) {
+        self.bounds_policy = if auto_expand {
+            BoundsPolicy::Expand
+        } else {
+            BoundsPolicy::Reject
+        };
+    }
+
+    /// Sets what happens to insertions (and relocations) whose region doesn't fit within the
+    /// quadtree. By default they're rejected.
+    ///
+    /// Like auto-expansion, the policy isn't compared by `==` and isn't serialized.
+    /// ```
+    /// use quadtree_rs::{area::AreaBuilder, BoundsPolicy, Quadtree};
+    ///
+    /// let mut qt = Quadtree::<u32, &str>::new(4);
+    /// qt.set_bounds_policy(BoundsPolicy::Clip);
+    ///
+    /// // A feature which runs a little past the edge of the tile.
+    /// let region = AreaBuilder::default()
+    ///     .anchor((12, 2).into())
+    ///     .dimensions((8, 3))
+    ///     .build().unwrap();
+    /// let handle = qt.insert(region, "road").unwrap();
+    ///
+    /// let clipped = qt.get(handle).unwrap().area();
+    /// assert_eq!((clipped.width(), clipped.height()), (4, 3));
+    /// ```
+    pub fn set_bounds_policy(&mut self, bounds_policy: BoundsPolicy) {
+        self.bounds_policy = bounds_policy;
+    }
+
+    /// What happens to insertions whose region doesn't fit within the quadtree. See
+    /// [`.set_bounds_policy()`].
+    ///
+    /// [`.set_bounds_policy()`]: #method.set_bounds_policy
+    pub fn bounds_policy(&self) -> BoundsPolicy {
+        self.bounds_policy
+    }
+
+    /// Sets the policy which decides when cells are subdivided. See the [`split`] module.
+    ///
+    /// By default, cells are subdivided as far as an entry's region allows ([`DepthLimited`]).
+    /// The depth always bounds how far cells can be subdivided, whatever the policy.
+    ///
+    /// Changing the policy rebuilds the tree, so it's best done straight after construction. It
+    /// isn't compared by `==`, and it isn't serialized.
+    /// ```
+    /// use quadtree_rs::{split::Hybrid, Quadtree};
+    ///
+    /// let mut qt = Quadtree::<u32, u8>::new(16);
+    /// qt.set_split_policy(Hybrid { max_items_per_node: 8, max_level: 10 });
+    ///
+    /// qt.extend((0..100).map(|i| ((i * 600, i * 600), 0)));
+    /// assert_eq!(qt.len(), 100);
+    /// ```
+    ///
+    /// [`split`]: split/index.html
+    /// [`DepthLimited`]: split/struct.DepthLimited.html
+    pub fn set_split_policy<P>(&mut self, split_policy: P)
+    where
+        P: SplitPolicy + 'static,
+    {
+        self.relayout(Layout {
+            split_policy: Arc::new(split_policy),
+            ..self.inner.layout().clone()
+        });
+    }
+
+    /// The policy which decides when cells are subdivided. See [`.set_split_policy()`].
+    ///
+    /// [`.set_split_policy()`]: #method.set_split_policy
+    pub fn split_policy(&self) -> &dyn SplitPolicy {
+        self.inner.layout().split_policy.as_ref()
+    }
+
+    /// Sets the number of entries a cell may hold before it's subdivided. By default this is
+    /// `None`, and cells are always subdivided as far as an entry's region allows.
+    ///
+    /// With a limit in place, a leaf only splits into subquadrants once it would otherwise hold
+    /// more than `max_items_per_node` entries, pushing down those which fit in a subquadrant.
+    /// Sparse parts of the tree stay shallow, saving memory and the cost of walking down to the
+    /// bottom depth, while dense clusters are still subdivided as far as they need to be.
+    ///
+    /// This is shorthand for [`.set_split_policy()`] with a [`CapacityLimited`] policy, or with
+    /// the default [`DepthLimited`] policy for `None`.
+    /// ```
+    /// use quadtree_rs::{area::AreaBuilder, Quadtree};
+    ///
+    /// let mut qt = Quadtree::<u32, u8>::new(16);
+    /// qt.set_max_items_per_node(Some(8));
+    ///
+    /// qt.extend((0..100).map(|i| ((i * 600, i * 600), 0)));
+    ///
+    /// let region = AreaBuilder::default()
+    ///     .anchor((0, 0).into())
+    ///     .dimensions((6000, 6000))
+    ///     .build().unwrap();
+    /// assert_eq!(qt.query(region).count(), 10);
+    /// ```
+    ///
+    /// [`.set_split_policy()`]: #method.set_split_policy
+    /// [`CapacityLimited`]: split/struct.CapacityLimited.html
+    /// [`DepthLimited`]: split/struct.DepthLimited.html
+    pub fn set_max_items_per_node(&mut self, max_items_per_node: Option<usize>) {
+        match max_items_per_node {
+            Some(max_items_per_node) => {
+                self.set_split_policy(CapacityLimited { max_items_per_node })
+            }
+            None => self.set_split_policy(DepthLimited),
         }
     }
 
+    /// Makes the quadtree loose, or tight again. By default, quadtrees are tight.
+    ///
+    /// In a tight quadtree, an entry is held by the smallest cell which totally contains its
+    /// region. A small region which happens to straddle the boundary between two large cells is
+    /// held high up in the tree, and every query which passes through there has to look at it;
+    /// as it moves around, it jumps between very different levels of the tree.
+    ///
+    /// In a loose quadtree, the bounds of every cell below the root are inflated by a factor of
+    /// `looseness` about the cell's center. Entries are held by the cell containing their center
+    /// whenever they fit within its bounds, so a region never gets stuck high up in the tree just
+    /// because of where it lies, only because of how big it is. This makes loose quadtrees a good
+    /// broad phase for lots of small moving objects. A `looseness` of 2 is typical: each cell's
+    /// bounds are then twice as wide and tall as the cell. A `looseness` of 1 makes the quadtree
+    /// tight.
+    ///
+    /// Queries return the same entries either way, though loose cells overlap, so a query may
+    /// have to look at more of them. Changing the looseness rebuilds the tree, so it's best done
+    /// straight after construction. It isn't compared by `==`, and it isn't serialized.
+    /// ```
+    /// use quadtree_rs::{area::AreaBuilder, Quadtree};
+    ///
+    /// let mut qt = Quadtree::<u32, char>::new(8);
+    /// qt.set_looseness(2.0);
+    /// assert_eq!(qt.looseness(), 2.0);
+    ///
+    /// // This straddles the center of the tree, but it's small enough for a loose subquadrant.
+    /// let region = AreaBuilder::default()
+    ///     .anchor((126, 126).into())
+    ///     .dimensions((4, 4))
+    ///     .build().unwrap();
+    /// qt.insert(region, 'a');
+    ///
+    /// let query = AreaBuilder::default()
+    ///     .anchor((129, 129).into())
+    ///     .build().unwrap();
+    /// assert_eq!(qt.query(query).count(), 1);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `looseness` is less than 1, or isn't finite.
+    pub fn set_looseness(&mut self, looseness: f64) {
+        assert!(
+            looseness.is_finite() && looseness >= 1.0,
+            "The looseness of a quadtree must be a finite number no less than 1."
+        );
+        self.relayout(Layout {
+            looseness,
+            ..self.inner.layout().clone()
+        });
+    }
+
+    /// How much the bounds of each cell are inflated by. See [`.set_looseness()`].
+    ///
+    /// [`.set_looseness()`]: #method.set_looseness
+    pub fn looseness(&self) -> f64 {
+        self.inner.layout().looseness
+    }
+
+    /// Whether or not the quadtree grows to fit out-of-bounds insertions. See
+    /// [`.set_auto_expand()`].
+    ///
+    /// [`.set_auto_expand()`]: #method.set_auto_expand
+    pub fn is_auto_expanding(&self) -> bool {
+        self.bounds_policy == BoundsPolicy::Expand
+    }
+
     /// The top-left corner (anchor) of the region which this quadtree represents.
     pub fn anchor(&self) -> point::Point<U> {
         self.inner.region().anchor()
@@ -247,12 +910,18 @@ where
 
     /// The width of the region which this quadtree represents.
     pub fn width(&self) -> usize {
-        self.inner.region().width().to_usize().unwrap()
+        self.bounds
+            .width()
+            .to_usize()
+            .expect("The quadtree is too wide to measure in a usize.")
     }
 
     /// The height of the region which this quadtree represents.
     pub fn height(&self) -> usize {
-        self.inner.region().height().to_usize().unwrap()
+        self.bounds
+            .height()
+            .to_usize()
+            .expect("The quadtree is too tall to measure in a usize.")
     }
 
     /// The depth of the quadtree.
@@ -270,9 +939,60 @@ where
         self.store.is_empty()
     }
 
+    /// The number of elements the quadtree can hold without reallocating its internal store.
+    ///
+    /// An [ordered] quadtree's store grows one entry at a time, so its capacity is always its
+    /// length.
+    ///
+    /// [ordered]: #method.new_ordered
+    pub fn capacity(&self) -> usize {
+        self.store.capacity()
+    }
+
+    /// Reserves room in the internal store for at least `additional` more elements, to avoid
+    /// repeated rehashing during a long run of insertions. Does nothing for an [ordered]
+    /// quadtree.
+    /// ```
+    /// use quadtree_rs::Quadtree;
+    ///
+    /// let mut qt = Quadtree::<u32, u8>::new(4);
+    /// qt.reserve(1000);
+    /// assert!(qt.capacity() >= 1000);
+    /// ```
+    ///
+    /// [ordered]: #method.new_ordered
+    pub fn reserve(&mut self, additional: usize) {
+        self.store.reserve(additional);
+    }
+
+    /// Releases as much spare memory as possible, for instance after deleting most of the
+    /// quadtree's elements.
+    ///
+    /// Besides shrinking the internal store, this drops any subquadrants which no longer hold
+    /// anything and compacts the nodes which are left.
+    /// ```
+    /// use quadtree_rs::Quadtree;
+    ///
+    /// let mut qt = Quadtree::<u32, u32>::with_capacity(8, 1000);
+    /// for i in 0..1000 {
+    ///     qt.insert_pt((i % 256, i / 256).into(), i);
+    /// }
+    /// for handle in 10..1000 {
+    ///     qt.delete_by_handle(handle);
+    /// }
+    ///
+    /// qt.shrink_to_fit();
+    /// assert!(qt.capacity() < 1000);
+    /// assert_eq!(qt.len(), 10);
+    /// ```
+    pub fn shrink_to_fit(&mut self) {
+        self.store.shrink_to_fit();
+        self.inner.shrink_to_fit();
+    }
+
     /// Whether or not some trial region could fit in the region which this quadtree represents.
     pub fn contains(&self, area: Area<U>) -> bool {
-        self.inner.region().contains(area)
+        self.bounds.contains(area)
     }
 
     /// Associate some value with a region in the quadtree.
@@ -280,7 +1000,8 @@ where
     /// If insertion is successful, returns a unique handle to the value.
     ///
     /// If the region is too large for, or doesn't overlap with, the region which this quadtree
-    /// represents, returns `None`.
+    /// represents, returns `None`. (Unless the quadtree's [bounds policy] says otherwise, in which
+    /// case it grows to fit, or the region is clipped to fit.)
     /// ```
     /// use quadtree_rs::{area::AreaBuilder, point::Point, Quadtree};
     ///
@@ -298,40 +1019,335 @@ where
     /// // two handles returned were not the same.
     /// assert_ne!(handle_a_1, handle_a_2);
     /// ```
+    ///
+    /// [bounds policy]: #method.set_bounds_policy
     pub fn insert(&mut self, region: Area<U>, val: V) -> Option<u64> {
-        if self.contains(region) {
-            return Some(
-                self.inner
-                    .insert_val_at_region(region, val, &mut self.store),
-            );
-        }
-        None
+        let region = self.fit(region)?;
+        let handle = self.next_handle;
+        self.insert_with_handle(region, val, handle);
+        Some(handle)
     }
 
-    /// Alias for [`.insert()`] which expects a [`Point`] instead of an [`Area`].
-    ///
-    /// (An [`Area`] is really just a [`Point`] with dimensions `(1, 1)`, so
-    /// the point still has to fit within the region.)
+    /// Associate some value with a region in the quadtree, given as an anchor and a `(width,
+    /// height)` size, explaining why if it can't.
     ///
+    /// Unlike [`.insert()`], this checks that the region is well-formed, so there's no need to
+    /// build an [`Area`] first.
     /// ```
-    /// use quadtree_rs::{point::Point, Quadtree};
+    /// use quadtree_rs::{error::InsertError, Quadtree};
     ///
-    /// let mut qt = Quadtree::<u32, i8>::new(2);
+    /// let mut qt = Quadtree::<u8, char>::new(4);
     ///
-    /// assert!(qt.insert_pt(Point { x: 1, y: 2 }, 5_i8).is_some());
+    /// assert_eq!(qt.try_insert((2, 3).into(), (4, 4), 'a'), Ok(0));
+    /// assert_eq!(qt.try_insert((14, 3).into(), (4, 4), 'b'), Err(InsertError::OutOfBounds));
+    /// assert_eq!(qt.try_insert((2, 3).into(), (0, 4), 'c'), Err(InsertError::ZeroSize));
+    /// assert_eq!(qt.try_insert((250, 3).into(), (9, 1), 'd'), Err(InsertError::Overflow));
     /// ```
     ///
     /// [`.insert()`]: #method.insert
     /// [`Area`]: area/struct.Area.html
-    /// [`Point`]: point/struct.Point.html
-    pub fn insert_pt(&mut self, point: Point<U>, val: V) -> Option<u64> {
-        if let Ok(area) = AreaBuilder::default().anchor(point).build() {
-            return self.insert(area, val);
+    pub fn try_insert(
+        &mut self,
+        anchor: Point<U>,
+        (width, height): (U, U),
+        val: V,
+    ) -> Result<u64, InsertError> {
+        if width <= U::zero() || height <= U::zero() {
+            return Err(InsertError::ZeroSize);
         }
-        None
+        if anchor.x().checked_add(&width).is_none() || anchor.y().checked_add(&height).is_none() {
+            return Err(InsertError::Overflow);
+        }
+        let region = AreaBuilder::default()
+            .anchor(anchor)
+            .dimensions((width, height))
+            .build()
+            .expect("Unexpected error in Quadtree::try_insert().");
+        self.insert(region, val).ok_or(InsertError::OutOfBounds)
     }
 
-    /// Given the handle from an [`.insert()`] operation, provides read-only
+    /// Modifies the value associated with exactly `region`, or associates `default` with it if
+    /// there's no such value yet. Returns the handle of the value either way, or `None` if
+    /// `default` had to be inserted but didn't fit.
+    ///
+    /// This saves querying for a region before deciding whether to insert it, which is handy for
+    /// caches and counters. Only a value whose region is identical to `region` is modified, not
+    /// one which merely overlaps or contains it. If there are several, which one is modified is
+    /// unspecified.
+    /// ```
+    /// use quadtree_rs::{area::AreaBuilder, Quadtree};
+    ///
+    /// let mut qt = Quadtree::<u32, u32>::new(4);
+    ///
+    /// let tile = AreaBuilder::default()
+    ///     .anchor((4, 4).into())
+    ///     .dimensions((2, 2))
+    ///     .build().unwrap();
+    /// let handle = qt.insert_or_modify(tile, 1, |count| *count += 1).unwrap();
+    /// assert_eq!(qt.insert_or_modify(tile, 1, |count| *count += 1), Some(handle));
+    ///
+    /// assert_eq!(qt.len(), 1);
+    /// assert_eq!(qt.get(handle).unwrap().value_ref(), &2);
+    /// ```
+    pub fn insert_or_modify<F>(&mut self, region: Area<U>, default: V, f: F) -> Option<u64>
+    where
+        F: FnOnce(&mut V),
+    {
+        match self.find_exact(region) {
+            Some(handle) => {
+                if let Some(entry) = self.store.get_mut(&handle) {
+                    f(entry.value_mut());
+                }
+                Some(handle)
+            }
+            None => self.insert(region, default),
+        }
+    }
+
+    /// Swaps `val` in for the value associated with exactly `region`, and returns the old value.
+    ///
+    /// The entry keeps its handle and nothing in the tree moves. If no value is associated with
+    /// exactly `region`, nothing changes, `val` is dropped and `None` is returned. As with
+    /// [`.insert_or_modify()`], if there are several such values, which one is replaced is
+    /// unspecified.
+    /// ```
+    /// use quadtree_rs::{area::AreaBuilder, Quadtree};
+    ///
+    /// let mut qt = Quadtree::<u32, char>::new(4);
+    ///
+    /// let tile = AreaBuilder::default()
+    ///     .anchor((4, 4).into())
+    ///     .dimensions((2, 2))
+    ///     .build().unwrap();
+    /// let handle = qt.insert(tile, 'a').unwrap();
+    ///
+    /// assert_eq!(qt.replace(tile, 'b'), Some('a'));
+    /// assert_eq!(qt.get(handle).unwrap().value_ref(), &'b');
+    /// ```
+    ///
+    /// [`.insert_or_modify()`]: #method.insert_or_modify
+    pub fn replace(&mut self, region: Area<U>, val: V) -> Option<V> {
+        let handle = self.find_exact(region)?;
+        self.store
+            .get_mut(&handle)
+            .map(|entry| std::mem::replace(entry.value_mut(), val))
+    }
+
+    /// Alias for [`.insert()`] which expects a [`Point`] instead of an [`Area`].
+    ///
+    /// (An [`Area`] is really just a [`Point`] with dimensions `(1, 1)`, so
+    /// the point still has to fit within the region.)
+    ///
+    /// ```
+    /// use quadtree_rs::{point::Point, Quadtree};
+    ///
+    /// let mut qt = Quadtree::<u32, i8>::new(2);
+    ///
+    /// assert!(qt.insert_pt(Point { x: 1, y: 2 }, 5_i8).is_some());
+    /// ```
+    ///
+    /// [`.insert()`]: #method.insert
+    /// [`Area`]: area/struct.Area.html
+    /// [`Point`]: point/struct.Point.html
+    pub fn insert_pt(&mut self, point: Point<U>, val: V) -> Option<u64> {
+        if let Ok(area) = AreaBuilder::default().anchor(point).build() {
+            return self.insert(area, val);
+        }
+        None
+    }
+
+    /// Inserts every `((x, y), value)` point, just like `extend()`, but hands back the items
+    /// which didn't fit rather than silently dropping them.
+    /// ```
+    /// use quadtree_rs::Quadtree;
+    ///
+    /// let mut qt = Quadtree::<u32, char>::new(2);
+    ///
+    /// let rejected = qt.try_extend(vec![((0, 0), 'a'), ((9, 9), 'b'), ((3, 3), 'c')]);
+    /// assert_eq!(rejected, vec![((9, 9), 'b')]);
+    /// assert_eq!(qt.len(), 2);
+    /// ```
+    pub fn try_extend<T>(&mut self, iter: T) -> Vec<((U, U), V)>
+    where
+        T: IntoIterator<Item = ((U, U), V)>,
+    {
+        let mut rejected = Vec::new();
+        for ((x, y), val) in iter {
+            let region = Self::area_at_pt(point::Point { x, y });
+            match self.fit(region) {
+                Some(region) => {
+                    let handle = self.next_handle;
+                    self.insert_with_handle(region, val, handle);
+                }
+                None => rejected.push(((x, y), val)),
+            }
+        }
+        rejected
+    }
+
+    /// Moves every entry of `other` into this quadtree, and returns the regions and values of any
+    /// which didn't fit.
+    ///
+    /// Each entry is given a new handle here, and whether it fits is up to this quadtree's
+    /// [bounds policy], just as if it had been inserted. If both quadtrees have the same anchor,
+    /// depth, looseness and split policy, and `other`'s bounds lie within this one's, everything
+    /// fits, and `other`'s subtrees are grafted onto this quadtree's wherever it has nothing,
+    /// rather than inserting their entries one at a time.
+    /// ```
+    /// use quadtree_rs::Quadtree;
+    ///
+    /// let mut left = Quadtree::<u32, char>::new(4);
+    /// left.extend(vec![((1, 1), 'a'), ((2, 2), 'b')]);
+    ///
+    /// let mut right = Quadtree::<u32, char>::new(4);
+    /// right.extend(vec![((12, 12), 'c')]);
+    ///
+    /// assert!(left.merge(right).is_empty());
+    /// assert_eq!(left.len(), 3);
+    /// assert_eq!(left.query_pt((12, 12).into()).next().unwrap().value_ref(), &'c');
+    ///
+    /// // A quadtree which is too small for some of another's entries hands them back.
+    /// let mut small = Quadtree::<u32, char>::new(2);
+    /// let rejected = small.merge(left);
+    /// assert_eq!(small.len(), 2);
+    /// assert_eq!(rejected.len(), 1);
+    /// ```
+    ///
+    /// [bounds policy]: #method.set_bounds_policy
+    pub fn merge<T>(&mut self, other: Quadtree<U, V, T>) -> Vec<(Area<U>, V)>
+    where
+        T: BuildHasher,
+    {
+        let graftable = self.inner.region() == other.inner.region()
+            && self.inner.layout().same_as(other.inner.layout())
+            && self.bounds.contains(other.bounds);
+        if !graftable {
+            let mut rejected = Vec::new();
+            for entry in other.store.into_values() {
+                let (region, val) = entry.into_parts();
+                match self.fit(region) {
+                    Some(region) => {
+                        let handle = self.next_handle;
+                        self.insert_with_handle(region, val, handle);
+                    }
+                    None => rejected.push((region, val)),
+                }
+            }
+            return rejected;
+        }
+
+        let Quadtree { inner, store, .. } = other;
+        let mut renumbered = HashMap::with_capacity(store.len());
+        self.store.reserve(store.len());
+        for entry in store.into_values() {
+            let handle = self.next_handle;
+            self.next_handle += 1;
+            renumbered.insert(entry.handle(), handle);
+            self.store
+                .insert(handle, Entry::new(entry.into_parts(), handle));
+        }
+        let store = &self.store;
+        self.inner
+            .graft(&inner, &|handle| renumbered[&handle], &|handle| {
+                store.get(&handle).map(Entry::area)
+            });
+        Vec::new()
+    }
+
+    /// Consumes the quadtree, splitting it into one quadtree for each of its four top-level
+    /// quadrants, in the order `[northwest, northeast, southwest, southeast]`.
+    ///
+    /// Each has the anchor of its quadrant and one less level of depth, and otherwise the same
+    /// settings as this quadtree. Entries keep their handles, and each new quadtree carries on
+    /// issuing handles from where this one left off. An entry whose region straddles more than
+    /// one quadrant is clipped to each of them, as with [`BoundsPolicy::Clip`], and so its value
+    /// is cloned into each.
+    ///
+    /// Panics if the quadtree has a depth of zero, and so no quadrants.
+    /// ```
+    /// use quadtree_rs::{area::AreaBuilder, Quadtree};
+    ///
+    /// let mut qt = Quadtree::<u32, char>::new(4);
+    /// let a = qt.insert_pt((1, 1).into(), 'a').unwrap();
+    /// qt.insert_pt((12, 3).into(), 'b');
+    /// let wide = AreaBuilder::default()
+    ///     .anchor((6, 10).into())
+    ///     .dimensions((4, 2))
+    ///     .build().unwrap();
+    /// qt.insert(wide, 'c');
+    ///
+    /// let [nw, ne, sw, se] = qt.split_quadrants();
+    /// assert_eq!(nw.get(a).unwrap().value_ref(), &'a');
+    /// assert_eq!(ne.anchor(), (8, 0).into());
+    /// assert_eq!(ne.depth(), 3);
+    /// assert_eq!(ne.values().collect::<Vec<_>>(), vec![&'b']);
+    ///
+    /// // 'c' straddles the southern quadrants.
+    /// assert_eq!(sw.regions().next().unwrap().width(), 2);
+    /// assert_eq!(se.regions().next().unwrap().width(), 2);
+    /// ```
+    ///
+    /// [`BoundsPolicy::Clip`]: enum.BoundsPolicy.html#variant.Clip
+    pub fn split_quadrants(self) -> [Quadtree<U, V, S>; 4]
+    where
+        V: Clone,
+        S: Clone,
+    {
+        assert!(
+            self.depth() > 0,
+            "A quadtree of depth 0 has no quadrants to split into."
+        );
+        let root = self.inner.region();
+        let mut quadrants = [
+            Quadrant::NorthWest,
+            Quadrant::NorthEast,
+            Quadrant::SouthWest,
+            Quadrant::SouthEast,
+        ]
+        .map(|quadrant| {
+            let inner = QTInner::new(
+                root.quarter(quadrant).anchor(),
+                self.depth() - 1,
+                self.inner.layout().clone(),
+            );
+            let hash_builder = self.hasher().clone();
+            Quadtree {
+                // A quadrant which lies wholly outside bounds clipped by `new_with_bounds()`
+                // keeps the whole of its region.
+                bounds: self
+                    .bounds
+                    .intersection(inner.region())
+                    .unwrap_or(inner.region()),
+                inner,
+                bounds_policy: self.bounds_policy,
+                store: if self.is_ordered() {
+                    StoreType::ordered(hash_builder)
+                } else {
+                    StoreType::hashed(hash_builder)
+                },
+                next_handle: self.next_handle,
+            }
+        });
+
+        for entry in self.store.into_values() {
+            let handle = entry.handle();
+            let (region, val) = entry.into_parts();
+            let mut overlapping = quadrants
+                .iter_mut()
+                .filter_map(|qt| Some((qt.bounds.intersection(region)?, qt)))
+                .peekable();
+            while let Some((clipped, qt)) = overlapping.next() {
+                if overlapping.peek().is_none() {
+                    qt.insert_with_handle(clipped, val, handle);
+                    break;
+                }
+                qt.insert_with_handle(clipped, val.clone(), handle);
+            }
+        }
+        quadrants
+    }
+
+    /// Given the handle from an [`.insert()`] operation, provides read-only
     /// access to the associated [`Entry<U, V>`] struct.
     ///
     /// Handles are unique and never re-used, so lookup of a handle to a now-deleted entry can
@@ -386,6 +1402,84 @@ where
         self.store.get_mut(&handle)
     }
 
+    /// Returns the [`&Entry<U, V>`] of some region which contains a point, if there is one.
+    ///
+    /// This is meant for quadtrees where at most one region covers any given point, such as
+    /// grids of tiles. It descends straight to the cell holding the point without building a
+    /// [`Query`], and returns the first region it finds there. If several regions contain the
+    /// point, which one is returned is unspecified; use [`.query_pt()`] to see all of them.
+    /// ```
+    /// use quadtree_rs::{area::AreaBuilder, point::Point, Quadtree};
+    ///
+    /// let mut qt = Quadtree::<u32, char>::new(4);
+    ///
+    /// let tile = AreaBuilder::default()
+    ///     .anchor((4, 4).into())
+    ///     .dimensions((4, 4))
+    ///     .build().unwrap();
+    /// qt.insert(tile, 'x');
+    ///
+    /// assert_eq!(qt.get_pt(Point {x: 5, y: 7}).unwrap().value_ref(), &'x');
+    /// assert!(qt.get_pt(Point {x: 8, y: 8}).is_none());
+    /// ```
+    ///
+    /// [`&Entry<U, V>`]: entry/struct.Entry.html
+    /// [`Query`]: iter/struct.Query.html
+    /// [`.query_pt()`]: #method.query_pt
+    pub fn get_pt(&self, point: Point<U>) -> Option<&Entry<U, V>> {
+        if !self.inner.region().contains_pt(point) {
+            return None;
+        }
+        let handle = self
+            .inner
+            .find_containing(Self::area_at_pt(point), |handle| {
+                self.store
+                    .get(&handle)
+                    .is_some_and(|entry| entry.area().contains_pt(point))
+            })?;
+        self.store.get(&handle)
+    }
+
+    /// Returns the [`&Entry<U, V>`] of every value associated with exactly `region`, in the order
+    /// they were inserted.
+    ///
+    /// Several values can share a region, so this treats the quadtree as a multimap. Unlike
+    /// [`.query()`], it ignores regions which merely overlap, contain or lie within `region`.
+    /// ```
+    /// use quadtree_rs::{area::AreaBuilder, Quadtree};
+    ///
+    /// let mut qt = Quadtree::<u32, char>::new(4);
+    ///
+    /// let tile = AreaBuilder::default()
+    ///     .anchor((4, 4).into())
+    ///     .dimensions((2, 2))
+    ///     .build().unwrap();
+    /// qt.insert(tile, 'a');
+    /// qt.insert(tile, 'b');
+    /// qt.insert_pt((4, 4).into(), 'c');
+    ///
+    /// let values: Vec<char> = qt.get_all(tile).iter().map(|e| *e.value_ref()).collect();
+    /// assert_eq!(values, vec!['a', 'b']);
+    /// ```
+    ///
+    /// [`&Entry<U, V>`]: entry/struct.Entry.html
+    /// [`.query()`]: #method.query
+    pub fn get_all(&self, region: Area<U>) -> Vec<&Entry<U, V>> {
+        if !self.inner.region().contains(region) {
+            return Vec::new();
+        }
+        let mut handles = self.inner.filter_containing(region, |handle| {
+            self.store
+                .get(&handle)
+                .is_some_and(|entry| entry.area() == region)
+        });
+        handles.sort_unstable();
+        handles
+            .iter()
+            .filter_map(|handle| self.store.get(handle))
+            .collect()
+    }
+
     /// Returns an iterator over [`&Entry<U, V>`] structs representing values
     /// within the query region.
     /// ```
@@ -452,137 +1546,1123 @@ where
     /// [`&Entry<U, V>`]: entry/struct.Entry.html
     /// [`.query()`]: #method.query
     // TODO(ambuc): Settle on a stable return order to avoid breaking callers.
-    pub fn query(&self, area: Area<U>) -> Query<U, V> {
-        Query::new(area, &self.inner, &self.store, Traversal::Overlapping)
+    pub fn query(&self, area: Area<U>) -> Query<'_, U, V, S> {
+        Query::new(
+            area.into(),
+            &self.inner,
+            &self.store,
+            Traversal::Overlapping,
+        )
     }
 
-    /// A strict variant of [`.query()`].
+    /// Alias for [`.query()`] which expects a [`Point`] instead of an [`Area`].
+    ///
+    /// ```
+    /// use quadtree_rs::{area::AreaBuilder, point::Point, Quadtree};
+    ///
+    /// let mut qt = Quadtree::<u32, char>::new(4);
+    /// qt.insert(AreaBuilder::default()
+    ///     .anchor((1, 1).into())
+    ///     .dimensions((3, 3))
+    ///     .build().unwrap(), 'a');
+    ///
+    /// assert_eq!(qt.query_pt(Point {x: 3, y: 2}).count(), 1);
+    /// assert_eq!(qt.query_pt(Point {x: 4, y: 2}).count(), 0);
+    /// ```
     ///
     /// [`.query()`]: #method.query
-    pub fn query_strict(&self, area: Area<U>) -> Query<U, V> {
-        Query::new(area, &self.inner, &self.store, Traversal::Strict)
+    /// [`Area`]: area/struct.Area.html
+    /// [`Point`]: point/struct.Point.html
+    pub fn query_pt(&self, point: Point<U>) -> Query<'_, U, V, S> {
+        self.query(Self::area_at_pt(point))
     }
 
-    /// Accepts a modification lambda and applies it to all elements in the
-    /// quadtree which intersecting the described region.
+    /// A strict variant of [`.query()`], which only returns [`&Entry<U, V>`] structs whose
+    /// regions are _totally contained by_ the query region.
     ///
     /// ```
     /// use quadtree_rs::{area::AreaBuilder, Quadtree};
     ///
-    /// let mut qt = Quadtree::<u8, bool>::new(3);
+    /// let mut qt = Quadtree::<u32, char>::new(4);
     ///
-    /// let region_a = AreaBuilder::default()
+    /// qt.insert(AreaBuilder::default()
+    ///     .anchor((1, 1).into())
+    ///     .dimensions((2, 2))
+    ///     .build().unwrap(), 'a');
+    /// qt.insert(AreaBuilder::default()
+    ///     .anchor((2, 2).into())
+    ///     .dimensions((4, 4))
+    ///     .build().unwrap(), 'b');
+    ///
+    /// let region = AreaBuilder::default()
     ///     .anchor((0, 0).into())
+    ///     .dimensions((4, 4))
     ///     .build().unwrap();
-    /// let handle = qt.insert(region_a, true).unwrap();
     ///
-    /// // Run a modification lambda over all values in region_a...
-    /// qt.modify(region_a, |i| *i = false);
+    /// // Both regions overlap the query region...
+    /// assert_eq!(qt.query(region).count(), 2);
     ///
-    /// // ...and verify that the value was applied.
-    /// assert_eq!(qt.get(handle).unwrap().value_ref(), &false);
+    /// // ...but only one of them fits inside it.
+    /// let mut query = qt.query_strict(region);
+    /// assert_eq!(query.next().unwrap().value_ref(), &'a');
+    /// assert!(query.next().is_none());
     /// ```
-    pub fn modify<F>(&mut self, area: Area<U>, f: F)
-    where
-        F: Fn(&mut V) + Copy,
-    {
-        self.modify_region(|a| a.intersects(area), f);
-    }
-
-    /// A strict variant of [`.modify()`].
     ///
-    /// [`.modify()`]: #method.modify
-    pub fn modify_strict<F>(&mut self, area: Area<U>, f: F)
-    where
-        F: Fn(&mut V) + Copy,
-    {
-        self.modify_region(|a| area.contains(a), f);
+    /// [`&Entry<U, V>`]: entry/struct.Entry.html
+    /// [`.query()`]: #method.query
+    pub fn query_strict(&self, area: Area<U>) -> Query<'_, U, V, S> {
+        Query::new(area.into(), &self.inner, &self.store, Traversal::Strict)
     }
 
-    /// Alias for [`.modify()`] which runs over the entire
-    /// quadtree.
+    /// Alias for [`.query_strict()`] which expects a [`Point`] instead of an [`Area`].
     ///
-    /// [`.modify()`]: #method.modify
-    pub fn modify_all<F>(&mut self, f: F)
-    where
-        F: Fn(&mut V) + Copy,
-    {
-        for entry in self.store.values_mut() {
-            f(entry.value_mut());
-        }
-    }
-
-    /// Resets the quadtree to a totally empty state.
-    pub fn reset(&mut self) {
-        self.store.clear();
-        self.inner.reset();
+    /// Since a point is a region with dimensions `(1, 1)`, this only returns entries whose regions
+    /// are exactly that point.
+    ///
+    /// [`.query_strict()`]: #method.query_strict
+    /// [`Area`]: area/struct.Area.html
+    /// [`Point`]: point/struct.Point.html
+    pub fn query_strict_pt(&self, point: Point<U>) -> Query<'_, U, V, S> {
+        self.query_strict(Self::area_at_pt(point))
     }
 
-    /// Deletes all value associations which overlap a region in the tree.
+    /// The inverse of [`.query_strict()`]: returns an iterator over [`&Entry<U, V>`] structs whose
+    /// regions _totally contain_ the query region.
     ///
-    /// Along the way, consumed [`Entry<U, V>`] entries are collected and returned in an iterator
-    /// [`IntoIter<U, V>`].
+    /// Only the subquadrants covering a single cell of the query region are searched, since any
+    /// region containing the query region must be held there.
     /// ```
     /// use quadtree_rs::{area::AreaBuilder, Quadtree};
     ///
-    /// let mut qt = Quadtree::<u32, f64>::new(4);
+    /// let mut qt = Quadtree::<u32, &str>::new(4);
     ///
-    /// let region_a = AreaBuilder::default()
+    /// qt.insert(AreaBuilder::default()
     ///     .anchor((0, 0).into())
+    ///     .dimensions((16, 16))
+    ///     .build().unwrap(), "background");
+    /// qt.insert(AreaBuilder::default()
+    ///     .anchor((2, 2).into())
+    ///     .dimensions((6, 4))
+    ///     .build().unwrap(), "window");
+    /// qt.insert(AreaBuilder::default()
+    ///     .anchor((3, 3).into())
+    ///     .dimensions((2, 1))
+    ///     .build().unwrap(), "button");
+    ///
+    /// let region = AreaBuilder::default()
+    ///     .anchor((4, 3).into())
     ///     .dimensions((2, 2))
     ///     .build().unwrap();
-    /// qt.insert(region_a, 1.23);
     ///
-    /// let region_b = AreaBuilder::default()
-    ///     .anchor((1, 1).into())
-    ///     .dimensions((3, 2))
-    ///     .build().unwrap();
-    /// qt.insert(region_b, 4.56);
+    /// let mut layers: Vec<&str> = qt.query_containing(region).map(|e| *e.value_ref()).collect();
+    /// layers.sort();
+    /// assert_eq!(layers, vec!["background", "window"]);
+    /// ```
     ///
-    /// //   0123
-    /// // 0 ░░
-    /// // 1 ░▓╳░  <-- ╳ is the deletion region
-    /// // 2  ░░░
+    /// [`&Entry<U, V>`]: entry/struct.Entry.html
+    /// [`.query_strict()`]: #method.query_strict
+    pub fn query_containing(&self, area: Area<U>) -> Query<'_, U, V, S> {
+        Query::new(area.into(), &self.inner, &self.store, Traversal::Containing)
+    }
+
+    /// Returns an iterator over [`&Entry<U, V>`] structs representing values whose regions are
+    /// exactly the query region.
     ///
-    /// let region_c = AreaBuilder::default()
-    ///     .anchor((2, 1).into()).build().unwrap();
-    /// let mut returned_entries = qt.delete(region_c);
+    /// This suits tile systems, where every region is an aligned tile and a query for a tile
+    /// shouldn't also turn up its neighbours or the tiles beneath it. Like
+    /// [`.query_containing()`], only the subquadrants covering a single cell of the query region
+    /// are searched. See also [`.get_all()`], which collects the same entries in insertion order.
+    /// ```
+    /// use quadtree_rs::{area::AreaBuilder, Quadtree};
     ///
-    /// // We've removed one object from the quadtree.
-    /// assert_eq!(returned_entries.next().unwrap().value_ref(),
-    ///            &4.56);
+    /// let mut qt = Quadtree::<u32, &str>::new(4);
     ///
-    /// // And left one behind.
-    /// assert_eq!(qt.len(), 1);
+    /// let tile = AreaBuilder::default()
+    ///     .anchor((4, 4).into())
+    ///     .dimensions((4, 4))
+    ///     .build().unwrap();
+    /// qt.insert(tile, "grass");
+    /// qt.insert(AreaBuilder::default()
+    ///     .anchor((4, 4).into())
+    ///     .dimensions((2, 2))
+    ///     .build().unwrap(), "flowers");
+    ///
+    /// let mut query = qt.query_exact(tile);
+    /// assert_eq!(query.next().unwrap().value_ref(), &"grass");
+    /// assert!(query.next().is_none());
     /// ```
     ///
-    /// [`IntoIter<U, V>`]: iter/struct.IntoIter.html
-    /// [`Entry<U, V>`]: entry/struct.Entry.html
-    /// [`.delete()`]: #method.delete
-    pub fn delete(&mut self, area: Area<U>) -> IntoIter<U, V> {
-        self.delete_handles_and_return(self.query(area).map(|e| e.handle()).collect())
+    /// [`&Entry<U, V>`]: entry/struct.Entry.html
+    /// [`.query_containing()`]: #method.query_containing
+    /// [`.get_all()`]: #method.get_all
+    pub fn query_exact(&self, area: Area<U>) -> Query<'_, U, V, S> {
+        Query::new(area.into(), &self.inner, &self.store, Traversal::Exact)
     }
 
-    /// A strict variant of [`.delete()`].
+    /// Alias for [`.query_containing()`] which expects a [`Point`] instead of an [`Area`].
     ///
-    /// [`.delete()`]: #method.delete
-    pub fn delete_strict(&mut self, area: Area<U>) -> IntoIter<U, V> {
-        self.delete_handles_and_return(self.query_strict(area).map(|e| e.handle()).collect())
+    /// For a point this is equivalent to [`.query_pt()`], but only the subquadrants on the path
+    /// down to that point are searched.
+    ///
+    /// [`.query_containing()`]: #method.query_containing
+    /// [`.query_pt()`]: #method.query_pt
+    /// [`Area`]: area/struct.Area.html
+    /// [`Point`]: point/struct.Point.html
+    pub fn query_containing_pt(&self, point: Point<U>) -> Query<'_, U, V, S> {
+        self.query_containing(Self::area_at_pt(point))
     }
 
-    #[allow(clippy::needless_pass_by_value)]
-    fn delete_handles_and_return(&mut self, handles: HashSet<u64>) -> IntoIter<U, V> {
-        let error: &'static str = "I tried to look up an handle in the store which I found in the tree, but it wasn't there!";
-
-        let mut entries: Vec<Entry<U, V>> = vec![];
-
-        handles.iter().for_each(|u| {
-            // We were just passed a hashset of handles taken from this quadtree, so it is safe to
-            // assume they all still exist.
-            entries.push(self.store.remove(u).expect(error));
-        });
-
-        IntoIter { entries }
+    /// Counts the regions which intersect some region, as [`.query()`] would return them.
+    ///
+    /// No iterator is built and nothing is allocated. Subquadrants which lie entirely within the
+    /// region are counted without examining any of the regions beneath them, so this is much
+    /// cheaper than `.query(area).count()` for large areas.
+    /// ```
+    /// use quadtree_rs::{area::AreaBuilder, Quadtree};
+    ///
+    /// let mut qt = Quadtree::<u32, u8>::new(4);
+    /// qt.extend(vec![((1, 1), 1), ((2, 3), 2), ((12, 12), 3)]);
+    ///
+    /// let region = AreaBuilder::default()
+    ///     .anchor((0, 0).into())
+    ///     .dimensions((8, 8))
+    ///     .build().unwrap();
+    /// assert_eq!(qt.count_in_region(region), 2);
+    /// assert_eq!(qt.count_in_region(region), qt.query(region).count());
+    /// ```
+    ///
+    /// [`.query()`]: #method.query
+    pub fn count_in_region(&self, area: Area<U>) -> usize {
+        self.inner
+            .count_intersecting(area, &|handle| self.store.get(&handle).map(Entry::area))
+    }
+
+    /// Counts the regions which intersect each cell of a grid laid over the quadtree, for drawing
+    /// heatmaps.
+    ///
+    /// The grid starts at the anchor of the quadtree's bounds and is made of `cell_size` squares,
+    /// trimmed where they overhang the bounds. It's returned as rows from north to south, each
+    /// running from west to east. A region is counted in every cell it overlaps, and each cell is
+    /// counted as [`.count_in_region()`] would count it.
+    ///
+    /// The grid is filled in a single pass over every entry, each adding one to the cells it
+    /// overlaps, so this costs O(entries + covered cells) on top of allocating the grid, where the
+    /// covered cells are summed over the entries. No part of the tree is skipped.
+    ///
+    /// Panics if `cell_size` isn't positive.
+    /// ```
+    /// use quadtree_rs::Quadtree;
+    ///
+    /// let mut qt = Quadtree::<u32, ()>::new(2);
+    /// qt.extend(vec![((0, 0), ()), ((1, 0), ()), ((3, 3), ())]);
+    ///
+    /// assert_eq!(qt.density_grid(2), vec![vec![2, 0], vec![0, 1]]);
+    /// assert_eq!(qt.density_grid(3), vec![vec![2, 0], vec![0, 1]]);
+    /// ```
+    ///
+    /// [`.count_in_region()`]: #method.count_in_region
+    pub fn density_grid(&self, cell_size: U) -> Vec<Vec<usize>> {
+        assert!(cell_size > U::zero(), "The cell size must be positive.");
+        let cells = |len: U| Self::cell_index(len - U::one(), cell_size) + 1;
+        let mut grid = vec![vec![0; cells(self.bounds.width())]; cells(self.bounds.height())];
+
+        // Each region is counted once, in every cell it overlaps.
+        let anchor = self.bounds.anchor();
+        for entry in self.iter() {
+            let region = entry.area();
+            if !region.intersects(self.bounds) {
+                continue;
+            }
+            // The first and last cells which the region overlaps along one axis.
+            let span = |start: U, end: U, origin: U, count: usize| {
+                (
+                    Self::cell_index(start.max(origin) - origin, cell_size),
+                    Self::cell_index(end - U::one() - origin, cell_size).min(count - 1),
+                )
+            };
+            let (top, bottom) = span(
+                region.top_edge(),
+                region.bottom_edge(),
+                anchor.y(),
+                grid.len(),
+            );
+            for row in &mut grid[top..=bottom] {
+                let (left, right) = span(
+                    region.left_edge(),
+                    region.right_edge(),
+                    anchor.x(),
+                    row.len(),
+                );
+                for count in &mut row[left..=right] {
+                    *count += 1;
+                }
+            }
+        }
+        grid
+    }
+
+    /// Returns an iterator ([`FreeSpace<U, V>`]) over the parts of `area` which no region in the
+    /// quadtree covers, for enumerating the free space in an occupancy map.
+    ///
+    /// The free space is returned as the largest empty cells of the quadtree's grid of cells,
+    /// clipped to `area`. A cell with anything in it is split into its four quadrants, which are
+    /// looked at in reading order, depth-first, down to single cells, unless one region covers the
+    /// whole of it. Nothing outside the bounds of the quadtree is returned.
+    /// ```
+    /// use quadtree_rs::{area::AreaBuilder, Quadtree};
+    ///
+    /// let mut qt = Quadtree::<u32, &str>::new(2);
+    /// let wall = AreaBuilder::default().anchor((0, 0).into()).dimensions((2, 3)).build().unwrap();
+    /// qt.insert(wall, "wall");
+    ///
+    /// let room = AreaBuilder::default().anchor((0, 0).into()).dimensions((4, 4)).build().unwrap();
+    /// let free: Vec<(u32, u32, u32, u32)> = qt
+    ///     .free_space(room)
+    ///     .map(|a| (a.anchor().x(), a.anchor().y(), a.width(), a.height()))
+    ///     .collect();
+    /// assert_eq!(free, vec![(2, 0, 2, 2), (0, 3, 1, 1), (1, 3, 1, 1), (2, 2, 2, 2)]);
+    /// ```
+    ///
+    /// [`FreeSpace<U, V>`]: iter/struct.FreeSpace.html
+    pub fn free_space(&self, area: Area<U>) -> FreeSpace<'_, U, V, S> {
+        FreeSpace::new(self, self.inner.region(), self.bounds.intersection(area))
+    }
+
+    /// Whether any region in the quadtree intersects some region.
+    ///
+    /// This is equivalent to `.query(area).next().is_some()`, but the search stops as soon as it
+    /// finds a single intersecting region, and a subquadrant lying entirely within the area is
+    /// known to be occupied as soon as it holds anything at all.
+    /// ```
+    /// use quadtree_rs::{area::AreaBuilder, Quadtree};
+    ///
+    /// let mut qt = Quadtree::<u32, u8>::new(4);
+    /// qt.extend(vec![((1, 1), 1), ((12, 12), 2)]);
+    ///
+    /// let occupied = AreaBuilder::default()
+    ///     .anchor((0, 0).into())
+    ///     .dimensions((2, 2))
+    ///     .build().unwrap();
+    /// assert!(qt.intersects_any(occupied));
+    ///
+    /// let vacant = AreaBuilder::default()
+    ///     .anchor((4, 4).into())
+    ///     .dimensions((6, 6))
+    ///     .build().unwrap();
+    /// assert!(!qt.intersects_any(vacant));
+    /// ```
+    pub fn intersects_any(&self, area: Area<U>) -> bool {
+        self.inner
+            .any_intersecting(area, &|handle| self.store.get(&handle).map(Entry::area))
+    }
+
+    /// Returns an iterator ([`Collisions<U, V>`]) over every pair of entries whose regions
+    /// intersect, in no particular order. Each pair is only returned once.
+    ///
+    /// This is the broad phase of collision detection. Rather than test every entry against every
+    /// other, each entry is only tested against those held in the same cell as it or in a cell
+    /// above it which reaches into its own, since the cells of siblings never overlap. (The cells
+    /// of a [loose] quadtree do, so there each entry is queried for instead.)
+    /// ```
+    /// use quadtree_rs::{area::AreaBuilder, Quadtree};
+    ///
+    /// let mut qt = Quadtree::<u32, char>::new(4);
+    /// let hitbox = |x, y| AreaBuilder::default()
+    ///     .anchor((x, y).into())
+    ///     .dimensions((3, 3))
+    ///     .build().unwrap();
+    /// qt.insert(hitbox(0, 0), 'a');
+    /// qt.insert(hitbox(2, 2), 'b');
+    /// qt.insert(hitbox(9, 9), 'c');
+    ///
+    /// let pairs: Vec<(char, char)> = qt
+    ///     .collisions()
+    ///     .map(|(x, y)| (*x.value_ref(), *y.value_ref()))
+    ///     .collect();
+    /// assert!(pairs == vec![('a', 'b')] || pairs == vec![('b', 'a')]);
+    /// ```
+    ///
+    /// [`Collisions<U, V>`]: iter/struct.Collisions.html
+    /// [loose]: #method.set_looseness
+    pub fn collisions(&self) -> Collisions<'_, U, V, S> {
+        let region_of = |handle| self.store.get(&handle).map(Entry::area);
+        let pairs = if self.inner.is_loose() {
+            self.iter()
+                .flat_map(|entry| {
+                    self.query(entry.area())
+                        .filter(move |other| other.handle() > entry.handle())
+                        .map(move |other| (entry.handle(), other.handle()))
+                })
+                .collect()
+        } else {
+            self.inner.intersecting_pairs(&region_of)
+        };
+        Collisions {
+            pairs: pairs.into_iter(),
+            store: &self.store,
+        }
+    }
+
+    /// A read-only view ([`QuadtreeView<U, V>`]) of the entries whose regions overlap `area`.
+    /// Nothing is copied out of the quadtree.
+    /// ```
+    /// use quadtree_rs::{area::AreaBuilder, Quadtree};
+    ///
+    /// let mut qt = Quadtree::<u32, char>::new(4);
+    /// qt.extend(vec![((1, 1), 'a'), ((6, 6), 'b'), ((12, 12), 'c')]);
+    ///
+    /// let west = AreaBuilder::default()
+    ///     .anchor((0, 0).into())
+    ///     .dimensions((8, 16))
+    ///     .build().unwrap();
+    /// let view = qt.view(west);
+    /// assert_eq!(view.len(), 2);
+    /// assert!(view.values().all(|v| *v != 'c'));
+    /// ```
+    ///
+    /// [`QuadtreeView<U, V>`]: view/struct.QuadtreeView.html
+    pub fn view(&self, area: Area<U>) -> QuadtreeView<'_, U, V, S> {
+        QuadtreeView {
+            tree: self,
+            region: area,
+        }
+    }
+
+    /// Returns an iterator over [`&Entry<U, V>`] structs whose regions don't intersect `area`,
+    /// the opposite of a [`.query()`] for it.
+    ///
+    /// Subquadrants which lie wholly inside `area` are never visited, and results are produced
+    /// lazily as with [`.query()`].
+    /// ```
+    /// use quadtree_rs::{area::AreaBuilder, Quadtree};
+    ///
+    /// let mut qt = Quadtree::<u32, char>::new(4);
+    /// qt.extend(vec![((1, 1), 'a'), ((6, 6), 'b'), ((12, 12), 'c')]);
+    ///
+    /// // Unload everything beyond the chunks around the player.
+    /// let around_player = AreaBuilder::default()
+    ///     .anchor((4, 4).into())
+    ///     .dimensions((8, 8))
+    ///     .build().unwrap();
+    /// let mut unload: Vec<char> = qt
+    ///     .query_outside(around_player)
+    ///     .map(|e| *e.value_ref())
+    ///     .collect();
+    /// unload.sort();
+    /// assert_eq!(unload, vec!['a', 'c']);
+    /// ```
+    ///
+    /// [`&Entry<U, V>`]: entry/struct.Entry.html
+    /// [`.query()`]: #method.query
+    pub fn query_outside(&self, area: Area<U>) -> Query<'_, U, V, S> {
+        // An entry which doesn't intersect the area lies wholly outside it.
+        Query::new(
+            Shape::Outside(area),
+            &self.inner,
+            &self.store,
+            Traversal::Strict,
+        )
+    }
+
+    /// Returns an iterator ([`QueryFilter<U, V, F>`]) over the [`&Entry<U, V>`] structs whose
+    /// regions intersect `area`, and whose values satisfy `pred`.
+    ///
+    /// Unlike `.filter()` on a [`.query()`], `pred` is checked within the query's own walk over
+    /// the tree, so rejected entries are passed over before anything is yielded. It's called once
+    /// for each entry whose region intersects `area`, and never for entries outside it.
+    /// ```
+    /// use quadtree_rs::{area::AreaBuilder, point::Point, Quadtree};
+    ///
+    /// let mut qt = Quadtree::<u32, (&str, u32)>::new(4);
+    /// qt.insert_pt(Point {x: 1, y: 1}, ("goblin", 3));
+    /// qt.insert_pt(Point {x: 2, y: 2}, ("goblin", 0));
+    /// qt.insert_pt(Point {x: 3, y: 1}, ("chest", 1));
+    /// qt.insert_pt(Point {x: 12, y: 12}, ("goblin", 5));
+    ///
+    /// let room = AreaBuilder::default()
+    ///     .anchor((0, 0).into())
+    ///     .dimensions((8, 8))
+    ///     .build().unwrap();
+    /// let living_goblins = qt
+    ///     .query_filter(room, |&(kind, hp)| kind == "goblin" && hp > 0)
+    ///     .count();
+    /// assert_eq!(living_goblins, 1);
+    /// ```
+    ///
+    /// [`QueryFilter<U, V, F>`]: iter/struct.QueryFilter.html
+    /// [`&Entry<U, V>`]: entry/struct.Entry.html
+    /// [`.query()`]: #method.query
+    pub fn query_filter<F>(&self, area: Area<U>, pred: F) -> QueryFilter<'_, U, V, F, S>
+    where
+        F: FnMut(&V) -> bool,
+    {
+        QueryFilter {
+            query: self.query(area),
+            pred,
+        }
+    }
+
+    /// Returns an iterator over the [`&Entry<U, V>`] structs whose regions have at least
+    /// `min_area` cells in common with `area`, along with how many cells each has in common with
+    /// it.
+    ///
+    /// Regions which only graze `area` by a sliver can be left out this way, such as when snapping
+    /// features to the tile they overlap most. With a `min_area` of zero or one this is
+    /// [`.query()`], with the overlap of each result attached; the overlaps can then be sorted by.
+    /// Overlaps are measured with [`Area::overlap()`]. Everything beneath a subquadrant lies
+    /// within its bounds, so subquadrants whose bounds have fewer than `min_area` cells in common
+    /// with `area` are never visited.
+    /// ```
+    /// use quadtree_rs::{area::AreaBuilder, Quadtree};
+    ///
+    /// let mut qt = Quadtree::<u32, char>::new(4);
+    /// let road = |x, y, w, h| AreaBuilder::default()
+    ///     .anchor((x, y).into())
+    ///     .dimensions((w, h))
+    ///     .build().unwrap();
+    /// qt.insert(road(0, 2, 8, 2), 'a');
+    /// qt.insert(road(3, 0, 1, 8), 'b');
+    /// qt.insert(road(7, 7, 4, 4), 'c');
+    ///
+    /// // Which roads really run through the tile, rather than clipping its corner?
+    /// let mut roads: Vec<(u32, char)> = qt
+    ///     .query_min_overlap(road(0, 0, 8, 8), 4)
+    ///     .map(|(overlap, e)| (overlap, *e.value_ref()))
+    ///     .collect();
+    /// roads.sort();
+    /// assert_eq!(roads, vec![(8, 'b'), (16, 'a')]);
+    /// ```
+    ///
+    /// [`&Entry<U, V>`]: entry/struct.Entry.html
+    /// [`.query()`]: #method.query
+    /// [`Area::overlap()`]: area/struct.Area.html#method.overlap
+    pub fn query_min_overlap(
+        &self,
+        area: Area<U>,
+        min_area: U,
+    ) -> impl Iterator<Item = (U, &Entry<U, V>)> + '_ {
+        // An overlap is never zero for regions which intersect.
+        let enough = move |region: Area<U>| {
+            Some(area.overlap(region)).filter(|&overlap| overlap > U::zero() && overlap >= min_area)
+        };
+        let inner = &self.inner;
+        let mut stack = vec![];
+        if enough(inner.root().bounds()).is_some() {
+            stack.push(inner.root());
+        }
+        let mut handles: &[u64] = &[];
+        std::iter::from_fn(move || loop {
+            while let Some((handle, rest)) = handles.split_first() {
+                handles = rest;
+                if let Some(entry) = self.store.get(handle) {
+                    if let Some(overlap) = enough(entry.area()) {
+                        return Some((overlap, entry));
+                    }
+                }
+            }
+            let node = stack.pop()?;
+            handles = node.handles();
+            stack.extend(
+                node.subquadrants()
+                    .into_iter()
+                    .flatten()
+                    .map(|id| inner.node(id))
+                    .filter(|sq| sq.len() > 0 && enough(sq.bounds()).is_some()),
+            );
+        })
+    }
+
+    /// Returns an iterator over [`&Entry<U, V>`] structs whose regions border `area`: those which
+    /// share an edge or a corner with it without overlapping it. With `include_overlaps`, those
+    /// which overlap it are returned too.
+    ///
+    /// Only the subquadrants within a cell of `area` are visited, and results are produced lazily
+    /// as with [`.query()`].
+    /// ```
+    /// use quadtree_rs::{area::AreaBuilder, Quadtree};
+    ///
+    /// let mut qt = Quadtree::<u32, char>::new(4);
+    /// let tile = |x, y| AreaBuilder::default()
+    ///     .anchor((x, y).into())
+    ///     .dimensions((2, 2))
+    ///     .build().unwrap();
+    /// qt.insert(tile(2, 2), 'a');
+    /// qt.insert(tile(4, 2), 'b');
+    /// qt.insert(tile(4, 4), 'c');
+    /// qt.insert(tile(3, 3), 'd');
+    /// qt.insert(tile(8, 8), 'e');
+    ///
+    /// let borders = |include_overlaps| {
+    ///     let mut found: Vec<char> = qt
+    ///         .query_touching(tile(2, 2), include_overlaps)
+    ///         .map(|e| *e.value_ref())
+    ///         .collect();
+    ///     found.sort();
+    ///     found
+    /// };
+    /// // 'b' shares an edge with 'a', and 'c' a corner.
+    /// assert_eq!(borders(false), vec!['b', 'c']);
+    /// assert_eq!(borders(true), vec!['a', 'b', 'c', 'd']);
+    /// ```
+    ///
+    /// [`&Entry<U, V>`]: entry/struct.Entry.html
+    /// [`.query()`]: #method.query
+    pub fn query_touching(
+        &self,
+        area: Area<U>,
+        include_overlaps: bool,
+    ) -> impl Iterator<Item = &Entry<U, V>> + '_ {
+        // Everything which touches @area overlaps it once it's grown by a cell.
+        self.query(Self::grow(area, U::one()))
+            .filter(move |entry| include_overlaps || !entry.area().intersects(area))
+    }
+
+    /// Returns an iterator over the [`&Entry<U, V>`] structs whose regions lie within `max_gap`
+    /// cells of the region of the entry with the given handle, not counting that entry itself.
+    ///
+    /// A `max_gap` of zero finds the regions which overlap it or touch it at an edge or a corner,
+    /// a `max_gap` of one those at most one cell away from it, and so on; a negative `max_gap` is
+    /// treated as zero. Gaps are measured along each axis, so the region is grown by `max_gap`
+    /// cells on every side and queried for as with [`.query()`]. Nothing is returned if the
+    /// handle isn't in the quadtree.
+    /// ```
+    /// use quadtree_rs::{area::AreaBuilder, Quadtree};
+    ///
+    /// let mut qt = Quadtree::<u32, char>::new(4);
+    /// let tile = |x, y| AreaBuilder::default()
+    ///     .anchor((x, y).into())
+    ///     .dimensions((2, 2))
+    ///     .build().unwrap();
+    /// let a = qt.insert(tile(2, 2), 'a').unwrap();
+    /// qt.insert(tile(4, 2), 'b');
+    /// qt.insert(tile(7, 2), 'c');
+    ///
+    /// let near = |max_gap| {
+    ///     let mut found: Vec<char> = qt.neighbors(a, max_gap).map(|e| *e.value_ref()).collect();
+    ///     found.sort();
+    ///     found
+    /// };
+    /// assert_eq!(near(0), vec!['b']);
+    /// assert_eq!(near(2), vec!['b']);
+    /// assert_eq!(near(3), vec!['b', 'c']);
+    /// ```
+    ///
+    /// [`&Entry<U, V>`]: entry/struct.Entry.html
+    /// [`.query()`]: #method.query
+    pub fn neighbors(&self, handle: u64, max_gap: U) -> impl Iterator<Item = &Entry<U, V>> + '_ {
+        let gap = max_gap.max(U::zero()).saturating_add(U::one());
+        self.get(handle)
+            .into_iter()
+            .flat_map(move |entry| self.query(Self::grow(entry.area(), gap)))
+            .filter(move |entry| entry.handle() != handle)
+    }
+
+    /// Returns an iterator over [`&Entry<U, V>`] structs whose regions intersect a disc.
+    ///
+    /// The disc covers every cell within `radius` (euclidean) of the cell at `center`, so a radius
+    /// of zero behaves like a point query. A negative radius covers nothing. Subquadrants which lie
+    /// wholly outside the disc are never visited, and results are produced lazily as with
+    /// [`.query()`].
+    /// ```
+    /// use quadtree_rs::{area::AreaBuilder, point::Point, Quadtree};
+    ///
+    /// let mut qt = Quadtree::<u32, char>::new(4);
+    ///
+    /// qt.insert_pt(Point {x: 5, y: 5}, 'a');
+    /// qt.insert_pt(Point {x: 8, y: 8}, 'b');
+    /// qt.insert(AreaBuilder::default()
+    ///     .anchor((9, 0).into())
+    ///     .dimensions((2, 5))
+    ///     .build().unwrap(), 'c');
+    ///
+    /// // 'b' is 3 cells away on each axis, so sqrt(18) > 4 from the center.
+    /// let mut query = qt.query_within_radius(Point {x: 5, y: 5}, 4);
+    /// assert_eq!(query.next().unwrap().value_ref(), &'a');
+    /// assert!(query.next().is_none());
+    ///
+    /// assert_eq!(qt.query_within_radius(Point {x: 5, y: 5}, 5).count(), 3);
+    /// ```
+    ///
+    /// [`&Entry<U, V>`]: entry/struct.Entry.html
+    /// [`.query()`]: #method.query
+    pub fn query_within_radius(&self, center: Point<U>, radius: U) -> Query<'_, U, V, S> {
+        Query::new(
+            Shape::Disc { center, radius },
+            &self.inner,
+            &self.store,
+            Traversal::Overlapping,
+        )
+    }
+
+    /// Returns an iterator over [`&Entry<U, V>`] structs whose regions overlap a ring.
+    ///
+    /// The ring covers every cell within `outer_r` (euclidean) of the cell at `center`, but not
+    /// within `inner_r` of it, so it's the disc of [`.query_within_radius()`] with a hole in the
+    /// middle. A region overlaps the ring if some part of it lies within `outer_r` and not all of
+    /// it lies within `inner_r`. With a negative `inner_r` there's no hole, and with an `inner_r`
+    /// of at least `outer_r` there's no ring. Subquadrants which lie wholly outside the outer
+    /// radius, or wholly inside the inner one, are never visited, and results are produced lazily
+    /// as with [`.query()`].
+    /// ```
+    /// use quadtree_rs::{point::Point, Quadtree};
+    ///
+    /// let mut qt = Quadtree::<u32, char>::new(5);
+    /// qt.insert_pt(Point {x: 10, y: 10}, 'a');
+    /// qt.insert_pt(Point {x: 13, y: 14}, 'b');
+    /// qt.insert_pt(Point {x: 20, y: 10}, 'c');
+    ///
+    /// // Near the player at (10, 10), but not too near.
+    /// let ring: Vec<char> = qt
+    ///     .query_annulus(Point {x: 10, y: 10}, 2, 5)
+    ///     .map(|e| *e.value_ref())
+    ///     .collect();
+    /// assert_eq!(ring, vec!['b']);
+    /// ```
+    ///
+    /// [`&Entry<U, V>`]: entry/struct.Entry.html
+    /// [`.query_within_radius()`]: #method.query_within_radius
+    /// [`.query()`]: #method.query
+    pub fn query_annulus(&self, center: Point<U>, inner_r: U, outer_r: U) -> Query<'_, U, V, S> {
+        Query::new(
+            Shape::Annulus {
+                center,
+                inner: inner_r,
+                outer: outer_r,
+            },
+            &self.inner,
+            &self.store,
+            Traversal::Overlapping,
+        )
+    }
+
+    /// Returns an iterator over [`&Entry<U, V>`] structs whose regions a line segment passes
+    /// through.
+    ///
+    /// The segment runs between the centres of the cells at `from` and `to`, and a region which it
+    /// only touches at an edge or a corner counts. Only the subquadrants along the segment's path
+    /// are visited, and results are produced lazily as with [`.query()`].
+    /// ```
+    /// use quadtree_rs::{area::AreaBuilder, point::Point, Quadtree};
+    ///
+    /// let mut qt = Quadtree::<u32, char>::new(4);
+    ///
+    /// qt.insert_pt(Point {x: 2, y: 2}, 'a');
+    /// qt.insert_pt(Point {x: 2, y: 9}, 'b');
+    /// qt.insert(AreaBuilder::default()
+    ///     .anchor((8, 0).into())
+    ///     .dimensions((2, 16))
+    ///     .build().unwrap(), 'c');
+    ///
+    /// // A diagonal line from 'a', through the wall 'c', which misses 'b'.
+    /// let mut hits: Vec<char> = qt
+    ///     .query_segment(Point {x: 2, y: 2}, Point {x: 13, y: 13})
+    ///     .map(|e| *e.value_ref())
+    ///     .collect();
+    /// hits.sort();
+    /// assert_eq!(hits, vec!['a', 'c']);
+    /// ```
+    ///
+    /// [`&Entry<U, V>`]: entry/struct.Entry.html
+    /// [`.query()`]: #method.query
+    pub fn query_segment(&self, from: Point<U>, to: Point<U>) -> Query<'_, U, V, S> {
+        Query::new(
+            Shape::Segment { from, to },
+            &self.inner,
+            &self.store,
+            Traversal::Overlapping,
+        )
+    }
+
+    /// Answers a [`.query()`] for each of `areas` at once, returning the [`&Entry<U, V>`] structs
+    /// whose regions intersect each of them, in the same order as `areas`.
+    ///
+    /// The quadtree is walked once for the whole batch rather than once per area. Each node is
+    /// only visited for the areas which reach into it, and each entry held there is checked
+    /// against those areas alone, which pays off when many small areas are queried together, such
+    /// as one per entity per frame. An entry appears at most once in each area's results, in no
+    /// particular order.
+    /// ```
+    /// use quadtree_rs::{area::AreaBuilder, Quadtree};
+    ///
+    /// let mut qt = Quadtree::<u32, char>::new(4);
+    /// qt.extend(vec![((1, 1), 'a'), ((2, 2), 'b'), ((12, 12), 'c')]);
+    ///
+    /// let around = |x, y| AreaBuilder::default()
+    ///     .anchor((x, y).into())
+    ///     .dimensions((2, 2))
+    ///     .build().unwrap();
+    /// let found: Vec<Vec<char>> = qt
+    ///     .query_many(&[around(0, 0), around(11, 11), around(6, 6)])
+    ///     .iter()
+    ///     .map(|hits| hits.iter().map(|e| *e.value_ref()).collect())
+    ///     .collect();
+    /// assert_eq!(found, vec![vec!['a'], vec!['c'], vec![]]);
+    /// ```
+    ///
+    /// [`.query()`]: #method.query
+    /// [`&Entry<U, V>`]: entry/struct.Entry.html
+    pub fn query_many(&self, areas: &[Area<U>]) -> Vec<Vec<&Entry<U, V>>> {
+        self.inner
+            .intersecting_each(areas, &|handle| self.store.get(&handle).map(Entry::area))
+            .into_iter()
+            .map(|handles| {
+                handles
+                    .into_iter()
+                    .filter_map(|handle| self.store.get(&handle))
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Returns an iterator over [`&Entry<U, V>`] structs whose regions overlap a triangle.
+    ///
+    /// The triangle's corners are the centres of the cells at `a`, `b` and `c`, and a region which
+    /// only touches one of its edges counts. Subquadrants which lie wholly outside the triangle are
+    /// never visited, and results are produced lazily as with [`.query()`]. Field-of-view cones
+    /// and frustum slices can be built out of triangles.
+    /// ```
+    /// use quadtree_rs::{point::Point, Quadtree};
+    ///
+    /// let mut qt = Quadtree::<u32, char>::new(4);
+    /// qt.insert_pt(Point {x: 6, y: 2}, 'a');
+    /// qt.insert_pt(Point {x: 4, y: 5}, 'b');
+    /// qt.insert_pt(Point {x: 12, y: 2}, 'c');
+    ///
+    /// // Looking south from (6, 0), with a field of view 90 degrees wide.
+    /// let mut seen: Vec<char> = qt
+    ///     .query_triangle(Point {x: 6, y: 0}, Point {x: 0, y: 6}, Point {x: 12, y: 6})
+    ///     .map(|e| *e.value_ref())
+    ///     .collect();
+    /// seen.sort();
+    /// assert_eq!(seen, vec!['a', 'b']);
+    /// ```
+    ///
+    /// [`&Entry<U, V>`]: entry/struct.Entry.html
+    /// [`.query()`]: #method.query
+    pub fn query_triangle(&self, a: Point<U>, b: Point<U>, c: Point<U>) -> Query<'_, U, V, S> {
+        Query::new(
+            Shape::Triangle { a, b, c },
+            &self.inner,
+            &self.store,
+            Traversal::Overlapping,
+        )
+    }
+
+    /// Returns the (up to) `k` [`&Entry<U, V>`] structs whose regions lie closest to some point,
+    /// nearest first.
+    ///
+    /// Distance is measured from the point to the nearest cell of each region, so any region
+    /// containing the point is at distance zero. Rather than scanning every entry, the search
+    /// visits subquadrants best-first and stops as soon as `k` entries have been found. Entries at
+    /// the same distance are returned in order of handle, so the oldest comes first.
+    /// ```
+    /// use quadtree_rs::{area::AreaBuilder, point::Point, Quadtree};
+    ///
+    /// let mut qt = Quadtree::<u32, char>::new(4);
+    ///
+    /// qt.insert_pt(Point {x: 1, y: 1}, 'a');
+    /// qt.insert_pt(Point {x: 9, y: 9}, 'b');
+    /// qt.insert(AreaBuilder::default()
+    ///     .anchor((5, 0).into())
+    ///     .dimensions((2, 8))
+    ///     .build().unwrap(), 'c');
+    ///
+    /// let nearest: Vec<char> = qt.knn(Point {x: 8, y: 7}, 2)
+    ///     .iter()
+    ///     .map(|e| *e.value_ref())
+    ///     .collect();
+    /// assert_eq!(nearest, vec!['c', 'b']);
+    /// ```
+    ///
+    /// [`&Entry<U, V>`]: entry/struct.Entry.html
+    pub fn knn(&self, pt: Point<U>, k: usize) -> Vec<&Entry<U, V>> {
+        NearestIter::new(&self.inner, &self.store, pt)
+            .take(k)
+            .map(|(_, entry)| entry)
+            .collect()
+    }
+
+    /// Returns the [`&Entry<U, V>`] whose region lies closest to some point, or `None` if the
+    /// quadtree is empty.
+    ///
+    /// Distances are measured as in [`.knn()`]. If `max_distance` is given, regions further than
+    /// that from the point are ignored, and the search never descends into subquadrants beyond
+    /// it.
+    /// ```
+    /// use quadtree_rs::{point::Point, Quadtree};
+    ///
+    /// let mut qt = Quadtree::<u32, char>::new(4);
+    ///
+    /// qt.insert_pt(Point {x: 2, y: 2}, 'a');
+    /// qt.insert_pt(Point {x: 12, y: 9}, 'b');
+    ///
+    /// // Click near 'b'...
+    /// assert_eq!(qt.nearest(Point {x: 10, y: 10}, None).unwrap().value_ref(), &'b');
+    ///
+    /// // ...or too far away from anything to select it.
+    /// assert!(qt.nearest(Point {x: 7, y: 7}, Some(3)).is_none());
+    /// ```
+    ///
+    /// [`&Entry<U, V>`]: entry/struct.Entry.html
+    /// [`.knn()`]: #method.knn
+    pub fn nearest(&self, pt: Point<U>, max_distance: Option<U>) -> Option<&Entry<U, V>> {
+        NearestIter::new_within(&self.inner, &self.store, pt, max_distance)
+            .next()
+            .map(|(_, entry)| entry)
+    }
+
+    /// Accepts a modification lambda and applies it to all elements in the
+    /// quadtree which intersecting the described region.
+    ///
+    /// The lambda is passed each value's region alongside the value, and may carry mutable state
+    /// between the values it visits, such as a running count. Returns the number of values it
+    /// was applied to.
+    ///
+    /// ```
+    /// use quadtree_rs::{area::AreaBuilder, Quadtree};
+    ///
+    /// let mut qt = Quadtree::<u8, bool>::new(3);
+    ///
+    /// let region_a = AreaBuilder::default()
+    ///     .anchor((0, 0).into())
+    ///     .build().unwrap();
+    /// let handle = qt.insert(region_a, true).unwrap();
+    ///
+    /// // Run a modification lambda over all values in region_a...
+    /// assert_eq!(qt.modify(region_a, |_, i| *i = false), 1);
+    ///
+    /// // ...and verify that the value was applied.
+    /// assert_eq!(qt.get(handle).unwrap().value_ref(), &false);
+    /// ```
+    ///
+    /// The region makes it possible to weigh each value by how much of it was touched:
+    /// ```
+    /// use quadtree_rs::{area::AreaBuilder, Quadtree};
+    ///
+    /// let mut qt = Quadtree::<u32, u32>::new(4);
+    /// let square = |x, y| AreaBuilder::default()
+    ///     .anchor((x, y).into())
+    ///     .dimensions((4, 4))
+    ///     .build().unwrap();
+    /// let region = square(0, 0);
+    /// let handle = qt.insert(region, 0).unwrap();
+    ///
+    /// // Paint (2, 2)->4x4, which covers a quarter of the region.
+    /// let brush = square(2, 2);
+    /// qt.modify(brush, |region, paint| {
+    ///     let overlap = region.intersection(brush).unwrap();
+    ///     *paint += 100 * overlap.width() * overlap.height() / (region.width() * region.height());
+    /// });
+    ///
+    /// assert_eq!(qt.get(handle).unwrap().value_ref(), &25);
+    /// ```
+    pub fn modify<F>(&mut self, area: Area<U>, mut f: F) -> usize
+    where
+        F: FnMut(&Area<U>, &mut V),
+    {
+        self.modify_region(area, Traversal::Overlapping, &mut f)
+    }
+
+    /// A strict variant of [`.modify()`].
+    ///
+    /// [`.modify()`]: #method.modify
+    pub fn modify_strict<F>(&mut self, area: Area<U>, mut f: F) -> usize
+    where
+        F: FnMut(&Area<U>, &mut V),
+    {
+        self.modify_region(area, Traversal::Strict, &mut f)
+    }
+
+    /// Alias for [`.modify()`] which runs over the entire
+    /// quadtree.
+    ///
+    /// [`.modify()`]: #method.modify
+    pub fn modify_all<F>(&mut self, mut f: F) -> usize
+    where
+        F: FnMut(&Area<U>, &mut V),
+    {
+        for entry in self.store.values_mut() {
+            let region = entry.area();
+            f(&region, entry.value_mut());
+        }
+        self.len()
+    }
+
+    /// Resets the quadtree to a totally empty state.
+    pub fn reset(&mut self) {
+        self.store.clear();
+        self.inner.reset();
+    }
+
+    /// Deletes all value associations which overlap a region in the tree.
+    ///
+    /// Along the way, consumed [`Entry<U, V>`] entries are collected and returned in an iterator
+    /// [`IntoIter<U, V>`].
+    /// ```
+    /// use quadtree_rs::{area::AreaBuilder, Quadtree};
+    ///
+    /// let mut qt = Quadtree::<u32, f64>::new(4);
+    ///
+    /// let region_a = AreaBuilder::default()
+    ///     .anchor((0, 0).into())
+    ///     .dimensions((2, 2))
+    ///     .build().unwrap();
+    /// qt.insert(region_a, 1.23);
+    ///
+    /// let region_b = AreaBuilder::default()
+    ///     .anchor((1, 1).into())
+    ///     .dimensions((3, 2))
+    ///     .build().unwrap();
+    /// qt.insert(region_b, 4.56);
+    ///
+    /// //   0123
+    /// // 0 ░░
+    /// // 1 ░▓╳░  <-- ╳ is the deletion region
+    /// // 2  ░░░
+    ///
+    /// let region_c = AreaBuilder::default()
+    ///     .anchor((2, 1).into()).build().unwrap();
+    /// let mut returned_entries = qt.delete(region_c);
+    ///
+    /// // We've removed one object from the quadtree.
+    /// assert_eq!(returned_entries.next().unwrap().value_ref(),
+    ///            &4.56);
+    ///
+    /// // And left one behind.
+    /// assert_eq!(qt.len(), 1);
+    /// ```
+    ///
+    /// [`IntoIter<U, V>`]: iter/struct.IntoIter.html
+    /// [`Entry<U, V>`]: entry/struct.Entry.html
+    /// [`.delete()`]: #method.delete
+    pub fn delete(&mut self, area: Area<U>) -> IntoIter<U, V> {
+        self.delete_handles_and_return(self.query(area).map(|e| e.handle()).collect())
+    }
+
+    /// A strict variant of [`.delete()`].
+    ///
+    /// [`.delete()`]: #method.delete
+    pub fn delete_strict(&mut self, area: Area<U>) -> IntoIter<U, V> {
+        self.delete_handles_and_return(self.query_strict(area).map(|e| e.handle()).collect())
+    }
+
+    /// Removes every entry from the quadtree and returns them in an iterator, leaving the
+    /// quadtree empty but otherwise as it was.
+    ///
+    /// Unlike consuming the quadtree with `into_iter()`, this keeps the quadtree around, along
+    /// with its settings and the capacity of its internal store, so it can be filled again. As
+    /// with `Vec::drain()`, each entry is removed as the iterator yields it, and whatever's left
+    /// when the iterator is dropped is removed then, so the quadtree is empty afterwards whether
+    /// or not the iterator was used up.
+    /// ```
+    /// use quadtree_rs::Quadtree;
+    ///
+    /// let mut qt = Quadtree::<u32, String>::new(4);
+    /// qt.extend(vec![((0, 0), "a".to_string()), ((3, 3), "b".to_string())]);
+    ///
+    /// let mut values: Vec<String> = qt.drain().map(|e| e.into_parts().1).collect();
+    /// values.sort();
+    ///
+    /// assert_eq!(values, vec!["a", "b"]);
+    /// assert!(qt.is_empty());
+    /// ```
+    pub fn drain(&mut self) -> Drain<'_, U, V, S> {
+        let handles: Vec<u64> = self.store.values().map(Entry::handle).collect();
+        Drain::new(self, handles, true)
+    }
+
+    /// Removes every entry which overlaps `area` and returns them in an iterator. See
+    /// [`.drain()`].
+    ///
+    /// This removes just what [`.delete()`] would, but does so as the iterator is advanced
+    /// rather than up front; whatever's left when it's dropped is removed then.
+    /// ```
+    /// use quadtree_rs::{area::AreaBuilder, Quadtree};
+    ///
+    /// let mut qt = Quadtree::<u32, char>::new(4);
+    /// qt.extend(vec![((0, 0), 'a'), ((1, 1), 'b'), ((9, 9), 'c')]);
+    ///
+    /// let corner = AreaBuilder::default()
+    ///     .anchor((0, 0).into())
+    ///     .dimensions((4, 4))
+    ///     .build().unwrap();
+    /// let mut drain = qt.drain_region(corner);
+    /// assert!(drain.next().is_some());
+    /// drop(drain);
+    ///
+    /// assert_eq!(qt.values().collect::<Vec<_>>(), vec![&'c']);
+    /// ```
+    ///
+    /// [`.drain()`]: #method.drain
+    /// [`.delete()`]: #method.delete
+    pub fn drain_region(&mut self, area: Area<U>) -> Drain<'_, U, V, S> {
+        let mut handles: Vec<u64> = self.query(area).map(Entry::handle).collect();
+        // Queries visit the tree in a fixed order, but an ordered quadtree hands entries back in
+        // insertion order.
+        if self.store.is_ordered() {
+            handles.sort_unstable();
+        }
+        Drain::new(self, handles, false)
+    }
+
+    /// Removes every value association which overlaps a region in the tree, and returns how many
+    /// were removed.
+    ///
+    /// This removes just what [`.delete()`] would, but drops the entries instead of handing them
+    /// back, which lets it skip a lot of work. Cells which the region totally covers are cleared
+    /// out wholesale, without checking what they hold, and any part of the tree which is left
+    /// empty is collapsed.
+    /// ```
+    /// use quadtree_rs::{area::AreaBuilder, Quadtree};
+    ///
+    /// let mut qt = Quadtree::<u32, char>::new(4);
+    /// qt.extend(vec![((0, 0), 'a'), ((1, 1), 'b'), ((9, 9), 'c')]);
+    ///
+    /// let region = AreaBuilder::default()
+    ///     .anchor((0, 0).into())
+    ///     .dimensions((8, 8))
+    ///     .build().unwrap();
+    /// assert_eq!(qt.clear(region), 2);
+    ///
+    /// assert_eq!(qt.len(), 1);
+    /// ```
+    ///
+    /// [`.delete()`]: #method.delete
+    pub fn clear(&mut self, area: Area<U>) -> usize {
+        let store = &self.store;
+        let handles = self
+            .inner
+            .remove_intersecting(area, &|handle| store.get(&handle).map(Entry::area));
+        for handle in &handles {
+            self.store.remove(handle);
+        }
+        handles.len()
+    }
+
+    fn delete_handles_and_return(&mut self, mut handles: Vec<u64>) -> IntoIter<U, V> {
+        let error: &'static str = "I tried to look up an handle in the store which I found in the tree, but it wasn't there!";
+
+        // Queries visit the tree in a fixed order, but an ordered quadtree hands entries back in
+        // insertion order.
+        if self.store.is_ordered() {
+            handles.sort_unstable();
+        }
+
+        let mut entries: Vec<Entry<U, V>> = vec![];
+
+        for handle in handles {
+            // We were just passed a set of distinct handles taken from this quadtree, so it is safe
+            // to assume they all still exist.
+            let entry = self.store.remove(&handle).expect(error);
+            // The tree has to forget the handle too, or it would count towards
+            // .count_in_region() and trip up later iteration.
+            self.inner.delete_by_handle(handle, entry.area());
+            entries.push(entry);
+        }
+
+        IntoIter {
+            entries: entries.into_iter(),
+        }
     }
 
     /// Given an handle, deletes a single item from the
@@ -602,6 +2682,203 @@ where
         None
     }
 
+    /// Given an handle, removes a single item from the Quadtree and returns its former region and
+    /// value by value. Otherwise, returns `None`.
+    ///
+    /// Unlike [`.delete_by_handle()`], the value is moved out of the tree rather than left wrapped
+    /// in an [`Entry<U, V>`].
+    ///
+    /// ```
+    /// use quadtree_rs::{area::AreaBuilder, Quadtree};
+    ///
+    /// let mut qt = Quadtree::<u32, String>::new(4);
+    ///
+    /// let region = AreaBuilder::default()
+    ///     .anchor((1, 1).into())
+    ///     .dimensions((2, 2))
+    ///     .build().unwrap();
+    /// let handle = qt.insert(region, "foo".to_string()).unwrap();
+    ///
+    /// let (area, value) = qt.remove(handle).unwrap();
+    /// assert_eq!(area, region);
+    /// assert_eq!(value, "foo");
+    ///
+    /// // Handles are never re-used, so a second removal finds nothing.
+    /// assert!(qt.remove(handle).is_none());
+    /// assert!(qt.is_empty());
+    /// ```
+    ///
+    /// [`.delete_by_handle()`]: #method.delete_by_handle
+    /// [`Entry<U, V>`]: entry/struct.Entry.html
+    pub fn remove(&mut self, handle: u64) -> Option<(Area<U>, V)> {
+        self.delete_by_handle(handle).map(Entry::into_parts)
+    }
+
+    /// Removes some region which contains a point, and returns it along with its value by value.
+    /// Returns `None` if no region contains the point.
+    ///
+    /// The region is the one [`.get_pt()`] would find, so if several regions contain the point,
+    /// which one is removed is unspecified.
+    /// ```
+    /// use quadtree_rs::{area::AreaBuilder, point::Point, Quadtree};
+    ///
+    /// let mut qt = Quadtree::<u32, String>::new(4);
+    ///
+    /// let region = AreaBuilder::default()
+    ///     .anchor((1, 1).into())
+    ///     .dimensions((2, 2))
+    ///     .build().unwrap();
+    /// qt.insert(region, "foo".to_string());
+    ///
+    /// assert_eq!(qt.pop_pt(Point {x: 2, y: 2}), Some((region, "foo".to_string())));
+    /// assert_eq!(qt.pop_pt(Point {x: 2, y: 2}), None);
+    /// ```
+    ///
+    /// [`.get_pt()`]: #method.get_pt
+    pub fn pop_pt(&mut self, point: Point<U>) -> Option<(Area<U>, V)> {
+        let handle = self.get_pt(point)?.handle();
+        self.remove(handle)
+    }
+
+    /// Removes every region which overlaps `area`, and returns them along with their values by
+    /// value.
+    ///
+    /// This is [`.delete()`], but with the entries already taken apart, so that the values can be
+    /// moved somewhere else without cloning them.
+    /// ```
+    /// use quadtree_rs::{area::AreaBuilder, Quadtree};
+    ///
+    /// let mut qt = Quadtree::<u32, String>::new(4);
+    /// qt.extend(vec![((0, 0), "a".to_string()), ((3, 3), "b".to_string())]);
+    ///
+    /// let area = AreaBuilder::default()
+    ///     .anchor((2, 2).into())
+    ///     .dimensions((2, 2))
+    ///     .build().unwrap();
+    /// let popped = qt.pop_region(area);
+    ///
+    /// assert_eq!(popped.len(), 1);
+    /// assert_eq!(popped[0].1, "b");
+    /// assert_eq!(qt.len(), 1);
+    /// ```
+    ///
+    /// [`.delete()`]: #method.delete
+    pub fn pop_region(&mut self, area: Area<U>) -> Vec<(Area<U>, V)> {
+        self.delete(area).map(Entry::into_parts).collect()
+    }
+
+    /// Moves the entry with the given handle to a new region, keeping its handle and value.
+    ///
+    /// Returns `true` if the entry was moved. If there's no such entry, or if the new region
+    /// doesn't fit in the quadtree (and the quadtree's [bounds policy] doesn't make it fit),
+    /// nothing changes and this returns `false`.
+    ///
+    /// This is cheaper than deleting and re-inserting the entry: only the part of the tree where
+    /// the entry's old and new positions differ is touched, which for small movements is usually
+    /// very little of it.
+    /// ```
+    /// use quadtree_rs::{area::AreaBuilder, point::Point, Quadtree};
+    ///
+    /// let mut qt = Quadtree::<u32, &str>::new(4);
+    /// let handle = qt.insert_pt(Point {x: 1, y: 1}, "player").unwrap();
+    ///
+    /// let region = AreaBuilder::default()
+    ///     .anchor((2, 1).into())
+    ///     .dimensions((2, 2))
+    ///     .build().unwrap();
+    /// assert!(qt.relocate(handle, region));
+    ///
+    /// assert_eq!(qt.get(handle).unwrap().area(), region);
+    /// assert!(qt.query_pt((1, 1).into()).next().is_none());
+    /// assert_eq!(qt.query_pt((3, 2).into()).next().unwrap().value_ref(), &"player");
+    /// ```
+    ///
+    /// [bounds policy]: #method.set_bounds_policy
+    pub fn relocate(&mut self, handle: u64, region: Area<U>) -> bool {
+        let from = match self.store.get(&handle) {
+            Some(entry) => entry.area(),
+            None => return false,
+        };
+        let region = match self.fit(region) {
+            Some(region) => region,
+            None => return false,
+        };
+        if let Some(entry) = self.store.get_mut(&handle) {
+            entry.set_area(region);
+        }
+
+        let store = &self.store;
+        self.inner.relocate(handle, from, region, &|handle| {
+            store.get(&handle).map(Entry::area)
+        });
+        true
+    }
+
+    /// Moves many entries at once, each to a new region, keeping their handles and values. Returns
+    /// the number of distinct entries which were moved.
+    ///
+    /// Each `(handle, region)` pair is treated as in [`.relocate()`]: pairs whose handle isn't in
+    /// the quadtree, or whose region doesn't fit, are skipped. If a handle appears more than once,
+    /// its entry ends up at the last region given for it.
+    ///
+    /// For lots of moving objects this is cheaper than calling [`.relocate()`] for each one. The
+    /// bookkeeping which keeps [`.count_in_region()`] fast is fixed up once, at the end, rather
+    /// than after every move, and parts of the tree which have emptied out are pruned away.
+    /// ```
+    /// use quadtree_rs::{area::AreaBuilder, Quadtree};
+    ///
+    /// let mut qt = Quadtree::<u32, u32>::new(8);
+    /// let handles: Vec<u64> = (0..100)
+    ///     .map(|i| qt.insert_pt((i, i).into(), i).unwrap())
+    ///     .collect();
+    ///
+    /// // Every entity steps one cell to the right.
+    /// let moved = qt.update_many(handles.iter().map(|&handle| {
+    ///     let anchor = qt.get(handle).unwrap().anchor();
+    ///     let region = AreaBuilder::default()
+    ///         .anchor((anchor.x() + 1, anchor.y()).into())
+    ///         .build().unwrap();
+    ///     (handle, region)
+    /// }).collect::<Vec<_>>());
+    /// assert_eq!(moved, 100);
+    ///
+    /// assert!(qt.query_pt((0, 0).into()).next().is_none());
+    /// assert_eq!(qt.query_pt((1, 0).into()).next().unwrap().value_ref(), &0);
+    /// ```
+    ///
+    /// [`.relocate()`]: #method.relocate
+    /// [`.count_in_region()`]: #method.count_in_region
+    pub fn update_many<I>(&mut self, moves: I) -> usize
+    where
+        I: IntoIterator<Item = (u64, Area<U>)>,
+    {
+        // Make room for everything up front: growing the tree rebuilds it from the store, which
+        // has to agree with the tree while that happens. Only the last region given for each
+        // handle is kept, so that every entry is detached from where it really is, and only once.
+        let mut destinations: HashMap<u64, Area<U>> = HashMap::new();
+        for (handle, region) in moves {
+            if !self.store.contains_key(&handle) {
+                continue;
+            }
+            if let Some(region) = self.fit(region) {
+                destinations.insert(handle, region);
+            }
+        }
+
+        let mut from = Vec::with_capacity(destinations.len());
+        for (handle, region) in destinations {
+            if let Some(entry) = self.store.get_mut(&handle) {
+                from.push((handle, entry.area()));
+                entry.set_area(region);
+            }
+        }
+
+        let store = &self.store;
+        self.inner
+            .relocate_many(&from, &|handle| store.get(&handle).map(Entry::area));
+        from.len()
+    }
+
     // TODO(ambuc): Test this fn.
     /// Retains only the elements specified by the predicate.
     ///
@@ -609,14 +2886,13 @@ where
     pub fn retain<F>(&mut self, mut f: F) -> IntoIter<U, V>
     where
         F: FnMut(&mut V) -> bool,
-        U: Hash,
     {
         // TODO(ambuc): I think this is technically correct but it seems to be interweaving three
         // routines. Is there a way to simplify this?
-        let mut doomed: HashSet<(u64, Area<U>)> = HashSet::new();
-        for (handle, entry) in &mut self.store {
+        let mut doomed: Vec<(u64, Area<U>)> = vec![];
+        for entry in self.store.values_mut() {
             if f(entry.value_mut()) {
-                doomed.insert((*handle, entry.area()));
+                doomed.push((entry.handle(), entry.area()));
             }
         }
         // TODO(ambuc): There is an optimization here to do one traversal with many matches, over
@@ -627,25 +2903,107 @@ where
             self.inner.delete_by_handle(handle, region);
         }
 
-        IntoIter { entries }
+        IntoIter {
+            entries: entries.into_iter(),
+        }
     }
     // TODO(ambuc): retain_within
 
     /// Returns an iterator ([`Iter<U, V>`]) over all [`&'a Entry<U, V>`]
     /// region/value associations in the Quadtree.
     ///
+    /// The order is unspecified, but stays the same until the quadtree is next changed, and the
+    /// iterator can be walked from either end: `.rev()` yields exactly the same entries in the
+    /// opposite order. The same goes for [`.regions()`] and [`.values()`].
+    /// ```
+    /// use quadtree_rs::Quadtree;
+    ///
+    /// let mut qt = Quadtree::<u32, char>::new(4);
+    /// qt.extend(vec![((1, 1), 'a'), ((6, 6), 'b'), ((12, 12), 'c')]);
+    ///
+    /// let forwards: Vec<char> = qt.values().copied().collect();
+    /// let mut backwards: Vec<char> = qt.values().rev().copied().collect();
+    /// backwards.reverse();
+    /// assert_eq!(forwards, backwards);
+    /// ```
+    ///
     /// [`Iter<U, V>`]: iter/struct.Iter.html
+    /// [`.regions()`]: #method.regions
+    /// [`.values()`]: #method.values
     /// [`&'a Entry<U, V>`]: entry/struct.Entry.html
-    pub fn iter(&self) -> Iter<U, V> {
+    pub fn iter(&self) -> Iter<'_, U, V, S> {
         Iter::new(&self.inner, &self.store)
     }
 
+    /// Returns an iterator ([`OrderedIter<U, V>`]) over all [`&'a Entry<U, V>`] region/value
+    /// associations in the Quadtree, taking its cells in the given [`Order`].
+    ///
+    /// Entries come out cell by cell, in the order of the cells which hold them; those held by
+    /// the same cell come out in no particular order. So with [`Order::BreadthFirst`], entries
+    /// which span large parts of the quadtree come before small ones, and with [`Order::Morton`]
+    /// or [`Order::Hilbert`] entries which are near each other tend to come out together.
+    /// ```
+    /// use quadtree_rs::{area::AreaBuilder, iter::Order, Quadtree};
+    ///
+    /// let mut qt = Quadtree::<u32, char>::new(4);
+    /// qt.insert_pt((12, 12).into(), 'a');
+    /// qt.insert(AreaBuilder::default()
+    ///     .anchor((0, 0).into())
+    ///     .dimensions((16, 16))
+    ///     .build().unwrap(), 'b');
+    /// qt.insert_pt((1, 1).into(), 'c');
+    ///
+    /// let walk = |order| -> Vec<char> { qt.iter_in(order).map(|e| *e.value_ref()).collect() };
+    /// assert_eq!(walk(Order::Morton), vec!['b', 'c', 'a']);
+    /// assert_eq!(walk(Order::BreadthFirst)[0], 'b');
+    /// assert_eq!(walk(Order::Hilbert), vec!['b', 'c', 'a']);
+    /// ```
+    ///
+    /// [`OrderedIter<U, V>`]: iter/struct.OrderedIter.html
+    /// [`&'a Entry<U, V>`]: entry/struct.Entry.html
+    /// [`Order`]: iter/enum.Order.html
+    /// [`Order::BreadthFirst`]: iter/enum.Order.html#variant.BreadthFirst
+    /// [`Order::Morton`]: iter/enum.Order.html#variant.Morton
+    /// [`Order::Hilbert`]: iter/enum.Order.html#variant.Hilbert
+    pub fn iter_in(&self, order: Order) -> OrderedIter<'_, U, V, S> {
+        OrderedIter::new(&self.inner, &self.store, order)
+    }
+
+    /// Returns an iterator ([`IterMut<U, V>`]) over every `(region, &'a mut V)` pair in the
+    /// Quadtree, in no particular order.
+    ///
+    /// Only the values can be changed; to move an entry, use [`.relocate()`].
+    /// ```
+    /// use quadtree_rs::Quadtree;
+    ///
+    /// let mut qt = Quadtree::<u32, u32>::new(4);
+    /// qt.insert_pt((1, 1).into(), 10);
+    /// qt.insert_pt((5, 5).into(), 20);
+    ///
+    /// // Decay every value by its distance from the origin.
+    /// for (region, value) in qt.iter_mut() {
+    ///     *value -= region.anchor().x();
+    /// }
+    ///
+    /// let mut values: Vec<u32> = qt.values().copied().collect();
+    /// values.sort();
+    /// assert_eq!(values, vec![9, 15]);
+    /// ```
+    ///
+    /// [`IterMut<U, V>`]: iter/struct.IterMut.html
+    /// [`.relocate()`]: #method.relocate
+    pub fn iter_mut(&mut self) -> IterMut<'_, U, V> {
+        IterMut {
+            entries: self.store.values_mut(),
+        }
+    }
+
     /// Returns an iterator ([`Regions<U, V>`]) over all [`Area<U>`] regions
     /// in the Quadtree.
     ///
     /// [`Regions<U, V>`]: iter/struct.Regions.html
     /// [`Area<U>`]: area/struct.Area.html
-    pub fn regions(&self) -> Regions<U, V> {
+    pub fn regions(&self) -> Regions<'_, U, V, S> {
         Regions {
             inner: Iter::new(&self.inner, &self.store),
         }
@@ -655,78 +3013,505 @@ where
     /// Quadtree.
     ///
     /// [`Values<U, V>`]: iter/struct.Values.html
-    pub fn values(&self) -> Values<U, V> {
+    pub fn values(&self) -> Values<'_, U, V, S> {
         Values {
             inner: Iter::new(&self.inner, &self.store),
         }
     }
 
+    /// Returns an iterator ([`ValuesMut<U, V>`]) over all `&'a mut V` values in the
+    /// Quadtree.
+    ///
+    /// [`ValuesMut<U, V>`]: iter/struct.ValuesMut.html
+    pub fn values_mut(&mut self) -> ValuesMut<'_, U, V> {
+        ValuesMut {
+            inner: self.iter_mut(),
+        }
+    }
+
+    /// Returns an iterator ([`Handles<U, V>`]) over the handles of every value in the Quadtree.
+    ///
+    /// These are the same handles which [`.insert()`] returned, so external structures can key
+    /// their own data by them.
+    ///
+    /// [`Handles<U, V>`]: iter/struct.Handles.html
+    /// [`.insert()`]: #method.insert
+    pub fn handles(&self) -> Handles<'_, U, V, S> {
+        Handles {
+            inner: Iter::new(&self.inner, &self.store),
+        }
+    }
+
+    /// Returns an iterator ([`IterWithHandles<U, V>`]) over every `(handle, region, &'a V)` triple
+    /// in the Quadtree.
+    /// ```
+    /// use quadtree_rs::Quadtree;
+    ///
+    /// let mut qt = Quadtree::<u32, char>::new(4);
+    /// let handle = qt.insert_pt((1, 2).into(), 'a').unwrap();
+    ///
+    /// let (h, region, value) = qt.iter_with_handles().next().unwrap();
+    /// assert_eq!(h, handle);
+    /// assert_eq!(region.anchor(), (1, 2).into());
+    /// assert_eq!(value, &'a');
+    /// ```
+    ///
+    /// [`IterWithHandles<U, V>`]: iter/struct.IterWithHandles.html
+    pub fn iter_with_handles(&self) -> IterWithHandles<'_, U, V, S> {
+        IterWithHandles {
+            inner: Iter::new(&self.inner, &self.store),
+        }
+    }
+
+    /// Returns an iterator ([`Nodes<U>`]) over the cells which the quadtree is currently divided
+    /// into, for drawing or debugging its structure.
+    ///
+    /// Each cell comes as a `(region, depth, item_count)` triple: the area it covers, how many
+    /// levels below the root it is (the root's cell is at depth zero), and how many entries are
+    /// held there. Entries which straddle the borders of the cells below are held at a cell
+    /// above them, so every entry is counted exactly once. Cells come out a level at a time, from
+    /// the root down.
+    /// ```
+    /// use quadtree_rs::Quadtree;
+    ///
+    /// let mut qt = Quadtree::<u32, ()>::new(2);
+    /// qt.insert_pt((0, 0).into(), ());
+    ///
+    /// let nodes: Vec<_> = qt.nodes().map(|(region, depth, count)| {
+    ///     (region.anchor().x(), region.anchor().y(), region.width(), depth, count)
+    /// }).collect();
+    /// // The root's cell is split into four, and the north-west one of those into four again.
+    /// assert_eq!(nodes.len(), 1 + 4 + 4);
+    /// assert_eq!(nodes[0], (0, 0, 4, 0, 0));
+    /// assert!(nodes.contains(&(0, 0, 1, 2, 1)));
+    /// assert_eq!(nodes.iter().map(|n| n.4).sum::<usize>(), qt.len());
+    /// ```
+    ///
+    /// [`Nodes<U>`]: iter/struct.Nodes.html
+    pub fn nodes(&self) -> Nodes<'_, U> {
+        self.nodes_in(Order::BreadthFirst)
+    }
+
+    /// Like [`.nodes()`], but takes the cells in the given [`Order`] rather than a level at a
+    /// time.
+    /// ```
+    /// use quadtree_rs::{iter::Order, Quadtree};
+    ///
+    /// let mut qt = Quadtree::<u32, ()>::new(2);
+    /// qt.extend(vec![((0, 0), ()), ((3, 3), ())]);
+    ///
+    /// let walk = |order| -> Vec<(u32, u32, usize)> {
+    ///     qt.nodes_in(order)
+    ///         .map(|(region, depth, _)| (region.anchor().x(), region.anchor().y(), depth))
+    ///         .collect()
+    /// };
+    /// // The root, then the north-west cell and the cells it's split into, before its siblings.
+    /// assert_eq!(&walk(Order::Morton)[..3], &[(0, 0, 0), (0, 0, 1), (0, 0, 2)]);
+    /// assert_eq!(&walk(Order::BreadthFirst)[..3], &[(0, 0, 0), (2, 0, 1), (0, 0, 1)]);
+    /// ```
+    ///
+    /// [`.nodes()`]: #method.nodes
+    /// [`Order`]: iter/enum.Order.html
+    pub fn nodes_in(&self, order: Order) -> Nodes<'_, U> {
+        Nodes::new(&self.inner, order)
+    }
+
+    /// Consumes the quadtree, returning one with the same regions and handles whose values are
+    /// `f`'s results.
+    ///
+    /// The tree's nodes are reused as they are, so this is much cheaper than building a new
+    /// quadtree out of the mapped entries. Every other setting carries over too.
+    /// ```
+    /// use quadtree_rs::Quadtree;
+    ///
+    /// let mut qt = Quadtree::<u32, &str>::new(4);
+    /// let handle = qt.insert_pt((3, 4).into(), "10").unwrap();
+    ///
+    /// let parsed: Quadtree<u32, u64> = qt.map_values(|_, raw| raw.parse().unwrap());
+    /// assert_eq!(parsed.get(handle).unwrap().value_ref(), &10);
+    /// assert_eq!(parsed.query_pt((3, 4).into()).count(), 1);
+    /// ```
+    pub fn map_values<W, F>(self, f: F) -> Quadtree<U, W, S>
+    where
+        F: FnMut(Area<U>, V) -> W,
+        S: Clone,
+    {
+        Quadtree {
+            inner: self.inner,
+            store: self.store.map_values(f),
+            bounds: self.bounds,
+            bounds_policy: self.bounds_policy,
+            next_handle: self.next_handle,
+        }
+    }
+
     // fn
 
-    fn modify_region<F, M>(&mut self, filter: F, modify: M)
+    // Rebuilds a quadtree from its bounds and entries, as read back from some serialized form.
+    // @dimensions are those of a quadtree built with `new_with_bounds()`, and None otherwise.
+    // Nothing here is trusted: the bounds have to be representable in @U and fit within the root,
+    // and every entry has to fit within them and carry a distinct handle which was issued before
+    // @next_handle.
+    fn restore<I>(
+        anchor: Point<U>,
+        depth: usize,
+        dimensions: Option<(U, U)>,
+        next_handle: u64,
+        entries: I,
+    ) -> Result<Self, String>
+    where
+        I: IntoIterator<Item = Entry<U, V>>,
+        S: Default,
+    {
+        let width = Self::check_root(anchor, depth)?;
+        let mut qt = Self::with_anchor_and_hasher(anchor, depth, S::default());
+        if let Some((w, h)) = dimensions {
+            if w.is_zero() || h.is_zero() || w > width || h > width {
+                return Err("The quadtree's bounds don't fit within its root.".to_string());
+            }
+            qt.bounds = AreaBuilder::default()
+                .anchor(anchor)
+                .dimensions((w, h))
+                .build()?;
+        }
+        for entry in entries {
+            let handle = entry.handle();
+            if handle >= next_handle {
+                return Err(format!("Handle {} was never issued.", handle));
+            }
+            if !qt.contains(entry.area()) {
+                return Err(format!("Handle {} lies outside the quadtree.", handle));
+            }
+            if qt.store.contains_key(&handle) {
+                return Err(format!("Handle {} appears more than once.", handle));
+            }
+            let region = entry.area();
+            qt.store.insert(handle, entry);
+            let store = &qt.store;
+            qt.inner.insert_handle_at_region(region, handle, &|handle| {
+                store.get(&handle).map(Entry::area)
+            });
+        }
+        qt.next_handle = next_handle;
+        Ok(qt)
+    }
+
+    // Inserts @val under a handle chosen by the caller, who has to make sure that @region fits
+    // and that @handle isn't already in use. Handles issued afterwards will be larger than it.
+    pub(crate) fn insert_with_handle(&mut self, region: Area<U>, val: V, handle: u64) {
+        debug_assert!(self.contains(region));
+        debug_assert!(!self.store.contains_key(&handle));
+        self.next_handle = self.next_handle.max(handle + 1);
+        self.store.insert(handle, Entry::new((region, val), handle));
+        let store = &self.store;
+        self.inner
+            .insert_handle_at_region(region, handle, &|handle| {
+                store.get(&handle).map(Entry::area)
+            });
+    }
+
+    // The dimensions of the bounds, if they've been clipped by `new_with_bounds()`.
+    pub(crate) fn clipped_dimensions(&self) -> Option<(U, U)> {
+        if self.bounds == self.inner.region() {
+            None
+        } else {
+            Some((self.bounds.width(), self.bounds.height()))
+        }
+    }
+
+    // Every entry, in ascending handle order. Anything written out of them, in whatever format,
+    // then comes out the same on every run.
+    pub(crate) fn entries_by_handle(&self) -> Vec<&Entry<U, V>> {
+        let mut entries: Vec<&Entry<U, V>> = self.store.values().collect();
+        entries.sort_unstable_by_key(|e| e.handle());
+        entries
+    }
+
+    // The region to store in place of @region, as the bounds policy has it: @region itself if it
+    // fits (once the tree has grown to fit it, if it's expanding), its clipped remainder if it's
+    // clipping, or None if it's rejected.
+    fn fit(&mut self, region: Area<U>) -> Option<Area<U>> {
+        if self.contains(region) {
+            return Some(region);
+        }
+        match self.bounds_policy {
+            BoundsPolicy::Reject => None,
+            BoundsPolicy::Clip => self.bounds.intersection(region),
+            BoundsPolicy::Expand => Some(region).filter(|&region| self.expand_to_contain(region)),
+        }
+    }
+
+    // Re-roots the tree at the smallest enclosing region which contains @region, doubling the
+    // region's extent toward it one step at a time. Returns false, leaving the tree untouched, if
+    // that region can't be represented in @U.
+    fn expand_to_contain(&mut self, region: Area<U>) -> bool {
+        let mut root = self.inner.region();
+        let mut depth = self.depth();
+        while !root.contains(region) {
+            let side = root.width();
+            let grown = side.checked_add(&side).and_then(|grown| {
+                let x = if region.anchor().x() < root.anchor().x() {
+                    root.anchor().x().checked_sub(&side)?
+                } else {
+                    root.anchor().x()
+                };
+                let y = if region.anchor().y() < root.anchor().y() {
+                    root.anchor().y().checked_sub(&side)?
+                } else {
+                    root.anchor().y()
+                };
+                // The far edges have to be representable too.
+                x.checked_add(&grown)?;
+                y.checked_add(&grown)?;
+                AreaBuilder::default()
+                    .anchor(Point { x, y })
+                    .dimensions((grown, grown))
+                    .build()
+                    .ok()
+            });
+            match grown {
+                Some(grown) => root = grown,
+                None => return false,
+            }
+            depth += 1;
+        }
+
+        // Rather than splice the old root in under a new one, rebuild the tree.
+        self.rebuild(QTInner::new(
+            root.anchor(),
+            depth,
+            self.inner.layout().clone(),
+        ));
+        self.bounds = root;
+        true
+    }
+
+    // Rebuilds the tree in place with a new @layout.
+    fn relayout(&mut self, layout: Layout) {
+        let (anchor, depth) = (self.anchor(), self.depth());
+        self.rebuild(QTInner::new(anchor, depth, layout));
+    }
+
+    // Replaces the node structure with @inner, which should be empty, and registers every entry
+    // in the store with it.
+    fn rebuild(&mut self, inner: QTInner<U>) {
+        self.inner = inner;
+        let store = &self.store;
+        for entry in store.values() {
+            self.inner
+                .insert_handle_at_region(entry.area(), entry.handle(), &|handle| {
+                    store.get(&handle).map(Entry::area)
+                });
+        }
+    }
+
+    // The width of a quadtree anchored at @anchor with depth @depth, as long as the whole of its
+    // region, far edges included, is representable in @U.
+    fn check_root(anchor: Point<U>, depth: usize) -> Result<U, String> {
+        let width = num::checked_pow(U::one() + U::one(), depth).ok_or_else(|| {
+            format!(
+                "A depth of {} is too large for this coordinate type.",
+                depth
+            )
+        })?;
+        if anchor.x().checked_add(&width).is_none() || anchor.y().checked_add(&width).is_none() {
+            return Err("The quadtree's region overflows its coordinate type.".to_string());
+        }
+        Ok(width)
+    }
+
+    // The depth and width of the smallest quadtree whose side is at least @side.
+    fn square_to_fit(side: U) -> (usize, U) {
+        let two = U::one() + U::one();
+        let (mut depth, mut width) = (0, U::one());
+        while width < side {
+            width = width
+                .checked_mul(&two)
+                .expect("The bounds are too large for this coordinate type.");
+            depth += 1;
+        }
+        (depth, width)
+    }
+
+    // The handle of some entry whose region is exactly @region, if there is one.
+    fn find_exact(&self, region: Area<U>) -> Option<u64> {
+        if !self.inner.region().contains(region) {
+            return None;
+        }
+        self.inner.find_containing(region, |handle| {
+            self.store
+                .get(&handle)
+                .is_some_and(|entry| entry.area() == region)
+        })
+    }
+
+    // @region, grown by @gap cells on every side, so that it overlaps everything within @gap cells
+    // of the original. Saturates at the ends of the range of U.
+    fn grow(region: Area<U>, gap: U) -> Area<U> {
+        let anchor = Point {
+            x: region.left_edge().saturating_sub(gap),
+            y: region.top_edge().saturating_sub(gap),
+        };
+        AreaBuilder::default()
+            .anchor(anchor)
+            .dimensions((
+                region.right_edge().saturating_add(gap) - anchor.x(),
+                region.bottom_edge().saturating_add(gap) - anchor.y(),
+            ))
+            .build()
+            .expect("Unexpected error in Quadtree::grow().")
+    }
+
+    // The index of the cell of a grid of @cell_size cells which holds @offset.
+    fn cell_index(offset: U, cell_size: U) -> usize {
+        (offset / cell_size)
+            .to_usize()
+            .expect("Unexpected error in Quadtree::cell_index().")
+    }
+
+    fn area_at_pt(point: Point<U>) -> Area<U> {
+        AreaBuilder::default()
+            .anchor(point)
+            .build()
+            .expect("Unexpected error in Quadtree::area_at_pt().")
+    }
+
+    // Applies @modify to every value which @traversal_method selects for @area, and counts them.
+    // The handles are collected by an ordinary query, so only the subquadrants which the area
+    // touches are visited.
+    fn modify_region<M>(
+        &mut self,
+        area: Area<U>,
+        traversal_method: Traversal,
+        modify: &mut M,
+    ) -> usize
     where
-        F: Fn(Area<U>) -> bool,
-        M: Fn(&mut V) + Copy,
+        M: FnMut(&Area<U>, &mut V),
     {
         let relevant_handles: Vec<u64> =
-            HandleIter::new(&self.inner, self.inner.region()).collect();
+            Query::new(area.into(), &self.inner, &self.store, traversal_method)
+                .map(|e| e.handle())
+                .collect();
+        let mut modified = 0;
         for i in relevant_handles {
             if let Some(entry) = self.store.get_mut(&i) {
-                if filter(entry.area()) {
-                    modify(entry.value_mut());
-                }
+                let region = entry.area();
+                modify(&region, entry.value_mut());
+                modified += 1;
             }
         }
+        modified
+    }
+}
+
+impl<U, V, S> Quadtree<U, V, S>
+where
+    U: PrimInt + Default,
+    V: PartialEq,
+    S: BuildHasher,
+{
+    // pub
+
+    /// Whether any region in the quadtree is associated with `val`.
+    ///
+    /// This has to look at every value, so for frequent lookups consider an [`IndexedQuadtree`].
+    /// ```
+    /// use quadtree_rs::Quadtree;
+    ///
+    /// let mut qt = Quadtree::<u32, &str>::new(4);
+    /// qt.insert_pt((1, 1).into(), "foo");
+    ///
+    /// assert!(qt.contains_value(&"foo"));
+    /// assert!(!qt.contains_value(&"bar"));
+    /// ```
+    ///
+    /// [`IndexedQuadtree`]: index/struct.IndexedQuadtree.html
+    pub fn contains_value(&self, val: &V) -> bool {
+        self.store.values().any(|entry| entry.value_ref() == val)
     }
 }
 
 /// `Extend<((U, U), V)>` will silently drop values whose coordinates do not fit in the region
 /// represented by the Quadtree. It is the responsibility of the callsite to ensure these points
 /// fit.
-impl<U, V> Extend<((U, U), V)> for Quadtree<U, V>
+impl<U, V, S> Extend<((U, U), V)> for Quadtree<U, V, S>
 where
     U: PrimInt + Default,
+    S: BuildHasher,
 {
     fn extend<T>(&mut self, iter: T)
     where
         T: IntoIterator<Item = ((U, U), V)>,
     {
-        for ((x, y), val) in iter {
-            // Ignore errors.
-            self.insert(
-                AreaBuilder::default()
-                    .anchor(point::Point { x, y })
-                    .build()
-                    .unwrap(),
-                val,
-            );
-        }
+        // Ignore errors. Use `.try_extend()` to find out which items didn't fit.
+        self.try_extend(iter);
     }
 }
 
 // Immutable iterator for the Quadtree, returning by-reference.
-impl<'a, U, V> IntoIterator for &'a Quadtree<U, V>
+impl<'a, U, V, S> IntoIterator for &'a Quadtree<U, V, S>
 where
     U: PrimInt + Default,
+    S: BuildHasher,
 {
     type Item = &'a Entry<U, V>;
-    type IntoIter = Iter<'a, U, V>;
+    type IntoIter = Iter<'a, U, V, S>;
 
-    fn into_iter(self) -> Iter<'a, U, V> {
+    fn into_iter(self) -> Iter<'a, U, V, S> {
         Iter::new(&self.inner, &self.store)
     }
 }
 
-impl<U, V> IntoIterator for Quadtree<U, V>
+impl<U, V, S> IntoIterator for Quadtree<U, V, S>
 where
     U: PrimInt + Default,
+    S: BuildHasher,
 {
     type Item = Entry<U, V>;
     type IntoIter = IntoIter<U, V>;
 
     fn into_iter(self) -> IntoIter<U, V> {
         IntoIter {
-            entries: self.store.into_values().collect(),
+            entries: self.store.into_values().into_iter(),
         }
     }
 }
+
+impl<U, V, S> fmt::Debug for Quadtree<U, V, S>
+where
+    U: PrimInt + Default + fmt::Debug,
+    V: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Quadtree")
+            .field("inner", &self.inner)
+            .field("bounds", &self.bounds)
+            .field("store", &self.store)
+            .field("next_handle", &self.next_handle)
+            .finish()
+    }
+}
+
+// Two quadtrees are equal if they have the same shape and hold the same entries under the same
+// handles, regardless of the hashers they were built with.
+impl<U, V, S> PartialEq for Quadtree<U, V, S>
+where
+    U: PrimInt + Default,
+    V: PartialEq,
+    S: BuildHasher,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.inner == other.inner
+            && self.bounds == other.bounds
+            && self.store == other.store
+            && self.next_handle == other.next_handle
+    }
+}
+
+impl<U, V, S> Eq for Quadtree<U, V, S>
+where
+    U: PrimInt + Default,
+    V: Eq,
+    S: BuildHasher,
+{
+}
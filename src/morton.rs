@@ -0,0 +1,105 @@
+// Copyright 2019 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use {
+    crate::{point::Point, qtinner::QTInner, Quadtree},
+    num::PrimInt,
+    std::{default::Default, hash::BuildHasher},
+};
+
+impl<U, V, S> Quadtree<U, V, S>
+where
+    U: PrimInt + Default,
+    S: BuildHasher,
+{
+    // pub
+
+    /// The Morton (Z-order) code of the cell `depth` levels below the root which contains `point`.
+    ///
+    /// The code interleaves the bits of the cell's column and row within the quadtree, with those
+    /// of the row in the odd positions, so a cell at depth `d` has a `2 * d`-bit code and the
+    /// root's is zero. Dropping the last two bits of a code gives the code of the cell's parent,
+    /// so every cell's code is a prefix of the codes of the cells beneath it, and cutting the
+    /// codes at some depth partitions the plane along the quadtree's own cells.
+    ///
+    /// Returns `None` if `point` lies outside the quadtree, if `depth` is greater than the
+    /// quadtree's depth, or if the quadtree is deeper than 64 levels, in which case the code
+    /// might not fit.
+    /// ```
+    /// use quadtree_rs::{point::Point, Quadtree};
+    ///
+    /// let qt = Quadtree::<u32, ()>::new(2);
+    /// let pt = Point { x: 3, y: 1 };
+    ///
+    /// assert_eq!(qt.morton_code(pt, 0), Some(0));
+    /// // The north-east quadrant.
+    /// assert_eq!(qt.morton_code(pt, 1), Some(0b01));
+    /// // Column 3 is 0b11 and row 1 is 0b01, so the bits interleave as 0b01_11.
+    /// assert_eq!(qt.morton_code(pt, 2), Some(0b0111));
+    ///
+    /// assert_eq!(qt.morton_code(pt, 3), None);
+    /// assert_eq!(qt.morton_code(Point { x: 4, y: 0 }, 1), None);
+    /// ```
+    pub fn morton_code(&self, point: Point<U>, depth: usize) -> Option<u128> {
+        let region = self.inner.region();
+        if self.depth() > 64 || depth > self.depth() || !region.contains_pt(point) {
+            return None;
+        }
+        let (x, y) = QTInner::offset(region.anchor(), point);
+        let shift = self.depth() - depth;
+        Some(QTInner::<U>::morton(
+            x.checked_shr(shift as u32).unwrap_or(0),
+            y.checked_shr(shift as u32).unwrap_or(0),
+        ))
+    }
+
+    /// The Morton code and depth of the cell which holds the entry with the given handle, if
+    /// there is one. See [`.morton_code()`].
+    ///
+    /// An entry is held by the smallest cell the quadtree has made which totally contains its
+    /// region, so the key of an entry whose region spans two cells is that of their common
+    /// ancestor. The entries of the cell with code `c` at depth `d` are exactly those whose keys
+    /// at or below `d` begin with `c`; [`.iter_in(Order::Morton)`] visits them in the order of
+    /// their keys, with the keys widened to a common depth.
+    /// ```
+    /// use quadtree_rs::{area::AreaBuilder, point::Point, Quadtree};
+    ///
+    /// let mut qt = Quadtree::<u32, ()>::new(2);
+    /// let small = qt.insert_pt(Point { x: 3, y: 1 }, ()).unwrap();
+    /// let wide = qt.insert(
+    ///     AreaBuilder::default()
+    ///         .anchor((1, 1).into())
+    ///         .dimensions((2, 1))
+    ///         .build().unwrap(),
+    ///     (),
+    /// ).unwrap();
+    ///
+    /// assert_eq!(qt.morton_key(small), Some((0b0111, 2)));
+    /// // Straddles the west and east halves, so it's held by the root.
+    /// assert_eq!(qt.morton_key(wide), Some((0, 0)));
+    /// assert_eq!(qt.morton_key(1234), None);
+    /// ```
+    ///
+    /// [`.morton_code()`]: #method.morton_code
+    /// [`.iter_in(Order::Morton)`]: #method.iter_in
+    pub fn morton_key(&self, handle: u64) -> Option<(u128, usize)> {
+        let entry = self.store.get(&handle)?;
+        let holder = *self.inner.path_to(entry.area()).last()?;
+        let node = self.inner.node(holder);
+        // Nodes count their depth down towards the leaves; the root's is the tree's.
+        let depth = self.depth() - node.depth();
+        self.morton_code(node.region().anchor(), depth)
+            .map(|code| (code, depth))
+    }
+}
@@ -0,0 +1,60 @@
+// Copyright 2019 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// Conversions to and from the points and vectors of `nalgebra`, behind the `nalgebra` feature.
+
+use {
+    crate::point::Point,
+    ::nalgebra::{Point2, Scalar, Vector2},
+    num::PrimInt,
+};
+
+impl<U> From<Point2<U>> for Point<U>
+where
+    U: PrimInt + Scalar,
+{
+    fn from(point: Point2<U>) -> Self {
+        Self {
+            x: point.x,
+            y: point.y,
+        }
+    }
+}
+
+impl<U> From<Point<U>> for Point2<U>
+where
+    U: PrimInt + Scalar,
+{
+    fn from(point: Point<U>) -> Self {
+        Point2::new(point.x, point.y)
+    }
+}
+
+impl<U> From<Vector2<U>> for Point<U>
+where
+    U: PrimInt + Scalar,
+{
+    fn from(v: Vector2<U>) -> Self {
+        Self { x: v.x, y: v.y }
+    }
+}
+
+impl<U> From<Point<U>> for Vector2<U>
+where
+    U: PrimInt + Scalar,
+{
+    fn from(point: Point<U>) -> Self {
+        Vector2::new(point.x, point.y)
+    }
+}
@@ -0,0 +1,798 @@
+// Copyright 2019 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A region tree over any number of dimensions, behind the `ndtree` feature.
+//!
+//! A [`Tree<U, V, D>`] associates values with boxes of whole cells in `D`-dimensional space,
+//! [`Region<U, D>`]s, whose coordinates are given as `[U; D]` arrays. Every node's cell is a
+//! hypercube, lazily subdivided into `2^D` children: a `Tree<U, V, 2>` is a region quadtree and a
+//! `Tree<U, V, 3>` is an [`Octree`].
+//!
+//! Each handle is held exactly once, at the smallest node whose cell wholly contains its region,
+//! just as in the crate's [`Quadtree`]. The `Quadtree` isn't built on this tree, since it lays
+//! out its nodes for loose bounds and pluggable split policies, and has a much larger API. But
+//! the two share their geometry, which is written once, axis by axis, for any number of
+//! dimensions, and their best-first nearest-neighbour search, so a `Tree<U, V, 2>` finds just
+//! what a `Quadtree` would. [`Point`]s and [`Area`]s convert into the coordinates and regions of a
+//! `Tree<U, V, 2>`.
+//!
+//! ```
+//! use quadtree_rs::ndtree::{Region, Tree};
+//!
+//! // A four-dimensional tree, with a side of 2^3.
+//! let mut tree = Tree::<u8, char, 4>::new(3);
+//! tree.insert_pt([1, 2, 3, 4], 'a');
+//! tree.insert(Region::new([4, 4, 4, 4], [2, 2, 2, 2]).unwrap(), 'b');
+//!
+//! let low = Region::new([0, 0, 0, 0], [4, 4, 4, 8]).unwrap();
+//! let found: Vec<char> = tree.query(low).map(|e| *e.value_ref()).collect();
+//! assert_eq!(found, vec!['a']);
+//!
+//! let nearest = tree.knn([7, 7, 7, 7], 1);
+//! assert_eq!(nearest[0].value_ref(), &'b');
+//! ```
+//!
+//! [`Tree<U, V, D>`]: struct.Tree.html
+//! [`Region<U, D>`]: struct.Region.html
+//! [`Octree`]: ../octree/type.Octree.html
+//! [`Quadtree`]: ../struct.Quadtree.html
+//! [`Point`]: ../point/struct.Point.html
+//! [`Area`]: ../area/struct.Area.html
+
+use {
+    crate::{
+        area::Area,
+        geometry,
+        nearest::{BestFirst, Candidate},
+        point::Point,
+    },
+    num::PrimInt,
+    std::{
+        collections::{hash_map, HashMap},
+        default::Default,
+        fmt::Debug,
+        iter::FusedIterator,
+    },
+};
+
+/// A box-shaped region in `D`-dimensional space: the counterpart to an [`Area`].
+///
+/// Lightweight, should be passed by value. Defined by its anchor, the corner with the smallest
+/// coordinates, and its extent along each axis, which must all be positive.
+///
+/// [`Area`]: ../area/struct.Area.html
+#[derive(PartialEq, Eq, Clone, Copy, Hash)]
+pub struct Region<U, const D: usize> {
+    anchor: [U; D],
+    dimensions: [U; D],
+}
+
+impl<U, const D: usize> Debug for Region<U, D>
+where
+    U: Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "({:?})->", self.anchor)?;
+        for (i, len) in self.dimensions.iter().enumerate() {
+            if i > 0 {
+                write!(f, "x")?;
+            }
+            write!(f, "{:?}", len)?;
+        }
+        Ok(())
+    }
+}
+
+/// The 2d region covering the same cells as an [`Area`].
+///
+/// [`Area`]: ../area/struct.Area.html
+impl<U> From<Area<U>> for Region<U, 2>
+where
+    U: PrimInt + Default,
+{
+    fn from(area: Area<U>) -> Self {
+        Region {
+            anchor: area.anchor().into(),
+            dimensions: [area.width(), area.height()],
+        }
+    }
+}
+
+/// The coordinates of a [`Point`], for use with a `Tree<U, V, 2>`.
+///
+/// [`Point`]: ../point/struct.Point.html
+impl<U> From<Point<U>> for [U; 2] {
+    fn from(pt: Point<U>) -> Self {
+        [pt.x, pt.y]
+    }
+}
+
+impl<U, const D: usize> Region<U, D>
+where
+    U: PrimInt,
+{
+    // pub
+
+    /// Creates a region from its anchor and its extent along each axis, or explains why it
+    /// can't.
+    /// ```
+    /// use quadtree_rs::ndtree::Region;
+    ///
+    /// let region = Region::new([-3, 2, 0], [4, 1, 2]).unwrap();
+    /// assert_eq!(region.dimensions(), [4, 1, 2]);
+    ///
+    /// assert!(Region::new([-3, 2, 0], [4, 1, 0]).is_err());
+    /// ```
+    pub fn new<P, Q>(anchor: P, dimensions: Q) -> Result<Self, String>
+    where
+        P: Into<[U; D]>,
+        Q: Into<[U; D]>,
+    {
+        let (anchor, dimensions) = (anchor.into(), dimensions.into());
+        if let Some(axis) = dimensions.iter().position(|&len| len <= U::zero()) {
+            return Err(format!(
+                "Regions may not have a nonpositive extent along axis {}.",
+                axis
+            ));
+        }
+        Ok(Self { anchor, dimensions })
+    }
+
+    /// The corner of the region with the smallest coordinates.
+    pub fn anchor(&self) -> [U; D] {
+        self.anchor
+    }
+
+    /// The extent of the region along each axis.
+    pub fn dimensions(&self) -> [U; D] {
+        self.dimensions
+    }
+
+    /// Whether or not a region intersects another region.
+    pub fn intersects(self, other: Self) -> bool {
+        geometry::intersects(self.bounds(), other.bounds())
+    }
+
+    /// Whether or not a region wholly contains another region.
+    pub fn contains(self, other: Self) -> bool {
+        geometry::contains(self.bounds(), other.bounds())
+    }
+
+    /// Whether or not a region contains a point.
+    pub fn contains_pt<P: Into<[U; D]>>(self, pt: P) -> bool {
+        self.contains(Self::unit(pt.into()))
+    }
+
+    // fn
+
+    // A region of a single cell.
+    fn unit(pt: [U; D]) -> Self {
+        Self {
+            anchor: pt,
+            dimensions: [U::one(); D],
+        }
+    }
+
+    // A hypercube with a side of 2^@depth.
+    fn cube(anchor: [U; D], depth: usize) -> Self {
+        #[allow(clippy::cast_possible_truncation)]
+        let side = (U::one() + U::one()).pow(depth as u32);
+        Self {
+            anchor,
+            dimensions: [side; D],
+        }
+    }
+
+    // The anchor and dimensions of the region, for the shared geometry.
+    fn bounds(self) -> geometry::Bounds<U, D> {
+        (self.anchor, self.dimensions)
+    }
+
+    // The squared euclidean distance from @pt to the nearest cell of this region. Zero if the
+    // region contains @pt.
+    fn distance_sq_to_pt(self, pt: [U; D]) -> u128 {
+        geometry::distance_sq_to_pt(self.bounds(), pt)
+    }
+}
+
+impl<U> Region<U, 2>
+where
+    U: PrimInt,
+{
+    /// The extent of the region along the x-axis.
+    pub fn width(&self) -> U {
+        self.dimensions[0]
+    }
+
+    /// The extent of the region along the y-axis.
+    pub fn height(&self) -> U {
+        self.dimensions[1]
+    }
+}
+
+impl<U> Region<U, 3>
+where
+    U: PrimInt,
+{
+    /// The extent of the region along the x-axis.
+    pub fn width(&self) -> U {
+        self.dimensions[0]
+    }
+
+    /// The extent of the region along the y-axis.
+    pub fn height(&self) -> U {
+        self.dimensions[1]
+    }
+
+    /// The extent of the region along the z-axis.
+    pub fn depth(&self) -> U {
+        self.dimensions[2]
+    }
+}
+
+/// A region/value association in a [`Tree`]: the counterpart to an [`entry::Entry`].
+///
+/// [`Tree`]: struct.Tree.html
+/// [`entry::Entry`]: ../entry/struct.Entry.html
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Entry<U, V, const D: usize> {
+    region: Region<U, D>,
+    value: V,
+    handle: u64,
+}
+
+impl<U, V, const D: usize> Entry<U, V, D>
+where
+    U: PrimInt,
+{
+    // pub
+
+    /// The returned region.
+    pub fn region(&self) -> Region<U, D> {
+        self.region
+    }
+
+    /// The anchor of the returned region.
+    pub fn anchor(&self) -> [U; D] {
+        self.region.anchor()
+    }
+
+    /// A mutable accessor to the returned value.
+    pub fn value_mut(&mut self) -> &mut V {
+        &mut self.value
+    }
+
+    /// A reference to the returned value.
+    pub fn value_ref(&self) -> &V {
+        &self.value
+    }
+
+    /// The handle the value was stored under.
+    pub fn handle(&self) -> u64 {
+        self.handle
+    }
+
+    /// Consumes the entry, returning its region and value.
+    pub fn into_parts(self) -> (Region<U, D>, V) {
+        (self.region, self.value)
+    }
+}
+
+// Nodes live in an arena and refer to one another by their index into it.
+type NodeId = usize;
+
+// A hypercube of the tree. Its 2^D children are numbered by which halves of the cube they lie
+// in: bit i is set for the far half along axis i.
+#[derive(Clone, Debug)]
+struct Node<U, const D: usize> {
+    cell: Region<U, D>,
+    // The cell has a side of 2^depth, so nodes of depth zero are single cells.
+    depth: usize,
+    handles: Vec<u64>,
+    // The children are allocated together, so only the first is kept.
+    first_child: Option<NodeId>,
+}
+
+impl<U, const D: usize> Node<U, D>
+where
+    U: PrimInt,
+{
+    const CHILDREN: usize = 1 << D;
+
+    fn new(cell: Region<U, D>, depth: usize) -> Self {
+        Node {
+            cell,
+            depth,
+            handles: vec![],
+            first_child: None,
+        }
+    }
+
+    fn children(&self) -> impl DoubleEndedIterator<Item = NodeId> {
+        self.first_child
+            .map_or(0..0, |first| first..first + Self::CHILDREN)
+    }
+
+    // The child which wholly contains @region, if any.
+    fn child_containing(&self, region: Region<U, D>) -> Option<usize> {
+        if self.depth == 0 {
+            return None;
+        }
+        (0..Self::CHILDREN).find(|&i| self.child_cell(i).contains(region))
+    }
+
+    fn child_cell(&self, child: usize) -> Region<U, D> {
+        let far = std::array::from_fn(|axis| child & (1 << axis) != 0);
+        let (anchor, dimensions) = geometry::orthant(self.cell.bounds(), far);
+        Region { anchor, dimensions }
+    }
+}
+
+/// A region tree over `D` dimensions. See the [module-level documentation].
+///
+/// A tree with depth `n` covers a hypercube with a side of `2^n`, anchored at its corner with the
+/// smallest coordinates.
+///
+/// [module-level documentation]: index.html
+#[derive(Clone, Debug)]
+pub struct Tree<U, V, const D: usize> {
+    nodes: Vec<Node<U, D>>,
+    store: HashMap<u64, Entry<U, V, D>>,
+    next_handle: u64,
+}
+
+impl<U, V, const D: usize> Tree<U, V, D>
+where
+    U: PrimInt + Default,
+{
+    // pub
+
+    /// Creates a new, empty tree with some depth, anchored at the origin.
+    /// ```
+    /// use quadtree_rs::ndtree::Tree;
+    ///
+    /// let tree = Tree::<u32, u8, 3>::new(3);
+    /// assert_eq!(tree.anchor(), [0, 0, 0]);
+    /// assert_eq!(tree.width(), 8);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `D` is zero or more than 16, or if `2^depth` isn't representable in `U`.
+    pub fn new(depth: usize) -> Self {
+        Self::new_with_anchor([U::zero(); D], depth)
+    }
+
+    /// Creates a new, empty tree with some depth and an explicit anchor.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `D` is zero or more than 16, or if the far edges of the tree, `anchor +
+    /// 2^depth`, aren't representable in `U`.
+    pub fn new_with_anchor<P: Into<[U; D]>>(anchor: P, depth: usize) -> Self {
+        assert!(
+            (1..=16).contains(&D),
+            "A tree must have between 1 and 16 dimensions."
+        );
+        let anchor = anchor.into();
+        let side = num::checked_pow(U::one() + U::one(), depth).unwrap_or_else(|| {
+            panic!(
+                "A depth of {} is too large for this coordinate type.",
+                depth
+            )
+        });
+        assert!(
+            anchor.iter().all(|c| c.checked_add(&side).is_some()),
+            "The tree's region overflows its coordinate type."
+        );
+        Tree {
+            nodes: vec![Node::new(Region::cube(anchor, depth), depth)],
+            store: HashMap::new(),
+            next_handle: 0,
+        }
+    }
+
+    /// The corner of the tree with the smallest coordinates.
+    pub fn anchor(&self) -> [U; D] {
+        self.root().cell.anchor()
+    }
+
+    /// The length of each side of the tree.
+    pub fn width(&self) -> usize {
+        self.root().cell.dimensions[0]
+            .to_usize()
+            .expect("The tree's width should fit in a usize.")
+    }
+
+    /// The depth of the tree.
+    pub fn depth(&self) -> usize {
+        self.root().depth
+    }
+
+    /// The number of elements in the tree.
+    pub fn len(&self) -> usize {
+        self.store.len()
+    }
+
+    /// Whether or not the tree is empty.
+    pub fn is_empty(&self) -> bool {
+        self.store.is_empty()
+    }
+
+    /// Whether or not some trial region could fit in the region which this tree represents.
+    pub fn contains(&self, region: Region<U, D>) -> bool {
+        self.root().cell.contains(region)
+    }
+
+    /// Associate some value with a region in the tree.
+    ///
+    /// If insertion is successful, returns a unique handle to the value. If the region doesn't
+    /// fit within the tree, returns `None`.
+    /// See [`Quadtree::insert()`].
+    ///
+    /// [`Quadtree::insert()`]: ../struct.Quadtree.html#method.insert
+    pub fn insert(&mut self, region: Region<U, D>, val: V) -> Option<u64> {
+        if !self.contains(region) {
+            return None;
+        }
+        let handle = self.next_handle;
+        self.next_handle += 1;
+
+        let id = self.descend_and_split(region);
+        self.nodes[id].handles.push(handle);
+        self.store.insert(
+            handle,
+            Entry {
+                region,
+                value: val,
+                handle,
+            },
+        );
+        Some(handle)
+    }
+
+    /// Alias for [`.insert()`] which expects a point instead of a [`Region`].
+    ///
+    /// [`.insert()`]: #method.insert
+    /// [`Region`]: struct.Region.html
+    pub fn insert_pt<P: Into<[U; D]>>(&mut self, point: P, val: V) -> Option<u64> {
+        self.insert(Region::unit(point.into()), val)
+    }
+
+    /// Given a handle, retrieves the corresponding entry, if any.
+    pub fn get(&self, handle: u64) -> Option<&Entry<U, V, D>> {
+        self.store.get(&handle)
+    }
+
+    /// A mutable variant of [`.get()`].
+    ///
+    /// [`.get()`]: #method.get
+    pub fn get_mut(&mut self, handle: u64) -> Option<&mut Entry<U, V, D>> {
+        self.store.get_mut(&handle)
+    }
+
+    /// Returns an iterator over every [`&Entry<U, V, D>`] whose region intersects `region`.
+    /// See [`Quadtree::query()`].
+    /// ```
+    /// use quadtree_rs::ndtree::{Region, Tree};
+    ///
+    /// let mut tree = Tree::<u8, i32, 3>::new(4);
+    /// tree.insert(Region::new([0, 0, 0], [9, 1, 1]).unwrap(), 1);
+    /// tree.insert_pt([8, 8, 8], 2);
+    ///
+    /// let slab = Region::new([8, 0, 0], [8, 16, 1]).unwrap();
+    /// let found: Vec<i32> = tree.query(slab).map(|e| *e.value_ref()).collect();
+    /// assert_eq!(found, vec![1]);
+    /// ```
+    ///
+    /// [`&Entry<U, V, D>`]: struct.Entry.html
+    /// [`Quadtree::query()`]: ../struct.Quadtree.html#method.query
+    pub fn query(&self, region: Region<U, D>) -> Query<'_, U, V, D> {
+        Query::new(self, region)
+    }
+
+    /// Returns the `k` [`&Entry<U, V, D>`]s whose regions lie closest to some point, nearest
+    /// first.
+    ///
+    /// Distances are euclidean, measured from the point to the nearest cell of each region, so
+    /// a region containing the point is at distance zero. Ties are broken by handle.
+    /// See [`Quadtree::knn()`].
+    /// ```
+    /// use quadtree_rs::ndtree::Tree;
+    ///
+    /// let mut tree = Tree::<u32, char, 3>::new(4);
+    /// tree.insert_pt([1, 1, 1], 'a');
+    /// tree.insert_pt([9, 9, 9], 'b');
+    /// tree.insert_pt([6, 6, 0], 'c');
+    ///
+    /// let nearest: Vec<char> = tree.knn([8, 8, 8], 2)
+    ///     .iter()
+    ///     .map(|e| *e.value_ref())
+    ///     .collect();
+    /// assert_eq!(nearest, vec!['b', 'c']);
+    /// ```
+    ///
+    /// [`&Entry<U, V, D>`]: struct.Entry.html
+    /// [`Quadtree::knn()`]: ../struct.Quadtree.html#method.knn
+    pub fn knn<P: Into<[U; D]>>(&self, pt: P, k: usize) -> Vec<&Entry<U, V, D>> {
+        // The same best-first search as a Quadtree's. Nodes are keyed by their distance, a lower
+        // bound on that of anything they hold; entries by their exact distance. Every entry is
+        // held by a node whose cell contains its region, so once an entry reaches the front of
+        // the queue nothing left can be any closer.
+        let pt = pt.into();
+        let mut queue = BestFirst::new();
+        queue.push(self.root().cell.distance_sq_to_pt(pt), Candidate::Node(0));
+
+        let mut nearest = vec![];
+        while nearest.len() < k {
+            let id = match queue.pop() {
+                Some((_, Candidate::Node(id))) => id,
+                Some((_, Candidate::Handle(handle))) => {
+                    nearest.extend(self.store.get(&handle));
+                    continue;
+                }
+                None => break,
+            };
+            let node = &self.nodes[id];
+            for &handle in &node.handles {
+                if let Some(entry) = self.store.get(&handle) {
+                    queue.push(
+                        entry.region.distance_sq_to_pt(pt),
+                        Candidate::Handle(handle),
+                    );
+                }
+            }
+            for child in node.children() {
+                let distance_sq = self.nodes[child].cell.distance_sq_to_pt(pt);
+                queue.push(distance_sq, Candidate::Node(child));
+            }
+        }
+        nearest
+    }
+
+    /// Deletes all value associations which overlap a region in the tree, returning them.
+    /// See [`Quadtree::delete()`].
+    ///
+    /// [`Quadtree::delete()`]: ../struct.Quadtree.html#method.delete
+    pub fn delete(&mut self, region: Region<U, D>) -> IntoIter<U, V, D> {
+        let handles: Vec<u64> = self.query(region).map(Entry::handle).collect();
+        let entries: Vec<Entry<U, V, D>> = handles
+            .into_iter()
+            .filter_map(|handle| self.delete_by_handle(handle))
+            .collect();
+        IntoIter {
+            entries: entries.into_iter(),
+        }
+    }
+
+    /// Given a handle, deletes a single item from the tree, returning its former entry, or
+    /// `None` if there was none.
+    pub fn delete_by_handle(&mut self, handle: u64) -> Option<Entry<U, V, D>> {
+        let entry = self.store.remove(&handle)?;
+        let id = self.descend(entry.region);
+        self.nodes[id].handles.retain(|&h| h != handle);
+        Some(entry)
+    }
+
+    /// Given a handle, removes a single item from the tree and returns its former region and
+    /// value by value. Otherwise, returns `None`.
+    pub fn remove(&mut self, handle: u64) -> Option<(Region<U, D>, V)> {
+        self.delete_by_handle(handle).map(Entry::into_parts)
+    }
+
+    /// Resets the tree to a totally empty state. Handles aren't reused afterwards.
+    pub fn reset(&mut self) {
+        self.store.clear();
+        self.nodes.truncate(1);
+        let root = &mut self.nodes[0];
+        root.handles.clear();
+        root.first_child = None;
+    }
+
+    /// Returns an iterator over every [`&Entry<U, V, D>`] in the tree, in arbitrary order.
+    ///
+    /// [`&Entry<U, V, D>`]: struct.Entry.html
+    pub fn iter(&self) -> Iter<'_, U, V, D> {
+        Iter {
+            entries: self.store.values(),
+        }
+    }
+
+    // fn
+
+    fn root(&self) -> &Node<U, D> {
+        &self.nodes[0]
+    }
+
+    // The smallest existing node whose cell wholly contains @region.
+    fn descend(&self, region: Region<U, D>) -> NodeId {
+        let mut id = 0;
+        loop {
+            let node = &self.nodes[id];
+            match (node.child_containing(region), node.first_child) {
+                (Some(child), Some(first)) => id = first + child,
+                _ => return id,
+            }
+        }
+    }
+
+    // The smallest node whose cell wholly contains @region, splitting nodes to reach it.
+    fn descend_and_split(&mut self, region: Region<U, D>) -> NodeId {
+        let mut id = 0;
+        while let Some(child) = self.nodes[id].child_containing(region) {
+            let first = match self.nodes[id].first_child {
+                Some(first) => first,
+                None => self.split(id),
+            };
+            id = first + child;
+        }
+        id
+    }
+
+    fn split(&mut self, id: NodeId) -> NodeId {
+        let first = self.nodes.len();
+        let node = &self.nodes[id];
+        let children: Vec<Node<U, D>> = (0..Node::<U, D>::CHILDREN)
+            .map(|child| Node::new(node.child_cell(child), node.depth - 1))
+            .collect();
+        self.nodes.extend(children);
+        self.nodes[id].first_child = Some(first);
+        first
+    }
+}
+
+impl<'a, U, V, const D: usize> IntoIterator for &'a Tree<U, V, D>
+where
+    U: PrimInt + Default,
+{
+    type Item = &'a Entry<U, V, D>;
+    type IntoIter = Iter<'a, U, V, D>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<U, V, const D: usize> IntoIterator for Tree<U, V, D>
+where
+    U: PrimInt + Default,
+{
+    type Item = Entry<U, V, D>;
+    type IntoIter = IntoIter<U, V, D>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        let entries: Vec<Entry<U, V, D>> = self.store.into_values().collect();
+        IntoIter {
+            entries: entries.into_iter(),
+        }
+    }
+}
+
+/// An iterator over all entries of a [`Tree`]. See [`Tree::iter()`].
+///
+/// [`Tree`]: struct.Tree.html
+/// [`Tree::iter()`]: struct.Tree.html#method.iter
+#[derive(Clone, Debug)]
+pub struct Iter<'a, U, V, const D: usize> {
+    entries: hash_map::Values<'a, u64, Entry<U, V, D>>,
+}
+
+impl<'a, U, V, const D: usize> Iterator for Iter<'a, U, V, D> {
+    type Item = &'a Entry<U, V, D>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.entries.next()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.entries.size_hint()
+    }
+}
+
+impl<U, V, const D: usize> ExactSizeIterator for Iter<'_, U, V, D> {}
+
+impl<U, V, const D: usize> FusedIterator for Iter<'_, U, V, D> {}
+
+/// A consuming iterator over entries removed from a [`Tree`]. See [`Tree::delete()`].
+///
+/// [`Tree`]: struct.Tree.html
+/// [`Tree::delete()`]: struct.Tree.html#method.delete
+#[derive(Clone, Debug)]
+pub struct IntoIter<U, V, const D: usize> {
+    entries: std::vec::IntoIter<Entry<U, V, D>>,
+}
+
+impl<U, V, const D: usize> Iterator for IntoIter<U, V, D> {
+    type Item = Entry<U, V, D>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.entries.next()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.entries.size_hint()
+    }
+}
+
+impl<U, V, const D: usize> ExactSizeIterator for IntoIter<U, V, D> {}
+
+impl<U, V, const D: usize> FusedIterator for IntoIter<U, V, D> {}
+
+/// An iterator over the entries of a [`Tree`] which intersect some region. See
+/// [`Tree::query()`].
+///
+/// [`Tree`]: struct.Tree.html
+/// [`Tree::query()`]: struct.Tree.html#method.query
+#[derive(Clone, Debug)]
+pub struct Query<'a, U, V, const D: usize> {
+    tree: &'a Tree<U, V, D>,
+    region: Region<U, D>,
+    // The nodes whose cells intersect the region, but whose handles haven't been looked at yet.
+    stack: Vec<NodeId>,
+    handles: std::slice::Iter<'a, u64>,
+}
+
+impl<'a, U, V, const D: usize> Query<'a, U, V, D>
+where
+    U: PrimInt + Default,
+{
+    fn new(tree: &'a Tree<U, V, D>, region: Region<U, D>) -> Self {
+        let stack = if tree.root().cell.intersects(region) {
+            vec![0]
+        } else {
+            vec![]
+        };
+        Query {
+            tree,
+            region,
+            stack,
+            handles: [].iter(),
+        }
+    }
+}
+
+impl<'a, U, V, const D: usize> Iterator for Query<'a, U, V, D>
+where
+    U: PrimInt + Default,
+{
+    type Item = &'a Entry<U, V, D>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (tree, region) = (self.tree, self.region);
+        loop {
+            // Every handle is held by exactly one node, so there's nothing to deduplicate.
+            if let Some(entry) = self
+                .handles
+                .by_ref()
+                .filter_map(|handle| tree.store.get(handle))
+                .find(|entry| entry.region.intersects(region))
+            {
+                return Some(entry);
+            }
+            let node = &tree.nodes[self.stack.pop()?];
+            self.stack.extend(
+                node.children()
+                    .rev()
+                    .filter(|&child| tree.nodes[child].cell.intersects(region)),
+            );
+            self.handles = node.handles.iter();
+        }
+    }
+}
+
+impl<U, V, const D: usize> FusedIterator for Query<'_, U, V, D> where U: PrimInt + Default {}
@@ -0,0 +1,210 @@
+// Copyright 2019 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use {
+    crate::{
+        entry::Entry,
+        point::Point,
+        qtinner::{Node, QTInner},
+        types::StoreType,
+    },
+    num::PrimInt,
+    std::{
+        cmp::{Ordering, Reverse},
+        collections::BinaryHeap,
+        default::Default,
+        hash::BuildHasher,
+        iter::FusedIterator,
+    },
+};
+
+// Something waiting in a best-first search's priority queue: either a node whose subtree hasn't
+// been expanded yet, or a handle whose region has already been measured. @N is however the tree
+// refers to its nodes.
+pub(crate) enum Candidate<N> {
+    Node(N),
+    Handle(u64),
+}
+
+// A candidate keyed by its distance from whatever is being searched for. For nodes this is a lower
+// bound on the distance of anything held beneath them; for handles it is exact.
+struct Keyed<K, N> {
+    distance: K,
+    candidate: Candidate<N>,
+}
+
+impl<K, N> Keyed<K, N> {
+    // On ties, nodes sort before handles, and handles by handle. Anything beneath a tied node can
+    // at best only match it, so expanding the node first queues every handle as close before any
+    // of them is taken, and they come out in order of handle.
+    fn tiebreak(&self) -> (u8, u64) {
+        match self.candidate {
+            Candidate::Node(_) => (0, 0),
+            Candidate::Handle(handle) => (1, handle),
+        }
+    }
+}
+
+impl<K: Ord, N> PartialEq for Keyed<K, N> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<K: Ord, N> Eq for Keyed<K, N> {}
+
+impl<K: Ord, N> PartialOrd for Keyed<K, N> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<K: Ord, N> Ord for Keyed<K, N> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.distance
+            .cmp(&other.distance)
+            .then(self.tiebreak().cmp(&other.tiebreak()))
+    }
+}
+
+// The priority queue of a best-first search, which pops the nearest candidate first. Shared by
+// every search which walks a tree in order of some distance.
+pub(crate) struct BestFirst<K, N> {
+    heap: BinaryHeap<Reverse<Keyed<K, N>>>,
+}
+
+impl<K: Ord, N> BestFirst<K, N> {
+    pub(crate) fn new() -> Self {
+        BestFirst {
+            heap: BinaryHeap::new(),
+        }
+    }
+
+    pub(crate) fn push(&mut self, distance: K, candidate: Candidate<N>) {
+        self.heap.push(Reverse(Keyed {
+            distance,
+            candidate,
+        }));
+    }
+
+    pub(crate) fn pop(&mut self) -> Option<(K, Candidate<N>)> {
+        self.heap
+            .pop()
+            .map(|Reverse(keyed)| (keyed.distance, keyed.candidate))
+    }
+}
+
+// A best-first traversal of the tree which yields entries in order of increasing distance from
+// some point, along with their squared distance.
+//
+// Every handle is held by some node which covers the cell of its region nearest to the search
+// point, and that node's distance can be no larger than the handle's. So by the time a handle
+// reaches the front of the queue, nothing left in the queue can be any closer, and entries at the
+// same distance come out in order of handle.
+pub(crate) struct NearestIter<'a, U, V, S>
+where
+    U: PrimInt + Default,
+{
+    qt: &'a QTInner<U>,
+    pt: Point<U>,
+    store: &'a StoreType<U, V, S>,
+    queue: BestFirst<u128, &'a Node<U>>,
+    // Candidates further away than this are never queued, bounding the traversal.
+    max_distance_sq: Option<u128>,
+}
+
+impl<'a, U, V, S> NearestIter<'a, U, V, S>
+where
+    U: PrimInt + Default,
+    S: BuildHasher,
+{
+    pub(crate) fn new(qt: &'a QTInner<U>, store: &'a StoreType<U, V, S>, pt: Point<U>) -> Self {
+        Self::new_within(qt, store, pt, None)
+    }
+
+    pub(crate) fn new_within(
+        qt: &'a QTInner<U>,
+        store: &'a StoreType<U, V, S>,
+        pt: Point<U>,
+        max_distance: Option<U>,
+    ) -> Self {
+        let mut iter = NearestIter {
+            qt,
+            pt,
+            store,
+            queue: BestFirst::new(),
+            max_distance_sq: max_distance.map(|d| {
+                let d = d.to_u128().unwrap_or(0);
+                d.saturating_mul(d)
+            }),
+        };
+        iter.push(
+            qt.region().distance_sq_to_pt(pt),
+            Candidate::Node(qt.root()),
+        );
+        iter
+    }
+
+    fn push(&mut self, distance_sq: u128, candidate: Candidate<&'a Node<U>>) {
+        if self.max_distance_sq.is_none_or(|max| distance_sq <= max) {
+            self.queue.push(distance_sq, candidate);
+        }
+    }
+
+    fn expand(&mut self, qt: &'a Node<U>) {
+        for &handle in qt.handles() {
+            if let Some(entry) = self.store.get(&handle) {
+                self.push(
+                    entry.area().distance_sq_to_pt(self.pt),
+                    Candidate::Handle(handle),
+                );
+            }
+        }
+        if let Some(subquadrants) = qt.subquadrants() {
+            let tree = self.qt;
+            for sq in subquadrants.iter().map(|&id| tree.node(id)) {
+                self.push(sq.bounds().distance_sq_to_pt(self.pt), Candidate::Node(sq));
+            }
+        }
+    }
+}
+
+impl<'a, U, V, S> Iterator for NearestIter<'a, U, V, S>
+where
+    U: PrimInt + Default,
+    S: BuildHasher,
+{
+    type Item = (u128, &'a Entry<U, V>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((distance_sq, candidate)) = self.queue.pop() {
+            match candidate {
+                Candidate::Node(qt) => self.expand(qt),
+                Candidate::Handle(handle) => {
+                    if let Some(entry) = self.store.get(&handle) {
+                        return Some((distance_sq, entry));
+                    }
+                }
+            }
+        }
+        None
+    }
+}
+
+impl<U, V, S> FusedIterator for NearestIter<'_, U, V, S>
+where
+    U: PrimInt + Default,
+    S: BuildHasher,
+{
+}
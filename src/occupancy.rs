@@ -0,0 +1,192 @@
+// Copyright 2019 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use {
+    crate::{area::Area, coverage::SetOperation, entry::Entry, qtinner::Node, Quadtree},
+    num::PrimInt,
+    std::{default::Default, hash::BuildHasher},
+};
+
+impl<U, S> Quadtree<U, (), S>
+where
+    U: PrimInt + Default,
+    S: BuildHasher + Clone,
+{
+    // pub
+
+    /// The cells occupied by either `self` or `other`, as a new occupancy quadtree with the same
+    /// region as `self`.
+    ///
+    /// Both quadtrees are treated as masks: a cell is occupied if any region covers it, however
+    /// many do. The masks are combined with the same sweep as [`.coverage_union()`], and the
+    /// combined mask is then cut along the cells of the tree, so that the result holds one region
+    /// per maximal occupied square, just as with [`.from_grid()`]. Anything in `other` outside the
+    /// bounds of `self` is ignored.
+    /// ```
+    /// use quadtree_rs::{area::AreaBuilder, Quadtree};
+    ///
+    /// let mut a = Quadtree::<u32, ()>::new(3);
+    /// let mut b = Quadtree::<u32, ()>::new(3);
+    /// let west = AreaBuilder::default().anchor((0, 0).into()).dimensions((4, 8)).build().unwrap();
+    /// let north_east = AreaBuilder::default()
+    ///     .anchor((4, 0).into())
+    ///     .dimensions((4, 4))
+    ///     .build().unwrap();
+    /// a.insert(west, ());
+    /// b.insert(north_east, ());
+    ///
+    /// // The western half and the north-eastern quarter, as three 4x4 squares.
+    /// let union = a.union(&b);
+    /// assert_eq!(union.len(), 3);
+    /// assert!(union.query_pt((5, 1).into()).next().is_some());
+    /// assert!(union.query_pt((5, 5).into()).next().is_none());
+    /// ```
+    ///
+    /// [`.coverage_union()`]: #method.coverage_union
+    /// [`.from_grid()`]: #method.from_grid
+    pub fn union<T>(&self, other: &Quadtree<U, (), T>) -> Self
+    where
+        T: BuildHasher,
+    {
+        self.combine(other, SetOperation::Union)
+    }
+
+    /// The cells occupied by both `self` and `other`, as a new occupancy quadtree with the same
+    /// region as `self`. See [`.union()`].
+    ///
+    /// [`.union()`]: #method.union
+    pub fn intersection<T>(&self, other: &Quadtree<U, (), T>) -> Self
+    where
+        T: BuildHasher,
+    {
+        self.combine(other, SetOperation::Intersection)
+    }
+
+    /// The cells occupied by `self` but not by `other`, as a new occupancy quadtree with the same
+    /// region as `self`. See [`.union()`].
+    ///
+    /// [`.union()`]: #method.union
+    pub fn difference<T>(&self, other: &Quadtree<U, (), T>) -> Self
+    where
+        T: BuildHasher,
+    {
+        self.combine(other, SetOperation::Difference)
+    }
+
+    // fn
+
+    fn combine<T>(&self, other: &Quadtree<U, (), T>, op: SetOperation) -> Self
+    where
+        T: BuildHasher,
+    {
+        // The combined mask as disjoint rectangles, in a tree with the same cells as this one.
+        let combined = self.combine_coverage(other.regions(), op);
+        let mut squares = vec![];
+        let root = combined.inner.region();
+        if Self::full_squares(
+            &combined,
+            Some(combined.inner.root()),
+            root,
+            &[],
+            &mut squares,
+        ) {
+            squares.push(root);
+        }
+
+        let mut qt =
+            Self::with_anchor_and_hasher(self.anchor(), self.depth(), self.hasher().clone());
+        qt.bounds = self.bounds;
+        for square in squares {
+            qt.insert(square, ());
+        }
+        qt
+    }
+
+    // Whether the disjoint regions of @tree cover every cell of @cell, which is the region of
+    // @node if the tree goes down that far. If not, the largest wholly covered squares of the
+    // tree within @cell are added to @squares. @inherited holds the regions held by the nodes
+    // above @cell, which might reach into it.
+    fn full_squares<T>(
+        tree: &Quadtree<U, (), T>,
+        node: Option<&Node<U>>,
+        cell: Area<U>,
+        inherited: &[Area<U>],
+        squares: &mut Vec<Area<U>>,
+    ) -> bool
+    where
+        T: BuildHasher,
+    {
+        let mut regions: Vec<Area<U>> = inherited
+            .iter()
+            .copied()
+            .filter(|region| region.intersects(cell))
+            .collect();
+        if let Some(node) = node {
+            regions.extend(
+                node.handles()
+                    .iter()
+                    .filter_map(|handle| tree.store.get(handle))
+                    .map(Entry::area),
+            );
+        }
+
+        // With nothing held beneath @cell, the regions reaching into it settle it: being
+        // disjoint, they cover it if their overlaps with it add up to the whole of it. Sizes which
+        // don't fit in a u128 can't be added up, but a cell which one region covers is still
+        // settled, and any other is split until its quarters can be measured.
+        if node.is_none_or(|node| node.len() == node.handles().len()) {
+            let overlaps: Vec<Area<U>> = regions
+                .iter()
+                .filter_map(|region| region.intersection(cell))
+                .collect();
+            if overlaps.is_empty() {
+                return false;
+            }
+            if overlaps.contains(&cell) {
+                return true;
+            }
+            let size = |area: Area<U>| {
+                area.width()
+                    .to_u128()?
+                    .checked_mul(area.height().to_u128()?)
+            };
+            let covered = overlaps.iter().try_fold(0_u128, |covered, &overlap| {
+                covered.checked_add(size(overlap)?)
+            });
+            if covered.is_some() && covered == size(cell) {
+                return true;
+            }
+        }
+
+        let subquadrants = node.and_then(Node::subquadrants);
+        let quarters = cell.quarters();
+        let full: Vec<bool> = (0..4)
+            .map(|i| {
+                let node = subquadrants.map(|ids| tree.inner.node(ids[i]));
+                Self::full_squares(tree, node, quarters[i], &regions, squares)
+            })
+            .collect();
+        if full.iter().all(|&f| f) {
+            return true;
+        }
+        squares.extend(
+            quarters
+                .iter()
+                .zip(full)
+                .filter(|&(_, f)| f)
+                .map(|(&q, _)| q),
+        );
+        false
+    }
+}
@@ -0,0 +1,135 @@
+// Copyright 2019 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A three-dimensional sibling of the [`Quadtree`], behind the `octree` feature.
+//!
+//! An [`Octree`] associates values with boxes of whole cells, [`Cuboid`]s, just as a quadtree
+//! associates them with [`Area`]s, and offers the same core operations: insertion by region or
+//! by point, lookup and deletion by handle, intersection queries, nearest-neighbour search and
+//! iteration. It's the three-dimensional [`ndtree::Tree`], so every node's cell is a cube, lazily
+//! subdivided into eight octants, and each handle is held exactly once.
+//!
+//! Points can be given as [`Point3`]s, `(x, y, z)` tuples or `[x, y, z]` arrays.
+//!
+//! ```
+//! use quadtree_rs::octree::{Cuboid, Octree, Point3};
+//!
+//! let mut ot = Octree::<u32, char>::new(4);
+//! ot.insert_pt(Point3 { x: 1, y: 2, z: 3 }, 'a');
+//! ot.insert(Cuboid::new((8, 8, 8), (4, 2, 1)).unwrap(), 'b');
+//!
+//! let near_origin = Cuboid::new((0, 0, 0), (4, 4, 4)).unwrap();
+//! let found: Vec<char> = ot.query(near_origin).map(|e| *e.value_ref()).collect();
+//! assert_eq!(found, vec!['a']);
+//!
+//! let nearest = ot.knn(Point3 { x: 15, y: 15, z: 15 }, 1);
+//! assert_eq!(nearest[0].value_ref(), &'b');
+//! ```
+//!
+//! [`Quadtree`]: ../struct.Quadtree.html
+//! [`Octree`]: type.Octree.html
+//! [`Cuboid`]: type.Cuboid.html
+//! [`Area`]: ../area/struct.Area.html
+//! [`ndtree::Tree`]: ../ndtree/struct.Tree.html
+//! [`Point3`]: struct.Point3.html
+
+use {
+    crate::ndtree::{self, Region, Tree},
+    num::PrimInt,
+    std::fmt::Debug,
+};
+
+/// A region octree: the three-dimensional [`ndtree::Tree`].
+///
+/// [`ndtree::Tree`]: ../ndtree/struct.Tree.html
+pub type Octree<U, V> = Tree<U, V, 3>;
+
+/// A box-shaped region in 3d space, the octree's counterpart to an [`Area`].
+///
+/// [`Area`]: ../area/struct.Area.html
+pub type Cuboid<U> = Region<U, 3>;
+
+/// A region/value association in an [`Octree`].
+///
+/// [`Octree`]: type.Octree.html
+pub type Entry<U, V> = ndtree::Entry<U, V, 3>;
+
+/// A point in three-dimensional space. Should be passed by value.
+#[derive(PartialEq, Eq, Clone, Copy, Hash)]
+pub struct Point3<U> {
+    pub x: U, // The x-coordinate of the point.
+    pub y: U, // The y-coordinate of the point.
+    pub z: U, // The z-coordinate of the point.
+}
+
+impl<U> Debug for Point3<U>
+where
+    U: Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{:?}x{:?}x{:?}", self.x, self.y, self.z)
+    }
+}
+
+impl<U> From<(U, U, U)> for Point3<U>
+where
+    U: PrimInt,
+{
+    fn from((x, y, z): (U, U, U)) -> Self {
+        Self { x, y, z }
+    }
+}
+
+impl<U> From<Point3<U>> for (U, U, U)
+where
+    U: PrimInt,
+{
+    fn from(value: Point3<U>) -> Self {
+        (value.x, value.y, value.z)
+    }
+}
+
+impl<U> From<[U; 3]> for Point3<U> {
+    fn from([x, y, z]: [U; 3]) -> Self {
+        Self { x, y, z }
+    }
+}
+
+impl<U> From<Point3<U>> for [U; 3] {
+    fn from(value: Point3<U>) -> Self {
+        [value.x, value.y, value.z]
+    }
+}
+
+impl<U> Point3<U>
+where
+    U: PrimInt,
+{
+    // pub
+
+    /// The x-coordinate of the point.
+    pub fn x(&self) -> U {
+        self.x
+    }
+
+    /// The y-coordinate of the point.
+    pub fn y(&self) -> U {
+        self.y
+    }
+
+    /// The z-coordinate of the point.
+    pub fn z(&self) -> U {
+        self.z
+    }
+}
@@ -0,0 +1,286 @@
+// Copyright 2019 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Parallel iterators over quadtrees, behind the `rayon` feature.
+//!
+//! The iterators over every entry walk the quadtree's store directly rather than its tree of
+//! nodes, so they split as evenly as the store does. [`ParQuery`] instead splits the tree itself.
+//!
+//! ```
+//! use quadtree_rs::Quadtree;
+//! use rayon::prelude::*;
+//!
+//! let mut qt = Quadtree::<u32, f64>::new(8);
+//! qt.extend((0..100).map(|i| ((i, i), i as f64)));
+//!
+//! // Advance every value in parallel...
+//! qt.par_values_mut().for_each(|v| *v *= 2.0);
+//!
+//! // ...and then reduce over all of them.
+//! let total: f64 = qt.par_values().sum();
+//! assert_eq!(total, 9900.0);
+//! assert_eq!((&qt).into_par_iter().count(), 100);
+//! ```
+//!
+//! [`ParQuery`]: struct.ParQuery.html
+
+use {
+    crate::{area::Area, entry::Entry, iter::Query, types::StoreType, Quadtree},
+    num::PrimInt,
+    rayon::{
+        collections::{btree_map, hash_map},
+        iter::{plumbing::UnindexedConsumer, Either, IntoParallelIterator, ParallelIterator},
+        prelude::*,
+    },
+    std::{default::Default, hash::BuildHasher},
+};
+
+type StoreIter<'a, U, V> =
+    Either<hash_map::Iter<'a, u64, Entry<U, V>>, btree_map::Iter<'a, u64, Entry<U, V>>>;
+type StoreIterMut<'a, U, V> =
+    Either<hash_map::IterMut<'a, u64, Entry<U, V>>, btree_map::IterMut<'a, u64, Entry<U, V>>>;
+
+/// A parallel iterator over all regions and values of a [`Quadtree`].
+///
+/// This struct is created by the [`par_iter`] method on [`Quadtree`].
+///
+/// [`par_iter`]: ../struct.Quadtree.html#method.par_iter
+/// [`Quadtree`]: ../struct.Quadtree.html
+pub struct ParIter<'a, U, V>
+where
+    U: PrimInt + Default,
+{
+    inner: StoreIter<'a, U, V>,
+}
+
+/// A parallel iterator over the values held within a [`Quadtree`].
+///
+/// This struct is created by the [`par_values`] method on [`Quadtree`].
+///
+/// [`par_values`]: ../struct.Quadtree.html#method.par_values
+/// [`Quadtree`]: ../struct.Quadtree.html
+pub struct ParValues<'a, U, V>
+where
+    U: PrimInt + Default,
+{
+    inner: StoreIter<'a, U, V>,
+}
+
+/// A mutable parallel iterator over the values held within a [`Quadtree`].
+///
+/// This struct is created by the [`par_values_mut`] method on [`Quadtree`].
+///
+/// [`par_values_mut`]: ../struct.Quadtree.html#method.par_values_mut
+/// [`Quadtree`]: ../struct.Quadtree.html
+pub struct ParValuesMut<'a, U, V>
+where
+    U: PrimInt + Default,
+{
+    inner: StoreIterMut<'a, U, V>,
+}
+
+/// A parallel iterator over the regions and values of a [`Quadtree`] which intersect some region.
+///
+/// This struct is created by the [`par_query`] method on [`Quadtree`].
+///
+/// [`par_query`]: ../struct.Quadtree.html#method.par_query
+/// [`Quadtree`]: ../struct.Quadtree.html
+pub struct ParQuery<'a, U, V, S>
+where
+    U: PrimInt + Default,
+{
+    query: Query<'a, U, V, S>,
+}
+
+impl<U, V, S> Quadtree<U, V, S>
+where
+    U: PrimInt + Default + Send + Sync,
+    V: Send + Sync,
+    S: BuildHasher + Sync,
+{
+    /// Returns a parallel iterator ([`ParIter<U, V>`]) over all [`&'a Entry<U, V>`]
+    /// region/value associations in the Quadtree.
+    ///
+    /// Unlike [`.iter()`], this makes no promises about the order in which entries are visited,
+    /// even for an ordered quadtree.
+    ///
+    /// [`ParIter<U, V>`]: par_iter/struct.ParIter.html
+    /// [`&'a Entry<U, V>`]: entry/struct.Entry.html
+    /// [`.iter()`]: #method.iter
+    pub fn par_iter(&self) -> ParIter<'_, U, V> {
+        ParIter {
+            inner: self.store_par_iter(),
+        }
+    }
+
+    /// Returns a parallel iterator ([`ParValues<U, V>`]) over all `&'a V` values in the
+    /// Quadtree.
+    ///
+    /// [`ParValues<U, V>`]: par_iter/struct.ParValues.html
+    pub fn par_values(&self) -> ParValues<'_, U, V> {
+        ParValues {
+            inner: self.store_par_iter(),
+        }
+    }
+
+    /// Returns a parallel iterator ([`ParValuesMut<U, V>`]) over all `&'a mut V` values in the
+    /// Quadtree. This is the parallel counterpart of [`.modify_all()`].
+    ///
+    /// [`ParValuesMut<U, V>`]: par_iter/struct.ParValuesMut.html
+    /// [`.modify_all()`]: #method.modify_all
+    pub fn par_values_mut(&mut self) -> ParValuesMut<'_, U, V> {
+        ParValuesMut {
+            inner: match &mut self.store {
+                StoreType::Hashed(map) => Either::Left(map.par_iter_mut()),
+                StoreType::Ordered(map, _) => Either::Right(map.par_iter_mut()),
+            },
+        }
+    }
+
+    /// A parallel variant of [`.query()`], returning a [`ParQuery<U, V, S>`] over the same
+    /// entries.
+    ///
+    /// The query descends to the lowest subquadrant which totally contains the region, just as
+    /// [`.query()`] does, and then searches each of that subquadrant's own subquadrants in
+    /// parallel. Results come back in no particular order.
+    /// ```
+    /// use quadtree_rs::{area::AreaBuilder, Quadtree};
+    /// use rayon::prelude::*;
+    ///
+    /// let mut qt = Quadtree::<u32, u32>::new(8);
+    /// qt.extend((0..256).map(|i| ((i, i), i)));
+    ///
+    /// let region = AreaBuilder::default()
+    ///     .anchor((0, 0).into())
+    ///     .dimensions((100, 100))
+    ///     .build().unwrap();
+    /// assert_eq!(qt.par_query(region).map(|e| *e.value_ref()).sum::<u32>(), 4950);
+    /// ```
+    ///
+    /// [`.query()`]: #method.query
+    /// [`ParQuery<U, V, S>`]: par_iter/struct.ParQuery.html
+    pub fn par_query(&self, area: Area<U>) -> ParQuery<'_, U, V, S> {
+        ParQuery {
+            query: self.query(area),
+        }
+    }
+
+    fn store_par_iter(&self) -> StoreIter<'_, U, V> {
+        match &self.store {
+            StoreType::Hashed(map) => Either::Left(map.par_iter()),
+            StoreType::Ordered(map, _) => Either::Right(map.par_iter()),
+        }
+    }
+}
+
+impl<'a, U, V, S> IntoParallelIterator for &'a Quadtree<U, V, S>
+where
+    U: PrimInt + Default + Send + Sync,
+    V: Send + Sync,
+    S: BuildHasher + Sync,
+{
+    type Item = &'a Entry<U, V>;
+    type Iter = ParIter<'a, U, V>;
+
+    fn into_par_iter(self) -> ParIter<'a, U, V> {
+        self.par_iter()
+    }
+}
+
+impl<'a, U, V> ParallelIterator for ParIter<'a, U, V>
+where
+    U: PrimInt + Default + Send + Sync,
+    V: Send + Sync,
+{
+    type Item = &'a Entry<U, V>;
+
+    fn drive_unindexed<C>(self, consumer: C) -> C::Result
+    where
+        C: UnindexedConsumer<Self::Item>,
+    {
+        self.inner.map(|(_, e)| e).drive_unindexed(consumer)
+    }
+}
+
+impl<'a, U, V> ParallelIterator for ParValues<'a, U, V>
+where
+    U: PrimInt + Default + Send + Sync,
+    V: Send + Sync,
+{
+    type Item = &'a V;
+
+    fn drive_unindexed<C>(self, consumer: C) -> C::Result
+    where
+        C: UnindexedConsumer<Self::Item>,
+    {
+        self.inner
+            .map(|(_, e)| e.value_ref())
+            .drive_unindexed(consumer)
+    }
+}
+
+impl<'a, U, V> ParallelIterator for ParValuesMut<'a, U, V>
+where
+    U: PrimInt + Default + Send + Sync,
+    V: Send + Sync,
+{
+    type Item = &'a mut V;
+
+    fn drive_unindexed<C>(self, consumer: C) -> C::Result
+    where
+        C: UnindexedConsumer<Self::Item>,
+    {
+        self.inner
+            .map(|(_, e)| e.value_mut())
+            .drive_unindexed(consumer)
+    }
+}
+
+impl<'a, U, V, S> ParallelIterator for ParQuery<'a, U, V, S>
+where
+    U: PrimInt + Default + Send + Sync,
+    V: Send + Sync,
+    S: BuildHasher + Sync,
+{
+    type Item = &'a Entry<U, V>;
+
+    fn drive_unindexed<C>(self, consumer: C) -> C::Result
+    where
+        C: UnindexedConsumer<Self::Item>,
+    {
+        let Query {
+            query_shape,
+            handle_iter,
+            store,
+            traversal_method,
+            ..
+        } = self.query;
+        let lookup = move |handle: u64| {
+            store
+                .get(&handle)
+                .filter(|entry| traversal_method.eval(entry.area(), query_shape))
+        };
+
+        let (handles, subtrees) = handle_iter.split();
+        handles
+            .into_par_iter()
+            .filter_map(lookup)
+            .chain(
+                subtrees
+                    .into_par_iter()
+                    .flat_map_iter(move |subtree| subtree.filter_map(lookup)),
+            )
+            .drive_unindexed(consumer)
+    }
+}
@@ -27,6 +27,7 @@ pub(crate) type Type<U> = (U, U);
 
 /// A type representing a point in space. Should be passed by value.
 #[derive(PartialEq, Eq, Clone, Copy, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Point<U> {
     pub x: U, // The x-coordinate of the point.
     pub y: U, // The y-coordinate of the point.
@@ -0,0 +1,852 @@
+// Copyright 2019 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A quadtree which holds only points, for particle and agent systems.
+//!
+//! A [`Quadtree`] keeps a region for every entry, and files each one by the cell which contains
+//! it. When every entry is a point, that bookkeeping is pure overhead. A [`PointQuadtree<U, V>`]
+//! keeps just a [`Point`] per entry, packed together with its value in the leaf which contains
+//! it, and splits a leaf only once it holds more than its [bucket size] of points. Leaves whose
+//! points have been deleted or moved away are merged back into their parent, so the tree stays as
+//! shallow as its points allow.
+//!
+//! ```
+//! use quadtree_rs::{area::AreaBuilder, point::Point, point_quadtree::PointQuadtree};
+//!
+//! let mut pqt = PointQuadtree::<u32, &str>::new(8);
+//! let ant = pqt.insert((10, 10).into(), "ant").unwrap();
+//! pqt.insert((12, 9).into(), "bee");
+//! pqt.insert((200, 100).into(), "cat");
+//!
+//! // Everything within 3 cells of the ant, the ant included.
+//! let near: Vec<&str> = pqt
+//!     .query_within_radius(Point { x: 10, y: 10 }, 3)
+//!     .map(|e| *e.value_ref())
+//!     .collect();
+//! assert_eq!(near.len(), 2);
+//!
+//! // Agents move by handle.
+//! assert!(pqt.relocate(ant, (199, 101).into()));
+//! let region = AreaBuilder::default()
+//!     .anchor((192, 96).into())
+//!     .dimensions((16, 16))
+//!     .build()
+//!     .unwrap();
+//! assert_eq!(pqt.query(region).count(), 2);
+//! ```
+//!
+//! [`Quadtree`]: ../struct.Quadtree.html
+//! [`PointQuadtree<U, V>`]: struct.PointQuadtree.html
+//! [`Point`]: ../point/struct.Point.html
+//! [bucket size]: struct.PointQuadtree.html#method.set_bucket_size
+
+use {
+    crate::{
+        area::{Area, AreaBuilder},
+        cursor::Quadrant,
+        point::Point,
+        Quadtree,
+    },
+    num::PrimInt,
+    std::{
+        cmp::Reverse,
+        collections::{BinaryHeap, HashMap},
+        default::Default,
+        iter::FusedIterator,
+    },
+};
+
+// The number of points a leaf may hold before it's split, unless set otherwise.
+const DEFAULT_BUCKET_SIZE: usize = 8;
+
+/// A point/value association in a [`PointQuadtree`]: the counterpart to an [`entry::Entry`].
+///
+/// [`PointQuadtree`]: struct.PointQuadtree.html
+/// [`entry::Entry`]: ../entry/struct.Entry.html
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PointEntry<U, V>
+where
+    U: PrimInt + Default,
+{
+    point: Point<U>,
+    value: V,
+    handle: u64,
+}
+
+impl<U, V> PointEntry<U, V>
+where
+    U: PrimInt + Default,
+{
+    // pub
+
+    /// The returned point.
+    pub fn point(&self) -> Point<U> {
+        self.point
+    }
+
+    /// A mutable accessor to the returned value.
+    pub fn value_mut(&mut self) -> &mut V {
+        &mut self.value
+    }
+
+    /// A reference to the returned value.
+    pub fn value_ref(&self) -> &V {
+        &self.value
+    }
+
+    /// The handle the value was stored under.
+    pub fn handle(&self) -> u64 {
+        self.handle
+    }
+
+    /// Consumes the entry, returning its point and value.
+    pub fn into_parts(self) -> (Point<U>, V) {
+        (self.point, self.value)
+    }
+}
+
+// Nodes live in an arena and refer to one another by their index into it.
+type NodeId = usize;
+
+#[derive(Clone, Debug)]
+struct Node<U, V>
+where
+    U: PrimInt + Default,
+{
+    cell: Area<U>,
+    // The cell has a side of 2^depth, so nodes of depth zero are single cells.
+    depth: usize,
+    // The number of points held by this node and everything beneath it.
+    len: usize,
+    // The points of a leaf, packed together. Always empty above the leaves.
+    bucket: Vec<PointEntry<U, V>>,
+    // The four children are allocated together, in the order [ne, nw, se, sw] as the
+    // subquadrants of a Quadtree are, so only the first is kept.
+    first_child: Option<NodeId>,
+}
+
+impl<U, V> Node<U, V>
+where
+    U: PrimInt + Default,
+{
+    fn new(cell: Area<U>, depth: usize) -> Self {
+        Node {
+            cell,
+            depth,
+            len: 0,
+            bucket: vec![],
+            first_child: None,
+        }
+    }
+
+    fn children(&self) -> impl DoubleEndedIterator<Item = NodeId> {
+        self.first_child.map_or(0..0, |first| first..first + 4)
+    }
+
+    // The index of the child whose cell contains @pt, which has to lie within this node's cell.
+    fn child_at(&self, pt: Point<U>) -> usize {
+        let half = self.cell.width() / (U::one() + U::one());
+        let east = pt.x() >= self.cell.left_edge() + half;
+        let south = pt.y() >= self.cell.top_edge() + half;
+        Quadrant::at(east, south).index()
+    }
+}
+
+// The square cell of a node at @depth.
+fn cell<U>(anchor: Point<U>, depth: usize) -> Area<U>
+where
+    U: PrimInt + Default,
+{
+    #[allow(clippy::cast_possible_truncation)]
+    let width = (U::one() + U::one()).pow(depth as u32);
+    AreaBuilder::default()
+        .anchor(anchor)
+        .dimensions((width, width))
+        .build()
+        .expect("Unexpected error in point_quadtree::cell().")
+}
+
+/// A bucketed point quadtree. See the [module-level documentation].
+///
+/// A point quadtree with depth `n` covers a square with a side of `2^n`, anchored at its
+/// upper-left corner, just as a [`Quadtree`] does. Every entry is a single cell.
+///
+/// [module-level documentation]: index.html
+/// [`Quadtree`]: ../struct.Quadtree.html
+#[derive(Clone, Debug)]
+pub struct PointQuadtree<U, V>
+where
+    U: PrimInt + Default,
+{
+    nodes: Vec<Node<U, V>>,
+    // The first of each block of four nodes left over from a merge, for the next split to reuse.
+    free: Vec<NodeId>,
+    // The point of every entry, by handle, so that it can be found without a search.
+    points: HashMap<u64, Point<U>>,
+    bucket_size: usize,
+    // The handle which the next insertion will be assigned. Handles are sequential and never
+    // re-used.
+    next_handle: u64,
+}
+
+impl<U, V> PointQuadtree<U, V>
+where
+    U: PrimInt + Default,
+{
+    // pub
+
+    /// Creates a new, empty point quadtree with some depth, anchored at the origin.
+    /// ```
+    /// use quadtree_rs::point_quadtree::PointQuadtree;
+    ///
+    /// let pqt = PointQuadtree::<u32, u8>::new(4);
+    /// assert_eq!(pqt.width(), 16);
+    /// assert!(pqt.is_empty());
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `2^depth` isn't representable in `U`.
+    pub fn new(depth: usize) -> Self {
+        Self::new_with_anchor(
+            Point {
+                x: U::zero(),
+                y: U::zero(),
+            },
+            depth,
+        )
+    }
+
+    /// Creates a new, empty point quadtree with some depth and an explicit anchor.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the far edges of the point quadtree, `anchor + 2^depth`, aren't representable in
+    /// `U`.
+    pub fn new_with_anchor(anchor: Point<U>, depth: usize) -> Self {
+        Quadtree::<U, V>::check_root(anchor, depth).unwrap_or_else(|msg| panic!("{}", msg));
+        PointQuadtree {
+            nodes: vec![Node::new(cell(anchor, depth), depth)],
+            free: vec![],
+            points: HashMap::new(),
+            bucket_size: DEFAULT_BUCKET_SIZE,
+            next_handle: 0,
+        }
+    }
+
+    /// Sets the number of points a leaf may hold before it's split into four. The default is
+    /// eight.
+    ///
+    /// Larger buckets make for a shallower tree, with more points to look at in each leaf a query
+    /// reaches. Leaves of a single cell are never split, however many points they hold. Changing
+    /// the bucket size rebuilds the tree, so it's best done before anything is inserted.
+    /// ```
+    /// use quadtree_rs::point_quadtree::PointQuadtree;
+    ///
+    /// let mut pqt = PointQuadtree::<u32, u8>::new(10);
+    /// pqt.set_bucket_size(64);
+    /// assert_eq!(pqt.bucket_size(), 64);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bucket_size` is zero.
+    pub fn set_bucket_size(&mut self, bucket_size: usize) {
+        assert!(bucket_size > 0, "A leaf must be able to hold some points.");
+        self.bucket_size = bucket_size;
+        let entries: Vec<PointEntry<U, V>> = self.drain_nodes().collect();
+        for entry in entries {
+            self.insert_entry(entry);
+        }
+    }
+
+    /// The number of points a leaf may hold before it's split. See [`.set_bucket_size()`].
+    ///
+    /// [`.set_bucket_size()`]: #method.set_bucket_size
+    pub fn bucket_size(&self) -> usize {
+        self.bucket_size
+    }
+
+    /// The top-left corner of the region covered by the point quadtree.
+    pub fn anchor(&self) -> Point<U> {
+        self.root().cell.anchor()
+    }
+
+    /// The width of the region covered by the point quadtree.
+    pub fn width(&self) -> usize {
+        self.root()
+            .cell
+            .width()
+            .to_usize()
+            .expect("The point quadtree's width should fit in a usize.")
+    }
+
+    /// The height of the region covered by the point quadtree.
+    pub fn height(&self) -> usize {
+        self.width()
+    }
+
+    /// The depth of the point quadtree.
+    pub fn depth(&self) -> usize {
+        self.root().depth
+    }
+
+    /// The number of elements in the point quadtree.
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    /// Whether or not the point quadtree is empty.
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    /// Whether or not some point lies within the region which this point quadtree represents.
+    pub fn contains(&self, pt: Point<U>) -> bool {
+        self.root().cell.contains_pt(pt)
+    }
+
+    /// Associate some value with a point in the point quadtree.
+    ///
+    /// If insertion is successful, returns a unique handle to the value. If the point lies
+    /// outside the point quadtree, returns `None`.
+    pub fn insert(&mut self, point: Point<U>, val: V) -> Option<u64> {
+        if !self.contains(point) {
+            return None;
+        }
+        let handle = self.next_handle;
+        self.next_handle += 1;
+        self.insert_entry(PointEntry {
+            point,
+            value: val,
+            handle,
+        });
+        Some(handle)
+    }
+
+    /// Given a handle, retrieves the corresponding entry, if any.
+    pub fn get(&self, handle: u64) -> Option<&PointEntry<U, V>> {
+        let leaf = self.leaf_at(*self.points.get(&handle)?);
+        self.nodes[leaf].bucket.iter().find(|e| e.handle == handle)
+    }
+
+    /// A mutable variant of [`.get()`].
+    ///
+    /// [`.get()`]: #method.get
+    pub fn get_mut(&mut self, handle: u64) -> Option<&mut PointEntry<U, V>> {
+        let leaf = self.leaf_at(*self.points.get(&handle)?);
+        self.nodes[leaf]
+            .bucket
+            .iter_mut()
+            .find(|e| e.handle == handle)
+    }
+
+    /// Returns an iterator over every [`&PointEntry<U, V>`] whose point lies within `area`.
+    ///
+    /// Leaves which `area` wholly covers are taken whole, without looking at their points.
+    ///
+    /// [`&PointEntry<U, V>`]: struct.PointEntry.html
+    pub fn query(&self, area: Area<U>) -> Query<'_, U, V> {
+        Query::new(self, Within::Area(area))
+    }
+
+    /// Returns an iterator over every [`&PointEntry<U, V>`] within `radius` (euclidean) of
+    /// `center`, as with [`Quadtree::query_within_radius()`]. A negative radius covers nothing.
+    ///
+    /// [`&PointEntry<U, V>`]: struct.PointEntry.html
+    /// [`Quadtree::query_within_radius()`]: ../struct.Quadtree.html#method.query_within_radius
+    pub fn query_within_radius(&self, center: Point<U>, radius: U) -> Query<'_, U, V> {
+        match radius.to_u128() {
+            Some(r) => Query::new(self, Within::Disc(center, r.saturating_mul(r))),
+            None => Query::empty(self),
+        }
+    }
+
+    /// Returns the (up to) `k` [`&PointEntry<U, V>`]s whose points lie closest to `pt`, nearest
+    /// first. Ties are broken by handle.
+    /// ```
+    /// use quadtree_rs::point_quadtree::PointQuadtree;
+    ///
+    /// let mut pqt = PointQuadtree::<u32, char>::new(4);
+    /// pqt.insert((1, 1).into(), 'a');
+    /// pqt.insert((9, 9).into(), 'b');
+    /// pqt.insert((6, 7).into(), 'c');
+    ///
+    /// let nearest: Vec<char> = pqt.knn((8, 7).into(), 2)
+    ///     .iter()
+    ///     .map(|e| *e.value_ref())
+    ///     .collect();
+    /// assert_eq!(nearest, vec!['c', 'b']);
+    /// ```
+    ///
+    /// [`&PointEntry<U, V>`]: struct.PointEntry.html
+    pub fn knn(&self, pt: Point<U>, k: usize) -> Vec<&PointEntry<U, V>> {
+        // A best-first search. Nodes are keyed by the distance to their cell, a lower bound on
+        // that of any point in them; points by their exact distance. Nodes sort before points on
+        // ties, so once a point reaches the front of the queue nothing left can be any closer,
+        // and every point as close has been queued, to be taken in order of handle.
+        const NODE: u8 = 0;
+        const ENTRY: u8 = 1;
+        let mut heap = BinaryHeap::new();
+        heap.push(Reverse((
+            self.root().cell.distance_sq_to_pt(pt),
+            NODE,
+            0,
+            0,
+            0,
+        )));
+
+        let mut nearest = vec![];
+        while nearest.len() < k {
+            let (kind, id, i) = match heap.pop() {
+                Some(Reverse((_, kind, _, id, i))) => (kind, id, i),
+                None => break,
+            };
+            let node = &self.nodes[id];
+            if kind == ENTRY {
+                nearest.push(&node.bucket[i]);
+                continue;
+            }
+            for (i, entry) in node.bucket.iter().enumerate() {
+                let distance_sq = cell(entry.point, 0).distance_sq_to_pt(pt);
+                heap.push(Reverse((distance_sq, ENTRY, entry.handle, id, i)));
+            }
+            for child in node.children().filter(|&child| self.nodes[child].len > 0) {
+                let distance_sq = self.nodes[child].cell.distance_sq_to_pt(pt);
+                heap.push(Reverse((distance_sq, NODE, child as u64, child, 0)));
+            }
+        }
+        nearest
+    }
+
+    /// Moves the entry with some handle to a new point, keeping its handle and value. Returns
+    /// whether it was moved: nothing happens if there's no such entry, or if `to` lies outside
+    /// the point quadtree.
+    ///
+    /// A point which stays within its leaf is moved in place.
+    pub fn relocate(&mut self, handle: u64, to: Point<U>) -> bool {
+        let from = match self.points.get(&handle) {
+            Some(&from) if self.contains(to) => from,
+            _ => return false,
+        };
+        let leaf = self.leaf_at(from);
+        if self.nodes[leaf].cell.contains_pt(to) {
+            if let Some(entry) = self.nodes[leaf]
+                .bucket
+                .iter_mut()
+                .find(|e| e.handle == handle)
+            {
+                entry.point = to;
+            }
+            self.points.insert(handle, to);
+            return true;
+        }
+        if let Some(mut entry) = self.delete_by_handle(handle) {
+            entry.point = to;
+            self.insert_entry(entry);
+        }
+        true
+    }
+
+    /// Deletes every entry whose point lies within `area`, returning them.
+    pub fn delete(&mut self, area: Area<U>) -> IntoIter<U, V> {
+        let handles: Vec<u64> = self.query(area).map(PointEntry::handle).collect();
+        let entries: Vec<PointEntry<U, V>> = handles
+            .into_iter()
+            .filter_map(|handle| self.delete_by_handle(handle))
+            .collect();
+        IntoIter {
+            entries: entries.into_iter(),
+        }
+    }
+
+    /// Given a handle, deletes a single item from the point quadtree, returning its former entry,
+    /// or `None` if there was none.
+    ///
+    /// If that leaves a node with no more points beneath it than fit in a bucket, everything
+    /// beneath it is merged back into it.
+    pub fn delete_by_handle(&mut self, handle: u64) -> Option<PointEntry<U, V>> {
+        let pt = self.points.remove(&handle)?;
+        let mut id = 0;
+        // The highest node on the way down which is now small enough to be a leaf.
+        let mut mergeable = None;
+        loop {
+            let node = &mut self.nodes[id];
+            node.len -= 1;
+            let first = match node.first_child {
+                Some(first) => first,
+                None => break,
+            };
+            if mergeable.is_none() && node.len <= self.bucket_size {
+                mergeable = Some(id);
+            }
+            id = first + node.child_at(pt);
+        }
+
+        let bucket = &mut self.nodes[id].bucket;
+        let i = bucket
+            .iter()
+            .position(|e| e.handle == handle)
+            .expect("Every handle in the point quadtree should be in the leaf at its point.");
+        let entry = bucket.swap_remove(i);
+        if let Some(id) = mergeable {
+            self.merge(id);
+        }
+        Some(entry)
+    }
+
+    /// Given a handle, removes a single item from the point quadtree and returns its former point
+    /// and value by value. Otherwise, returns `None`.
+    pub fn remove(&mut self, handle: u64) -> Option<(Point<U>, V)> {
+        self.delete_by_handle(handle).map(PointEntry::into_parts)
+    }
+
+    /// Resets the point quadtree to a totally empty state. Handles aren't reused afterwards.
+    pub fn reset(&mut self) {
+        self.points.clear();
+        self.free.clear();
+        self.nodes.truncate(1);
+        let root = &mut self.nodes[0];
+        root.bucket.clear();
+        root.len = 0;
+        root.first_child = None;
+    }
+
+    /// Returns an iterator over every [`&PointEntry<U, V>`] in the point quadtree, a leaf at a
+    /// time.
+    ///
+    /// [`&PointEntry<U, V>`]: struct.PointEntry.html
+    pub fn iter(&self) -> Iter<'_, U, V> {
+        Iter {
+            nodes: self.nodes.iter(),
+            entries: [].iter(),
+            remaining: self.len(),
+        }
+    }
+
+    // fn
+
+    fn root(&self) -> &Node<U, V> {
+        &self.nodes[0]
+    }
+
+    // The leaf whose cell contains @pt, which has to lie within the point quadtree.
+    fn leaf_at(&self, pt: Point<U>) -> NodeId {
+        let mut id = 0;
+        while let Some(first) = self.nodes[id].first_child {
+            id = first + self.nodes[id].child_at(pt);
+        }
+        id
+    }
+
+    // Adds @entry, whose point lies within the point quadtree, to the leaf containing it, and
+    // splits the leaf if that overfills it.
+    fn insert_entry(&mut self, entry: PointEntry<U, V>) {
+        self.points.insert(entry.handle, entry.point);
+        let mut id = 0;
+        loop {
+            let node = &mut self.nodes[id];
+            node.len += 1;
+            match node.first_child {
+                Some(first) => id = first + node.child_at(entry.point),
+                None => break,
+            }
+        }
+        self.nodes[id].bucket.push(entry);
+        self.split_if_full(id);
+    }
+
+    // Splits the leaf @id into four if it holds more than a bucket's worth of points, and so on
+    // down, for as long as they all land in the same child.
+    fn split_if_full(&mut self, id: NodeId) {
+        let node = &self.nodes[id];
+        if node.bucket.len() <= self.bucket_size || node.depth == 0 {
+            return;
+        }
+        let children: Vec<Node<U, V>> = node
+            .cell
+            .quarters()
+            .iter()
+            .map(|&quarter| Node::new(quarter, node.depth - 1))
+            .collect();
+        let first = match self.free.pop() {
+            Some(first) => {
+                for (slot, child) in self.nodes[first..first + 4].iter_mut().zip(children) {
+                    *slot = child;
+                }
+                first
+            }
+            None => {
+                self.nodes.extend(children);
+                self.nodes.len() - 4
+            }
+        };
+
+        let node = &mut self.nodes[id];
+        node.first_child = Some(first);
+        let bucket = std::mem::take(&mut node.bucket);
+        for entry in bucket {
+            let child = first + self.nodes[id].child_at(entry.point);
+            let child = &mut self.nodes[child];
+            child.len += 1;
+            child.bucket.push(entry);
+        }
+        for child in first..first + 4 {
+            self.split_if_full(child);
+        }
+    }
+
+    // Gathers every point beneath @id into it, making it a leaf, and frees the nodes below.
+    fn merge(&mut self, id: NodeId) {
+        let mut bucket = vec![];
+        let mut stack: Vec<NodeId> = self.nodes[id].children().collect();
+        if let Some(first) = self.nodes[id].first_child.take() {
+            self.free.push(first);
+        }
+        while let Some(id) = stack.pop() {
+            let node = &mut self.nodes[id];
+            bucket.append(&mut node.bucket);
+            stack.extend(node.children());
+            if let Some(first) = node.first_child.take() {
+                self.free.push(first);
+            }
+        }
+        self.nodes[id].bucket = bucket;
+    }
+
+    // Empties the point quadtree, without forgetting its handles, and returns what it held.
+    fn drain_nodes(&mut self) -> impl Iterator<Item = PointEntry<U, V>> {
+        let nodes = std::mem::take(&mut self.nodes);
+        let root = &nodes[0];
+        self.nodes.push(Node::new(root.cell, root.depth));
+        self.free.clear();
+        self.points.clear();
+        nodes.into_iter().flat_map(|node| node.bucket)
+    }
+}
+
+impl<'a, U, V> IntoIterator for &'a PointQuadtree<U, V>
+where
+    U: PrimInt + Default,
+{
+    type Item = &'a PointEntry<U, V>;
+    type IntoIter = Iter<'a, U, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<U, V> IntoIterator for PointQuadtree<U, V>
+where
+    U: PrimInt + Default,
+{
+    type Item = PointEntry<U, V>;
+    type IntoIter = IntoIter<U, V>;
+
+    fn into_iter(mut self) -> Self::IntoIter {
+        let entries: Vec<PointEntry<U, V>> = self.drain_nodes().collect();
+        IntoIter {
+            entries: entries.into_iter(),
+        }
+    }
+}
+
+/// An iterator over all entries of a [`PointQuadtree`]. See [`PointQuadtree::iter()`].
+///
+/// [`PointQuadtree`]: struct.PointQuadtree.html
+/// [`PointQuadtree::iter()`]: struct.PointQuadtree.html#method.iter
+#[derive(Clone, Debug)]
+pub struct Iter<'a, U, V>
+where
+    U: PrimInt + Default,
+{
+    nodes: std::slice::Iter<'a, Node<U, V>>,
+    entries: std::slice::Iter<'a, PointEntry<U, V>>,
+    remaining: usize,
+}
+
+impl<'a, U, V> Iterator for Iter<'a, U, V>
+where
+    U: PrimInt + Default,
+{
+    type Item = &'a PointEntry<U, V>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(entry) = self.entries.next() {
+                self.remaining -= 1;
+                return Some(entry);
+            }
+            // Only leaves hold points, and the nodes freed by a merge are left empty.
+            self.entries = self.nodes.next()?.bucket.iter();
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<U, V> ExactSizeIterator for Iter<'_, U, V> where U: PrimInt + Default {}
+
+impl<U, V> FusedIterator for Iter<'_, U, V> where U: PrimInt + Default {}
+
+/// A consuming iterator over entries removed from a [`PointQuadtree`]. See
+/// [`PointQuadtree::delete()`].
+///
+/// [`PointQuadtree`]: struct.PointQuadtree.html
+/// [`PointQuadtree::delete()`]: struct.PointQuadtree.html#method.delete
+#[derive(Clone, Debug)]
+pub struct IntoIter<U, V>
+where
+    U: PrimInt + Default,
+{
+    entries: std::vec::IntoIter<PointEntry<U, V>>,
+}
+
+impl<U, V> Iterator for IntoIter<U, V>
+where
+    U: PrimInt + Default,
+{
+    type Item = PointEntry<U, V>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.entries.next()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.entries.size_hint()
+    }
+}
+
+impl<U, V> ExactSizeIterator for IntoIter<U, V> where U: PrimInt + Default {}
+
+impl<U, V> FusedIterator for IntoIter<U, V> where U: PrimInt + Default {}
+
+// The part of the plane a query covers.
+#[derive(Clone, Copy, Debug)]
+enum Within<U>
+where
+    U: PrimInt + Default,
+{
+    Area(Area<U>),
+    // Every cell within some squared distance of a point.
+    Disc(Point<U>, u128),
+}
+
+impl<U> Within<U>
+where
+    U: PrimInt + Default,
+{
+    fn reaches(self, cell: Area<U>) -> bool {
+        match self {
+            Within::Area(area) => area.intersects(cell),
+            Within::Disc(center, r_sq) => cell.distance_sq_to_pt(center) <= r_sq,
+        }
+    }
+
+    fn covers(self, cell: Area<U>) -> bool {
+        match self {
+            Within::Area(area) => area.contains(cell),
+            Within::Disc(center, r_sq) => cell.farthest_distance_sq_to_pt(center) <= r_sq,
+        }
+    }
+
+    fn contains(self, pt: Point<U>) -> bool {
+        match self {
+            Within::Area(area) => area.contains_pt(pt),
+            Within::Disc(center, r_sq) => cell(pt, 0).distance_sq_to_pt(center) <= r_sq,
+        }
+    }
+}
+
+/// An iterator over the entries of a [`PointQuadtree`] within some region. See
+/// [`PointQuadtree::query()`].
+///
+/// [`PointQuadtree`]: struct.PointQuadtree.html
+/// [`PointQuadtree::query()`]: struct.PointQuadtree.html#method.query
+#[derive(Clone, Debug)]
+pub struct Query<'a, U, V>
+where
+    U: PrimInt + Default,
+{
+    pqt: &'a PointQuadtree<U, V>,
+    within: Within<U>,
+    // The nodes which the query reaches, but whose points haven't been looked at yet.
+    stack: Vec<NodeId>,
+    entries: std::slice::Iter<'a, PointEntry<U, V>>,
+    // Whether the query covers the whole of the leaf which @entries comes from.
+    covered: bool,
+}
+
+impl<'a, U, V> Query<'a, U, V>
+where
+    U: PrimInt + Default,
+{
+    fn new(pqt: &'a PointQuadtree<U, V>, within: Within<U>) -> Self {
+        let stack = if within.reaches(pqt.root().cell) {
+            vec![0]
+        } else {
+            vec![]
+        };
+        Query {
+            pqt,
+            within,
+            stack,
+            entries: [].iter(),
+            covered: false,
+        }
+    }
+
+    fn empty(pqt: &'a PointQuadtree<U, V>) -> Self {
+        Query {
+            stack: vec![],
+            ..Self::new(pqt, Within::Disc(pqt.anchor(), 0))
+        }
+    }
+}
+
+impl<'a, U, V> Iterator for Query<'a, U, V>
+where
+    U: PrimInt + Default,
+{
+    type Item = &'a PointEntry<U, V>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (pqt, within) = (self.pqt, self.within);
+        loop {
+            let covered = self.covered;
+            if let Some(entry) = self
+                .entries
+                .by_ref()
+                .find(|entry| covered || within.contains(entry.point))
+            {
+                return Some(entry);
+            }
+            let node = &pqt.nodes[self.stack.pop()?];
+            self.stack.extend(node.children().rev().filter(|&child| {
+                let child = &pqt.nodes[child];
+                child.len > 0 && within.reaches(child.cell)
+            }));
+            self.entries = node.bucket.iter();
+            self.covered = within.covers(node.cell);
+        }
+    }
+}
+
+impl<U, V> FusedIterator for Query<'_, U, V> where U: PrimInt + Default {}
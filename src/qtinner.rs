@@ -15,16 +15,68 @@
 use {
     crate::{
         area::{Area, AreaBuilder},
-        entry::Entry,
+        cursor::Quadrant,
         point::Point,
-        types::StoreType,
+        split::{DepthLimited, SplitPolicy},
     },
     num::PrimInt,
-    std::{default::Default, fmt::Debug},
+    smallvec::SmallVec,
+    std::{default::Default, fmt::Debug, sync::Arc},
 };
 
-#[derive(Clone, PartialEq, Eq)]
+// The index of a node within its tree's arena.
+pub(crate) type NodeId = usize;
+
+// Most nodes hold no more than a couple of handles, so they are kept inline rather than in a
+// separate allocation. Two u64s fit in the same space as an empty Vec.
+type Handles = SmallVec<[u64; 2]>;
+
+// The tree of cells which make up a quadtree. Every node lives in a single arena, @nodes, and
+// refers to its children by index. The root is always at index 0.
+#[derive(Clone)]
 pub(crate) struct QTInner<U>
+where
+    U: PrimInt + Default,
+{
+    nodes: Vec<Node<U>>,
+    layout: Layout,
+}
+
+// How a tree arranges its handles among its cells. This never changes over the life of a tree;
+// changing it means building a new tree.
+#[derive(Clone, Debug)]
+pub(crate) struct Layout {
+    // Decides whether a leaf is subdivided when a handle which would fit further down arrives.
+    // Shared, since cloning a tree shouldn't require cloning it.
+    pub split_policy: Arc<dyn SplitPolicy>,
+
+    // How many times wider and taller than its cell the bounds of every node below the root are.
+    // At 1.0 the tree is tight, and the bounds are just the cell.
+    pub looseness: f64,
+}
+
+impl Default for Layout {
+    fn default() -> Self {
+        Self {
+            split_policy: Arc::new(DepthLimited),
+            looseness: 1.0,
+        }
+    }
+}
+
+impl Layout {
+    // Whether this and @other arrange their handles in the same way. Split policies can't be
+    // compared directly, so they're taken to be the same if they're shared or print the same, as
+    // the built-in policies which are equal do.
+    pub fn same_as(&self, other: &Layout) -> bool {
+        self.looseness == other.looseness
+            && (Arc::ptr_eq(&self.split_policy, &other.split_policy)
+                || format!("{:?}", self.split_policy) == format!("{:?}", other.split_policy))
+    }
+}
+
+#[derive(Clone)]
+pub(crate) struct Node<U>
 where
     U: PrimInt + Default,
 {
@@ -34,16 +86,22 @@ where
     // The region  of the current cell.
     region: Area<U>,
 
-    // The regions held at this level in the tree. (NB: That doesn't mean each value in `values`
-    // is at self.region).
-    kept_handles: Vec<u64>,
+    // The region which everything held by this cell and beneath it lies within. In a loose tree
+    // this is the cell inflated by the looseness (and clipped to the root); otherwise it's the
+    // cell itself.
+    bounds: Area<U>,
 
-    // The subquadrants under this cell. [ne, nw, se, sw]. If there are no subquadrants, this
-    // entire list could be None.
-    subquadrants: Option<[Box<QTInner<U>>; 4]>,
+    // The regions held at this level in the tree. Every handle is held by exactly one node, which
+    // totally contains its region. A cell with subquadrants only holds regions which no single one
+    // of them totally contains, but a leaf may hold anything which fits in it.
+    kept_handles: Handles,
 
-    // The last-inserted handle. This is a monotonically increasing counter.
-    handle_counter: u64,
+    // The number of handles held by this cell and everything beneath it.
+    len: usize,
+
+    // The index of the first of the four subquadrants under this cell, which are always allocated
+    // together in the order [ne, nw, se, sw]. If there are no subquadrants, this is None.
+    subquadrants: Option<NodeId>,
 }
 
 impl<U> Debug for QTInner<U>
@@ -51,172 +109,889 @@ where
     U: PrimInt + Default + Debug,
 {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        if self.subquadrants.is_some() {
+        NodeDebug {
+            tree: self,
+            id: Self::ROOT,
+        }
+        .fmt(f)
+    }
+}
+
+// Formats the subtree rooted at @id the way a tree of boxed nodes would be.
+struct NodeDebug<'a, U>
+where
+    U: PrimInt + Default,
+{
+    tree: &'a QTInner<U>,
+    id: NodeId,
+}
+
+impl<U> Debug for NodeDebug<'_, U>
+where
+    U: PrimInt + Default + Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let node = self.tree.node(self.id);
+        if let Some(subquadrants) = node.subquadrants() {
+            let subquadrants = subquadrants.map(|id| NodeDebug {
+                tree: self.tree,
+                id,
+            });
             write!(
                 f,
                 "{:?} :: {:?} {:#?}",
-                self.region,
-                self.kept_handles,
-                self.subquadrants.as_ref().unwrap()
+                node.region, node.kept_handles, subquadrants
             )
         } else {
-            write!(f, "{:?} :: {:?}", self.region, self.kept_handles,)
+            write!(f, "{:?} :: {:?}", node.region, node.kept_handles,)
+        }
+    }
+}
+
+// Two trees are equal if they have the same shape and hold the same handles in the same places,
+// regardless of the order in which their nodes were allocated.
+impl<U> PartialEq for QTInner<U>
+where
+    U: PrimInt + Default,
+{
+    fn eq(&self, other: &Self) -> bool {
+        let mut stack = vec![(Self::ROOT, Self::ROOT)];
+        while let Some((a, b)) = stack.pop() {
+            let (a, b) = (self.node(a), other.node(b));
+            if a.depth != b.depth || a.region != b.region || a.kept_handles != b.kept_handles {
+                return false;
+            }
+            match (a.subquadrants(), b.subquadrants()) {
+                (None, None) => (),
+                (Some(sqs_a), Some(sqs_b)) => stack.extend(sqs_a.iter().copied().zip(sqs_b)),
+                _ => return false,
+            }
         }
+        true
     }
 }
 
+impl<U> Eq for QTInner<U> where U: PrimInt + Default {}
+
 impl<U> QTInner<U>
 where
     U: PrimInt + Default,
 {
+    pub const ROOT: NodeId = 0;
+
     // pub
 
-    pub fn new(anchor: Point<U>, depth: usize) -> Self {
-        #[allow(clippy::cast_possible_truncation)]
-        let width: U = Self::two().pow(depth as u32);
-        let height: U = width;
-        Self::new_with_area(
-            AreaBuilder::default()
-                .anchor(anchor)
-                .dimensions((width, height))
-                .build()
-                .expect("Unexpected error in QTInner::new()."),
-            depth,
-        )
+    pub fn new(anchor: Point<U>, depth: usize, layout: Layout) -> Self {
+        let region = Node::cell(anchor, depth);
+        Self {
+            nodes: vec![Node::new(region, region, depth)],
+            layout,
+        }
+    }
+
+    // Builds a tight, depth-limited tree holding every one of @items, which all have to fit within
+    // it, in one pass.
+    //
+    // Each handle belongs to the lowest cell which totally contains its region, and that cell can
+    // be worked out directly from the bits of the region's corners. Sorting the handles by the
+    // Morton code of their cell (ancestors before descendants) lays them out in the order of a
+    // depth-first walk of the tree, so every subtree's handles are contiguous and each node can
+    // be built exactly once, from its slice. Trees too deep for a Morton code to fit in a u128
+    // are built by ordinary insertion instead.
+    pub fn bulk_load(anchor: Point<U>, depth: usize, items: &[(u64, Area<U>)]) -> Self {
+        let mut qt = Self::new(anchor, depth, Layout::default());
+        if depth > 64 {
+            for &(handle, region) in items {
+                qt.insert_below(Self::ROOT, region, handle, &|_| None);
+            }
+            return qt;
+        }
+
+        let mut keyed: Vec<(u128, usize, u64)> = items
+            .iter()
+            .map(|&(handle, region)| {
+                let (x0, y0) = Self::offset(anchor, region.anchor());
+                let (x1, y1) = (
+                    x0 + (region.width() - U::one()).to_u64().unwrap(),
+                    y0 + (region.height() - U::one()).to_u64().unwrap(),
+                );
+                // The cell is as large as the highest bit in which the corners differ.
+                let shift = 64 - ((x0 ^ x1) | (y0 ^ y1)).leading_zeros();
+                let mask = u64::MAX.checked_shl(shift).unwrap_or(0);
+                (
+                    Self::morton(x0 & mask, y0 & mask),
+                    depth - shift as usize,
+                    handle,
+                )
+            })
+            .collect();
+        keyed.sort_unstable();
+        qt.build_from_sorted(Self::ROOT, 0, &keyed);
+        qt
+    }
+
+    pub fn layout(&self) -> &Layout {
+        &self.layout
+    }
+
+    // Whether the bounds of a node can reach beyond its cell.
+    pub fn is_loose(&self) -> bool {
+        self.layout.looseness > 1.0
     }
 
     pub fn depth(&self) -> usize {
-        self.depth
+        self.root().depth()
     }
 
     pub fn region(&self) -> Area<U> {
-        self.region
+        self.root().region()
     }
 
-    pub fn handles(&self) -> &Vec<u64> {
-        &self.kept_handles
+    pub fn root(&self) -> &Node<U> {
+        self.node(Self::ROOT)
+    }
+
+    pub fn node(&self, id: NodeId) -> &Node<U> {
+        &self.nodes[id]
+    }
+
+    // The number of nodes in the arena. Every id below this is a node.
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
     }
 
-    pub fn subquadrants(&self) -> &Option<[Box<Self>; 4]> {
-        &self.subquadrants
+    // The nodes which a handle with the region @req could be held by, from the root down. The
+    // node which does hold it, if any, is the last.
+    pub fn path_to(&self, req: Area<U>) -> Vec<NodeId> {
+        let mut path = vec![Self::ROOT];
+        let mut id = Self::ROOT;
+        while let (Some(sqs), Some(quadrant)) = (
+            self.nodes[id].subquadrants(),
+            self.quadrant_containing(id, req),
+        ) {
+            id = sqs[quadrant];
+            path.push(id);
+        }
+        path
     }
 
     // Resets this quadtree.
     pub fn reset(&mut self) {
-        self.kept_handles.clear();
-        self.subquadrants = None;
+        self.nodes.truncate(1);
+        let root = &mut self.nodes[Self::ROOT];
+        root.kept_handles.clear();
+        root.len = 0;
+        root.subquadrants = None;
     }
 
-    // Attempts to insert the value at the requested region. Returns false if the region was too
-    // large.
-    pub fn insert_val_at_region<V>(
-        &mut self,
-        req: Area<U>,
-        val: V,
-        store: &mut StoreType<U, V>,
-    ) -> u64 {
-        let handle = self.handle_counter;
-        self.handle_counter += 1;
-        store.insert(handle, Entry::new((req, val), handle));
-        self.insert_handle_at_region(req, handle, store);
-        handle
-    }
-
-    // Delete all instances of @handle from this level's @kept_handles.
+    // Drops every subquadrant with nothing beneath it, then releases the arena's spare memory.
+    pub fn shrink_to_fit(&mut self) {
+        self.recount_and_prune();
+        self.nodes.shrink_to_fit();
+        for node in &mut self.nodes {
+            node.kept_handles.shrink_to_fit();
+        }
+    }
+
+    // Delete @handle from the node which holds it.
     pub fn delete_by_handle(&mut self, handle: u64, req: Area<U>) {
-        self.kept_handles.retain(|&x| x != handle);
-        // And potentially recurse into the subquadrants...
-        if let Some(sqs) = self.subquadrants.as_mut() {
-            for sq in sqs.iter_mut() {
-                // ...but not all of them.
-                if sq.region.intersects(req) {
-                    sq.delete_by_handle(handle, req);
+        self.delete_below(Self::ROOT, handle, req);
+    }
+
+    // Moves @handle from the node which holds it, given its old region @from, to wherever its new
+    // region @to belongs. The caller is responsible for updating the store first.
+    //
+    // Only the nodes below the point where the paths to @from and @to part ways are touched:
+    // above that, the same cells hold the handle before and after.
+    pub fn relocate<F>(&mut self, handle: u64, from: Area<U>, to: Area<U>, region_of: &F)
+    where
+        F: Fn(u64) -> Option<Area<U>>,
+    {
+        let mut id = Self::ROOT;
+        while !self.nodes[id].kept_handles.contains(&handle) {
+            match (
+                self.nodes[id].subquadrants(),
+                self.quadrant_containing(id, from),
+                self.quadrant_containing(id, to),
+            ) {
+                (Some(sqs), Some(a), Some(b)) if a == b => id = sqs[a],
+                _ => break,
+            }
+        }
+        self.delete_below(id, handle, from);
+        self.insert_below(id, to, handle, region_of);
+    }
+
+    // Moves many handles at once. Each is given with its old region; its new region is looked up
+    // with @region_of, so the caller is responsible for updating the store first. A handle listed
+    // more than once is only moved once.
+    //
+    // Rather than keep the counts up to date one move at a time, this fixes them all up in a single
+    // pass at the end, pruning away any subtrees which were left empty and compacting the arena.
+    pub fn relocate_many<F>(&mut self, moves: &[(u64, Area<U>)], region_of: &F)
+    where
+        F: Fn(u64) -> Option<Area<U>>,
+    {
+        let detached: Vec<u64> = moves
+            .iter()
+            .filter(|&&(handle, from)| self.detach(handle, from))
+            .map(|&(handle, _)| handle)
+            .collect();
+        for handle in detached {
+            let to = region_of(handle).expect("Every handle in the tree should have a region.");
+            self.insert_below(Self::ROOT, to, handle, region_of);
+        }
+        self.recount_and_prune();
+    }
+
+    // Registers @handle in the lowest node whose bounds totally contain its region, or in a leaf
+    // above that which the split policy doesn't want subdivided. The caller is responsible for
+    // putting the corresponding entry in the store first, since subdividing a full leaf looks up
+    // the regions it holds with @region_of.
+    pub fn insert_handle_at_region<F>(&mut self, req: Area<U>, handle: u64, region_of: &F)
+    where
+        F: Fn(u64) -> Option<Area<U>>,
+    {
+        self.insert_below(Self::ROOT, req, handle, region_of);
+    }
+
+    // Removes every handle whose region intersects @req and returns them, then collapses the
+    // subtrees which were left empty. Everything beneath a cell which @req totally contains is
+    // cut away wholesale, without looking up its regions with @region_of.
+    pub fn remove_intersecting<F>(&mut self, req: Area<U>, region_of: &F) -> Vec<u64>
+    where
+        F: Fn(u64) -> Option<Area<U>>,
+    {
+        let mut removed = Vec::new();
+        let mut stack = vec![Self::ROOT];
+        while let Some(id) = stack.pop() {
+            let node = &mut self.nodes[id];
+            if node.len == 0 || !node.bounds.intersects(req) {
+                continue;
+            }
+            if req.contains(node.bounds) {
+                let mut below = vec![id];
+                while let Some(id) = below.pop() {
+                    let node = &mut self.nodes[id];
+                    removed.extend(node.kept_handles.drain(..));
+                    below.extend(node.subquadrants().iter().flatten());
                 }
+                self.nodes[id].subquadrants = None;
+                continue;
             }
+            node.kept_handles.retain(|handle| {
+                let hit = region_of(*handle).is_some_and(|region| region.intersects(req));
+                if hit {
+                    removed.push(*handle);
+                }
+                !hit
+            });
+            stack.extend(node.subquadrants().iter().flatten());
         }
+        if !removed.is_empty() {
+            self.recount_and_prune();
+        }
+        removed
+    }
+
+    // Counts the handles whose regions intersect @req, looking up a region with @region_of only
+    // where it can't be avoided. Everything beneath a cell which @req totally contains is counted
+    // without looking at it.
+    pub fn count_intersecting<F>(&self, req: Area<U>, region_of: &F) -> usize
+    where
+        F: Fn(u64) -> Option<Area<U>>,
+    {
+        self.count_intersecting_below(Self::ROOT, req, region_of)
+    }
+
+    // Searches the nodes whose bounds totally contain @req, from the root down, and returns the
+    // first handle held by one of them which satisfies @pred. Any region which contains @req is
+    // held by one of these nodes. In a tight tree, they form a single path.
+    pub fn find_containing<F>(&self, req: Area<U>, mut pred: F) -> Option<u64>
+    where
+        F: FnMut(u64) -> bool,
+    {
+        let mut stack = vec![Self::ROOT];
+        while let Some(id) = stack.pop() {
+            let node = &self.nodes[id];
+            if !node.bounds.contains(req) || node.len == 0 {
+                continue;
+            }
+            if let Some(&handle) = node.kept_handles.iter().find(|&&handle| pred(handle)) {
+                return Some(handle);
+            }
+            stack.extend(node.subquadrants().iter().flatten());
+        }
+        None
+    }
+
+    // Every handle held by a node whose bounds totally contain @req which satisfies @pred. See
+    // find_containing().
+    pub fn filter_containing<F>(&self, req: Area<U>, mut pred: F) -> Vec<u64>
+    where
+        F: FnMut(u64) -> bool,
+    {
+        let mut found = Vec::new();
+        let mut stack = vec![Self::ROOT];
+        while let Some(id) = stack.pop() {
+            let node = &self.nodes[id];
+            if !node.bounds.contains(req) || node.len == 0 {
+                continue;
+            }
+            found.extend(node.kept_handles.iter().filter(|&&handle| pred(handle)));
+            stack.extend(node.subquadrants().iter().flatten());
+        }
+        found
+    }
+
+    // Adds every handle in @other, a tree with the same root and looseness, to this one, under the
+    // new handle which @renumber gives it. The caller is responsible for putting the renumbered
+    // entries in the store first, since their regions are looked up with @region_of.
+    //
+    // Wherever this tree has nothing, @other's nodes are copied across as they are. Only where both
+    // trees end in a leaf are @other's handles inserted one at a time.
+    pub fn graft<R, F>(&mut self, other: &Self, renumber: &R, region_of: &F)
+    where
+        R: Fn(u64) -> u64,
+        F: Fn(u64) -> Option<Area<U>>,
+    {
+        debug_assert!(self.region() == other.region());
+        self.graft_below(Self::ROOT, other, Self::ROOT, renumber, region_of);
+    }
+
+    // Every pair of handles whose regions, looked up with @region_of, intersect. Only meaningful
+    // in a tight tree.
+    //
+    // Two regions can only intersect if one is held at or above the other, since the cells of
+    // siblings are disjoint. So each handle is checked against the others held at its node, and
+    // against those held by its ancestors which reach into its node's cell.
+    pub fn intersecting_pairs<F>(&self, region_of: &F) -> Vec<(u64, u64)>
+    where
+        F: Fn(u64) -> Option<Area<U>>,
+    {
+        debug_assert!(!self.is_loose());
+        let mut pairs = Vec::new();
+        self.intersecting_pairs_below(Self::ROOT, &[], region_of, &mut pairs);
+        pairs
+    }
+
+    // For each of @reqs, the handles whose regions, looked up with @region_of, intersect it. The
+    // tree is walked once for all of them: each node is only visited for the @reqs which reach into
+    // its cell, and each region is only looked up once however many @reqs it's checked against.
+    pub fn intersecting_each<F>(&self, reqs: &[Area<U>], region_of: &F) -> Vec<Vec<u64>>
+    where
+        F: Fn(u64) -> Option<Area<U>>,
+    {
+        let mut found = vec![Vec::new(); reqs.len()];
+        let mut stack = vec![(Self::ROOT, (0..reqs.len()).collect::<Vec<usize>>())];
+        while let Some((id, active)) = stack.pop() {
+            let node = &self.nodes[id];
+            if node.len == 0 {
+                continue;
+            }
+            for &handle in &node.kept_handles {
+                if let Some(region) = region_of(handle) {
+                    for &i in active.iter().filter(|&&i| reqs[i].intersects(region)) {
+                        found[i].push(handle);
+                    }
+                }
+            }
+            for sq in node.subquadrants().iter().flatten() {
+                let bounds = self.nodes[*sq].bounds;
+                let reaching: Vec<usize> = active
+                    .iter()
+                    .copied()
+                    .filter(|&i| reqs[i].intersects(bounds))
+                    .collect();
+                if !reaching.is_empty() {
+                    stack.push((*sq, reaching));
+                }
+            }
+        }
+        found
+    }
+
+    // Whether any handle's region intersects @req. Stops at the first one found.
+    pub fn any_intersecting<F>(&self, req: Area<U>, region_of: &F) -> bool
+    where
+        F: Fn(u64) -> Option<Area<U>>,
+    {
+        self.any_intersecting_below(Self::ROOT, req, region_of)
     }
 
     // fn
 
-    fn new_with_area(region: Area<U>, depth: usize) -> Self {
-        Self {
-            depth,
-            region,
-            kept_handles: Vec::new(),
-            subquadrants: None,
-            handle_counter: 0_u64,
+    // Removes @handle from the node which holds it, given its region @req, without updating any
+    // counts. Returns whether it was found.
+    fn detach(&mut self, handle: u64, req: Area<U>) -> bool {
+        let mut id = Self::ROOT;
+        loop {
+            let node = &mut self.nodes[id];
+            if let Some(i) = node.kept_handles.iter().position(|&x| x == handle) {
+                node.kept_handles.remove(i);
+                return true;
+            }
+            match (node.subquadrants(), self.quadrant_containing(id, req)) {
+                (Some(sqs), Some(quadrant)) => id = sqs[quadrant],
+                _ => return false,
+            }
         }
     }
 
-    // Attempts to insert the value at the requested region. Returns false if the region was too
-    // large.
-    fn insert_handle_at_region<V>(
-        &mut self,
-        req: Area<U>,
-        handle: u64,
-        _store: &mut StoreType<U, V>,
-    ) {
-        // If we're at the bottom depth, it had better fit.
-        if self.depth == 0 {
-            self.kept_handles.push(handle);
+    // Recomputes the count of every node from scratch, then drops the subquadrants of any node
+    // with nothing beneath it and lays the surviving nodes out afresh.
+    fn recount_and_prune(&mut self) {
+        // Subquadrants are always allocated after their parent, so walking the arena backwards
+        // visits every node after everything beneath it.
+        for id in (0..self.nodes.len()).rev() {
+            let below: usize = self.nodes[id]
+                .subquadrants()
+                .map_or(0, |sqs| sqs.iter().map(|&sq| self.nodes[sq].len).sum());
+            let node = &mut self.nodes[id];
+            node.len = node.kept_handles.len() + below;
+            if below == 0 {
+                node.subquadrants = None;
+            }
+        }
+
+        // Copy across everything still reachable from the root, breadth-first.
+        let old = std::mem::take(&mut self.nodes);
+        self.nodes.push(old[Self::ROOT].clone());
+        let mut id = Self::ROOT;
+        while id < self.nodes.len() {
+            if let Some(sqs) = self.nodes[id].subquadrants() {
+                let first = self.nodes.len();
+                self.nodes.extend(sqs.iter().map(|&sq| old[sq].clone()));
+                self.nodes[id].subquadrants = Some(first);
+            }
+            id += 1;
+        }
+    }
+
+    // Fills in the subtree at @id, which is @level levels below the root, from @keyed: the Morton
+    // code, level and handle of everything belonging in it, sorted.
+    fn build_from_sorted(&mut self, id: NodeId, level: usize, keyed: &[(u128, usize, u64)]) {
+        let node = &mut self.nodes[id];
+        node.len = keyed.len();
+        // Anything held by this node sorts before everything beneath it.
+        let here = keyed.partition_point(|&(_, l, _)| l == level);
+        node.kept_handles
+            .extend(keyed[..here].iter().map(|&(_, _, handle)| handle));
+        let mut rest = &keyed[here..];
+        if rest.is_empty() {
             return;
         }
 
-        if req.contains(self.region) {
-            self.kept_handles.push(handle);
+        let sqs = self.expand_subquadrants(id);
+        let shift = 2 * (self.depth() - level - 1);
+        // Morton order visits the quadrants [nw, ne, sw, se], as (y, x) bit pairs.
+        for (digit, &quadrant) in [1, 0, 3, 2].iter().enumerate() {
+            let len = rest.partition_point(|&(key, _, _)| (key >> shift) & 3 == digit as u128);
+            self.build_from_sorted(sqs[quadrant], level + 1, &rest[..len]);
+            rest = &rest[len..];
+        }
+    }
+
+    // The offset of @pt from @anchor, as a pair of u64s. Only meaningful within a tree of depth
+    // 64 or less.
+    pub fn offset(anchor: Point<U>, pt: Point<U>) -> (u64, u64) {
+        (
+            (pt.x() - anchor.x()).to_u64().unwrap(),
+            (pt.y() - anchor.y()).to_u64().unwrap(),
+        )
+    }
+
+    // Interleaves the bits of @x and @y, with those of @y in the odd positions.
+    pub fn morton(x: u64, y: u64) -> u128 {
+        fn spread(v: u64) -> u128 {
+            let mut v = u128::from(v);
+            v = (v | (v << 32)) & 0x0000_0000_ffff_ffff_0000_0000_ffff_ffff;
+            v = (v | (v << 16)) & 0x0000_ffff_0000_ffff_0000_ffff_0000_ffff;
+            v = (v | (v << 8)) & 0x00ff_00ff_00ff_00ff_00ff_00ff_00ff_00ff;
+            v = (v | (v << 4)) & 0x0f0f_0f0f_0f0f_0f0f_0f0f_0f0f_0f0f_0f0f;
+            v = (v | (v << 2)) & 0x3333_3333_3333_3333_3333_3333_3333_3333;
+            v = (v | (v << 1)) & 0x5555_5555_5555_5555_5555_5555_5555_5555;
+            v
+        }
+        spread(x) | (spread(y) << 1)
+    }
+
+    // Deletes @handle from the node at or beneath @start which holds it, given its region @req.
+    fn delete_below(&mut self, start: NodeId, handle: u64, req: Area<U>) {
+        let mut id = start;
+        let holder = loop {
+            let node = &mut self.nodes[id];
+            if let Some(i) = node.kept_handles.iter().position(|&x| x == handle) {
+                node.kept_handles.remove(i);
+                break id;
+            }
+            // The handle can only be further down the path which its region would have taken.
+            match (node.subquadrants(), self.quadrant_containing(id, req)) {
+                (Some(sqs), Some(quadrant)) => id = sqs[quadrant],
+                _ => return,
+            }
+        };
+
+        // Walk the same path again to update the counts above the handle.
+        let mut id = start;
+        loop {
+            self.nodes[id].len -= 1;
+            if id == holder {
+                return;
+            }
+            match (
+                self.nodes[id].subquadrants(),
+                self.quadrant_containing(id, req),
+            ) {
+                (Some(sqs), Some(quadrant)) => id = sqs[quadrant],
+                _ => unreachable!("The path to a handle shouldn't change between two walks."),
+            }
+        }
+    }
+
+    fn insert_below<F>(&mut self, mut id: NodeId, req: Area<U>, handle: u64, region_of: &F)
+    where
+        F: Fn(u64) -> Option<Area<U>>,
+    {
+        loop {
+            self.nodes[id].len += 1;
+            let node = &self.nodes[id];
+            // If we're at the bottom depth, or if the region straddles more than one subquadrant,
+            // it belongs here.
+            let quadrant = match self.quadrant_containing(id, req) {
+                Some(quadrant) => quadrant,
+                None => break,
+            };
+            let sqs = match node.subquadrants() {
+                Some(sqs) => sqs,
+                None if !self.should_split(id) => break,
+                None => self.split(id, region_of),
+            };
+            id = sqs[quadrant];
+        }
+        self.nodes[id].kept_handles.push(handle);
+    }
+
+    // Grafts the subtree of @other at @theirs onto the node @id, which has the same cell.
+    fn graft_below<R, F>(
+        &mut self,
+        id: NodeId,
+        other: &Self,
+        theirs: NodeId,
+        renumber: &R,
+        region_of: &F,
+    ) where
+        R: Fn(u64) -> u64,
+        F: Fn(u64) -> Option<Area<U>>,
+    {
+        let (mine, theirs) = (&self.nodes[id], other.node(theirs));
+        if theirs.len == 0 {
             return;
         }
+        // Whatever @other holds at a node with subquadrants straddles them, so it belongs at the
+        // same node here, as long as this tree has subquadrants there too or can take them on.
+        let sqs = match (mine.subquadrants(), theirs.subquadrants()) {
+            (Some(sqs), Some(_)) => Some(sqs),
+            (None, Some(_)) if mine.len == 0 => Some(self.expand_subquadrants(id)),
+            (None, None) if mine.len == 0 => None,
+            _ => {
+                let mut below = vec![theirs];
+                while let Some(node) = below.pop() {
+                    for &handle in &node.kept_handles {
+                        let handle = renumber(handle);
+                        let region = region_of(handle)
+                            .expect("Every handle in the tree should have a region.");
+                        self.insert_below(id, region, handle, region_of);
+                    }
+                    below.extend(
+                        node.subquadrants()
+                            .iter()
+                            .flatten()
+                            .map(|&sq| other.node(sq)),
+                    );
+                }
+                return;
+            }
+        };
 
-        if req == self.region {
-            self.kept_handles.push(handle);
+        let node = &mut self.nodes[id];
+        node.len += theirs.len;
+        node.kept_handles
+            .extend(theirs.kept_handles.iter().map(|&handle| renumber(handle)));
+        if let (Some(sqs), Some(their_sqs)) = (sqs, theirs.subquadrants()) {
+            for (&sq, their_sq) in sqs.iter().zip(their_sqs) {
+                self.graft_below(sq, other, their_sq, renumber, region_of);
+            }
+        }
+    }
+
+    // Finds the pairs for intersecting_pairs() held at or beneath @id, given the handles held
+    // above it whose regions reach into its cell.
+    fn intersecting_pairs_below<F>(
+        &self,
+        id: NodeId,
+        above: &[(u64, Area<U>)],
+        region_of: &F,
+        pairs: &mut Vec<(u64, u64)>,
+    ) where
+        F: Fn(u64) -> Option<Area<U>>,
+    {
+        let node = &self.nodes[id];
+        if node.len == 0 {
             return;
         }
+        let here: Vec<(u64, Area<U>)> = node
+            .kept_handles
+            .iter()
+            .filter_map(|&handle| Some((handle, region_of(handle)?)))
+            .collect();
+        for (i, &(handle, region)) in here.iter().enumerate() {
+            pairs.extend(
+                above
+                    .iter()
+                    .chain(&here[i + 1..])
+                    .filter(|(_, other)| region.intersects(*other))
+                    .map(|&(other, _)| (other, handle)),
+            );
+        }
 
-        if self.subquadrants.is_none() {
-            self.expand_subquadrants_by_pt(self.region.center_pt());
+        if let Some(sqs) = node.subquadrants() {
+            let above: Vec<(u64, Area<U>)> = above.iter().chain(&here).copied().collect();
+            for sq in sqs {
+                let bounds = self.nodes[sq].bounds;
+                let reaching: Vec<(u64, Area<U>)> = above
+                    .iter()
+                    .filter(|(_, region)| region.intersects(bounds))
+                    .copied()
+                    .collect();
+                self.intersecting_pairs_below(sq, &reaching, region_of, pairs);
+            }
         }
+    }
 
-        assert!(self.subquadrants.is_some()); // We should have Someified this in .split().
+    fn should_split(&self, id: NodeId) -> bool {
+        let node = &self.nodes[id];
+        self.layout
+            .split_policy
+            .should_split(self.depth() - node.depth, node.kept_handles.len())
+    }
 
-        if let Some(sqs) = self.subquadrants.as_mut() {
-            for sq in sqs.iter_mut() {
-                if sq.region.intersects(req) {
-                    sq.insert_handle_at_region(req, handle, _store);
-                }
+    // Subdivides the leaf @id, and pushes down every handle it holds which fits in one of its new
+    // subquadrants. The count at @id doesn't change.
+    fn split<F>(&mut self, id: NodeId, region_of: &F) -> [NodeId; 4]
+    where
+        F: Fn(u64) -> Option<Area<U>>,
+    {
+        let sqs = self.expand_subquadrants(id);
+        for handle in std::mem::take(&mut self.nodes[id].kept_handles) {
+            let region = region_of(handle).expect("Every handle in the tree should have a region.");
+            match self.quadrant_containing(id, region) {
+                Some(quadrant) => self.insert_below(sqs[quadrant], region, handle, region_of),
+                None => self.nodes[id].kept_handles.push(handle),
             }
         }
+        sqs
+    }
+
+    // Depth-first, so the only bookkeeping is the call stack, which is no deeper than the tree.
+    fn count_intersecting_below<F>(&self, id: NodeId, req: Area<U>, region_of: &F) -> usize
+    where
+        F: Fn(u64) -> Option<Area<U>>,
+    {
+        let node = &self.nodes[id];
+        if node.len == 0 || !node.bounds.intersects(req) {
+            return 0;
+        }
+        if req.contains(node.bounds) {
+            return node.len;
+        }
+
+        let here = node
+            .kept_handles
+            .iter()
+            .filter(|&&handle| region_of(handle).is_some_and(|region| region.intersects(req)))
+            .count();
+        let below: usize = node.subquadrants().map_or(0, |sqs| {
+            sqs.iter()
+                .map(|&sq| self.count_intersecting_below(sq, req, region_of))
+                .sum()
+        });
+        here + below
+    }
+
+    fn any_intersecting_below<F>(&self, id: NodeId, req: Area<U>, region_of: &F) -> bool
+    where
+        F: Fn(u64) -> Option<Area<U>>,
+    {
+        let node = &self.nodes[id];
+        if node.len == 0 || !node.bounds.intersects(req) {
+            return false;
+        }
+        if req.contains(node.bounds) {
+            return true;
+        }
+
+        node.kept_handles
+            .iter()
+            .any(|&handle| region_of(handle).is_some_and(|region| region.intersects(req)))
+            || node.subquadrants().is_some_and(|sqs| {
+                sqs.iter()
+                    .any(|&sq| self.any_intersecting_below(sq, req, region_of))
+            })
+    }
+
+    // The index (into the [ne, nw, se, sw] order) of the subquadrant of @id whose bounds would
+    // totally contain @req, if any. This doesn't require the subquadrants to exist yet.
+    //
+    // In a loose tree the bounds of the subquadrants overlap, so @req goes to the one whose cell
+    // holds its center. That way, a region small enough for some subquadrant always fits in that
+    // one, however it straddles the boundaries between cells.
+    fn quadrant_containing(&self, id: NodeId, req: Area<U>) -> Option<usize> {
+        let node = &self.nodes[id];
+        if !self.is_loose() {
+            return node.quadrant_containing(req);
+        }
+        if node.depth == 0 {
+            return None;
+        }
+        let (p, c) = (node.region.center_pt(), req.center_pt());
+        let quadrant = Quadrant::at(c.x() >= p.x(), c.y() >= p.y()).index();
+        if self
+            .bounds_of(node.subquadrant_cell(quadrant))
+            .contains(req)
+        {
+            Some(quadrant)
+        } else {
+            None
+        }
     }
 
-    // a--+--+--+    +--+--+--+ // a <- self.region.anchor()
+    // The bounds of a node whose cell is @cell: the cell inflated by the looseness on every side,
+    // as far as the root allows.
+    fn bounds_of(&self, cell: Area<U>) -> Area<U> {
+        if !self.is_loose() {
+            return cell;
+        }
+        let root = self.region();
+        let side = cell.width().to_f64().unwrap_or(f64::MAX);
+        let margin =
+            U::from(side * (self.layout.looseness - 1.0) / 2.0).unwrap_or_else(U::max_value);
+
+        // Work out each margin from distances within the root, so that nothing can overflow.
+        let left = margin.min(cell.left_edge() - root.left_edge());
+        let top = margin.min(cell.top_edge() - root.top_edge());
+        let right = margin.min(root.right_edge() - cell.right_edge());
+        let bottom = margin.min(root.bottom_edge() - cell.bottom_edge());
+        AreaBuilder::default()
+            .anchor(Point {
+                x: cell.left_edge() - left,
+                y: cell.top_edge() - top,
+            })
+            .dimensions((left + cell.width() + right, top + cell.height() + bottom))
+            .build()
+            .expect("Unexpected error in QTInner::bounds_of().")
+    }
+
+    // a--+--+--+    +--+--+--+ // a <- node.region.anchor()
     // |        |    |     |  |
-    // +     p  + => +--+--+--+ // p
+    // +     p  + => +--+--+--+ // p <- node.region.center_pt()
     // |        |    |     |  |
     // +--+--+--+    +--+--+--+
-    fn expand_subquadrants_by_pt(&mut self, p: Point<U>) {
-        assert!(self.region.contains_pt(p));
-
-        self.subquadrants = Some([
-            // Northeast
-            Box::new(Self::new(
-                Point {
-                    x: p.x(),
-                    y: self.region.anchor().y(),
-                },
-                self.depth - 1,
-            )),
-            // Northwest
-            Box::new(Self::new(self.region.anchor(), self.depth - 1)),
-            // Southeast
-            Box::new(Self::new(p, self.depth - 1)),
-            // Southwest
-            Box::new(Self::new(
-                Point {
-                    x: self.region.anchor().x(),
-                    y: p.y(),
-                },
-                self.depth - 1,
-            )),
-        ]);
+    fn expand_subquadrants(&mut self, id: NodeId) -> [NodeId; 4] {
+        let (node, first) = (&self.nodes[id], self.nodes.len());
+        let subquadrants = node
+            .region
+            .quarters()
+            .map(|cell| Node::new(cell, self.bounds_of(cell), node.depth - 1));
+        self.nodes.extend(subquadrants);
+        self.nodes[id].subquadrants = Some(first);
+        [first, first + 1, first + 2, first + 3]
+    }
+}
+
+impl<U> Node<U>
+where
+    U: PrimInt + Default,
+{
+    // pub
+
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    pub fn region(&self) -> Area<U> {
+        self.region
+    }
+
+    pub fn bounds(&self) -> Area<U> {
+        self.bounds
+    }
+
+    // The number of handles held by this node and everything beneath it.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn handles(&self) -> &[u64] {
+        &self.kept_handles
+    }
+
+    pub fn subquadrants(&self) -> Option<[NodeId; 4]> {
+        self.subquadrants
+            .map(|first| [first, first + 1, first + 2, first + 3])
+    }
+
+    // fn
+
+    // The index (into the [ne, nw, se, sw] order) of the subquadrant which would totally contain
+    // @req, if any. This doesn't require the subquadrants to exist yet.
+    fn quadrant_containing(&self, req: Area<U>) -> Option<usize> {
+        if self.depth == 0 {
+            return None;
+        }
+        let p = self.region.center_pt();
+        let east = if req.anchor().x() >= p.x() {
+            true
+        } else if req.right_edge() <= p.x() {
+            false
+        } else {
+            return None;
+        };
+        let south = if req.bottom_edge() <= p.y() {
+            false
+        } else if req.anchor().y() >= p.y() {
+            true
+        } else {
+            return None;
+        };
+        Some(Quadrant::at(east, south).index())
+    }
+
+    // The cell of this node's subquadrant at @quadrant, in the [ne, nw, se, sw] order.
+    fn subquadrant_cell(&self, quadrant: usize) -> Area<U> {
+        self.region.quarter(Quadrant::from_index(quadrant))
+    }
+
+    // The square cell of a node at @depth.
+    fn cell(anchor: Point<U>, depth: usize) -> Area<U> {
+        #[allow(clippy::cast_possible_truncation)]
+        let width: U = Self::two().pow(depth as u32);
+        AreaBuilder::default()
+            .anchor(anchor)
+            .dimensions((width, width))
+            .build()
+            .expect("Unexpected error in Node::cell().")
+    }
+
+    fn new(region: Area<U>, bounds: Area<U>, depth: usize) -> Self {
+        Self {
+            depth,
+            region,
+            bounds,
+            kept_handles: Handles::new(),
+            len: 0,
+            subquadrants: None,
+        }
     }
 
     // Strongly-typed alias for U::one() + U::One()
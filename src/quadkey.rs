@@ -0,0 +1,209 @@
+// Copyright 2019 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use {
+    crate::{
+        area::Area,
+        cursor::{Cursor, Quadrant},
+        iter::Tiles,
+        point::Point,
+        Quadtree,
+    },
+    num::PrimInt,
+    std::{default::Default, hash::BuildHasher},
+};
+
+impl<U, V, S> Quadtree<U, V, S>
+where
+    U: PrimInt + Default,
+    S: BuildHasher,
+{
+    // pub
+
+    /// The quadkey of the cell `depth` levels below the root which contains `point`.
+    ///
+    /// A quadkey names a cell by the path to it from the root, as in Bing Maps' tile system: a
+    /// digit for each step down, `0` for the north-west quarter, `1` for the north-east, `2` for
+    /// the south-west and `3` for the south-east. The root's quadkey is empty, and every cell's
+    /// quadkey is a prefix of the quadkeys of the cells beneath it. Read as base-4 numbers,
+    /// quadkeys are the [Morton codes] of their cells.
+    ///
+    /// Returns `None` if `point` lies outside the quadtree, or if `depth` is greater than the
+    /// quadtree's depth.
+    /// ```
+    /// use quadtree_rs::{point::Point, Quadtree};
+    ///
+    /// let qt = Quadtree::<u32, ()>::new(2);
+    /// let pt = Point { x: 3, y: 1 };
+    ///
+    /// assert_eq!(qt.quadkey(pt, 0).as_deref(), Some(""));
+    /// assert_eq!(qt.quadkey(pt, 1).as_deref(), Some("1"));
+    /// assert_eq!(qt.quadkey(pt, 2).as_deref(), Some("13"));
+    ///
+    /// assert_eq!(qt.quadkey(pt, 3), None);
+    /// assert_eq!(qt.quadkey(Point { x: 4, y: 0 }, 1), None);
+    /// ```
+    ///
+    /// [Morton codes]: #method.morton_code
+    pub fn quadkey(&self, point: Point<U>, depth: usize) -> Option<String> {
+        let mut cell = self.inner.region();
+        if depth > self.depth() || !cell.contains_pt(point) {
+            return None;
+        }
+        let mut quadkey = String::with_capacity(depth);
+        for _ in 0..depth {
+            let center = cell.center_pt();
+            let quadrant = Quadrant::at(point.x() >= center.x(), point.y() >= center.y());
+            quadkey.push(quadrant.quadkey_digit());
+            cell = cell.quarter(quadrant);
+        }
+        Some(quadkey)
+    }
+
+    /// The quadkey of the cell which holds the entry with the given handle, if there is one. See
+    /// [`.quadkey()`].
+    ///
+    /// An entry is held by the smallest cell the quadtree has made which totally contains its
+    /// region, so the quadkey of an entry whose region spans two cells is that of their common
+    /// ancestor.
+    /// ```
+    /// use quadtree_rs::{area::AreaBuilder, point::Point, Quadtree};
+    ///
+    /// let mut qt = Quadtree::<u32, ()>::new(2);
+    /// let small = qt.insert_pt(Point { x: 3, y: 1 }, ()).unwrap();
+    /// let wide = qt.insert(
+    ///     AreaBuilder::default()
+    ///         .anchor((0, 2).into())
+    ///         .dimensions((2, 2))
+    ///         .build().unwrap(),
+    ///     (),
+    /// ).unwrap();
+    ///
+    /// assert_eq!(qt.quadkey_of(small).as_deref(), Some("13"));
+    /// assert_eq!(qt.quadkey_of(wide).as_deref(), Some("2"));
+    /// assert_eq!(qt.quadkey_of(1234), None);
+    /// ```
+    ///
+    /// [`.quadkey()`]: #method.quadkey
+    pub fn quadkey_of(&self, handle: u64) -> Option<String> {
+        let entry = self.store.get(&handle)?;
+        let holder = *self.inner.path_to(entry.area()).last()?;
+        let node = self.inner.node(holder);
+        // Nodes count their depth down towards the leaves; the root's is the tree's.
+        self.quadkey(node.region().anchor(), self.depth() - node.depth())
+    }
+
+    /// The region of the cell with the given quadkey. See [`.quadkey()`].
+    ///
+    /// The cell needn't have been made by the quadtree yet. Returns `None` if the quadkey holds
+    /// anything but the digits `0` to `3`, or if it's longer than the quadtree is deep.
+    /// ```
+    /// use quadtree_rs::{area::AreaBuilder, Quadtree};
+    ///
+    /// let qt = Quadtree::<u32, ()>::new(3);
+    ///
+    /// assert_eq!(qt.quadkey_region(""), Some(
+    ///     AreaBuilder::default().anchor((0, 0).into()).dimensions((8, 8)).build().unwrap()
+    /// ));
+    /// assert_eq!(qt.quadkey_region("30"), Some(
+    ///     AreaBuilder::default().anchor((4, 4).into()).dimensions((2, 2)).build().unwrap()
+    /// ));
+    ///
+    /// assert_eq!(qt.quadkey_region("0123"), None);
+    /// assert_eq!(qt.quadkey_region("4"), None);
+    /// ```
+    ///
+    /// [`.quadkey()`]: #method.quadkey
+    pub fn quadkey_region(&self, quadkey: &str) -> Option<Area<U>> {
+        if quadkey.len() > self.depth() {
+            return None;
+        }
+        quadkey
+            .chars()
+            .try_fold(self.inner.region(), |cell, digit| {
+                Quadrant::from_quadkey_digit(digit).map(|quadrant| cell.quarter(quadrant))
+            })
+    }
+
+    /// A [`Cursor`] on the cell with the given quadkey. See [`.quadkey()`].
+    ///
+    /// Returns `None` if the quadkey isn't valid, or if the quadtree hasn't split its cells far
+    /// enough down to have made that cell.
+    /// ```
+    /// use quadtree_rs::Quadtree;
+    ///
+    /// let mut qt = Quadtree::<u32, char>::new(3);
+    /// qt.insert_pt((5, 1).into(), 'a');
+    ///
+    /// let cursor = qt.node_at_quadkey("103").unwrap();
+    /// assert_eq!(cursor.depth(), 3);
+    /// assert_eq!(cursor.quadkey(), "103");
+    /// assert_eq!(cursor.items().map(|e| *e.value_ref()).collect::<Vec<_>>(), vec!['a']);
+    ///
+    /// // The cell exists, but nothing beneath it does.
+    /// assert!(qt.node_at_quadkey("11").is_some());
+    /// assert!(qt.node_at_quadkey("110").is_none());
+    /// ```
+    ///
+    /// [`Cursor`]: cursor/struct.Cursor.html
+    /// [`.quadkey()`]: #method.quadkey
+    pub fn node_at_quadkey(&self, quadkey: &str) -> Option<Cursor<'_, U, V, S>> {
+        let mut cursor = self.cursor();
+        for digit in quadkey.chars() {
+            if !cursor.descend(Quadrant::from_quadkey_digit(digit)?) {
+                return None;
+            }
+        }
+        Some(cursor)
+    }
+
+    /// Returns an iterator ([`Tiles<U, V>`]) over the tiles at zoom level `zoom` which anything
+    /// in the quadtree touches, each with the entries whose regions intersect it, as a web map
+    /// would cut the quadtree up.
+    ///
+    /// The tiles at zoom `z` are the cells `z` levels below the root, addressed like slippy-map
+    /// tiles by `(z, x, y)`: `x` is the tile's column counting east from zero and `y` its row
+    /// counting south from zero. Tiles come in the order of their [quadkeys], and an entry whose
+    /// region spans several tiles comes with each of them. Empty tiles are skipped. Yields nothing
+    /// if `zoom` is greater than the quadtree's depth, or than 64.
+    /// ```
+    /// use quadtree_rs::{area::AreaBuilder, Quadtree};
+    ///
+    /// let mut qt = Quadtree::<u32, char>::new(3);
+    /// qt.insert_pt((1, 1).into(), 'a');
+    /// qt.insert(
+    ///     AreaBuilder::default().anchor((3, 5).into()).dimensions((2, 1)).build().unwrap(),
+    ///     'b',
+    /// );
+    ///
+    /// let tiles: Vec<((usize, u64, u64), Vec<char>)> = qt
+    ///     .tiles(1)
+    ///     .map(|(id, entries)| (id, entries.iter().map(|e| *e.value_ref()).collect()))
+    ///     .collect();
+    /// assert_eq!(tiles, vec![
+    ///     ((1, 0, 0), vec!['a']),
+    ///     ((1, 0, 1), vec!['b']),
+    ///     ((1, 1, 1), vec!['b']),
+    /// ]);
+    ///
+    /// assert_eq!(qt.tiles(4).count(), 0);
+    /// ```
+    ///
+    /// [`Tiles<U, V>`]: iter/struct.Tiles.html
+    /// [quadkeys]: #method.quadkey
+    pub fn tiles(&self, zoom: usize) -> Tiles<'_, U, V, S> {
+        let zoom = Some(zoom).filter(|&zoom| zoom <= self.depth() && zoom <= 64);
+        Tiles::new(self, self.inner.region(), zoom)
+    }
+}
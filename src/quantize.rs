@@ -0,0 +1,33 @@
+// Copyright 2019 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// Snapping floating-point coordinates, measured in cells, onto whole cells.
+
+// The run of whole cells [start, end) which covers the span from @lo to @hi. Even a span of no
+// length covers a cell.
+pub(crate) fn cover(lo: f64, hi: f64) -> (f64, f64) {
+    let start = snap(lo).floor();
+    (start, snap(hi).ceil().max(start + 1.0))
+}
+
+// Rounds @v to a whole number if it's within rounding error of one, so that an edge which was
+// written out from a whole cell lands back on that cell.
+fn snap(v: f64) -> f64 {
+    let rounded = v.round();
+    if (v - rounded).abs() < 1e-9 {
+        rounded
+    } else {
+        v
+    }
+}
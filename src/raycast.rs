@@ -0,0 +1,240 @@
+// Copyright 2019 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use {
+    crate::{
+        area::Area,
+        entry::Entry,
+        nearest::{BestFirst, Candidate},
+        point::Point,
+        qtinner::{Node, QTInner},
+        types::StoreType,
+        Quadtree,
+    },
+    num::PrimInt,
+    std::{cmp::Ordering, default::Default, hash::BuildHasher, iter::FusedIterator},
+};
+
+// A half-line through the plane of cells, which stops after some distance. Coordinates are
+// continuous, so the cell at (x, y) covers [x, x + 1) x [y, y + 1).
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Ray {
+    origin: [f64; 2],
+    // Of unit length.
+    direction: [f64; 2],
+    max_len: f64,
+}
+
+impl Ray {
+    // Panics if @direction is zero or isn't finite.
+    pub(crate) fn new(origin: [f64; 2], direction: (f64, f64), max_len: f64) -> Self {
+        let len = direction.0.hypot(direction.1);
+        assert!(
+            len > 0.0 && len.is_finite(),
+            "A ray needs a finite, non-zero direction."
+        );
+        Ray {
+            origin,
+            direction: [direction.0 / len, direction.1 / len],
+            max_len,
+        }
+    }
+
+    // How far along the ray it first touches @area, if it does before it stops. Zero if it starts
+    // inside.
+    pub(crate) fn distance_to<U>(self, area: Area<U>) -> Option<f64>
+    where
+        U: PrimInt + Default,
+    {
+        let (mut near, mut far) = (0.0_f64, self.max_len);
+        let extents = [
+            (area.left_edge(), area.width()),
+            (area.top_edge(), area.height()),
+        ];
+        for (axis, (lo, len)) in extents.iter().enumerate() {
+            let (o, d) = (self.origin[axis], self.direction[axis]);
+            let lo = lo.to_f64()?;
+            let hi = lo + len.to_f64()?;
+            if d == 0.0 {
+                if o < lo || o > hi {
+                    return None;
+                }
+                continue;
+            }
+            let (a, b) = ((lo - o) / d, (hi - o) / d);
+            near = near.max(a.min(b));
+            far = far.min(a.max(b));
+        }
+        Some(near).filter(|&near| near <= far)
+    }
+}
+
+// How far along a ray something is. Never NaN, so it can key a best-first search.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Distance(f64);
+
+impl Eq for Distance {}
+
+impl PartialOrd for Distance {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Distance {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.total_cmp(&other.0)
+    }
+}
+
+// A best-first traversal of the tree which yields the entries a ray passes through in the order it
+// reaches them, along with how far along the ray each is. Nodes are keyed by how far along the ray
+// it first touches their bounds, and handles by how far it first touches their regions.
+//
+// Everything beneath a node lies within its bounds, so the ray can't reach any of it before it
+// reaches the node. Nodes the ray misses are never expanded at all.
+pub(crate) struct RayIter<'a, U, V, S>
+where
+    U: PrimInt + Default,
+{
+    qt: &'a QTInner<U>,
+    store: &'a StoreType<U, V, S>,
+    ray: Ray,
+    queue: BestFirst<Distance, &'a Node<U>>,
+}
+
+impl<'a, U, V, S> RayIter<'a, U, V, S>
+where
+    U: PrimInt + Default,
+    S: BuildHasher,
+{
+    pub(crate) fn new(qt: &'a QTInner<U>, store: &'a StoreType<U, V, S>, ray: Ray) -> Self {
+        let mut iter = RayIter {
+            qt,
+            store,
+            ray,
+            queue: BestFirst::new(),
+        };
+        iter.push(qt.root().bounds(), Candidate::Node(qt.root()));
+        iter
+    }
+
+    fn push(&mut self, area: Area<U>, candidate: Candidate<&'a Node<U>>) {
+        if let Some(distance) = self.ray.distance_to(area) {
+            self.queue.push(Distance(distance), candidate);
+        }
+    }
+
+    fn expand(&mut self, qt: &'a Node<U>) {
+        for &handle in qt.handles() {
+            if let Some(entry) = self.store.get(&handle) {
+                self.push(entry.area(), Candidate::Handle(handle));
+            }
+        }
+        if let Some(subquadrants) = qt.subquadrants() {
+            let tree = self.qt;
+            for sq in subquadrants.iter().map(|&id| tree.node(id)) {
+                self.push(sq.bounds(), Candidate::Node(sq));
+            }
+        }
+    }
+}
+
+impl<'a, U, V, S> Iterator for RayIter<'a, U, V, S>
+where
+    U: PrimInt + Default,
+    S: BuildHasher,
+{
+    type Item = (f64, &'a Entry<U, V>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((Distance(distance), candidate)) = self.queue.pop() {
+            match candidate {
+                Candidate::Node(qt) => self.expand(qt),
+                Candidate::Handle(handle) => {
+                    if let Some(entry) = self.store.get(&handle) {
+                        return Some((distance, entry));
+                    }
+                }
+            }
+        }
+        None
+    }
+}
+
+impl<U, V, S> FusedIterator for RayIter<'_, U, V, S>
+where
+    U: PrimInt + Default,
+    S: BuildHasher,
+{
+}
+
+impl<U, V, S> Quadtree<U, V, S>
+where
+    U: PrimInt + Default,
+    S: BuildHasher,
+{
+    // pub
+
+    /// Casts a ray from the centre of the cell at `origin` in some `direction`, and returns the
+    /// [`&Entry<U, V>`] structs whose regions it passes through, nearest first, along with how far
+    /// along the ray each one is.
+    ///
+    /// Cells are taken to be unit squares, so the ray starts half a cell in from the cell's
+    /// top-left corner, and a region is as far along the ray as the point where the ray first
+    /// touches it (zero, if the ray starts inside it), and regions at the same distance come out in
+    /// order of handle. The ray stops after `max_len` cells.
+    ///
+    /// Rather than testing every entry, the search is best-first, as in [`.knn()`]: subquadrants
+    /// wait in a priority queue keyed by how far along the ray it first touches their bounds, and
+    /// the nearest is always expanded next. Those the ray misses are never visited, so taking just
+    /// the first hit is cheap.
+    ///
+    /// Panics if `direction` is zero, or isn't finite.
+    /// ```
+    /// use quadtree_rs::{area::AreaBuilder, point::Point, Quadtree};
+    ///
+    /// let mut qt = Quadtree::<u32, &str>::new(5);
+    /// let wall = |x, y| AreaBuilder::default()
+    ///     .anchor((x, y).into())
+    ///     .dimensions((1, 8))
+    ///     .build().unwrap();
+    /// qt.insert(wall(20, 0), "far wall");
+    /// qt.insert(wall(10, 0), "near wall");
+    /// qt.insert(wall(10, 20), "out of the way");
+    ///
+    /// // Look east along the row at y = 4.
+    /// let hits: Vec<(f64, &str)> = qt
+    ///     .raycast(Point { x: 2, y: 4 }, (1.0, 0.0), 100.0)
+    ///     .map(|(distance, e)| (distance, *e.value_ref()))
+    ///     .collect();
+    /// assert_eq!(hits, vec![(7.5, "near wall"), (17.5, "far wall")]);
+    ///
+    /// // The ray doesn't reach the far wall.
+    /// assert_eq!(qt.raycast(Point { x: 2, y: 4 }, (1.0, 0.0), 10.0).count(), 1);
+    /// ```
+    ///
+    /// [`&Entry<U, V>`]: entry/struct.Entry.html
+    /// [`.knn()`]: #method.knn
+    pub fn raycast(
+        &self,
+        origin: Point<U>,
+        direction: (f64, f64),
+        max_len: f64,
+    ) -> impl Iterator<Item = (f64, &Entry<U, V>)> + '_ {
+        let centre = |c: U| c.to_f64().map_or(f64::NAN, |c| c + 0.5);
+        let ray = Ray::new([centre(origin.x()), centre(origin.y())], direction, max_len);
+        RayIter::new(&self.inner, &self.store, ray)
+    }
+}
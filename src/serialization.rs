@@ -0,0 +1,100 @@
+// Copyright 2019 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// Serde support for `Quadtree<U, V>`, behind the `serde` feature.
+//
+// Only the tree's bounds and its entries are written out. The node structure is a pure function of
+// those, so on the way back in we rebuild it by re-registering each handle rather than trusting a
+// serialized copy of it.
+
+use {
+    crate::{entry::Entry, point::Point, Quadtree},
+    num::PrimInt,
+    serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer},
+    std::{default::Default, hash::BuildHasher},
+};
+
+#[derive(Serialize)]
+#[serde(rename = "Quadtree")]
+struct QuadtreeRef<'a, U, V>
+where
+    U: PrimInt + Default,
+{
+    anchor: Point<U>,
+    depth: usize,
+    // Only written for quadtrees built with `new_with_bounds()`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    dimensions: Option<(U, U)>,
+    next_handle: u64,
+    entries: Vec<&'a Entry<U, V>>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename = "Quadtree")]
+struct QuadtreeData<U, V>
+where
+    U: PrimInt + Default,
+{
+    anchor: Point<U>,
+    depth: usize,
+    #[serde(default)]
+    dimensions: Option<(U, U)>,
+    next_handle: u64,
+    entries: Vec<Entry<U, V>>,
+}
+
+impl<U, V, H> Serialize for Quadtree<U, V, H>
+where
+    U: PrimInt + Default + Serialize,
+    V: Serialize,
+    H: BuildHasher,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let entries = self.entries_by_handle();
+
+        QuadtreeRef {
+            anchor: self.anchor(),
+            depth: self.depth(),
+            dimensions: self.clipped_dimensions(),
+            next_handle: self.next_handle,
+            entries,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de, U, V, H> Deserialize<'de> for Quadtree<U, V, H>
+where
+    U: PrimInt + Default + Deserialize<'de>,
+    V: Deserialize<'de>,
+    H: BuildHasher + Default,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let data = QuadtreeData::<U, V>::deserialize(deserializer)?;
+        Quadtree::restore(
+            data.anchor,
+            data.depth,
+            data.dimensions,
+            data.next_handle,
+            data.entries,
+        )
+        .map_err(D::Error::custom)
+    }
+}
@@ -0,0 +1,325 @@
+// Copyright 2019 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use {
+    crate::{
+        area::{Area, AreaBuilder},
+        point::Point,
+        raycast::Ray,
+    },
+    num::PrimInt,
+    std::default::Default,
+};
+
+// The shape of a query. Traversals use it both to prune subquadrants and to evaluate the regions
+// of the entries they find.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Shape<U>
+where
+    U: PrimInt + Default,
+{
+    Rect(Area<U>),
+    // Every cell which isn't in the area. The only shape without a bounding box.
+    Outside(Area<U>),
+    // Every cell within @radius (euclidean) of the cell at @center.
+    Disc {
+        center: Point<U>,
+        radius: U,
+    },
+    // Every cell within @outer (euclidean) of the cell at @center, but not within @inner.
+    Annulus {
+        center: Point<U>,
+        inner: U,
+        outer: U,
+    },
+    // Every cell which the line between the centres of the cells at @from and @to passes through.
+    Segment {
+        from: Point<U>,
+        to: Point<U>,
+    },
+    // Every cell which the triangle between the centres of the cells at @a, @b and @c overlaps.
+    Triangle {
+        a: Point<U>,
+        b: Point<U>,
+        c: Point<U>,
+    },
+}
+
+impl<U> From<Area<U>> for Shape<U>
+where
+    U: PrimInt + Default,
+{
+    fn from(area: Area<U>) -> Self {
+        Shape::Rect(area)
+    }
+}
+
+impl<U> Shape<U>
+where
+    U: PrimInt + Default,
+{
+    // Whether or not any cell of @area lies within this shape.
+    pub(crate) fn intersects(self, area: Area<U>) -> bool {
+        match self {
+            Shape::Rect(rect) => rect.intersects(area),
+            Shape::Outside(rect) => !rect.contains(area),
+            Shape::Disc { center, radius } => match Self::radius_sq(radius) {
+                Some(r_sq) => area.distance_sq_to_pt(center) <= r_sq,
+                None => false,
+            },
+            // Anything wholly within the inner radius is in the hole. A region can reach both
+            // inside the hole and outside the ring when there's no ring to overlap.
+            Shape::Annulus { inner, outer, .. } if inner >= outer => false,
+            Shape::Annulus {
+                center,
+                inner,
+                outer,
+            } => match (Self::radius_sq(inner), Self::radius_sq(outer)) {
+                (_, None) => false,
+                (None, Some(o_sq)) => area.distance_sq_to_pt(center) <= o_sq,
+                (Some(i_sq), Some(o_sq)) => {
+                    area.distance_sq_to_pt(center) <= o_sq
+                        && area.farthest_distance_sq_to_pt(center) > i_sq
+                }
+            },
+            Shape::Segment { from, to } => match Self::ray_between(from, to) {
+                Some(ray) => ray.distance_to(area).is_some(),
+                None => area.contains_pt(from),
+            },
+            Shape::Triangle { a, b, c } => Self::triangle_overlaps(a, b, c, area),
+        }
+    }
+
+    // Whether or not every cell of @area lies within this shape.
+    pub(crate) fn contains(self, area: Area<U>) -> bool {
+        match self {
+            Shape::Rect(rect) => rect.contains(area),
+            Shape::Outside(rect) => !rect.intersects(area),
+            Shape::Disc { center, radius } => match Self::radius_sq(radius) {
+                Some(r_sq) => area.farthest_distance_sq_to_pt(center) <= r_sq,
+                None => false,
+            },
+            Shape::Annulus {
+                center,
+                inner,
+                outer,
+            } => match (Self::radius_sq(inner), Self::radius_sq(outer)) {
+                (_, None) => false,
+                (None, Some(o_sq)) => area.farthest_distance_sq_to_pt(center) <= o_sq,
+                (Some(i_sq), Some(o_sq)) => {
+                    area.farthest_distance_sq_to_pt(center) <= o_sq
+                        && area.distance_sq_to_pt(center) > i_sq
+                }
+            },
+            // A line is too thin to cover a whole cell.
+            Shape::Segment { .. } => false,
+            // A triangle is convex, so it covers @area if it covers each of its corners.
+            Shape::Triangle { a, b, c } => {
+                let (l, t) = (area.left_edge(), area.top_edge());
+                let (r, bottom) = (area.right_edge(), area.bottom_edge());
+                [(l, t), (r, t), (r, bottom), (l, bottom)]
+                    .iter()
+                    .all(|&(x, y)| Self::triangle_covers(a, b, c, Point { x, y }))
+            }
+        }
+    }
+
+    // Whether or not @area covers every cell of this shape.
+    pub(crate) fn contained_by(self, area: Area<U>) -> bool {
+        match self {
+            Shape::Rect(rect) => area.contains(rect),
+            // There's always a cell outside @area too.
+            Shape::Outside(_) => false,
+            // The outermost cells of a disc touch every edge of its bounding box, as do those of
+            // a ring. So do the ends of a segment, and the corners of a triangle.
+            Shape::Disc { .. }
+            | Shape::Annulus { .. }
+            | Shape::Segment { .. }
+            | Shape::Triangle { .. } => self.bounding_box().is_some_and(|bb| area.contains(bb)),
+        }
+    }
+
+    // Whether @area covers exactly the cells of this shape. Only a rectangle can be an area.
+    pub(crate) fn is(self, area: Area<U>) -> bool {
+        match self {
+            Shape::Rect(rect) => rect == area,
+            Shape::Outside(_)
+            | Shape::Disc { .. }
+            | Shape::Annulus { .. }
+            | Shape::Segment { .. }
+            | Shape::Triangle { .. } => false,
+        }
+    }
+
+    // Some cell of this shape. Any region which contains the shape must contain this cell.
+    pub(crate) fn interior_pt(self) -> Point<U> {
+        match self {
+            Shape::Rect(rect) => rect.anchor(),
+            // Nothing contains it, so any cell will do.
+            Shape::Outside(rect) => rect.anchor(),
+            Shape::Disc { center, .. } => center,
+            // Not a cell of the ring, but any rectangle which surrounds the ring surrounds it too.
+            Shape::Annulus { center, .. } => center,
+            Shape::Segment { from, .. } => from,
+            Shape::Triangle { a, .. } => a,
+        }
+    }
+
+    // The smallest area which contains this shape, if it can be represented in @U.
+    pub(crate) fn bounding_box(self) -> Option<Area<U>> {
+        match self {
+            Shape::Rect(rect) => Some(rect),
+            Shape::Outside(_) => None,
+            Shape::Disc { center, radius } => {
+                if radius < U::zero() {
+                    return None;
+                }
+                let anchor = Point {
+                    x: center.x().checked_sub(&radius)?,
+                    y: center.y().checked_sub(&radius)?,
+                };
+                let side = radius.checked_add(&radius)?.checked_add(&U::one())?;
+                // The far edges have to be representable too.
+                anchor.x().checked_add(&side)?;
+                anchor.y().checked_add(&side)?;
+                AreaBuilder::default()
+                    .anchor(anchor)
+                    .dimensions((side, side))
+                    .build()
+                    .ok()
+            }
+            // An empty ring has no bounding box; otherwise its outermost cells are in it.
+            Shape::Annulus {
+                center,
+                inner,
+                outer,
+            } => {
+                if inner >= outer {
+                    return None;
+                }
+                Shape::Disc {
+                    center,
+                    radius: outer,
+                }
+                .bounding_box()
+            }
+            Shape::Segment { from, to } => Self::bounding_box_of(&[from, to]),
+            Shape::Triangle { a, b, c } => Self::bounding_box_of(&[a, b, c]),
+        }
+    }
+
+    // The smallest area which contains the cells at each of @pts.
+    fn bounding_box_of(pts: &[Point<U>]) -> Option<Area<U>> {
+        let min = |f: fn(&Point<U>) -> U| pts.iter().map(f).min();
+        let max = |f: fn(&Point<U>) -> U| pts.iter().map(f).max();
+        let anchor = Point {
+            x: min(Point::x)?,
+            y: min(Point::y)?,
+        };
+        let width = max(Point::x)? - anchor.x();
+        let height = max(Point::y)? - anchor.y();
+        AreaBuilder::default()
+            .anchor(anchor)
+            .dimensions((
+                width.checked_add(&U::one())?,
+                height.checked_add(&U::one())?,
+            ))
+            .build()
+            .ok()
+    }
+
+    // The centre of the cell at @pt.
+    fn centre(pt: Point<U>) -> Option<(f64, f64)> {
+        Some((pt.x().to_f64()? + 0.5, pt.y().to_f64()? + 0.5))
+    }
+
+    // Whether the triangle between the centres of the cells at @a, @b and @c overlaps @area,
+    // edges included. Two convex shapes overlap unless there's an axis along which their
+    // projections don't, and the only axes worth checking are the normals of their edges.
+    fn triangle_overlaps(a: Point<U>, b: Point<U>, c: Point<U>, area: Area<U>) -> bool {
+        let (tri, corners) = match (
+            Self::centre(a),
+            Self::centre(b),
+            Self::centre(c),
+            area.left_edge().to_f64(),
+            area.top_edge().to_f64(),
+            area.width().to_f64(),
+            area.height().to_f64(),
+        ) {
+            (Some(a), Some(b), Some(c), Some(l), Some(t), Some(w), Some(h)) => {
+                ([a, b, c], [(l, t), (l + w, t), (l + w, t + h), (l, t + h)])
+            }
+            _ => return false,
+        };
+        let axes = [
+            (1.0, 0.0),
+            (0.0, 1.0),
+            (tri[0].1 - tri[1].1, tri[1].0 - tri[0].0),
+            (tri[1].1 - tri[2].1, tri[2].0 - tri[1].0),
+            (tri[2].1 - tri[0].1, tri[0].0 - tri[2].0),
+        ];
+        let project = |pts: &[(f64, f64)], (ax, ay): (f64, f64)| {
+            pts.iter()
+                .map(|&(x, y)| x * ax + y * ay)
+                .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), p| {
+                    (lo.min(p), hi.max(p))
+                })
+        };
+        axes.iter().all(|&axis| {
+            let (tri_lo, tri_hi) = project(&tri, axis);
+            let (box_lo, box_hi) = project(&corners, axis);
+            tri_lo <= box_hi && box_lo <= tri_hi
+        })
+    }
+
+    // Whether the triangle between the centres of the cells at @a, @b and @c covers the point
+    // @pt, edges included.
+    fn triangle_covers(a: Point<U>, b: Point<U>, c: Point<U>, pt: Point<U>) -> bool {
+        let (a, b, c, p) = match (
+            Self::centre(a),
+            Self::centre(b),
+            Self::centre(c),
+            pt.x().to_f64().zip(pt.y().to_f64()),
+        ) {
+            (Some(a), Some(b), Some(c), Some(p)) => (a, b, c, p),
+            _ => return false,
+        };
+        let side = |(x0, y0): (f64, f64), (x1, y1): (f64, f64)| {
+            (x1 - x0) * (p.1 - y0) - (y1 - y0) * (p.0 - x0)
+        };
+        let sides = [side(a, b), side(b, c), side(c, a)];
+        sides.iter().all(|&s| s >= 0.0) || sides.iter().all(|&s| s <= 0.0)
+    }
+
+    // The ray from the centre of the cell at @from to the centre of the cell at @to, or None if
+    // they're the same cell.
+    fn ray_between(from: Point<U>, to: Point<U>) -> Option<Ray> {
+        let (x0, y0, x1, y1) = (
+            from.x().to_f64()?,
+            from.y().to_f64()?,
+            to.x().to_f64()?,
+            to.y().to_f64()?,
+        );
+        let (dx, dy) = (x1 - x0, y1 - y0);
+        if dx == 0.0 && dy == 0.0 {
+            return None;
+        }
+        Some(Ray::new([x0 + 0.5, y0 + 0.5], (dx, dy), dx.hypot(dy)))
+    }
+
+    // A negative radius covers nothing.
+    fn radius_sq(radius: U) -> Option<u128> {
+        radius.to_u128().map(|r| r.saturating_mul(r))
+    }
+}
@@ -0,0 +1,438 @@
+// Copyright 2019 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A compact, versioned binary encoding for quadtrees.
+//!
+//! See [`Quadtree::write_to()`] and [`Quadtree::read_from()`]. Values are encoded through the
+//! [`Record`] trait, which is implemented for the common primitive types and can be implemented
+//! for your own.
+//!
+//! The format is a magic number and version, followed by the tree's depth, anchor, bounds and next
+//! handle, then one record per entry holding its handle, region and value. Integers are written as
+//! (zigzag) LEB128 varints, so small coordinates cost a byte or two regardless of `U`.
+//!
+//! [`Quadtree::write_to()`]: ../struct.Quadtree.html#method.write_to
+//! [`Quadtree::read_from()`]: ../struct.Quadtree.html#method.read_from
+//! [`Record`]: trait.Record.html
+
+use {
+    crate::{area::AreaBuilder, entry::Entry, point::Point, Quadtree},
+    num::PrimInt,
+    std::{
+        convert::TryFrom,
+        default::Default,
+        hash::BuildHasher,
+        io::{self, Read, Write},
+    },
+};
+
+const MAGIC: &[u8; 4] = b"QTRS";
+// Version 1 predates `new_with_bounds()`, and has no bounds. It can still be read.
+const VERSION: u8 = 2;
+
+/// A value which can be written to and read back from a snapshot.
+///
+/// ```
+/// use {
+///     quadtree_rs::{point::Point, snapshot::Record, Quadtree},
+///     std::io::{self, Read, Write},
+/// };
+///
+/// #[derive(Debug, PartialEq)]
+/// struct Tile {
+///     id: u32,
+///     name: String,
+/// }
+///
+/// impl Record for Tile {
+///     fn write_record<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+///         self.id.write_record(writer)?;
+///         self.name.write_record(writer)
+///     }
+///
+///     fn read_record<R: Read>(reader: &mut R) -> io::Result<Self> {
+///         Ok(Tile {
+///             id: u32::read_record(reader)?,
+///             name: String::read_record(reader)?,
+///         })
+///     }
+/// }
+///
+/// let mut qt = Quadtree::<u16, Tile>::new(8);
+/// let handle = qt.insert_pt(Point {x: 3, y: 4}, Tile {id: 7, name: "grass".to_string()}).unwrap();
+///
+/// let mut buffer: Vec<u8> = vec![];
+/// qt.write_to(&mut buffer).unwrap();
+/// let qt2 = Quadtree::<u16, Tile>::read_from(&buffer[..]).unwrap();
+///
+/// assert_eq!(qt2.get(handle).unwrap().value_ref(), qt.get(handle).unwrap().value_ref());
+/// ```
+pub trait Record: Sized {
+    /// Writes this value to `writer`.
+    fn write_record<W: Write>(&self, writer: &mut W) -> io::Result<()>;
+
+    /// Reads a value previously written by [`.write_record()`] from `reader`.
+    ///
+    /// [`.write_record()`]: #tymethod.write_record
+    fn read_record<R: Read>(reader: &mut R) -> io::Result<Self>;
+}
+
+fn invalid_data(msg: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+fn write_varint<W: Write>(writer: &mut W, mut n: u128) -> io::Result<()> {
+    loop {
+        let byte = (n & 0x7f) as u8;
+        n >>= 7;
+        if n == 0 {
+            return writer.write_all(&[byte]);
+        }
+        writer.write_all(&[byte | 0x80])?;
+    }
+}
+
+fn read_varint<R: Read>(reader: &mut R) -> io::Result<u128> {
+    let mut n: u128 = 0;
+    for shift in (0..128).step_by(7) {
+        let mut byte = [0_u8];
+        reader.read_exact(&mut byte)?;
+        n |= u128::from(byte[0] & 0x7f) << shift;
+        if byte[0] & 0x80 == 0 {
+            return Ok(n);
+        }
+    }
+    Err(invalid_data("Varint is too long.".to_string()))
+}
+
+fn write_zigzag<W: Write>(writer: &mut W, n: i128) -> io::Result<()> {
+    write_varint(writer, ((n << 1) ^ (n >> 127)) as u128)
+}
+
+fn read_zigzag<R: Read>(reader: &mut R) -> io::Result<i128> {
+    let n = read_varint(reader)?;
+    Ok((n >> 1) as i128 ^ -((n & 1) as i128))
+}
+
+fn write_coord<W: Write, U: PrimInt>(writer: &mut W, u: U) -> io::Result<()> {
+    let n = u
+        .to_i128()
+        .ok_or_else(|| invalid_data("Coordinate is beyond the range of an i128.".to_string()))?;
+    write_zigzag(writer, n)
+}
+
+fn read_coord<R: Read, U: PrimInt>(reader: &mut R) -> io::Result<U> {
+    let n = read_zigzag(reader)?;
+    U::from(n).ok_or_else(|| invalid_data(format!("Coordinate {} is out of range.", n)))
+}
+
+fn read_len<R: Read>(reader: &mut R) -> io::Result<usize> {
+    let n = read_varint(reader)?;
+    usize::try_from(n).map_err(|_| invalid_data(format!("Length {} is out of range.", n)))
+}
+
+macro_rules! unsigned_record {
+    ($($t:ty),*) => {$(
+        impl Record for $t {
+            fn write_record<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+                write_varint(writer, u128::from(*self))
+            }
+
+            fn read_record<R: Read>(reader: &mut R) -> io::Result<Self> {
+                let n = read_varint(reader)?;
+                <$t>::try_from(n).map_err(|_| invalid_data(format!("{} is out of range.", n)))
+            }
+        }
+    )*};
+}
+
+macro_rules! signed_record {
+    ($($t:ty),*) => {$(
+        impl Record for $t {
+            fn write_record<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+                write_zigzag(writer, i128::from(*self))
+            }
+
+            fn read_record<R: Read>(reader: &mut R) -> io::Result<Self> {
+                let n = read_zigzag(reader)?;
+                <$t>::try_from(n).map_err(|_| invalid_data(format!("{} is out of range.", n)))
+            }
+        }
+    )*};
+}
+
+macro_rules! float_record {
+    ($($t:ty),*) => {$(
+        impl Record for $t {
+            fn write_record<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+                writer.write_all(&self.to_le_bytes())
+            }
+
+            fn read_record<R: Read>(reader: &mut R) -> io::Result<Self> {
+                let mut bytes = [0_u8; std::mem::size_of::<$t>()];
+                reader.read_exact(&mut bytes)?;
+                Ok(<$t>::from_le_bytes(bytes))
+            }
+        }
+    )*};
+}
+
+unsigned_record!(u8, u16, u32, u64);
+signed_record!(i8, i16, i32, i64);
+float_record!(f32, f64);
+
+impl Record for usize {
+    fn write_record<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        write_varint(writer, *self as u128)
+    }
+
+    fn read_record<R: Read>(reader: &mut R) -> io::Result<Self> {
+        read_len(reader)
+    }
+}
+
+impl Record for isize {
+    fn write_record<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        write_zigzag(writer, *self as i128)
+    }
+
+    fn read_record<R: Read>(reader: &mut R) -> io::Result<Self> {
+        let n = read_zigzag(reader)?;
+        isize::try_from(n).map_err(|_| invalid_data(format!("{} is out of range.", n)))
+    }
+}
+
+impl Record for bool {
+    fn write_record<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writer.write_all(&[u8::from(*self)])
+    }
+
+    fn read_record<R: Read>(reader: &mut R) -> io::Result<Self> {
+        match u8::read_record(reader)? {
+            0 => Ok(false),
+            1 => Ok(true),
+            b => Err(invalid_data(format!("{} is not a bool.", b))),
+        }
+    }
+}
+
+impl Record for char {
+    fn write_record<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        u32::from(*self).write_record(writer)
+    }
+
+    fn read_record<R: Read>(reader: &mut R) -> io::Result<Self> {
+        let n = u32::read_record(reader)?;
+        char::from_u32(n).ok_or_else(|| invalid_data(format!("{} is not a char.", n)))
+    }
+}
+
+impl Record for () {
+    fn write_record<W: Write>(&self, _writer: &mut W) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn read_record<R: Read>(_reader: &mut R) -> io::Result<Self> {
+        Ok(())
+    }
+}
+
+impl Record for String {
+    fn write_record<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        self.len().write_record(writer)?;
+        writer.write_all(self.as_bytes())
+    }
+
+    fn read_record<R: Read>(reader: &mut R) -> io::Result<Self> {
+        let len = read_len(reader)?;
+        let mut bytes = vec![];
+        reader.take(len as u64).read_to_end(&mut bytes)?;
+        if bytes.len() != len {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        String::from_utf8(bytes).map_err(|e| invalid_data(e.to_string()))
+    }
+}
+
+impl<T: Record> Record for Option<T> {
+    fn write_record<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        match self {
+            Some(t) => {
+                true.write_record(writer)?;
+                t.write_record(writer)
+            }
+            None => false.write_record(writer),
+        }
+    }
+
+    fn read_record<R: Read>(reader: &mut R) -> io::Result<Self> {
+        if bool::read_record(reader)? {
+            Ok(Some(T::read_record(reader)?))
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+impl<T: Record> Record for Vec<T> {
+    fn write_record<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        self.len().write_record(writer)?;
+        self.iter().try_for_each(|t| t.write_record(writer))
+    }
+
+    fn read_record<R: Read>(reader: &mut R) -> io::Result<Self> {
+        let len = read_len(reader)?;
+        // Don't trust @len with an allocation up front.
+        let mut v = vec![];
+        for _ in 0..len {
+            v.push(T::read_record(reader)?);
+        }
+        Ok(v)
+    }
+}
+
+impl<A: Record, B: Record> Record for (A, B) {
+    fn write_record<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        self.0.write_record(writer)?;
+        self.1.write_record(writer)
+    }
+
+    fn read_record<R: Read>(reader: &mut R) -> io::Result<Self> {
+        Ok((A::read_record(reader)?, B::read_record(reader)?))
+    }
+}
+
+impl<U, V, S> Quadtree<U, V, S>
+where
+    U: PrimInt + Default,
+    V: Record,
+    S: BuildHasher,
+{
+    /// Writes a compact binary snapshot of the quadtree to `writer`.
+    ///
+    /// Every entry is written along with its handle, so a quadtree restored by [`.read_from()`]
+    /// hands out the same handles as the original. For the details of the format, see the
+    /// [`snapshot`] module.
+    ///
+    /// Coordinates are written as `i128`s, so a `u128` quadtree which reaches beyond
+    /// `i128::MAX` can't be written; this is reported with an error of kind [`InvalidData`].
+    /// ```
+    /// use quadtree_rs::{area::AreaBuilder, Quadtree};
+    ///
+    /// let mut qt = Quadtree::<u32, String>::new(4);
+    /// let region = AreaBuilder::default()
+    ///     .anchor((1, 2).into())
+    ///     .dimensions((3, 4))
+    ///     .build().unwrap();
+    /// let handle = qt.insert(region, "foo".to_string()).unwrap();
+    ///
+    /// let mut buffer: Vec<u8> = vec![];
+    /// qt.write_to(&mut buffer).unwrap();
+    ///
+    /// let qt2 = Quadtree::<u32, String>::read_from(&buffer[..]).unwrap();
+    /// assert_eq!(qt2.get(handle).unwrap().area(), region);
+    /// assert_eq!(qt2.get(handle).unwrap().value_ref(), "foo");
+    /// ```
+    ///
+    /// [`.read_from()`]: #method.read_from
+    /// [`snapshot`]: snapshot/index.html
+    /// [`InvalidData`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.InvalidData
+    pub fn write_to<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writer.write_all(MAGIC)?;
+        writer.write_all(&[VERSION])?;
+
+        self.depth().write_record(&mut writer)?;
+        write_coord(&mut writer, self.anchor().x())?;
+        write_coord(&mut writer, self.anchor().y())?;
+        match self.clipped_dimensions() {
+            Some((width, height)) => {
+                true.write_record(&mut writer)?;
+                write_coord(&mut writer, width)?;
+                write_coord(&mut writer, height)?;
+            }
+            None => false.write_record(&mut writer)?,
+        }
+        self.next_handle.write_record(&mut writer)?;
+
+        let entries = self.entries_by_handle();
+
+        entries.len().write_record(&mut writer)?;
+        for entry in entries {
+            entry.handle().write_record(&mut writer)?;
+            write_coord(&mut writer, entry.anchor().x())?;
+            write_coord(&mut writer, entry.anchor().y())?;
+            write_coord(&mut writer, entry.width())?;
+            write_coord(&mut writer, entry.height())?;
+            entry.value_ref().write_record(&mut writer)?;
+        }
+        writer.flush()
+    }
+
+    /// Reads back a quadtree written by [`.write_to()`].
+    ///
+    /// The node structure is rebuilt from the entries rather than read in. Snapshots with an
+    /// unknown version, coordinates which don't fit in `U`, or entries which lie outside the tree
+    /// are rejected with an error of kind [`InvalidData`].
+    ///
+    /// [`.write_to()`]: #method.write_to
+    /// [`InvalidData`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.InvalidData
+    pub fn read_from<R: Read>(mut reader: R) -> io::Result<Self>
+    where
+        S: Default,
+    {
+        let mut header = [0_u8; 5];
+        reader.read_exact(&mut header)?;
+        if &header[..4] != MAGIC {
+            return Err(invalid_data("Not a quadtree snapshot.".to_string()));
+        }
+        if header[4] == 0 || header[4] > VERSION {
+            return Err(invalid_data(format!(
+                "Unsupported snapshot version {}.",
+                header[4]
+            )));
+        }
+
+        let depth = usize::read_record(&mut reader)?;
+        let anchor = Point {
+            x: read_coord(&mut reader)?,
+            y: read_coord(&mut reader)?,
+        };
+        let dimensions = if header[4] >= 2 && bool::read_record(&mut reader)? {
+            Some((read_coord(&mut reader)?, read_coord(&mut reader)?))
+        } else {
+            None
+        };
+        let next_handle = u64::read_record(&mut reader)?;
+
+        let len = read_len(&mut reader)?;
+        let mut entries = vec![];
+        for _ in 0..len {
+            let handle = u64::read_record(&mut reader)?;
+            let anchor = Point {
+                x: read_coord(&mut reader)?,
+                y: read_coord(&mut reader)?,
+            };
+            let dimensions = (read_coord(&mut reader)?, read_coord(&mut reader)?);
+            let region = AreaBuilder::default()
+                .anchor(anchor)
+                .dimensions(dimensions)
+                .build()
+                .map_err(invalid_data)?;
+            let value = V::read_record(&mut reader)?;
+            entries.push(Entry::new((region, value), handle));
+        }
+
+        Self::restore(anchor, depth, dimensions, next_handle, entries).map_err(invalid_data)
+    }
+}
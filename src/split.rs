@@ -0,0 +1,107 @@
+// Copyright 2019 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Policies which decide when a cell of a quadtree is subdivided.
+//!
+//! Every entry is held by a single cell which totally contains its region. When an entry lands in
+//! a leaf which it would fit further down in, the quadtree asks its [`SplitPolicy`] whether to
+//! subdivide the leaf and push its entries down, or to keep the entry where it is. See
+//! [`Quadtree::set_split_policy()`].
+//!
+//! [`SplitPolicy`]: trait.SplitPolicy.html
+//! [`Quadtree::set_split_policy()`]: ../struct.Quadtree.html#method.set_split_policy
+
+use std::fmt::Debug;
+
+/// Decides whether a leaf should be subdivided before it takes on another entry.
+///
+/// ```
+/// use quadtree_rs::{split::SplitPolicy, Quadtree};
+///
+/// // Keep the top few levels of the tree coarse, and subdivide everything below them.
+/// #[derive(Debug)]
+/// struct CoarseTop;
+///
+/// impl SplitPolicy for CoarseTop {
+///     fn should_split(&self, level: usize, len: usize) -> bool {
+///         level >= 3 || len >= 64
+///     }
+/// }
+///
+/// let mut qt = Quadtree::<u32, u8>::new(10);
+/// qt.set_split_policy(CoarseTop);
+/// qt.extend((0..100).map(|i| ((i * 10, i), 0)));
+/// assert_eq!(qt.len(), 100);
+/// ```
+pub trait SplitPolicy: Debug + Send + Sync {
+    /// Whether a leaf `level` levels below the root (which is at level zero), and which already
+    /// holds `len` entries, should be subdivided before another entry is added to it.
+    ///
+    /// This is only asked of leaves above the bottom depth, and only on behalf of entries which
+    /// would fit in one of the leaf's subquadrants.
+    fn should_split(&self, level: usize, len: usize) -> bool;
+}
+
+/// Subdivides cells as far as an entry's region allows, down to the bottom depth. This is the
+/// default.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DepthLimited;
+
+impl SplitPolicy for DepthLimited {
+    fn should_split(&self, _level: usize, _len: usize) -> bool {
+        true
+    }
+}
+
+/// Subdivides a leaf only once it holds some number of entries. See
+/// [`Quadtree::set_max_items_per_node()`].
+///
+/// [`Quadtree::set_max_items_per_node()`]: ../struct.Quadtree.html#method.set_max_items_per_node
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CapacityLimited {
+    /// The number of entries a leaf may hold before it's subdivided.
+    pub max_items_per_node: usize,
+}
+
+impl SplitPolicy for CapacityLimited {
+    fn should_split(&self, _level: usize, len: usize) -> bool {
+        len >= self.max_items_per_node
+    }
+}
+
+/// Subdivides a leaf once it holds some number of entries, but never below some level.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Hybrid {
+    /// The number of entries a leaf may hold before it's subdivided.
+    pub max_items_per_node: usize,
+    /// The lowest level which cells may be subdivided into. The root is at level zero.
+    pub max_level: usize,
+}
+
+impl SplitPolicy for Hybrid {
+    fn should_split(&self, level: usize, len: usize) -> bool {
+        level < self.max_level && len >= self.max_items_per_node
+    }
+}
+
+/// Never subdivides anything. Every entry is held by the root, and every query looks at every
+/// entry.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct NeverSplit;
+
+impl SplitPolicy for NeverSplit {
+    fn should_split(&self, _level: usize, _len: usize) -> bool {
+        false
+    }
+}
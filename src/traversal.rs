@@ -12,22 +12,52 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use {crate::area::Area, num::PrimInt, std::default::Default};
+use {
+    crate::{
+        area::{Area, AreaBuilder},
+        shape::Shape,
+    },
+    num::PrimInt,
+    std::default::Default,
+};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub(crate) enum Traversal {
     Overlapping,
     Strict,
+    Containing,
+    Exact,
 }
 
 impl Traversal {
-    pub(crate) fn eval<U>(self, bounding_box: Area<U>, query_region: Area<U>) -> bool
+    pub(crate) fn eval<U>(self, bounding_box: Area<U>, query_shape: Shape<U>) -> bool
     where
         U: PrimInt + Default,
     {
         match self {
-            Traversal::Overlapping => query_region.intersects(bounding_box),
-            Traversal::Strict => query_region.contains(bounding_box),
+            Traversal::Overlapping => query_shape.intersects(bounding_box),
+            Traversal::Strict => query_shape.contains(bounding_box),
+            Traversal::Containing => query_shape.contained_by(bounding_box),
+            Traversal::Exact => query_shape.is(bounding_box),
+        }
+    }
+
+    // The shape which subquadrants have to intersect to be worth searching. A region containing
+    // the query shape is held by a node which contains every one of its cells, so for containment
+    // it suffices to follow a single cell down the tree. A region equal to the query shape contains
+    // it, so the same goes for exact matches.
+    pub(crate) fn search_shape<U>(self, query_shape: Shape<U>) -> Shape<U>
+    where
+        U: PrimInt + Default,
+    {
+        match self {
+            Traversal::Overlapping | Traversal::Strict => query_shape,
+            Traversal::Containing | Traversal::Exact => Shape::Rect(
+                AreaBuilder::default()
+                    .anchor(query_shape.interior_pt())
+                    .build()
+                    .expect("Unexpected error in Traversal::search_shape()."),
+            ),
         }
     }
 }
@@ -12,5 +12,235 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-// The hashmap storage type for qtinners. Made explicit here for brevity in other files.
-pub(crate) type StoreType<U, V> = std::collections::HashMap<u64, crate::entry::Entry<U, V>>;
+use {
+    crate::{area::Area, entry::Entry},
+    num::PrimInt,
+    std::{
+        collections::{btree_map, hash_map, BTreeMap, HashMap},
+        fmt,
+        hash::BuildHasher,
+    },
+};
+
+// The storage type for qtinners, mapping handles to entries. Made explicit here for brevity in
+// other files.
+//
+// The hashed backend is the default. The ordered backend keeps entries sorted by handle (and so by
+// insertion order), which makes every walk over the store deterministic. It still carries a hash
+// builder so that `Quadtree::hasher()` has something to return.
+#[derive(Clone)]
+pub(crate) enum StoreType<U, V, S>
+where
+    U: PrimInt + Default,
+{
+    Hashed(HashMap<u64, Entry<U, V>, S>),
+    Ordered(BTreeMap<u64, Entry<U, V>>, S),
+}
+
+impl<U, V, S> StoreType<U, V, S>
+where
+    U: PrimInt + Default,
+    S: BuildHasher,
+{
+    pub(crate) fn hashed(hash_builder: S) -> Self {
+        StoreType::Hashed(HashMap::with_hasher(hash_builder))
+    }
+
+    pub(crate) fn ordered(hash_builder: S) -> Self {
+        StoreType::Ordered(BTreeMap::new(), hash_builder)
+    }
+
+    pub(crate) fn is_ordered(&self) -> bool {
+        match self {
+            StoreType::Hashed(_) => false,
+            StoreType::Ordered(..) => true,
+        }
+    }
+
+    pub(crate) fn hasher(&self) -> &S {
+        match self {
+            StoreType::Hashed(map) => map.hasher(),
+            StoreType::Ordered(_, hash_builder) => hash_builder,
+        }
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        match self {
+            StoreType::Hashed(map) => map.len(),
+            StoreType::Ordered(map, _) => map.len(),
+        }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    // An ordered store allocates per entry, so it never has room to spare.
+    pub(crate) fn capacity(&self) -> usize {
+        match self {
+            StoreType::Hashed(map) => map.capacity(),
+            StoreType::Ordered(map, _) => map.len(),
+        }
+    }
+
+    pub(crate) fn reserve(&mut self, additional: usize) {
+        if let StoreType::Hashed(map) = self {
+            map.reserve(additional);
+        }
+    }
+
+    pub(crate) fn shrink_to_fit(&mut self) {
+        if let StoreType::Hashed(map) = self {
+            map.shrink_to_fit();
+        }
+    }
+
+    pub(crate) fn contains_key(&self, handle: &u64) -> bool {
+        self.get(handle).is_some()
+    }
+
+    pub(crate) fn get(&self, handle: &u64) -> Option<&Entry<U, V>> {
+        match self {
+            StoreType::Hashed(map) => map.get(handle),
+            StoreType::Ordered(map, _) => map.get(handle),
+        }
+    }
+
+    pub(crate) fn get_mut(&mut self, handle: &u64) -> Option<&mut Entry<U, V>> {
+        match self {
+            StoreType::Hashed(map) => map.get_mut(handle),
+            StoreType::Ordered(map, _) => map.get_mut(handle),
+        }
+    }
+
+    pub(crate) fn insert(&mut self, handle: u64, entry: Entry<U, V>) -> Option<Entry<U, V>> {
+        match self {
+            StoreType::Hashed(map) => map.insert(handle, entry),
+            StoreType::Ordered(map, _) => map.insert(handle, entry),
+        }
+    }
+
+    pub(crate) fn remove(&mut self, handle: &u64) -> Option<Entry<U, V>> {
+        match self {
+            StoreType::Hashed(map) => map.remove(handle),
+            StoreType::Ordered(map, _) => map.remove(handle),
+        }
+    }
+
+    pub(crate) fn clear(&mut self) {
+        match self {
+            StoreType::Hashed(map) => map.clear(),
+            StoreType::Ordered(map, _) => map.clear(),
+        }
+    }
+
+    // Walks the entries in ascending handle order if the store is ordered, or in arbitrary order
+    // otherwise.
+    pub(crate) fn values(&self) -> Box<dyn Iterator<Item = &Entry<U, V>> + '_> {
+        match self {
+            StoreType::Hashed(map) => Box::new(map.values()),
+            StoreType::Ordered(map, _) => Box::new(map.values()),
+        }
+    }
+
+    // Walks the entries in the same order as values(), without boxing, so that `IterMut` can
+    // wrap it directly.
+    pub(crate) fn values_mut(&mut self) -> EntriesMut<'_, U, V> {
+        match self {
+            StoreType::Hashed(map) => EntriesMut::Hashed(map.values_mut()),
+            StoreType::Ordered(map, _) => EntriesMut::Ordered(map.values_mut()),
+        }
+    }
+
+    pub(crate) fn into_values(self) -> Vec<Entry<U, V>> {
+        match self {
+            StoreType::Hashed(map) => map.into_values().collect(),
+            StoreType::Ordered(map, _) => map.into_values().collect(),
+        }
+    }
+
+    // Builds a store of the same kind holding @f's result for each entry, under the same handle and
+    // region. An ordered store hands its entries to @f in ascending handle order.
+    pub(crate) fn map_values<W, F>(self, mut f: F) -> StoreType<U, W, S>
+    where
+        F: FnMut(Area<U>, V) -> W,
+        S: Clone,
+    {
+        let mut map_entry = |(handle, entry): (u64, Entry<U, V>)| {
+            let (region, value) = entry.into_parts();
+            (handle, Entry::new((region, f(region, value)), handle))
+        };
+        match self {
+            StoreType::Hashed(map) => {
+                let mut mapped = HashMap::with_capacity_and_hasher(map.len(), map.hasher().clone());
+                mapped.extend(map.into_iter().map(&mut map_entry));
+                StoreType::Hashed(mapped)
+            }
+            StoreType::Ordered(map, hash_builder) => {
+                StoreType::Ordered(map.into_iter().map(map_entry).collect(), hash_builder)
+            }
+        }
+    }
+}
+
+// A mutable walk over the entries of a store. See `StoreType::values_mut()`.
+#[derive(Debug)]
+pub(crate) enum EntriesMut<'a, U, V>
+where
+    U: PrimInt + Default,
+{
+    Hashed(hash_map::ValuesMut<'a, u64, Entry<U, V>>),
+    Ordered(btree_map::ValuesMut<'a, u64, Entry<U, V>>),
+}
+
+impl<'a, U, V> Iterator for EntriesMut<'a, U, V>
+where
+    U: PrimInt + Default,
+{
+    type Item = &'a mut Entry<U, V>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            EntriesMut::Hashed(values) => values.next(),
+            EntriesMut::Ordered(values) => values.next(),
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match self {
+            EntriesMut::Hashed(values) => values.size_hint(),
+            EntriesMut::Ordered(values) => values.size_hint(),
+        }
+    }
+}
+
+impl<U, V, S> fmt::Debug for StoreType<U, V, S>
+where
+    U: PrimInt + Default + fmt::Debug,
+    V: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StoreType::Hashed(map) => map.fmt(f),
+            StoreType::Ordered(map, _) => map.fmt(f),
+        }
+    }
+}
+
+// Two stores are equal if they map the same handles to the same entries, whichever backends they
+// use.
+impl<U, V, S> PartialEq for StoreType<U, V, S>
+where
+    U: PrimInt + Default,
+    V: PartialEq,
+    S: BuildHasher,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len()
+            && self
+                .values()
+                .all(|e| other.get(&e.handle()).is_some_and(|o| e == o))
+    }
+}
@@ -0,0 +1,185 @@
+// Copyright 2019 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A read-only view of part of a quadtree.
+//!
+//! A [`QuadtreeView`] borrows a [`Quadtree`] and only answers for the entries whose regions
+//! overlap the region it was created with, so that a subsystem can be handed its part of the
+//! world without copying anything out of the quadtree.
+//!
+//! ```
+//! use quadtree_rs::{area::AreaBuilder, Quadtree};
+//!
+//! let mut qt = Quadtree::<u32, char>::new(4);
+//! qt.extend(vec![((1, 1), 'a'), ((6, 6), 'b'), ((12, 12), 'c')]);
+//!
+//! let west = AreaBuilder::default()
+//!     .anchor((0, 0).into())
+//!     .dimensions((8, 8))
+//!     .build().unwrap();
+//! let view = qt.view(west);
+//! assert_eq!(view.len(), 2);
+//!
+//! // Queries never reach beyond the view, however large they are.
+//! let everything = AreaBuilder::default()
+//!     .anchor((0, 0).into())
+//!     .dimensions((16, 16))
+//!     .build().unwrap();
+//! assert_eq!(view.query(everything).count(), 2);
+//! assert!(view.query_pt((12, 12).into()).next().is_none());
+//! ```
+//!
+//! [`QuadtreeView`]: struct.QuadtreeView.html
+//! [`Quadtree`]: ../struct.Quadtree.html
+
+use {
+    crate::{area::Area, entry::Entry, point::Point, Quadtree},
+    num::PrimInt,
+    std::{collections::hash_map::RandomState, hash::BuildHasher},
+};
+
+/// A read-only view of the entries of a [`Quadtree`] which overlap some region.
+///
+/// This struct is created by the [`view`] method on [`Quadtree`]. See the [module-level
+/// documentation] for more.
+///
+/// [`view`]: ../struct.Quadtree.html#method.view
+/// [`Quadtree`]: ../struct.Quadtree.html
+/// [module-level documentation]: index.html
+#[derive(Debug)]
+pub struct QuadtreeView<'a, U, V, S = RandomState>
+where
+    U: PrimInt + Default,
+{
+    pub(crate) tree: &'a Quadtree<U, V, S>,
+    pub(crate) region: Area<U>,
+}
+
+impl<'a, U, V, S> QuadtreeView<'a, U, V, S>
+where
+    U: PrimInt + Default,
+    S: BuildHasher,
+{
+    // pub
+
+    /// The region which the view covers.
+    pub fn region(&self) -> Area<U> {
+        self.region
+    }
+
+    /// The number of entries in view.
+    pub fn len(&self) -> usize {
+        self.tree.count_in_region(self.region)
+    }
+
+    /// Whether there are no entries in view.
+    pub fn is_empty(&self) -> bool {
+        !self.tree.intersects_any(self.region)
+    }
+
+    /// Whether or not the view covers a region.
+    pub fn contains(&self, area: Area<U>) -> bool {
+        self.region.contains(area)
+    }
+
+    /// The entry with the given handle, if it's in view.
+    /// See [`Quadtree::get()`].
+    ///
+    /// [`Quadtree::get()`]: ../struct.Quadtree.html#method.get
+    pub fn get(&self, handle: u64) -> Option<&'a Entry<U, V>> {
+        let region = self.region;
+        self.tree
+            .get(handle)
+            .filter(move |entry| entry.area().intersects(region))
+    }
+
+    /// Every entry in view, in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = &'a Entry<U, V>> + 'a {
+        self.tree.query(self.region)
+    }
+
+    /// The regions of every entry in view.
+    pub fn regions(&self) -> impl Iterator<Item = Area<U>> + 'a {
+        self.iter().map(Entry::area)
+    }
+
+    /// The values of every entry in view.
+    pub fn values(&self) -> impl Iterator<Item = &'a V> + 'a {
+        self.iter().map(Entry::value_ref)
+    }
+
+    /// The entries in view whose regions intersect `area`.
+    /// See [`Quadtree::query()`].
+    ///
+    /// [`Quadtree::query()`]: ../struct.Quadtree.html#method.query
+    pub fn query(&self, area: Area<U>) -> impl Iterator<Item = &'a Entry<U, V>> + 'a {
+        // Rectangles which each intersect both of two intersecting rectangles also intersect
+        // what those two have in common, so there's no need to check against the view again.
+        let tree = self.tree;
+        self.region
+            .intersection(area)
+            .into_iter()
+            .flat_map(move |area| tree.query(area))
+    }
+
+    /// Alias for [`.query()`] which expects a [`Point`] instead of an [`Area`].
+    ///
+    /// [`.query()`]: #method.query
+    /// [`Point`]: ../point/struct.Point.html
+    /// [`Area`]: ../area/struct.Area.html
+    pub fn query_pt(&self, point: Point<U>) -> impl Iterator<Item = &'a Entry<U, V>> + 'a {
+        self.query(Quadtree::<U, V, S>::area_at_pt(point))
+    }
+
+    /// The entries in view whose regions are totally contained by `area`.
+    /// See [`Quadtree::query_strict()`].
+    ///
+    /// [`Quadtree::query_strict()`]: ../struct.Quadtree.html#method.query_strict
+    pub fn query_strict(&self, area: Area<U>) -> impl Iterator<Item = &'a Entry<U, V>> + 'a {
+        self.query(area)
+            .filter(move |entry| area.contains(entry.area()))
+    }
+
+    /// Counts the entries in view whose regions intersect `area`.
+    /// See [`Quadtree::count_in_region()`].
+    ///
+    /// [`Quadtree::count_in_region()`]: ../struct.Quadtree.html#method.count_in_region
+    pub fn count_in_region(&self, area: Area<U>) -> usize {
+        self.region
+            .intersection(area)
+            .map_or(0, |area| self.tree.count_in_region(area))
+    }
+
+    /// Whether any entry in view intersects `area`.
+    /// See [`Quadtree::intersects_any()`].
+    ///
+    /// [`Quadtree::intersects_any()`]: ../struct.Quadtree.html#method.intersects_any
+    pub fn intersects_any(&self, area: Area<U>) -> bool {
+        self.region
+            .intersection(area)
+            .is_some_and(|area| self.tree.intersects_any(area))
+    }
+}
+
+// A view only holds a reference, so it can be copied whatever it's a view of.
+impl<U, V, S> Clone for QuadtreeView<'_, U, V, S>
+where
+    U: PrimInt + Default,
+{
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<U, V, S> Copy for QuadtreeView<'_, U, V, S> where U: PrimInt + Default {}
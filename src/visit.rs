@@ -0,0 +1,169 @@
+// Copyright 2019 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Walking a quadtree's cells with a callback.
+//!
+//! [`Quadtree::visit()`] hands each cell of the tree to a visitor, along with the entries held
+//! there, and the visitor decides whether the cells beneath it are worth visiting. Queries of any
+//! shape, and statistics about the tree, can be built on top of it without the crate having to
+//! know about them.
+//!
+//! ```
+//! use quadtree_rs::{visit::VisitControl, Quadtree};
+//!
+//! let mut qt = Quadtree::<u32, u32>::new(4);
+//! qt.extend(vec![((1, 1), 10), ((2, 3), 20), ((13, 12), 30)]);
+//!
+//! // Sum the values in the western half, without looking at any cell which lies to the east.
+//! let mut total = 0;
+//! qt.visit(|node, items| {
+//!     if node.region().left_edge() >= 8 {
+//!         return VisitControl::Skip;
+//!     }
+//!     total += items
+//!         .iter()
+//!         .filter(|(region, _)| region.left_edge() < 8)
+//!         .map(|(_, value)| **value)
+//!         .sum::<u32>();
+//!     VisitControl::Continue
+//! });
+//! assert_eq!(total, 30);
+//! ```
+//!
+//! [`Quadtree::visit()`]: ../struct.Quadtree.html#method.visit
+
+use {
+    crate::{
+        area::Area,
+        qtinner::{NodeId, QTInner},
+        Quadtree,
+    },
+    num::PrimInt,
+    std::{default::Default, hash::BuildHasher},
+};
+
+/// What a visitor passed to [`Quadtree::visit()`] knows about the cell it's looking at.
+///
+/// [`Quadtree::visit()`]: ../struct.Quadtree.html#method.visit
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct NodeInfo<U>
+where
+    U: PrimInt + Default,
+{
+    region: Area<U>,
+    bounds: Area<U>,
+    depth: usize,
+    len: usize,
+}
+
+impl<U> NodeInfo<U>
+where
+    U: PrimInt + Default,
+{
+    /// The area which the cell covers.
+    pub fn region(&self) -> Area<U> {
+        self.region
+    }
+
+    /// The area which every region held at or beneath the cell lies within. This is the cell's
+    /// region, unless the quadtree is [loose].
+    ///
+    /// [loose]: ../struct.Quadtree.html#method.set_looseness
+    pub fn bounds(&self) -> Area<U> {
+        self.bounds
+    }
+
+    /// How many levels below the root the cell is. The root's cell is at depth zero.
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// The number of entries held at or beneath the cell.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether there are no entries at or beneath the cell. Never true of a visited cell.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+/// What [`Quadtree::visit()`] should do after a visitor has looked at a cell.
+///
+/// [`Quadtree::visit()`]: ../struct.Quadtree.html#method.visit
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VisitControl {
+    /// Go on to visit the cells beneath this one.
+    Continue,
+    /// Leave out the cells beneath this one, but go on with the rest of the tree.
+    Skip,
+    /// Stop visiting altogether.
+    Stop,
+}
+
+impl<U, V, S> Quadtree<U, V, S>
+where
+    U: PrimInt + Default,
+    S: BuildHasher,
+{
+    // pub
+
+    /// Walks the quadtree's cells depth-first from the root, handing each to `visitor` along
+    /// with the `(region, value)` pairs of the entries held there, and letting it choose whether
+    /// to go on beneath it. See the [module-level documentation] for an example.
+    ///
+    /// Every entry is held by exactly one cell, and everything held beneath a cell lies within
+    /// its [`bounds()`]; a visitor which returns [`Skip`] for a cell whose bounds can't matter
+    /// to it misses nothing. Cells with no entries at or beneath them are never visited. The
+    /// cells beneath each cell are visited in the order north-east, north-west, south-east,
+    /// south-west.
+    ///
+    /// [module-level documentation]: visit/index.html
+    /// [`bounds()`]: visit/struct.NodeInfo.html#method.bounds
+    /// [`Skip`]: visit/enum.VisitControl.html#variant.Skip
+    pub fn visit<F>(&self, mut visitor: F)
+    where
+        F: FnMut(NodeInfo<U>, &[(&Area<U>, &V)]) -> VisitControl,
+    {
+        let root_depth = self.inner.root().depth();
+        let mut stack: Vec<NodeId> = vec![QTInner::<U>::ROOT];
+        while let Some(id) = stack.pop() {
+            let node = self.inner.node(id);
+            if node.len() == 0 {
+                continue;
+            }
+            let items: Vec<(&Area<U>, &V)> = node
+                .handles()
+                .iter()
+                .filter_map(|handle| self.store.get(handle))
+                .map(|entry| (entry.area_ref(), entry.value_ref()))
+                .collect();
+            let info = NodeInfo {
+                region: node.region(),
+                bounds: node.bounds(),
+                depth: root_depth - node.depth(),
+                len: node.len(),
+            };
+            match visitor(info, &items) {
+                VisitControl::Continue => {
+                    // Reversed, so that they come off the stack in order.
+                    stack.extend(node.subquadrants().iter().flatten().rev());
+                }
+                VisitControl::Skip => {}
+                VisitControl::Stop => return,
+            }
+        }
+    }
+}
@@ -0,0 +1,299 @@
+// Copyright 2019 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Reading and writing regions as [Well-Known Text], behind the `wkt` feature.
+//!
+//! A region is written as a `POINT` if it's a single cell, and as a rectangular `POLYGON`
+//! otherwise; see [`Area::to_wkt()`]. Any `POINT` or `POLYGON` can be read back, a polygon as its
+//! bounding box; see [`Area::from_wkt()`]. For whole files, such as a PostGIS table dumped with
+//! `ST_AsText()`, [`Quadtree::read_wkt()`] and [`Quadtree::write_wkt()`] read and write one
+//! geometry per line, each optionally followed by a tab and a value.
+//!
+//! ```
+//! use quadtree_rs::Quadtree;
+//!
+//! let dump = "POINT (3 4)\tlamp\nPOLYGON ((0 0, 10 0, 10 2, 0 2, 0 0))\twall\n";
+//! let qt = Quadtree::<i32, String>::read_wkt(dump.as_bytes()).unwrap();
+//!
+//! let wall = qt.get(1).unwrap();
+//! assert_eq!(wall.value_ref(), "wall");
+//! assert_eq!((wall.width(), wall.height()), (10, 2));
+//!
+//! let mut out: Vec<u8> = vec![];
+//! qt.write_wkt(&mut out).unwrap();
+//! assert_eq!(String::from_utf8(out).unwrap(), dump);
+//! ```
+//!
+//! [Well-Known Text]: https://en.wikipedia.org/wiki/Well-known_text_representation_of_geometry
+//! [`Area::to_wkt()`]: ../area/struct.Area.html#method.to_wkt
+//! [`Area::from_wkt()`]: ../area/struct.Area.html#method.from_wkt
+//! [`Quadtree::read_wkt()`]: ../struct.Quadtree.html#method.read_wkt
+//! [`Quadtree::write_wkt()`]: ../struct.Quadtree.html#method.write_wkt
+
+use {
+    crate::{area::Area, point::Point, Quadtree},
+    num::PrimInt,
+    std::{
+        default::Default,
+        fmt::Display,
+        hash::BuildHasher,
+        io::{self, BufRead, Write},
+        str::FromStr,
+    },
+};
+
+impl<U> Area<U>
+where
+    U: PrimInt + Default,
+{
+    /// The region as Well-Known Text: a `POINT` at its anchor if it's a single cell, or else a
+    /// `POLYGON` tracing its edges clockwise from the anchor. See the [`wkt`] module.
+    /// ```
+    /// use quadtree_rs::{area::AreaBuilder, point::Point};
+    ///
+    /// let cell = AreaBuilder::default().anchor(Point {x: -1, y: 2}).build().unwrap();
+    /// assert_eq!(cell.to_wkt(), "POINT (-1 2)");
+    ///
+    /// let region = AreaBuilder::default()
+    ///     .anchor(Point {x: 1, y: 2})
+    ///     .dimensions((3, 4))
+    ///     .build().unwrap();
+    /// assert_eq!(region.to_wkt(), "POLYGON ((1 2, 4 2, 4 6, 1 6, 1 2))");
+    /// ```
+    ///
+    /// [`wkt`]: ../wkt/index.html
+    pub fn to_wkt(&self) -> String {
+        let c = |u: U| u.to_i128().expect("Coordinates should fit in an i128.");
+        let (x0, y0) = (c(self.left_edge()), c(self.top_edge()));
+        if self.width() == U::one() && self.height() == U::one() {
+            return format!("POINT ({} {})", x0, y0);
+        }
+        // The far edges might not be representable in U, but they are in an i128.
+        let (x1, y1) = (x0 + c(self.width()), y0 + c(self.height()));
+        format!(
+            "POLYGON (({x0} {y0}, {x1} {y0}, {x1} {y1}, {x0} {y1}, {x0} {y0}))",
+            x0 = x0,
+            y0 = y0,
+            x1 = x1,
+            y1 = y1
+        )
+    }
+
+    /// Reads a region back from a Well-Known Text `POINT` or `POLYGON`, or explains why it can't.
+    /// See the [`wkt`] module.
+    ///
+    /// A point is read as the single cell anchored there, and a polygon as its bounding box, so
+    /// any region written by [`.to_wkt()`] reads back unchanged. Keywords are case-insensitive,
+    /// an EWKT `SRID=...;` prefix is skipped, and the third and fourth ordinates of `Z`, `M` and
+    /// `ZM` geometries are ignored. Coordinates must be whole numbers which fit in `U`.
+    /// ```
+    /// use quadtree_rs::{area::Area, point::Point};
+    ///
+    /// let region = Area::<u32>::from_wkt("SRID=4326;polygon((2 3, 5 1, 6 4, 2 3))").unwrap();
+    /// assert_eq!(region.anchor(), Point {x: 2, y: 1});
+    /// assert_eq!((region.width(), region.height()), (4, 3));
+    ///
+    /// assert_eq!(Area::<u32>::from_wkt("POINT Z (7 8 9)").unwrap().anchor(), Point {x: 7, y: 8});
+    ///
+    /// assert!(Area::<u32>::from_wkt("POINT EMPTY").is_err());
+    /// assert!(Area::<u32>::from_wkt("POINT (-7 8)").is_err());
+    /// assert!(Area::<u32>::from_wkt("LINESTRING (0 0, 1 1)").is_err());
+    /// ```
+    ///
+    /// [`wkt`]: ../wkt/index.html
+    /// [`.to_wkt()`]: #method.to_wkt
+    pub fn from_wkt(wkt: &str) -> Result<Self, String> {
+        let text = wkt.trim();
+        let text = match text.get(..5) {
+            Some(prefix) if prefix.eq_ignore_ascii_case("SRID=") => text
+                .split_once(';')
+                .map(|(_, geometry)| geometry.trim_start())
+                .ok_or_else(|| format!("{:?} has an SRID but no geometry.", wkt))?,
+            _ => text,
+        };
+        let open = text
+            .find('(')
+            .ok_or_else(|| format!("{:?} has no coordinates.", wkt))?;
+        let mut tag = text[..open].split_whitespace();
+        let nesting = match tag.next().map(str::to_ascii_uppercase).as_deref() {
+            Some("POINT") => 1,
+            Some("POLYGON") => 2,
+            _ => return Err(format!("{:?} is neither a POINT nor a POLYGON.", wkt)),
+        };
+        match tag.next().map(str::to_ascii_uppercase).as_deref() {
+            None | Some("Z") | Some("M") | Some("ZM") if tag.next().is_none() => {}
+            _ => return Err(format!("{:?} has an unknown dimension.", wkt)),
+        }
+
+        let body = &text[open..];
+        if !Self::nests(body, nesting) {
+            return Err(format!("{:?} has mismatched parentheses.", wkt));
+        }
+        let mut positions = body
+            .split(['(', ')', ','])
+            .map(str::trim)
+            .filter(|position| !position.is_empty())
+            .map(|position| {
+                let mut ordinates = position.split_whitespace().map(Self::coordinate);
+                match (ordinates.next(), ordinates.next(), ordinates.count()) {
+                    (Some(x), Some(y), 0..=2) => Ok(Point { x: x?, y: y? }),
+                    _ => Err(format!("{:?} is not a position.", position)),
+                }
+            });
+
+        let first = positions
+            .next()
+            .ok_or_else(|| format!("{:?} has no coordinates.", wkt))??;
+        if nesting == 1 {
+            return match positions.next() {
+                None => Area::new(first, (U::one(), U::one())),
+                Some(_) => Err(format!("{:?} has more than one position.", wkt)),
+            };
+        }
+        let (near, far) = positions.try_fold((first, first), |(near, far), pt| {
+            let pt = pt?;
+            Ok::<_, String>((
+                Point {
+                    x: near.x().min(pt.x()),
+                    y: near.y().min(pt.y()),
+                },
+                Point {
+                    x: far.x().max(pt.x()),
+                    y: far.y().max(pt.y()),
+                },
+            ))
+        })?;
+        match (
+            far.x().checked_sub(&near.x()),
+            far.y().checked_sub(&near.y()),
+        ) {
+            (Some(width), Some(height)) => Area::new(near, (width, height)),
+            _ => Err(format!("{:?} is too large for this coordinate type.", wkt)),
+        }
+    }
+
+    // fn
+
+    // Whether the parentheses of @body are balanced, and nested exactly @depth deep at their
+    // deepest, with nothing after the last one closes.
+    fn nests(body: &str, depth: usize) -> bool {
+        let (mut open, mut deepest) = (0_usize, 0);
+        for (i, c) in body.char_indices() {
+            match c {
+                '(' => {
+                    open += 1;
+                    deepest = deepest.max(open);
+                }
+                ')' => match open.checked_sub(1) {
+                    Some(0) => return deepest == depth && body[i + 1..].trim().is_empty(),
+                    Some(n) => open = n,
+                    None => return false,
+                },
+                _ => {}
+            }
+        }
+        false
+    }
+
+    // A whole-numbered ordinate, which may be written with a fractional part of zero.
+    fn coordinate(ordinate: &str) -> Result<U, String> {
+        let out_of_range = || format!("{:?} is not a coordinate in range.", ordinate);
+        match U::from_str_radix(ordinate, 10) {
+            Ok(u) => Ok(u),
+            Err(_) => match ordinate.parse::<f64>() {
+                Ok(f) if f.fract() == 0.0 => U::from(f).ok_or_else(out_of_range),
+                _ => Err(out_of_range()),
+            },
+        }
+    }
+}
+
+impl<U, V> Quadtree<U, V>
+where
+    U: PrimInt + Default,
+    V: FromStr,
+{
+    // pub
+
+    /// Reads one Well-Known Text geometry per line from `reader` into a new quadtree just large
+    /// enough to hold them. See the [`wkt`] module.
+    ///
+    /// Each line holds a `POINT` or `POLYGON`, read as by [`Area::from_wkt()`], which may be
+    /// followed by a tab and the text of its value. Lines without a tab are given the value parsed
+    /// from the empty string. Blank lines are skipped. The `n`th geometry is given the handle `n`,
+    /// just as with [`.from_regions()`].
+    ///
+    /// Lines which don't parse are rejected with an error of kind [`InvalidData`], naming the
+    /// line.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the regions are too spread out for a square covering them to be representable in
+    /// `U`.
+    ///
+    /// [`wkt`]: wkt/index.html
+    /// [`Area::from_wkt()`]: area/struct.Area.html#method.from_wkt
+    /// [`.from_regions()`]: #method.from_regions
+    /// [`InvalidData`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.InvalidData
+    pub fn read_wkt<R: BufRead>(reader: R) -> io::Result<Self> {
+        let mut items = vec![];
+        for (i, line) in reader.lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let invalid = |msg: String| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Line {}: {}", i + 1, msg),
+                )
+            };
+            let (geometry, value) = line.split_once('\t').unwrap_or((&line, ""));
+            let region = Area::from_wkt(geometry).map_err(invalid)?;
+            let value = value
+                .parse()
+                .map_err(|_| invalid(format!("{:?} is not a value.", value)))?;
+            items.push((region, value));
+        }
+        Ok(Self::from_regions(items))
+    }
+}
+
+impl<U, V, S> Quadtree<U, V, S>
+where
+    U: PrimInt + Default,
+    V: Display,
+    S: BuildHasher,
+{
+    // pub
+
+    /// Writes every entry to `writer` as a line of Well-Known Text, as by [`Area::to_wkt()`],
+    /// followed by a tab and its value. See the [`wkt`] module.
+    ///
+    /// Entries are written in the order of their handles, so the output is stable across runs and
+    /// [`.read_wkt()`] hands the entries of a quadtree with no gaps in its handles back their
+    /// original handles. Values which display with a tab or a newline won't read back.
+    ///
+    /// [`Area::to_wkt()`]: area/struct.Area.html#method.to_wkt
+    /// [`wkt`]: wkt/index.html
+    /// [`.read_wkt()`]: #method.read_wkt
+    pub fn write_wkt<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        let entries = self.entries_by_handle();
+
+        for entry in entries {
+            writeln!(writer, "{}\t{}", entry.area().to_wkt(), entry.value_ref())?;
+        }
+        writer.flush()
+    }
+}
@@ -0,0 +1,199 @@
+// Copyright 2019 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![cfg(feature = "aggregate")]
+
+mod util; // For area.
+
+// For testing AggregatedQuadtree.
+mod aggregate_tests {
+    use {
+        crate::util::area,
+        quadtree_rs::{
+            aggregate::{Aggregate, AggregatedQuadtree, Count, Visit, Visited},
+            area::Area,
+            Quadtree,
+        },
+    };
+
+    #[derive(Clone, Debug, PartialEq)]
+    struct Sum {
+        count: usize,
+        total: u64,
+    }
+
+    impl Aggregate<u32, u32> for Sum {
+        fn empty() -> Self {
+            Sum { count: 0, total: 0 }
+        }
+
+        fn of(_: Area<u32>, value: &u32) -> Self {
+            Sum {
+                count: 1,
+                total: u64::from(*value),
+            }
+        }
+
+        fn combine(&self, other: &Self) -> Self {
+            Sum {
+                count: self.count + other.count,
+                total: self.total + other.total,
+            }
+        }
+    }
+
+    // The sum of the entries of @qt which @pred picks out.
+    fn sum_where<F>(qt: &Quadtree<u32, u32>, pred: F) -> Sum
+    where
+        F: Fn(Area<u32>) -> bool,
+    {
+        qt.iter()
+            .filter(|e| pred(e.area()))
+            .fold(Sum::empty(), |acc, e| {
+                acc.combine(&Sum::of(e.area(), e.value_ref()))
+            })
+    }
+
+    // Checks the summary of every subtree of @qt, which has to be tight, against a scan of the
+    // entries which lie within its bounds.
+    fn check_summaries(qt: &AggregatedQuadtree<u32, u32, Sum>) {
+        let tree = qt.as_quadtree();
+        debug_assert_eq!(qt.aggregate(), &sum_where(tree, |_| true));
+
+        let mut entries = 0;
+        for visited in qt.traverse(|bounds, aggregate| {
+            debug_assert_eq!(aggregate, &sum_where(tree, |r| bounds.contains(r)));
+            Visit::Descend
+        }) {
+            match visited {
+                Visited::Entry(_) => entries += 1,
+                Visited::Subtree { .. } => unreachable!(),
+            }
+        }
+        debug_assert_eq!(entries, tree.len());
+    }
+
+    #[test]
+    fn summaries_follow_changes() {
+        let mut qt = AggregatedQuadtree::<u32, u32, Sum>::new(5);
+        let handles: Vec<u64> = (0..80)
+            .map(|i| {
+                qt.insert(area(i * 7 % 29, i * 11 % 30, 1 + i % 3, 1 + i % 2), i)
+                    .unwrap()
+            })
+            .collect();
+        check_summaries(&qt);
+
+        debug_assert!(qt.relocate(handles[3], area(20, 20, 4, 4)));
+        debug_assert!(qt.relocate(handles[4], area(0, 0, 1, 1)));
+        check_summaries(&qt);
+
+        debug_assert_eq!(qt.remove(handles[5]).map(|(_, v)| v), Some(5));
+        debug_assert!(qt.delete_by_handle(handles[5]).is_none());
+        debug_assert!(qt.delete(area(0, 0, 8, 8)).count() > 0);
+        check_summaries(&qt);
+
+        qt.modify(area(10, 10, 10, 10), |_, v| *v += 100);
+        check_summaries(&qt);
+        qt.modify_all(|_, v| *v *= 2);
+        check_summaries(&qt);
+        qt.retain(|v| *v % 3 == 0);
+        check_summaries(&qt);
+
+        qt.reset();
+        debug_assert_eq!(qt.aggregate(), &Sum::empty());
+        check_summaries(&qt);
+    }
+
+    #[test]
+    fn from_quadtree() {
+        let mut tree = Quadtree::<u32, u32>::new(4);
+        tree.set_max_items_per_node(Some(2));
+        tree.extend((0..30).map(|i| ((i % 16, i * 5 % 16), i)));
+
+        let mut qt = AggregatedQuadtree::<u32, u32, Sum>::from(tree.clone());
+        check_summaries(&qt);
+        qt.insert(area(3, 3, 1, 1), 1000);
+        check_summaries(&qt);
+        debug_assert_eq!(qt.into_quadtree().len(), tree.len() + 1);
+    }
+
+    #[test]
+    fn traverse() {
+        let mut qt = AggregatedQuadtree::<u32, u32, Count>::new(6);
+        for i in 0..200 {
+            qt.insert_pt((i * 13 % 64, i * 29 % 64).into(), i);
+        }
+
+        // Accepting every subtree wholly within a region, and descending into those which cross
+        // it, counts the entries in it without visiting most of them one by one.
+        let region = area(5, 9, 40, 33);
+        let (mut count, mut subtrees) = (0, 0);
+        for visited in qt.traverse(|bounds, _| {
+            if region.contains(bounds) {
+                Visit::Accept
+            } else if region.intersects(bounds) {
+                Visit::Descend
+            } else {
+                Visit::Reject
+            }
+        }) {
+            match visited {
+                Visited::Subtree { bounds, aggregate } => {
+                    debug_assert!(region.contains(bounds));
+                    count += aggregate.0;
+                    subtrees += 1;
+                }
+                Visited::Entry(e) if region.intersects(e.area()) => count += 1,
+                Visited::Entry(_) => {}
+            }
+        }
+        debug_assert_eq!(count, qt.as_quadtree().count_in_region(region));
+        debug_assert!(subtrees > 0);
+
+        let everything = qt.traverse(|_, _| Visit::Accept);
+        debug_assert_eq!(everything.len(), 1);
+        debug_assert!(qt.traverse(|_, _| Visit::Reject).is_empty());
+    }
+
+    #[test]
+    fn aggregate_in_region() {
+        let mut tight = AggregatedQuadtree::<u32, u32, Sum>::new(6);
+        let mut loose = AggregatedQuadtree::<u32, u32, Sum>::from({
+            let mut tree = Quadtree::<u32, u32>::new(6);
+            tree.set_looseness(2.0);
+            tree
+        });
+        for i in 0..300 {
+            let region = area(i * 13 % 61, i * 29 % 59, 1 + i % 4, 1 + i % 3);
+            tight.insert(region, i);
+            loose.insert(region, i);
+        }
+        tight.delete(area(10, 10, 5, 5));
+        loose.delete(area(10, 10, 5, 5));
+
+        for x in (0..64).step_by(7) {
+            for y in (0..64).step_by(9) {
+                let region = area(x, y, 1 + x % 23, 1 + y % 31);
+                let expected = sum_where(tight.as_quadtree(), |r| r.intersects(region));
+                debug_assert_eq!(tight.aggregate_in_region(region), expected);
+                debug_assert_eq!(loose.aggregate_in_region(region), expected);
+            }
+        }
+        debug_assert_eq!(
+            &tight.aggregate_in_region(area(0, 0, 64, 64)),
+            tight.aggregate()
+        );
+    }
+}
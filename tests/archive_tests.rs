@@ -0,0 +1,129 @@
+// Copyright 2019 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![cfg(feature = "rkyv")]
+
+mod util; // For unordered_elements_are.
+
+// For testing FrozenQuadtree and its archived form.
+mod archive_tests {
+    use {
+        crate::util::unordered_elements_are,
+        quadtree_rs::{archive::FrozenQuadtree, area::AreaBuilder, Quadtree},
+        std::convert::TryFrom,
+    };
+
+    fn mk_quadtree_for_archive_tests() -> Quadtree<i32, u32> {
+        let mut qt = Quadtree::<i32, u32>::new_with_anchor((-16, -16).into(), 5);
+        let mut value = 0;
+        for x in (-16..16).step_by(3) {
+            for y in (-16..16).step_by(5) {
+                qt.insert(
+                    AreaBuilder::default()
+                        .anchor((x, y).into())
+                        .dimensions((1 + (x + 16) % 4, 1 + (y + 16) % 6))
+                        .build()
+                        .unwrap(),
+                    value,
+                );
+                value += 1;
+            }
+        }
+        qt
+    }
+
+    fn archive(qt: Quadtree<i32, u32>) -> rkyv::AlignedVec {
+        rkyv::to_bytes::<_, 1024>(&FrozenQuadtree::from(qt)).unwrap()
+    }
+
+    #[test]
+    fn archived_queries_match() {
+        let bytes = archive(mk_quadtree_for_archive_tests());
+        let archived = rkyv::check_archived_root::<FrozenQuadtree<i32, u32>>(&bytes[..]).unwrap();
+        let qt = mk_quadtree_for_archive_tests();
+
+        debug_assert_eq!(archived.len(), qt.len());
+        debug_assert_eq!(archived.anchor(), qt.anchor());
+        debug_assert_eq!(archived.depth(), qt.depth());
+
+        for x in (-16..16).step_by(7) {
+            for y in (-16..16).step_by(5) {
+                let region = AreaBuilder::default()
+                    .anchor((x, y).into())
+                    .dimensions((3, 4))
+                    .build()
+                    .unwrap();
+                debug_assert!(unordered_elements_are(
+                    archived.query(region).map(|e| (e.handle(), *e.value())),
+                    qt.query(region).map(|e| (e.handle(), *e.value_ref())),
+                ));
+                debug_assert!(unordered_elements_are(
+                    archived
+                        .query_pt((x, y).into())
+                        .map(|e| (e.handle(), *e.value())),
+                    qt.query_pt((x, y).into())
+                        .map(|e| (e.handle(), *e.value_ref())),
+                ));
+            }
+        }
+    }
+
+    #[test]
+    fn archived_get() {
+        let qt = mk_quadtree_for_archive_tests();
+        let handles: Vec<(u64, u32)> = qt.iter().map(|e| (e.handle(), *e.value_ref())).collect();
+        let bytes = archive(qt);
+        let archived = rkyv::check_archived_root::<FrozenQuadtree<i32, u32>>(&bytes[..]).unwrap();
+
+        for (handle, value) in handles {
+            debug_assert_eq!(*archived.get(handle).unwrap().value(), value);
+        }
+        debug_assert!(archived.get(u64::MAX).is_none());
+        debug_assert_eq!(archived.iter().count(), archived.len());
+    }
+
+    #[test]
+    fn thaw() {
+        let qt = mk_quadtree_for_archive_tests();
+        let frozen = FrozenQuadtree::from(mk_quadtree_for_archive_tests());
+        let thawed: Quadtree<i32, u32> = Quadtree::try_from(frozen).unwrap();
+        debug_assert!(unordered_elements_are(
+            thawed
+                .iter()
+                .map(|e| (e.handle(), e.area(), *e.value_ref())),
+            qt.iter().map(|e| (e.handle(), e.area(), *e.value_ref())),
+        ));
+    }
+
+    #[test]
+    fn thaw_bounds() {
+        let mut qt = Quadtree::<i32, u32>::new_with_bounds((-4, -4).into(), 10, 3);
+        qt.insert_pt((5, -2).into(), 1);
+        let thawed: Quadtree<i32, u32> =
+            Quadtree::try_from(FrozenQuadtree::from(qt.clone())).unwrap();
+        debug_assert_eq!(thawed, qt);
+    }
+
+    #[test]
+    fn rejects_garbage() {
+        let bytes = archive(mk_quadtree_for_archive_tests());
+        let mut garbage = bytes.clone();
+        let len = garbage.len();
+        // Scribble over the root's relative pointers at the end of the buffer.
+        for b in garbage[len - 16..].iter_mut() {
+            *b = 0xff;
+        }
+        debug_assert!(rkyv::check_archived_root::<FrozenQuadtree<i32, u32>>(&garbage[..]).is_err());
+    }
+}
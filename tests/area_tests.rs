@@ -13,7 +13,10 @@
 // limitations under the License.
 
 mod area_tests {
-    use quadtree_rs::area::{Area, AreaBuilder};
+    use {
+        quadtree_rs::area::{Area, AreaBuilder},
+        std::convert::TryFrom,
+    };
 
     mod builder {
         use super::*;
@@ -37,7 +40,21 @@ mod area_tests {
                 .dimensions(*dims)
                 .build()
                 .is_err());
+            debug_assert!(Area::new((0, 0).into(), *dims).is_err());
+            debug_assert!(Area::try_from(((0, 0), *dims)).is_err());
         }
+        debug_assert_eq!(
+            Area::new((0, 0).into(), (0, 4)),
+            Err("Areas may not have nonpositive widths.".to_string())
+        );
+        debug_assert_eq!(
+            Area::new((0, 0).into(), (4, -4)),
+            Err("Areas may not have nonpositive heights.".to_string())
+        );
+        debug_assert_eq!(
+            Area::new((-1, -1).into(), (1, 1)),
+            AreaBuilder::default().anchor((-1, -1).into()).build()
+        );
     }
 
     #[test]
@@ -997,4 +1014,72 @@ mod area_tests {
             ));
         }
     }
+
+    mod near_max {
+        use super::*;
+
+        #[test]
+        fn edges_saturate() {
+            let a = Area::<u8>::new((250, 255).into(), (10, 1)).unwrap();
+            debug_assert_eq!(a.right_edge(), 255);
+            debug_assert_eq!(a.bottom_edge(), 255);
+
+            let b = Area::<i8>::new((127, 100).into(), (1, 50)).unwrap();
+            debug_assert_eq!(b.right_edge(), 127);
+            debug_assert_eq!(b.bottom_edge(), 127);
+        }
+
+        #[test]
+        fn intersects() {
+            let a = Area::<u8>::new((250, 250).into(), (10, 10)).unwrap();
+            debug_assert!(a.intersects(Area::new((255, 255).into(), (1, 1)).unwrap()));
+            debug_assert!(a.intersects(Area::new((240, 240).into(), (11, 11)).unwrap()));
+            debug_assert!(!a.intersects(Area::new((240, 240).into(), (10, 10)).unwrap()));
+
+            let b = Area::<i8>::new((127, 127).into(), (1, 1)).unwrap();
+            debug_assert!(b.intersects(b));
+            debug_assert!(!b.intersects(Area::new((100, 100).into(), (27, 28)).unwrap()));
+        }
+
+        #[test]
+        fn contains() {
+            let a = Area::<u8>::new((200, 200).into(), (55, 55)).unwrap();
+            debug_assert!(a.contains(Area::new((254, 254).into(), (1, 1)).unwrap()));
+            debug_assert!(!a.contains(Area::new((254, 254).into(), (2, 2)).unwrap()));
+            debug_assert!(!a.contains_pt((255, 255).into()));
+
+            let b = Area::<i8>::new((100, 100).into(), (100, 100)).unwrap();
+            debug_assert!(b.contains(Area::new((120, 120).into(), (90, 90)).unwrap()));
+            debug_assert!(b.contains_pt((127, 127).into()));
+            debug_assert!(!Area::new((120, 120).into(), (90, 90)).unwrap().contains(b));
+        }
+
+        #[test]
+        fn intersection() {
+            let a = Area::<u8>::new((250, 200).into(), (10, 100)).unwrap();
+            let b = Area::<u8>::new((240, 252).into(), (20, 20)).unwrap();
+            let c = a.intersection(b).unwrap();
+            debug_assert_eq!(c.anchor(), (250, 252).into());
+            debug_assert_eq!((c.width(), c.height()), (10, 20));
+
+            let d = Area::<i8>::new((-128, 120).into(), (127, 127)).unwrap();
+            let e = d.intersection(Area::new((-2, 1).into(), (127, 127)).unwrap());
+            debug_assert_eq!(e, Area::new((-2, 120).into(), (1, 8)).ok());
+        }
+
+        #[test]
+        fn overlap() {
+            let a = Area::<u8>::new((250, 200).into(), (10, 100)).unwrap();
+            debug_assert_eq!(
+                a.overlap(Area::new((240, 252).into(), (20, 20)).unwrap()),
+                200
+            );
+            debug_assert_eq!(a.overlap(Area::new((0, 0).into(), (250, 250)).unwrap()), 0);
+            // 10 * 100 cells don't fit in a u8.
+            debug_assert_eq!(a.overlap(a), 255);
+
+            let b = Area::<i8>::new((-4, -4).into(), (8, 8)).unwrap();
+            debug_assert_eq!(b.overlap(Area::new((-8, 2).into(), (6, 6)).unwrap()), 4);
+        }
+    }
 }
@@ -0,0 +1,184 @@
+// Copyright 2019 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+mod util; // For area.
+
+// For testing .connected_components(), .cluster().
+mod clustering_tests {
+    use {
+        crate::util::area,
+        quadtree_rs::{area::Area, point::Point, Quadtree},
+        std::collections::HashMap,
+    };
+
+    // The number of empty cells between @a and @b along whichever axis they're furthest apart.
+    fn separation(a: Area<i32>, b: Area<i32>) -> i32 {
+        let x = (b.left_edge() - a.right_edge()).max(a.left_edge() - b.right_edge());
+        let y = (b.top_edge() - a.bottom_edge()).max(a.top_edge() - b.bottom_edge());
+        x.max(y)
+    }
+
+    // The handles of @qt's connected components, found by flood-filling from each entry in turn.
+    fn components(qt: &Quadtree<i32, i32>, gap: i32) -> Vec<Vec<u64>> {
+        let mut entries: Vec<(u64, Area<i32>)> =
+            qt.iter().map(|e| (e.handle(), e.area())).collect();
+        entries.sort_unstable_by_key(|(handle, _)| *handle);
+        let connected =
+            |a: Area<i32>, b: Area<i32>| a.intersects(b) || separation(a, b) <= gap.max(0);
+
+        let mut seen = vec![false; entries.len()];
+        let mut groups = vec![];
+        for start in 0..entries.len() {
+            if seen[start] {
+                continue;
+            }
+            seen[start] = true;
+            let mut group = vec![start];
+            let mut next = 0;
+            while next < group.len() {
+                let region = entries[group[next]].1;
+                for (i, (_, other)) in entries.iter().enumerate() {
+                    if !seen[i] && connected(region, *other) {
+                        seen[i] = true;
+                        group.push(i);
+                    }
+                }
+                next += 1;
+            }
+            group.sort_unstable();
+            groups.push(group.into_iter().map(|i| entries[i].0).collect());
+        }
+        groups
+    }
+
+    #[test]
+    fn connected_components() {
+        let mut tight = Quadtree::<i32, i32>::new_with_anchor((-16, -16).into(), 5);
+        let mut loose = Quadtree::<i32, i32>::new_with_anchor((-16, -16).into(), 5);
+        loose.set_looseness(2.0);
+        for i in 0..90 {
+            let k = (i * 37) % 101;
+            let region = area(k % 29 - 16, (k * 7) % 31 - 16, 1 + k % 3, 1 + (k / 5) % 3);
+            for qt in [&mut tight, &mut loose].iter_mut() {
+                qt.insert(region, i);
+            }
+        }
+
+        for gap in -1..4 {
+            let expected = components(&tight, gap);
+            debug_assert!(expected.iter().any(|group| group.len() > 1));
+            for qt in [&tight, &loose].iter() {
+                let found: Vec<Vec<u64>> = qt
+                    .connected_components(gap)
+                    .iter()
+                    .map(|group| group.iter().map(|e| e.handle()).collect())
+                    .collect();
+                debug_assert_eq!(found, expected, "with a gap of {}", gap);
+            }
+        }
+        debug_assert_eq!(tight.connected_components(100).len(), 1);
+    }
+
+    #[test]
+    fn connected_components_of_nothing() {
+        let qt = Quadtree::<u32, ()>::new(3);
+        debug_assert!(qt.connected_components(0).is_empty());
+        debug_assert!(qt.connected_components(2).is_empty());
+    }
+
+    // DBSCAN by brute force: the core points are grouped by flood-filling, and each border point
+    // joins the first cluster with a core point near it.
+    fn dbscan(
+        points: &[(u64, Point<i32>)],
+        eps: i32,
+        min_pts: usize,
+    ) -> HashMap<u64, Option<usize>> {
+        let near = |a: Point<i32>, b: Point<i32>| {
+            let (dx, dy) = (a.x() - b.x(), a.y() - b.y());
+            eps >= 0 && dx * dx + dy * dy <= eps * eps
+        };
+        let core: Vec<bool> = points
+            .iter()
+            .map(|(_, p)| points.iter().filter(|(_, q)| near(*p, *q)).count() >= min_pts)
+            .collect();
+
+        let mut labels: Vec<Option<usize>> = vec![None; points.len()];
+        let mut clusters = 0;
+        for start in 0..points.len() {
+            if !core[start] || labels[start].is_some() {
+                continue;
+            }
+            labels[start] = Some(clusters);
+            let mut group = vec![start];
+            let mut next = 0;
+            while next < group.len() {
+                let p = points[group[next]].1;
+                for (i, (_, q)) in points.iter().enumerate() {
+                    if core[i] && labels[i].is_none() && near(p, *q) {
+                        labels[i] = Some(clusters);
+                        group.push(i);
+                    }
+                }
+                next += 1;
+            }
+            clusters += 1;
+        }
+        let border: Vec<Option<usize>> = (0..points.len())
+            .map(|i| match labels[i] {
+                Some(label) => Some(label),
+                None => (0..points.len())
+                    .filter(|&j| core[j] && near(points[i].1, points[j].1))
+                    .filter_map(|j| labels[j])
+                    .min(),
+            })
+            .collect();
+        points.iter().map(|(h, _)| *h).zip(border).collect()
+    }
+
+    #[test]
+    fn cluster() {
+        let mut qt = Quadtree::<i32, ()>::new_with_anchor((-32, -32).into(), 6);
+        // Three blobs, and a scattering of noise.
+        for (cx, cy) in [(-20, -20), (0, 5), (18, -10)] {
+            for i in 0..25 {
+                let pt = Point {
+                    x: cx + (i * 7) % 9 - 4,
+                    y: cy + (i * 5) % 7 - 3,
+                };
+                qt.insert_pt(pt, ());
+            }
+        }
+        for i in 0..40 {
+            qt.insert_pt(((i * 41) % 64 - 32, (i * 23) % 64 - 32).into(), ());
+        }
+
+        let mut points: Vec<(u64, Point<i32>)> =
+            qt.iter().map(|e| (e.handle(), e.anchor())).collect();
+        points.sort_unstable_by_key(|(handle, _)| *handle);
+        for (eps, min_pts) in [(1, 3), (2, 4), (3, 6), (5, 2), (0, 1), (-1, 1)] {
+            let labels = qt.cluster(eps, min_pts);
+            debug_assert_eq!(labels.len(), qt.len());
+            debug_assert_eq!(
+                labels,
+                dbscan(&points, eps, min_pts),
+                "eps {}, min_pts {}",
+                eps,
+                min_pts
+            );
+        }
+        debug_assert!(qt.cluster(2, 4).values().any(|label| *label == Some(2)));
+        debug_assert!(qt.cluster(2, 4).values().any(|label| label.is_none()));
+        debug_assert!(Quadtree::<u32, ()>::new(2).cluster(1, 1).is_empty());
+    }
+}
@@ -0,0 +1,231 @@
+// Copyright 2019 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![cfg(feature = "concurrent")]
+
+mod util; // For area and unordered_elements_are.
+
+// For testing ConcurrentQuadtree.
+mod concurrent_tests {
+    use {
+        crate::util::{area, unordered_elements_are},
+        quadtree_rs::{
+            concurrent::{ConcurrentQuadtree, SnapshotQuadtree},
+            BoundsPolicy, Quadtree,
+        },
+        std::{sync::Arc, thread},
+    };
+
+    #[test]
+    fn insert_and_query() {
+        let qt = ConcurrentQuadtree::<u32, char>::new(4);
+        // One region in a single quadrant, one straddling all four, and one out of bounds.
+        let a = qt.insert(area(1, 1, 2, 2), 'a').unwrap();
+        let b = qt.insert(area(6, 6, 4, 4), 'b').unwrap();
+        debug_assert!(qt.insert(area(15, 15, 2, 2), 'c').is_none());
+        debug_assert_ne!(a, b);
+        debug_assert_eq!(qt.len(), 2);
+
+        debug_assert!(unordered_elements_are(
+            qt.query(area(0, 0, 8, 8)).iter().map(|e| *e.value_ref()),
+            vec!['a', 'b'],
+        ));
+        debug_assert!(unordered_elements_are(
+            qt.query(area(9, 9, 7, 7)).iter().map(|e| *e.value_ref()),
+            vec!['b'],
+        ));
+        debug_assert_eq!(qt.count_in_region(area(0, 0, 16, 16)), 2);
+        debug_assert_eq!(qt.get(b).unwrap().area(), area(6, 6, 4, 4));
+    }
+
+    #[test]
+    fn modify_and_delete() {
+        let qt = ConcurrentQuadtree::<u32, u32>::new(4);
+        let a = qt.insert(area(1, 1, 1, 1), 1).unwrap();
+        qt.insert(area(6, 6, 4, 4), 2).unwrap();
+        qt.insert(area(12, 12, 1, 1), 3).unwrap();
+
+        debug_assert_eq!(qt.modify(area(0, 0, 8, 8), |_, v| *v *= 10), 2);
+        debug_assert!(unordered_elements_are(
+            qt.query(area(0, 0, 16, 16)).iter().map(|e| *e.value_ref()),
+            vec![10, 20, 3],
+        ));
+
+        debug_assert_eq!(qt.delete_by_handle(a).unwrap().value_ref(), &10);
+        debug_assert!(qt.delete_by_handle(a).is_none());
+
+        let deleted = qt.delete(area(9, 9, 7, 7));
+        debug_assert!(unordered_elements_are(
+            deleted.iter().map(|e| *e.value_ref()),
+            vec![20, 3],
+        ));
+        debug_assert!(qt.is_empty());
+    }
+
+    #[test]
+    fn depth_zero() {
+        let qt = ConcurrentQuadtree::<u32, u8>::new(0);
+        debug_assert!(qt.insert_pt((0, 0).into(), 1).is_some());
+        debug_assert!(qt.insert_pt((1, 0).into(), 1).is_none());
+        debug_assert_eq!(qt.query(area(0, 0, 1, 1)).len(), 1);
+    }
+
+    #[test]
+    fn many_threads() {
+        let qt = Arc::new(ConcurrentQuadtree::<u32, u32>::new(8));
+
+        let threads: Vec<_> = (0..8)
+            .map(|t| {
+                let qt = Arc::clone(&qt);
+                thread::spawn(move || {
+                    let mut handles = vec![];
+                    for i in 0..100 {
+                        let (x, y) = ((t * 31 + i * 7) % 256, (t * 17 + i * 13) % 256);
+                        handles.push(qt.insert_pt((x, y).into(), t).unwrap());
+                        // Readers and writers interleave freely.
+                        debug_assert!(qt.count_in_region(area(x, y, 1, 1)) >= 1);
+                    }
+                    handles
+                })
+            })
+            .collect();
+        let mut handles: Vec<u64> = threads
+            .into_iter()
+            .flat_map(|thread| thread.join().unwrap())
+            .collect();
+
+        // Every handle issued was distinct.
+        handles.sort_unstable();
+        handles.dedup();
+        debug_assert_eq!(handles.len(), 800);
+        debug_assert_eq!(qt.len(), 800);
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut original = Quadtree::<u32, u32>::new(5);
+        original.insert(area(0, 0, 3, 3), 1);
+        original.insert(area(10, 10, 12, 12), 2);
+        original.insert(area(30, 2, 1, 1), 3);
+        let doomed = original.insert(area(20, 20, 1, 1), 4).unwrap();
+        original.delete_by_handle(doomed);
+
+        let concurrent = ConcurrentQuadtree::from(original);
+        debug_assert_eq!(concurrent.len(), 3);
+        // New handles carry on from where the original left off.
+        debug_assert_eq!(concurrent.insert(area(5, 5, 1, 1), 5), Some(4));
+
+        let qt = concurrent.into_quadtree();
+        debug_assert!(unordered_elements_are(
+            qt.iter().map(|e| (e.handle(), *e.value_ref())),
+            vec![(0, 1), (1, 2), (2, 3), (4, 5)],
+        ));
+        debug_assert_eq!(qt.count_in_region(area(0, 0, 32, 32)), 4);
+    }
+
+    #[test]
+    fn conversion_keeps_settings() {
+        let mut original = Quadtree::<u32, u32>::new(5);
+        original.set_max_items_per_node(Some(2));
+        original.set_looseness(2.0);
+        original.set_bounds_policy(BoundsPolicy::Clip);
+
+        let concurrent = ConcurrentQuadtree::from(original);
+        let handle = concurrent.insert(area(28, 0, 8, 2), 1).unwrap();
+        debug_assert_eq!(concurrent.get(handle).unwrap().area(), area(28, 0, 4, 2));
+
+        let qt = concurrent.into_quadtree();
+        debug_assert_eq!(qt.bounds_policy(), BoundsPolicy::Clip);
+        debug_assert_eq!(qt.looseness(), 2.0);
+        debug_assert_eq!(
+            format!("{:?}", qt.split_policy()),
+            "CapacityLimited { max_items_per_node: 2 }"
+        );
+
+        // A concurrent quadtree can't grow, so it rejects what an expanding one wouldn't.
+        let mut original = Quadtree::<u32, u32>::new(5);
+        original.set_auto_expand(true);
+        let concurrent = ConcurrentQuadtree::from(original);
+        debug_assert!(concurrent.insert(area(28, 0, 8, 2), 1).is_none());
+        debug_assert!(concurrent.into_quadtree().is_auto_expanding());
+    }
+
+    #[test]
+    fn snapshot_isolation() {
+        let qt = SnapshotQuadtree::<u32, u32>::new(4);
+        debug_assert!(qt.snapshot().is_empty());
+
+        let a = qt.write(|next| next.insert(area(1, 1, 2, 2), 1)).unwrap();
+        let first = qt.publish();
+        debug_assert!(Arc::ptr_eq(&first, &qt.snapshot()));
+
+        // Unpublished changes are invisible to readers.
+        qt.write(|next| {
+            next.delete_by_handle(a);
+            next.insert(area(6, 6, 4, 4), 2);
+        });
+        debug_assert_eq!(qt.snapshot().get(a).unwrap().value_ref(), &1);
+
+        let second = qt.publish();
+        debug_assert!(unordered_elements_are(
+            first.iter().map(|e| *e.value_ref()),
+            vec![1],
+        ));
+        debug_assert!(unordered_elements_are(
+            second.iter().map(|e| *e.value_ref()),
+            vec![2],
+        ));
+
+        // Unpublished changes survive into_quadtree().
+        qt.write(|next| next.insert(area(0, 0, 1, 1), 3));
+        debug_assert_eq!(qt.into_quadtree().len(), 2);
+    }
+
+    #[test]
+    fn snapshot_readers_and_writer() {
+        let mut initial = Quadtree::<u32, u32>::new(6);
+        initial.insert(area(0, 0, 1, 1), 0);
+        let qt = Arc::new(SnapshotQuadtree::from(initial));
+
+        let readers: Vec<_> = (0..4)
+            .map(|_| {
+                let qt = Arc::clone(&qt);
+                thread::spawn(move || {
+                    for _ in 0..100 {
+                        // Every published version holds a whole frame's worth of entries.
+                        let snapshot = qt.snapshot();
+                        debug_assert_eq!(snapshot.len() % 10, 1);
+                        debug_assert_eq!(
+                            snapshot.count_in_region(area(0, 0, 64, 64)),
+                            snapshot.len()
+                        );
+                    }
+                })
+            })
+            .collect();
+
+        for frame in 0..20 {
+            qt.write(|next| {
+                for i in 0..10 {
+                    next.insert_pt((frame, i).into(), frame);
+                }
+            });
+            qt.publish();
+        }
+        for reader in readers {
+            reader.join().unwrap();
+        }
+        debug_assert_eq!(qt.snapshot().len(), 201);
+    }
+}
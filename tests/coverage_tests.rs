@@ -0,0 +1,129 @@
+// Copyright 2019 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+mod util; // For area.
+
+// For testing .coverage_union(), .coverage_intersection(), .coverage_difference().
+mod coverage_tests {
+    use {
+        crate::util::area,
+        quadtree_rs::{area::Area, Quadtree},
+    };
+
+    // A deterministic scatter of @n regions over (-8, -8)->24x24, some of which poke out of a
+    // quadtree anchored at (-8, -8) with depth 4.
+    fn scatter(n: i32, seed: i32) -> Vec<Area<i32>> {
+        (0..n)
+            .map(|i| {
+                let k = (i * 37 + seed * 11) % 97;
+                area(k % 19 - 8, (k * 7) % 21 - 8, 1 + k % 6, 1 + (k / 3) % 5)
+            })
+            .collect()
+    }
+
+    fn covers(regions: &[Area<i32>], x: i32, y: i32) -> bool {
+        regions.iter().any(|r| r.contains(area(x, y, 1, 1)))
+    }
+
+    // Checks that @combined is made of disjoint regions covering exactly the cells for which @op
+    // holds.
+    fn check<F>(combined: &Quadtree<i32, ()>, ours: &[Area<i32>], theirs: &[Area<i32>], op: F)
+    where
+        F: Fn(bool, bool) -> bool,
+    {
+        let regions: Vec<Area<i32>> = combined.regions().collect();
+        for (i, a) in regions.iter().enumerate() {
+            debug_assert!(regions[i + 1..].iter().all(|b| !a.intersects(*b)));
+        }
+        for x in -8..8 {
+            for y in -8..8 {
+                debug_assert_eq!(
+                    covers(&regions, x, y),
+                    op(covers(ours, x, y), covers(theirs, x, y)),
+                    "at ({}, {})",
+                    x,
+                    y
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn set_operations() {
+        for seed in 0..6 {
+            let mut qt = Quadtree::<i32, i32>::new_with_anchor((-8, -8).into(), 4);
+            let ours: Vec<Area<i32>> = scatter(12, seed)
+                .into_iter()
+                .filter(|&r| qt.insert(r, 0).is_some())
+                .collect();
+            let theirs = scatter(9, seed + 50);
+
+            check(
+                &qt.coverage_union(theirs.clone()),
+                &ours,
+                &theirs,
+                |a, b| a || b,
+            );
+            check(
+                &qt.coverage_intersection(theirs.clone()),
+                &ours,
+                &theirs,
+                |a, b| a && b,
+            );
+            check(
+                &qt.coverage_difference(theirs.clone()),
+                &ours,
+                &theirs,
+                |a, b| a && !b,
+            );
+        }
+    }
+
+    #[test]
+    fn with_another_quadtree() {
+        let mut a = Quadtree::<i32, char>::new_with_anchor((-8, -8).into(), 4);
+        a.insert(area(-8, -8, 8, 8), 'a');
+        let mut b = Quadtree::<i32, char>::new_with_anchor((-8, -8).into(), 4);
+        b.insert(area(-4, -4, 8, 8), 'b');
+
+        let union = a.coverage_union(b.regions());
+        debug_assert_eq!(union.anchor(), a.anchor());
+        debug_assert_eq!(union.depth(), a.depth());
+        debug_assert_eq!(
+            union.regions().map(|r| r.width() * r.height()).sum::<i32>(),
+            64 + 64 - 16
+        );
+        debug_assert_eq!(
+            a.coverage_intersection(b.regions())
+                .regions()
+                .collect::<Vec<_>>(),
+            vec![area(-4, -4, 4, 4)]
+        );
+        debug_assert!(a.coverage_difference(a.regions()).is_empty());
+    }
+
+    #[test]
+    fn empty() {
+        let qt = Quadtree::<i32, ()>::new(3);
+        debug_assert!(qt.coverage_union(vec![]).is_empty());
+        debug_assert!(qt.coverage_intersection(vec![area(0, 0, 2, 2)]).is_empty());
+        debug_assert_eq!(
+            qt.coverage_union(vec![area(0, 0, 2, 2), area(6, 6, 4, 4)])
+                .regions()
+                .map(|r| r.width() * r.height())
+                .sum::<i32>(),
+            4 + 4
+        );
+    }
+}
@@ -0,0 +1,96 @@
+// Copyright 2019 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+mod util; // For area.
+
+// For testing .from_csv().
+mod csv_tests {
+    use {
+        crate::util::area,
+        quadtree_rs::{point::Point, Quadtree},
+    };
+
+    #[test]
+    fn from_csv() {
+        let csv = "\
+            -3,4,a\n\
+            \n\
+            1.25, 2.75 ,  b  \n\
+            -1.5,-1.5,1,2.5,c\n\
+            0,0,4,4,\"d, with \"\"quotes\"\"\"\n\
+            10,10,0,0,e\n";
+        let (qt, errors) = Quadtree::<i32, String>::from_csv(csv.as_bytes(), 1.0).unwrap();
+        debug_assert!(errors.is_empty());
+        debug_assert_eq!(qt.len(), 5);
+
+        let expected = [
+            (area(-3, 4, 1, 1), "a"),
+            (area(1, 2, 1, 1), "b"),
+            (area(-2, -2, 2, 3), "c"),
+            (area(0, 0, 4, 4), "d, with \"quotes\""),
+            (area(10, 10, 1, 1), "e"),
+        ];
+        for (handle, (region, value)) in expected.iter().enumerate() {
+            let entry = qt.get(handle as u64).unwrap();
+            debug_assert_eq!(entry.area(), *region);
+            debug_assert_eq!(entry.value_ref(), value);
+        }
+    }
+
+    #[test]
+    fn from_csv_quantizes() {
+        let csv = "x,y,value\n0.25,0.5,1\n0.75,0.5,2\n12.5,3,3\n";
+        let (qt, errors) = Quadtree::<u32, u8>::from_csv(csv.as_bytes(), 0.5).unwrap();
+        debug_assert!(errors.is_empty());
+        debug_assert_eq!(qt.anchor(), Point { x: 0, y: 1 });
+        debug_assert_eq!(qt.get(0).unwrap().anchor(), Point { x: 0, y: 1 });
+        debug_assert_eq!(qt.get(1).unwrap().anchor(), Point { x: 1, y: 1 });
+        debug_assert_eq!(qt.get(2).unwrap().anchor(), Point { x: 25, y: 6 });
+        debug_assert_eq!(qt.query_pt((25, 6).into()).next().unwrap().value_ref(), &3);
+    }
+
+    #[test]
+    fn from_csv_reports_bad_rows() {
+        let csv = "\
+            1,1,7\n\
+            1,1\n\
+            1,1,1,7\n\
+            x,1,7\n\
+            1,1,-1,1,7\n\
+            -1,1,7\n\
+            1,1,300\n\
+            1,1,\"7\n\
+            1e20,1,7\n\
+            NaN,1,7\n\
+            2,2,8\n";
+        let (qt, errors) = Quadtree::<u32, u8>::from_csv(csv.as_bytes(), 1.0).unwrap();
+        debug_assert_eq!(qt.len(), 2);
+        debug_assert_eq!(
+            errors.iter().map(|e| e.line()).collect::<Vec<_>>(),
+            (2..=10).collect::<Vec<_>>()
+        );
+        debug_assert!(errors[0].reason().contains("found 2"));
+        debug_assert!(errors[0].to_string().starts_with("Line 2: "));
+
+        let (empty, errors) = Quadtree::<u32, u8>::from_csv(&b"x,y,v\n"[..], 1.0).unwrap();
+        debug_assert!(empty.is_empty());
+        debug_assert!(errors.is_empty());
+    }
+
+    #[test]
+    #[should_panic]
+    fn from_csv_with_no_cell_size() {
+        let _ = Quadtree::<u32, u8>::from_csv(&b""[..], 0.0);
+    }
+}
@@ -0,0 +1,211 @@
+// Copyright 2019 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+mod util; // For unordered_elements_are.
+
+// For testing .cursor(), .quadkey(), .quadkey_of(), .quadkey_region(), .node_at_quadkey(),
+// .tiles().
+mod cursor_tests {
+    use {
+        crate::util::unordered_elements_are,
+        quadtree_rs::{
+            area::AreaBuilder,
+            cursor::{Cursor, Quadrant},
+            Quadtree,
+        },
+    };
+
+    const QUADRANTS: [Quadrant; 4] = [
+        Quadrant::NorthEast,
+        Quadrant::NorthWest,
+        Quadrant::SouthEast,
+        Quadrant::SouthWest,
+    ];
+
+    fn mk_quadtree() -> Quadtree<u32, u32> {
+        let mut qt = Quadtree::<u32, u32>::new(6);
+        for i in 0..150 {
+            let region = AreaBuilder::default()
+                .anchor((i * 13 % 61, i * 29 % 59).into())
+                .dimensions((1 + i % 4, 1 + i % 3))
+                .build()
+                .unwrap();
+            qt.insert(region, i);
+        }
+        qt
+    }
+
+    // Collects the handles held at and beneath the cursor, checking the cells along the way, and
+    // leaves the cursor where it started.
+    fn walk(cursor: &mut Cursor<u32, u32>, found: &mut Vec<u64>) {
+        let (region, depth) = (cursor.region(), cursor.depth());
+        let before = found.len();
+        found.extend(cursor.items().map(|e| e.handle()));
+        for e in cursor.items() {
+            debug_assert!(cursor.bounds().contains(e.area()));
+        }
+        if cursor.is_leaf() {
+            debug_assert!(!cursor.descend(Quadrant::NorthEast));
+        }
+        for quadrant in QUADRANTS {
+            if !cursor.descend(quadrant) {
+                continue;
+            }
+            let half = region.width() / 2;
+            let (x, y) = match quadrant {
+                Quadrant::NorthEast => (half, 0),
+                Quadrant::NorthWest => (0, 0),
+                Quadrant::SouthEast => (half, half),
+                Quadrant::SouthWest => (0, half),
+            };
+            debug_assert_eq!(
+                cursor.region().anchor(),
+                (region.anchor().x() + x, region.anchor().y() + y).into()
+            );
+            debug_assert_eq!(cursor.region().width(), half);
+            debug_assert_eq!(cursor.depth(), depth + 1);
+            walk(cursor, found);
+            debug_assert!(cursor.ascend());
+            debug_assert_eq!(cursor.region(), region);
+        }
+        debug_assert_eq!(found.len() - before, cursor.len());
+    }
+
+    #[test]
+    fn cursor_walks_everything() {
+        let qt = mk_quadtree();
+        let mut cursor = qt.cursor();
+        let mut found = vec![];
+        walk(&mut cursor, &mut found);
+        debug_assert!(cursor.is_root());
+        debug_assert_eq!(found.len(), qt.len());
+        debug_assert!(unordered_elements_are(found, qt.handles()));
+    }
+
+    #[test]
+    fn cursor_ends() {
+        let qt = mk_quadtree();
+        let mut cursor = qt.cursor();
+        debug_assert!(cursor.is_root());
+        debug_assert!(!cursor.ascend());
+        debug_assert_eq!(cursor.depth(), 0);
+        debug_assert_eq!(cursor.len(), qt.len());
+
+        while cursor.descend(Quadrant::SouthWest) {}
+        debug_assert!(cursor.is_leaf());
+        let leaf = cursor.clone();
+        cursor.reset();
+        debug_assert!(cursor.is_root());
+        debug_assert!(leaf.depth() > 0);
+
+        let empty = Quadtree::<u32, u32>::new(4);
+        let mut cursor = empty.cursor();
+        debug_assert!(cursor.is_empty());
+        debug_assert!(cursor.is_leaf());
+        debug_assert!(!cursor.descend(Quadrant::NorthWest));
+        debug_assert_eq!(cursor.items().count(), 0);
+    }
+
+    #[test]
+    fn quadkeys() {
+        let qt = mk_quadtree();
+
+        // Every cell the tree has made can be found again by its quadkey.
+        let mut stack = vec![qt.cursor()];
+        while let Some(cursor) = stack.pop() {
+            let quadkey = cursor.quadkey();
+            debug_assert_eq!(quadkey.len(), cursor.depth());
+            debug_assert_eq!(qt.quadkey_region(&quadkey), Some(cursor.region()));
+            debug_assert_eq!(
+                qt.quadkey(cursor.region().anchor(), cursor.depth()),
+                Some(quadkey.clone())
+            );
+            let found = qt.node_at_quadkey(&quadkey).unwrap();
+            debug_assert_eq!(found.region(), cursor.region());
+            debug_assert_eq!(found.len(), cursor.len());
+            for e in cursor.items() {
+                debug_assert_eq!(qt.quadkey_of(e.handle()), Some(quadkey.clone()));
+            }
+            for quadrant in QUADRANTS {
+                let mut child = cursor.clone();
+                if child.descend(quadrant) {
+                    stack.push(child);
+                }
+            }
+        }
+
+        // A point's quadkey at each depth extends the one above it.
+        let pt = (37, 22).into();
+        for depth in 1..=qt.depth() {
+            let parent = qt.quadkey(pt, depth - 1).unwrap();
+            let key = qt.quadkey(pt, depth).unwrap();
+            debug_assert!(key.starts_with(&parent));
+            debug_assert!(qt.quadkey_region(&key).unwrap().contains_pt(pt));
+        }
+
+        debug_assert_eq!(qt.quadkey((64, 0).into(), 1), None);
+        debug_assert_eq!(qt.quadkey(pt, 7), None);
+        debug_assert_eq!(qt.quadkey_region("0000000"), None);
+        debug_assert_eq!(qt.quadkey_region("01x"), None);
+        debug_assert!(qt.node_at_quadkey("").unwrap().is_root());
+        debug_assert!(qt.node_at_quadkey("9").is_none());
+        debug_assert_eq!(qt.quadkey_of(1000), None);
+    }
+
+    #[test]
+    fn tiles() {
+        let qt = mk_quadtree();
+        for zoom in 0..=qt.depth() {
+            let tiles: Vec<_> = qt.tiles(zoom).collect();
+            let width = 64 >> zoom;
+            let mut expected = vec![];
+            for y in 0..1 << zoom {
+                for x in 0..1 << zoom {
+                    let tile = AreaBuilder::default()
+                        .anchor((x * width, y * width).into())
+                        .dimensions((width, width))
+                        .build()
+                        .unwrap();
+                    let entries: Vec<u64> = qt
+                        .iter()
+                        .filter(|e| e.area().intersects(tile))
+                        .map(|e| e.handle())
+                        .collect();
+                    if !entries.is_empty() {
+                        expected.push(((zoom, u64::from(x), u64::from(y)), entries));
+                    }
+                }
+            }
+            debug_assert_eq!(tiles.len(), expected.len());
+            for (id, entries) in &tiles {
+                let (_, found) = expected.iter().find(|(e, _)| e == id).unwrap();
+                debug_assert!(unordered_elements_are(
+                    entries.iter().map(|e| e.handle()),
+                    found.iter().copied()
+                ));
+            }
+            // Tiles come in the order of their quadkeys.
+            let quadkeys: Vec<String> = tiles
+                .iter()
+                .map(|&((z, x, y), _)| {
+                    let pt = ((x as u32) * width, (y as u32) * width).into();
+                    qt.quadkey(pt, z).unwrap()
+                })
+                .collect();
+            debug_assert!(quadkeys.windows(2).all(|w| w[0] < w[1]));
+        }
+        debug_assert_eq!(qt.tiles(7).count(), 0);
+        debug_assert_eq!(Quadtree::<u32, u32>::new(4).tiles(2).count(), 0);
+    }
+}
@@ -0,0 +1,66 @@
+// Copyright 2019 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![cfg(feature = "euclid")]
+
+// For testing the conversions to and from euclid, and euclid::Typed.
+mod euclid_tests {
+    use {
+        euclid::{Point2D, Rect, Size2D, UnknownUnit},
+        quadtree_rs::{
+            area::{Area, AreaBuilder},
+            euclid::Typed,
+            point::Point,
+        },
+        std::convert::TryFrom,
+    };
+
+    enum World {}
+
+    #[test]
+    fn points() {
+        let pt = Point { x: -3, y: 7 };
+        let world: Point2D<i32, World> = Point2D::new(-3, 7);
+        debug_assert_eq!(Point::from(world), pt);
+        debug_assert_eq!(Point2D::<i32, UnknownUnit>::from(pt), Point2D::new(-3, 7));
+
+        let typed = Typed::from(world);
+        debug_assert_eq!(typed, Typed::new(pt));
+        debug_assert_eq!(typed.get(), pt);
+        debug_assert_eq!(Point2D::from(typed), world);
+        debug_assert_eq!(format!("{:?}", typed), "Typed(-3x7)");
+    }
+
+    #[test]
+    fn rects() {
+        let area = AreaBuilder::default()
+            .anchor((2, -4).into())
+            .dimensions((3, 5))
+            .build()
+            .unwrap();
+        let world: Rect<i32, World> = Rect::new(Point2D::new(2, -4), Size2D::new(3, 5));
+        debug_assert_eq!(Area::try_from(world), Ok(area));
+        debug_assert_eq!(Rect::from(area), world.to_untyped());
+
+        let typed = Typed::<Area<i32>, World>::try_from(world).unwrap();
+        debug_assert_eq!(typed.get(), area);
+        debug_assert_eq!(Rect::from(typed), world);
+
+        for size in [Size2D::new(0, 5), Size2D::new(3, 0), Size2D::new(-3, 5)] {
+            let rect: Rect<i32, World> = Rect::new(Point2D::new(2, -4), size);
+            debug_assert!(Area::try_from(rect).is_err());
+            debug_assert!(Typed::<Area<i32>, World>::try_from(rect).is_err());
+        }
+    }
+}
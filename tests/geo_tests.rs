@@ -0,0 +1,77 @@
+// Copyright 2019 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![cfg(feature = "geo")]
+
+// For testing the conversions to and from geo-types.
+mod geo_tests {
+    use {
+        geo_types::{coord, Coord, Rect},
+        quadtree_rs::{
+            area::{Area, AreaBuilder},
+            point::Point,
+            Quadtree,
+        },
+        std::convert::TryFrom,
+    };
+
+    #[test]
+    fn points() {
+        let pt = Point { x: -3, y: 7 };
+        debug_assert_eq!(Coord::from(pt), coord! {x: -3, y: 7});
+        debug_assert_eq!(Point::from(coord! {x: -3, y: 7}), pt);
+        debug_assert_eq!(geo_types::Point::from(pt), geo_types::Point::new(-3, 7));
+        debug_assert_eq!(Point::from(geo_types::Point::new(-3, 7)), pt);
+    }
+
+    #[test]
+    fn rects() {
+        let area = AreaBuilder::default()
+            .anchor((2, -4).into())
+            .dimensions((3, 5))
+            .build()
+            .unwrap();
+        let rect = Rect::from(area);
+        debug_assert_eq!(rect.min(), coord! {x: 2, y: -4});
+        debug_assert_eq!(rect.max(), coord! {x: 5, y: 1});
+        debug_assert_eq!(Area::try_from(rect), Ok(area));
+
+        // The corners may come in either order.
+        let flipped = Rect::new(coord! {x: 5, y: -4}, coord! {x: 2, y: 1});
+        debug_assert_eq!(Area::try_from(flipped), Ok(area));
+
+        debug_assert!(Area::try_from(Rect::new(coord! {x: 0, y: 0}, coord! {x: 0, y: 3})).is_err());
+        debug_assert!(Area::try_from(Rect::new(coord! {x: 0, y: 0}, coord! {x: 3, y: 0})).is_err());
+    }
+
+    #[test]
+    fn with_a_quadtree() {
+        let mut qt = Quadtree::<u32, u32>::new(4);
+        for i in 0..8_u32 {
+            qt.insert_pt(coord! {x: i, y: 2 * i}.into(), i);
+        }
+        let window = Rect::new(coord! {x: 2, y: 0}, coord! {x: 5, y: 16});
+        let mut found: Vec<u32> = qt
+            .query(Area::try_from(window).unwrap())
+            .map(|e| *e.value_ref())
+            .collect();
+        found.sort_unstable();
+        debug_assert_eq!(found, vec![2, 3, 4]);
+
+        let nearest = qt
+            .nearest(geo_types::Point::new(7, 13).into(), None)
+            .unwrap();
+        debug_assert_eq!(Coord::from(nearest.anchor()), coord! {x: 7, y: 14});
+    }
+}
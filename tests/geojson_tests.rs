@@ -0,0 +1,159 @@
+// Copyright 2019 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![cfg(feature = "geojson")]
+
+// For testing .from_geojson() and .to_geojson().
+mod geojson_tests {
+    use {
+        quadtree_rs::{area::AreaBuilder, geojson::Quantizer, Quadtree},
+        serde_json::{json, Value},
+        std::io,
+    };
+
+    fn read(document: &Value, quantizer: Quantizer) -> io::Result<Quadtree<i32, Value>> {
+        Quadtree::from_geojson(document.to_string().as_bytes(), quantizer)
+    }
+
+    #[test]
+    fn from_geojson() {
+        let document = json!({
+            "type": "FeatureCollection",
+            "features": [
+                {
+                    "type": "Feature",
+                    "bbox": [1.0, -3.0, 4.0, 2.0],
+                    "geometry": null,
+                    "properties": {"name": "box"},
+                },
+                {
+                    "type": "Feature",
+                    "geometry": {
+                        "type": "LineString",
+                        "coordinates": [[-2.5, 0.5], [0.5, -1.5], [1.5, 1.5]],
+                    },
+                    "properties": {"name": "line"},
+                },
+                {
+                    "type": "Feature",
+                    "geometry": {
+                        "type": "GeometryCollection",
+                        "geometries": [
+                            {"type": "Point", "coordinates": [3.0, 3.0, 100.0]},
+                            {"type": "Point", "coordinates": [5.5, 4.0]},
+                        ],
+                    },
+                },
+            ],
+        });
+        // Unit cells, with the north-west corner at (0, 0).
+        let qt = read(&document, Quantizer::new(0.0, 0.0, 1.0)).unwrap();
+        debug_assert_eq!(qt.len(), 3);
+
+        let region = |x, y, w, h| {
+            AreaBuilder::default()
+                .anchor((x, y).into())
+                .dimensions((w, h))
+                .build()
+                .unwrap()
+        };
+        let entries = [
+            (region(1, -2, 3, 5), json!({"name": "box"})),
+            (region(-3, -2, 5, 4), json!({"name": "line"})),
+            (region(3, -4, 3, 1), Value::Null),
+        ];
+        for (handle, (area, value)) in entries.iter().enumerate() {
+            let entry = qt.get(handle as u64).unwrap();
+            debug_assert_eq!(entry.area(), *area);
+            debug_assert_eq!(entry.value_ref(), value);
+        }
+
+        // A single feature is read as a collection of one.
+        let feature = json!({
+            "type": "Feature",
+            "geometry": {"type": "Point", "coordinates": [0.25, -0.25]},
+            "properties": 7,
+        });
+        let qt = read(&feature, Quantizer::new(0.0, 0.0, 0.5)).unwrap();
+        let entry = qt.iter().next().unwrap();
+        debug_assert_eq!(entry.area(), region(0, 0, 1, 1));
+        debug_assert_eq!(entry.value_ref(), &json!(7));
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut qt = Quadtree::<i32, Value>::new_with_anchor((-20, -20).into(), 6);
+        for i in 0..40 {
+            qt.insert(
+                AreaBuilder::default()
+                    .anchor((i * 7 % 40 - 20, i * 11 % 37 - 20).into())
+                    .dimensions((1 + i % 5, 1 + i % 3))
+                    .build()
+                    .unwrap(),
+                json!({ "i": i, "even": i % 2 == 0 }),
+            );
+        }
+        let quantizer = Quantizer::new(-73.9, 40.8, 0.001);
+
+        let mut out: Vec<u8> = vec![];
+        qt.to_geojson(&mut out, quantizer).unwrap();
+        let document: Value = serde_json::from_slice(&out).unwrap();
+        debug_assert_eq!(document["type"], "FeatureCollection");
+        debug_assert_eq!(document["features"].as_array().unwrap().len(), 40);
+        debug_assert_eq!(document["features"][3]["properties"]["i"], 3);
+        debug_assert_eq!(document["features"][3]["geometry"]["type"], "Polygon");
+
+        let qt2 = Quadtree::<i32, Value>::from_geojson(&out[..], quantizer).unwrap();
+        debug_assert_eq!(qt2.len(), qt.len());
+        for handle in 0..40 {
+            let (a, b) = (qt.get(handle).unwrap(), qt2.get(handle).unwrap());
+            debug_assert_eq!(a.area(), b.area());
+            debug_assert_eq!(a.value_ref(), b.value_ref());
+        }
+
+        // Writing is stable.
+        let mut again: Vec<u8> = vec![];
+        qt.to_geojson(&mut again, quantizer).unwrap();
+        debug_assert_eq!(out, again);
+    }
+
+    #[test]
+    fn rejects_bad_input() {
+        let quantizer = Quantizer::new(0.0, 0.0, 1.0);
+        let invalid = |document: Value| {
+            read(&document, quantizer).unwrap_err().kind() == io::ErrorKind::InvalidData
+        };
+        debug_assert!(invalid(json!({"type": "Point", "coordinates": [0.0, 0.0]})));
+        debug_assert!(invalid(json!({"type": "FeatureCollection"})));
+        debug_assert!(invalid(json!({"type": "Feature", "geometry": null})));
+        debug_assert!(invalid(json!({"type": "Feature", "bbox": [0.0, 1.0]})));
+        debug_assert!(invalid(
+            json!({"type": "Feature", "bbox": [0.0, 0.0, 1e12, 1.0]})
+        ));
+        debug_assert!(Quadtree::<i32, Value>::from_geojson(&b"{"[..], quantizer).is_err());
+
+        let empty = read(
+            &json!({"type": "FeatureCollection", "features": []}),
+            quantizer,
+        )
+        .unwrap();
+        debug_assert!(empty.is_empty());
+    }
+
+    #[test]
+    #[should_panic]
+    fn quantizer_with_no_size() {
+        Quantizer::new(0.0, 0.0, 0.0);
+    }
+}
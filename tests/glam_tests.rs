@@ -0,0 +1,48 @@
+// Copyright 2019 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![cfg(feature = "glam")]
+
+// For testing the conversions to and from glam.
+mod glam_tests {
+    use {
+        glam::{I64Vec2, IVec2, U8Vec2, UVec2},
+        quadtree_rs::{point::Point, Quadtree},
+    };
+
+    #[test]
+    fn vectors() {
+        debug_assert_eq!(Point::from(IVec2::new(-3, 7)), Point { x: -3, y: 7 });
+        debug_assert_eq!(IVec2::from(Point { x: -3, y: 7 }), IVec2::new(-3, 7));
+        debug_assert_eq!(Point::from(U8Vec2::new(1, 2)), Point { x: 1_u8, y: 2 });
+        debug_assert_eq!(
+            I64Vec2::from(Point {
+                x: i64::MIN,
+                y: i64::MAX
+            }),
+            I64Vec2::new(i64::MIN, i64::MAX)
+        );
+    }
+
+    #[test]
+    fn with_a_quadtree() {
+        let mut qt = Quadtree::<u32, &str>::new(4);
+        let handle = qt.insert_pt(UVec2::new(3, 9).into(), "player").unwrap();
+
+        let found = qt.query_pt(UVec2::new(3, 9).into()).next().unwrap();
+        debug_assert_eq!(found.handle(), handle);
+        debug_assert_eq!(UVec2::from(found.anchor()), UVec2::new(3, 9));
+        debug_assert!(qt.query_pt(UVec2::new(9, 3).into()).next().is_none());
+    }
+}
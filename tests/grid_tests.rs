@@ -0,0 +1,84 @@
+// Copyright 2019 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// For testing .from_grid(), .from_mask().
+mod grid_tests {
+    use quadtree_rs::{point::Point, Quadtree};
+
+    // A disc of radius 9 centred on (10, 7), in a 21x15 grid.
+    fn in_disc(x: u16, y: u16) -> bool {
+        let (dx, dy) = (i32::from(x) - 10, i32::from(y) - 7);
+        dx * dx + dy * dy <= 81
+    }
+
+    #[test]
+    fn every_cell_matches_the_grid() {
+        let qt = Quadtree::<u16, ()>::from_grid(21, 15, in_disc);
+        assert_eq!((qt.width(), qt.height()), (21, 15));
+        for x in 0..21 {
+            for y in 0..15 {
+                assert_eq!(
+                    qt.query_pt(Point { x, y }).count(),
+                    usize::from(in_disc(x, y)),
+                    "at ({}, {})",
+                    x,
+                    y
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn uniform_blocks_are_merged() {
+        let qt = Quadtree::<u16, ()>::from_grid(21, 15, in_disc);
+        let cells = (0..21)
+            .flat_map(|x| (0..15).map(move |y| (x, y)))
+            .filter(|&(x, y)| in_disc(x, y))
+            .count();
+        assert!(qt.len() < cells / 2);
+        // Every block is an aligned square of the tree.
+        for entry in qt.iter() {
+            let side = entry.width();
+            assert_eq!(side, entry.height());
+            assert!(side.is_power_of_two());
+            assert_eq!(entry.anchor().x() % side, 0);
+            assert_eq!(entry.anchor().y() % side, 0);
+        }
+
+        let full = Quadtree::<u32, ()>::from_grid(64, 64, |_, _| true);
+        assert_eq!(full.len(), 1);
+        let empty = Quadtree::<u32, ()>::from_grid(64, 64, |_, _| false);
+        assert!(empty.is_empty());
+    }
+
+    #[test]
+    fn mask_matches_grid() {
+        let mask: Vec<u8> = (0..15)
+            .flat_map(|y| (0..21).map(move |x| u8::from(in_disc(x, y))))
+            .collect();
+        let from_mask = Quadtree::<u16, ()>::from_mask(21, 15, &mask);
+        let from_grid = Quadtree::<u16, ()>::from_grid(21, 15, in_disc);
+        let mut a: Vec<_> = from_mask.regions().collect();
+        let mut b: Vec<_> = from_grid.regions().collect();
+        a.sort_by_key(|r| (r.anchor().x(), r.anchor().y()));
+        b.sort_by_key(|r| (r.anchor().x(), r.anchor().y()));
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    #[should_panic]
+    fn mask_of_the_wrong_size() {
+        Quadtree::<u8, ()>::from_mask(4, 4, &[1; 15]);
+    }
+}
@@ -0,0 +1,93 @@
+// Copyright 2019 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// For testing .from_image(), .render_to_buffer().
+mod image_tests {
+    use quadtree_rs::Quadtree;
+
+    // A 13x9 RGBA image: a horizontal red gradient, a vertical green one, and an opaque square.
+    fn mk_image() -> Vec<u8> {
+        let mut pixels = vec![];
+        for y in 0..9_u8 {
+            for x in 0..13_u8 {
+                let opaque = (2..6).contains(&x) && (3..7).contains(&y);
+                pixels.extend_from_slice(&[x * 19, y * 28, 77, if opaque { 255 } else { 128 }]);
+            }
+        }
+        pixels
+    }
+
+    fn rms_error(a: &[u8], b: &[u8]) -> f64 {
+        let sum: f64 = a
+            .iter()
+            .zip(b)
+            .map(|(&a, &b)| (f64::from(a) - f64::from(b)).powi(2))
+            .sum();
+        (sum / a.len() as f64).sqrt()
+    }
+
+    #[test]
+    fn lossless_round_trip() {
+        let pixels = mk_image();
+        let qt = Quadtree::<u16, [u8; 4]>::from_image(13, 9, &pixels, 0.0);
+        assert_eq!((qt.width(), qt.height()), (13, 9));
+
+        let mut rendered = vec![0; pixels.len()];
+        qt.render_to_buffer(&mut rendered);
+        assert_eq!(rendered, pixels);
+    }
+
+    #[test]
+    fn error_stays_within_threshold() {
+        let pixels = mk_image();
+        let mut previous = usize::MAX;
+        for &threshold in &[2.0, 8.0, 20.0, 60.0] {
+            let qt = Quadtree::<u16, [u8; 4]>::from_image(13, 9, &pixels, threshold);
+            assert!(qt.len() <= previous);
+            previous = qt.len();
+
+            let mut rendered = vec![0; pixels.len()];
+            qt.render_to_buffer(&mut rendered);
+            // Rounding each block's average adds at most half a level.
+            assert!(rms_error(&pixels, &rendered) <= threshold + 0.5);
+        }
+    }
+
+    #[test]
+    fn blocks_tile_the_image() {
+        let pixels = mk_image();
+        let qt = Quadtree::<u16, [u8; 4]>::from_image(13, 9, &pixels, 10.0);
+        let cells: u16 = qt.regions().map(|r| r.width() * r.height()).sum();
+        assert_eq!(cells, 13 * 9);
+        for x in 0..13 {
+            for y in 0..9 {
+                assert_eq!(qt.query_pt((x, y).into()).count(), 1);
+            }
+        }
+    }
+
+    #[test]
+    fn grayscale_averages() {
+        let pixels: Vec<u8> = (0..16).map(|i| i * 10).collect();
+        let qt = Quadtree::<u8, [u8; 1]>::from_image(4, 4, &pixels, 255.0);
+        assert_eq!(qt.len(), 1);
+        assert_eq!(qt.iter().next().unwrap().value_ref(), &[75]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn image_of_the_wrong_size() {
+        Quadtree::<u8, [u8; 3]>::from_image(2, 2, &[0; 11], 0.0);
+    }
+}
@@ -0,0 +1,123 @@
+// Copyright 2019 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![cfg(feature = "index")]
+
+mod util; // For area and unordered_elements_are.
+
+// For testing IndexedQuadtree.
+mod index_tests {
+    use {
+        crate::util::{area, unordered_elements_are},
+        quadtree_rs::{area::Area, index::IndexedQuadtree, Quadtree},
+    };
+
+    // Checks @qt's index against a scan of every entry, for every value it might hold.
+    fn check_index(qt: &IndexedQuadtree<u32, u32>) {
+        for v in 0..20 {
+            let expected: Vec<Area<u32>> = qt
+                .as_quadtree()
+                .iter()
+                .filter(|e| *e.value_ref() == v)
+                .map(|e| e.area())
+                .collect();
+            debug_assert_eq!(qt.find_value(&v).count(), expected.len());
+            debug_assert_eq!(qt.contains_value(&v), !expected.is_empty());
+            debug_assert!(unordered_elements_are(qt.find_value(&v), expected));
+            debug_assert_eq!(qt.contains_value(&v), qt.as_quadtree().contains_value(&v));
+        }
+    }
+
+    #[test]
+    fn find_value() {
+        let mut qt = IndexedQuadtree::<u32, u32>::new(5);
+        for i in 0..60 {
+            qt.insert(area(i % 30, i % 7, 1 + i % 3, 2), i % 10);
+        }
+        check_index(&qt);
+        debug_assert_eq!(qt.find_value(&3).count(), 6);
+        debug_assert_eq!(qt.find_value(&15).count(), 0);
+
+        // Out of bounds, so neither stored nor indexed.
+        debug_assert!(qt.insert_pt((40, 40).into(), 15).is_none());
+        debug_assert_eq!(qt.find_value(&15).count(), 0);
+    }
+
+    #[test]
+    fn removal() {
+        let mut qt = IndexedQuadtree::<u32, u32>::new(5);
+        let handles: Vec<u64> = (0..40)
+            .map(|i| qt.insert(area(i % 20, i % 9, 2, 2), i % 5).unwrap())
+            .collect();
+
+        debug_assert_eq!(qt.remove(handles[0]).map(|(_, v)| v), Some(0));
+        debug_assert!(qt.delete_by_handle(handles[1]).is_some());
+        debug_assert!(qt.delete_by_handle(handles[1]).is_none());
+        check_index(&qt);
+
+        debug_assert!(qt.delete(area(0, 0, 10, 10)).count() > 0);
+        check_index(&qt);
+
+        let removed = qt.retain(|v| *v % 2 == 0).count();
+        debug_assert!(removed > 0);
+        check_index(&qt);
+
+        qt.reset();
+        debug_assert_eq!(qt.find_value(&2).count(), 0);
+    }
+
+    #[test]
+    fn changes_to_values() {
+        let mut qt = IndexedQuadtree::<u32, u32>::new(5);
+        for i in 0..40 {
+            qt.insert(area(i % 20, i % 9, 1, 1), i % 5);
+        }
+
+        qt.modify(area(0, 0, 8, 8), |_, v| *v += 10);
+        check_index(&qt);
+        debug_assert_eq!(qt.modify_all(|_, v| *v %= 3), 40);
+        check_index(&qt);
+        debug_assert_eq!(qt.find_value(&10).count(), 0);
+
+        // Values which the retain predicate changes are re-indexed too.
+        qt.retain(|v| {
+            *v += 1;
+            *v > 100
+        });
+        check_index(&qt);
+        debug_assert_eq!(qt.find_value(&0).count(), 0);
+    }
+
+    #[test]
+    fn relocate() {
+        let mut qt = IndexedQuadtree::<u32, u32>::new(4);
+        let handle = qt.insert_pt((1, 1).into(), 7).unwrap();
+        debug_assert!(qt.relocate(handle, area(5, 6, 2, 2)));
+        debug_assert_eq!(
+            qt.find_value(&7).collect::<Vec<_>>(),
+            vec![area(5, 6, 2, 2)]
+        );
+    }
+
+    #[test]
+    fn from_quadtree() {
+        let mut tree = Quadtree::<u32, u32>::new(4);
+        tree.extend(vec![((1, 1), 3), ((2, 2), 3), ((3, 3), 4)]);
+
+        let qt = IndexedQuadtree::from(tree.clone());
+        check_index(&qt);
+        debug_assert_eq!(qt.find_value(&3).count(), 2);
+        debug_assert_eq!(qt.into_quadtree(), tree);
+    }
+}
@@ -12,9 +12,9 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-mod util; // For unordered_elements_are.
+mod util; // For area and unordered_elements_are.
 
-use quadtree_rs::{area::AreaBuilder, Quadtree};
+use quadtree_rs::{area::AreaBuilder, error::InsertError, BoundsPolicy, Quadtree};
 
 mod new {
     use super::*;
@@ -34,6 +34,347 @@ mod new {
         let _q1 = Quadtree::<u32, u32>::new_with_anchor((0, 510123).into(), 1);
         let _q2 = Quadtree::<u32, f64>::new_with_anchor((4009, 4009).into(), 2);
     }
+
+    #[test]
+    fn new_at_type_limits() {
+        debug_assert_eq!(Quadtree::<u8, ()>::new(7).width(), 128);
+        debug_assert_eq!(Quadtree::<i8, ()>::new(6).width(), 64);
+        let qt = Quadtree::<u8, ()>::new_with_anchor((127, 0).into(), 7);
+        debug_assert_eq!(qt.width(), 128);
+        let qt = Quadtree::<i8, ()>::new_with_anchor((-128, -128).into(), 6);
+        debug_assert_eq!(qt.width(), 64);
+    }
+
+    #[test]
+    #[should_panic(expected = "A depth of 8 is too large for this coordinate type.")]
+    fn new_too_deep() {
+        Quadtree::<u8, ()>::new(8);
+    }
+
+    #[test]
+    #[should_panic(expected = "The quadtree's region overflows its coordinate type.")]
+    fn new_with_anchor_too_far_out() {
+        Quadtree::<i8, ()>::new_with_anchor((100, 0).into(), 5);
+    }
+
+    #[test]
+    #[should_panic(expected = "The quadtree's region overflows its coordinate type.")]
+    fn new_ordered_too_far_out() {
+        Quadtree::<u8, ()>::new_ordered_with_anchor((0, 200).into(), 6);
+    }
+
+    #[test]
+    fn new_with_bounds() {
+        let mut qt = Quadtree::<u32, u8>::new_with_bounds((10, 20).into(), 5, 12);
+        debug_assert_eq!(qt.depth(), 4);
+        debug_assert_eq!(qt.anchor(), (10, 20).into());
+        debug_assert_eq!((qt.width(), qt.height()), (5, 12));
+
+        debug_assert!(qt.insert_pt((14, 31).into(), 1).is_some());
+        debug_assert!(qt.insert_pt((15, 31).into(), 2).is_none());
+        debug_assert!(qt.insert_pt((14, 32).into(), 3).is_none());
+        let region = AreaBuilder::default()
+            .anchor((10, 20).into())
+            .dimensions((5, 12))
+            .build()
+            .unwrap();
+        debug_assert!(qt.contains(region));
+        debug_assert!(qt.insert(region, 4).is_some());
+        debug_assert_eq!(qt.count_in_region(region), 2);
+
+        // Clipping survives a reset, and sets the quadtree apart from an unclipped one.
+        qt.reset();
+        debug_assert_eq!((qt.width(), qt.height()), (5, 12));
+        debug_assert_ne!(qt, Quadtree::<u32, u8>::new_with_anchor((10, 20).into(), 4));
+
+        // Exact powers of two pick the tightest depth.
+        debug_assert_eq!(
+            Quadtree::<u32, u8>::new_with_bounds((0, 0).into(), 1, 1).depth(),
+            0
+        );
+        debug_assert_eq!(
+            Quadtree::<u32, u8>::new_with_bounds((0, 0).into(), 8, 3).depth(),
+            3
+        );
+        debug_assert_eq!(
+            Quadtree::<u32, u8>::new_with_bounds((0, 0).into(), 9, 3).depth(),
+            4
+        );
+        debug_assert_eq!(
+            Quadtree::<u32, u8>::new_with_bounds((0, 0).into(), 8, 8),
+            Quadtree::<u32, u8>::new(3)
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn new_with_zero_bounds() {
+        Quadtree::<u32, u8>::new_with_bounds((0, 0).into(), 0, 4);
+    }
+
+    #[test]
+    fn bulk_load() {
+        let mk_region = |i: i32| {
+            AreaBuilder::default()
+                .anchor(((i * 37) % 70 - 20, (i * 53) % 67 - 20).into())
+                .dimensions((1 + i % 9, 1 + (i * 7) % 5))
+                .build()
+                .unwrap()
+        };
+        let items: Vec<_> = (0..300).map(|i| (mk_region(i), i)).collect();
+        let root = AreaBuilder::default()
+            .anchor((-16, -16).into())
+            .dimensions((64, 64))
+            .build()
+            .unwrap();
+
+        let mut expected = Quadtree::<i32, i32>::new_with_anchor((-16, -16).into(), 6);
+        let mut handles = Vec::new();
+        for (handle, &(region, i)) in items.iter().enumerate() {
+            if let Some(got) = expected.insert(region, i) {
+                debug_assert_eq!(got, handle as u64);
+                handles.push(got);
+            } else {
+                // Keep the handles in step with bulk_load(), which skips dropped items' handles.
+                let skipped = expected.insert(root, -1).unwrap();
+                expected.delete_by_handle(skipped);
+            }
+        }
+        let mut qt = Quadtree::bulk_load((-16, -16).into(), 6, items);
+        debug_assert!(handles.len() < 300);
+        debug_assert_eq!(qt.len(), handles.len());
+        debug_assert_eq!(qt, expected);
+
+        for &handle in &handles {
+            debug_assert_eq!(qt.get(handle), expected.get(handle));
+        }
+        debug_assert_eq!(qt.insert(root, 0), expected.insert(root, 0));
+        debug_assert_eq!(qt, expected);
+    }
+
+    #[test]
+    fn from_points() {
+        let pts = vec![
+            ((7, 3), 'a'),
+            ((100, 64), 'b'),
+            ((7, 3), 'c'),
+            ((70, 50), 'd'),
+        ];
+        let qt =
+            Quadtree::<u8, char>::from_points(pts.iter().map(|&((x, y), c)| ((x, y).into(), c)));
+        debug_assert_eq!(qt.anchor(), (7, 3).into());
+        debug_assert_eq!(qt.depth(), 7);
+        debug_assert_eq!(qt.len(), 4);
+        let mut expected = Quadtree::<u8, char>::new_with_anchor((7, 3).into(), 7);
+        expected.extend(pts);
+        debug_assert_eq!(qt, expected);
+
+        // Exactly filling the square.
+        let qt = Quadtree::<u8, ()>::from_points(vec![((0, 10).into(), ()), ((127, 0).into(), ())]);
+        debug_assert_eq!(qt.anchor(), (0, 0).into());
+        debug_assert_eq!(qt.depth(), 7);
+
+        let qt = Quadtree::<u8, ()>::from_points(vec![((9, 9).into(), ())]);
+        debug_assert_eq!(qt.anchor(), (9, 9).into());
+        debug_assert_eq!(qt.depth(), 0);
+        debug_assert_eq!(qt.len(), 1);
+
+        let qt = Quadtree::<u8, ()>::from_points(vec![]);
+        debug_assert_eq!(qt.depth(), 0);
+        debug_assert!(qt.is_empty());
+    }
+
+    #[test]
+    fn from_regions() {
+        let mk_region = |x, y, w, h| {
+            AreaBuilder::default()
+                .anchor((x, y).into())
+                .dimensions((w, h))
+                .build()
+                .unwrap()
+        };
+        let qt = Quadtree::<i16, u8>::from_regions(vec![
+            (mk_region(-5, 0, 3, 3), 0),
+            (mk_region(0, -2, 1, 12), 1),
+            (mk_region(4, 4, 2, 1), 2),
+        ]);
+        debug_assert_eq!(qt.anchor(), (-5, -2).into());
+        // The bounding box is 11x12.
+        debug_assert_eq!(qt.depth(), 4);
+        debug_assert_eq!(qt.len(), 3);
+        debug_assert!(qt.contains(mk_region(-5, -2, 11, 12)));
+    }
+
+    #[test]
+    #[should_panic]
+    fn from_points_too_spread_out() {
+        Quadtree::<i8, ()>::from_points(vec![((-100, 0).into(), ()), ((100, 0).into(), ())]);
+    }
+
+    #[test]
+    fn bulk_load_drops_items_outside() {
+        let pt = |x, y| {
+            AreaBuilder::default()
+                .anchor((x, y).into())
+                .build()
+                .unwrap()
+        };
+        let items = vec![(pt(0, 0), 'a'), (pt(8, 8), 'b'), (pt(3, 3), 'c')];
+        let qt = Quadtree::<u8, char>::bulk_load((0, 0).into(), 2, items);
+
+        debug_assert_eq!(qt.len(), 2);
+        debug_assert_eq!(qt.get(0).unwrap().value_ref(), &'a');
+        debug_assert!(qt.get(1).is_none());
+        debug_assert_eq!(qt.get(2).unwrap().value_ref(), &'c');
+    }
+
+    #[test]
+    fn bulk_load_deep() {
+        let pt = |x, y| {
+            AreaBuilder::default()
+                .anchor((x, y).into())
+                .build()
+                .unwrap()
+        };
+        let items = vec![(pt(0, 0), 1), (pt(1 << 70, 5), 2), (pt(3, 1 << 90), 3)];
+        let mut expected = Quadtree::<u128, u8>::new(100);
+        for &(region, val) in &items {
+            expected.insert(region, val);
+        }
+
+        debug_assert_eq!(Quadtree::bulk_load((0, 0).into(), 100, items), expected);
+    }
+
+    // Handles are sequential, so they can be used as their own hash.
+    #[derive(Default)]
+    struct IdentityHasher(u64);
+
+    impl std::hash::Hasher for IdentityHasher {
+        fn finish(&self) -> u64 {
+            self.0
+        }
+        fn write(&mut self, bytes: &[u8]) {
+            for &byte in bytes {
+                self.0 = (self.0 << 8) | u64::from(byte);
+            }
+        }
+        fn write_u64(&mut self, i: u64) {
+            self.0 = i;
+        }
+    }
+
+    #[test]
+    fn with_hasher() {
+        let hash_builder = std::hash::BuildHasherDefault::<IdentityHasher>::default();
+        let mut qt = Quadtree::<u32, u8, _>::with_anchor_and_hasher((2, 2).into(), 3, hash_builder);
+        qt.extend(vec![((2, 2), 1), ((5, 5), 2), ((9, 9), 3)]);
+        debug_assert_eq!(qt.len(), 3);
+
+        let region = AreaBuilder::default()
+            .anchor((4, 4).into())
+            .dimensions((2, 2))
+            .build()
+            .unwrap();
+        let hits: Vec<u8> = qt.query(region).map(|e| *e.value_ref()).collect();
+        debug_assert_eq!(hits, vec![2]);
+
+        debug_assert_eq!(qt.delete(region).count(), 1);
+        debug_assert_eq!(qt.len(), 2);
+    }
+}
+
+#[test]
+fn clone() {
+    let mut qt = Quadtree::<u32, u8>::new(4);
+    qt.extend(vec![((1, 1), 1), ((7, 9), 2), ((15, 0), 3)]);
+
+    let mut copy = qt.clone();
+    debug_assert_eq!(copy, qt);
+
+    // The two trees are independent of one another.
+    copy.extend(vec![((3, 3), 4)]);
+    debug_assert_eq!(copy.len(), 4);
+    debug_assert_eq!(qt.len(), 3);
+    debug_assert_ne!(copy, qt);
+}
+
+#[test]
+fn auto_expand() {
+    let mut qt = Quadtree::<i32, u8>::new_with_anchor((0, 0).into(), 2);
+    debug_assert!(!qt.is_auto_expanding());
+    let a = qt.insert_pt((1, 1).into(), 1).unwrap();
+    debug_assert!(qt.insert_pt((-3, 9).into(), 2).is_none());
+
+    qt.set_auto_expand(true);
+    debug_assert!(qt.is_auto_expanding());
+    // Leftward to cover x = -3, then downward twice to cover y = 9.
+    let b = qt.insert_pt((-3, 9).into(), 2).unwrap();
+    debug_assert_eq!(qt.anchor(), (-4, 0).into());
+    debug_assert_eq!(qt.depth(), 4);
+    debug_assert_eq!(qt.width(), 16);
+
+    // A region straddling the tree on both sides grows it in both directions.
+    let wide = AreaBuilder::default()
+        .anchor((-10, 1).into())
+        .dimensions((40, 1))
+        .build()
+        .unwrap();
+    let c = qt.insert(wide, 3).unwrap();
+    debug_assert!(qt.contains(wide));
+    debug_assert_eq!(qt.depth(), 6);
+
+    let mut hits: Vec<u64> = qt.query_pt((1, 1).into()).map(|e| e.handle()).collect();
+    hits.sort_unstable();
+    debug_assert_eq!(hits, vec![a, c]);
+    debug_assert_eq!(qt.get_pt((-3, 9).into()).unwrap().handle(), b);
+    debug_assert_eq!(qt.count_in_region(wide), 2);
+    debug_assert_eq!(qt.delete_by_handle(c).unwrap().value_ref(), &3);
+    debug_assert_eq!(qt.len(), 2);
+
+    // Growth stops short of overflowing the coordinate type.
+    let mut qt = Quadtree::<u8, u8>::new_with_bounds((0, 0).into(), 3, 3);
+    qt.set_auto_expand(true);
+    debug_assert!(qt.insert_pt((100, 2).into(), 1).is_some());
+    debug_assert_eq!((qt.width(), qt.height()), (128, 128));
+    debug_assert!(qt.insert_pt((200, 2).into(), 2).is_none());
+    debug_assert_eq!(qt.width(), 128);
+}
+
+#[test]
+fn bounds_policy() {
+    let mk_region = |x, y, w, h| {
+        AreaBuilder::default()
+            .anchor((x, y).into())
+            .dimensions((w, h))
+            .build()
+            .unwrap()
+    };
+    let mut qt = Quadtree::<i32, u8>::new_with_bounds((0, 0).into(), 10, 6);
+    debug_assert_eq!(qt.bounds_policy(), BoundsPolicy::Reject);
+    debug_assert!(qt.insert(mk_region(8, 4, 3, 1), 0).is_none());
+
+    qt.set_bounds_policy(BoundsPolicy::Clip);
+    debug_assert!(!qt.is_auto_expanding());
+    let handle = qt.insert(mk_region(8, 4, 3, 1), 1).unwrap();
+    debug_assert_eq!(qt.get(handle).unwrap().area(), mk_region(8, 4, 2, 1));
+    let handle = qt.insert(mk_region(-5, -5, 20, 20), 2).unwrap();
+    debug_assert_eq!(qt.get(handle).unwrap().area(), mk_region(0, 0, 10, 6));
+    // Clipping never leaves the bounds, even within the underlying 16x16 square.
+    debug_assert!(qt.insert(mk_region(10, 0, 2, 2), 3).is_none());
+    debug_assert!(qt.insert(mk_region(-3, 0, 3, 2), 4).is_none());
+    debug_assert!(qt.relocate(handle, mk_region(5, 5, 9, 9)));
+    debug_assert_eq!(qt.get(handle).unwrap().area(), mk_region(5, 5, 5, 1));
+    debug_assert_eq!(qt.len(), 2);
+    debug_assert_eq!(qt.query(mk_region(9, 5, 1, 1)).count(), 1);
+
+    qt.set_bounds_policy(BoundsPolicy::Expand);
+    debug_assert!(qt.is_auto_expanding());
+    let handle = qt.insert(mk_region(8, 4, 30, 1), 5).unwrap();
+    debug_assert_eq!(qt.get(handle).unwrap().area(), mk_region(8, 4, 30, 1));
+    debug_assert_eq!(qt.width(), 64);
+
+    qt.set_auto_expand(false);
+    debug_assert_eq!(qt.bounds_policy(), BoundsPolicy::Reject);
 }
 
 #[test]
@@ -153,6 +494,122 @@ mod insert {
             )
             .is_none());
     }
+
+    #[test]
+    fn insert_near_max() {
+        let mut qt = Quadtree::<u8, u8>::new_with_anchor((127, 127).into(), 7);
+        debug_assert!(qt.insert_pt((254, 254).into(), 1).is_some());
+        debug_assert!(qt.insert_pt((255, 255).into(), 2).is_none());
+        let overhanging = AreaBuilder::default()
+            .anchor((250, 250).into())
+            .dimensions((10, 10))
+            .build()
+            .unwrap();
+        debug_assert!(qt.insert(overhanging, 3).is_none());
+        debug_assert_eq!(qt.query(overhanging).count(), 1);
+
+        qt.set_bounds_policy(BoundsPolicy::Clip);
+        let handle = qt.insert(overhanging, 4).unwrap();
+        let clipped = qt.get(handle).unwrap().area();
+        debug_assert_eq!(clipped.anchor(), (250, 250).into());
+        debug_assert_eq!((clipped.width(), clipped.height()), (5, 5));
+
+        let mut qt = Quadtree::<i8, u8>::new_with_anchor((63, 63).into(), 6);
+        debug_assert!(qt.insert_pt((126, 126).into(), 1).is_some());
+        debug_assert!(qt.insert_pt((127, 127).into(), 2).is_none());
+        debug_assert_eq!(qt.query_pt((126, 126).into()).count(), 1);
+        debug_assert_eq!(qt.query_pt((127, 127).into()).count(), 0);
+    }
+
+    #[test]
+    fn insert_or_modify() {
+        let mut qt = Quadtree::<u32, u32>::new(3);
+        let tile = AreaBuilder::default()
+            .anchor((2, 2).into())
+            .dimensions((2, 2))
+            .build()
+            .unwrap();
+        let inner = AreaBuilder::default()
+            .anchor((2, 2).into())
+            .build()
+            .unwrap();
+
+        let handle = qt.insert_or_modify(tile, 10, |v| *v += 1).unwrap();
+        debug_assert_eq!(qt.get(handle).unwrap().value_ref(), &10);
+        debug_assert_eq!(qt.insert_or_modify(tile, 10, |v| *v += 1), Some(handle));
+        debug_assert_eq!(qt.get(handle).unwrap().value_ref(), &11);
+
+        // A region within the tile is a different region.
+        let other = qt.insert_or_modify(inner, 20, |v| *v += 1).unwrap();
+        debug_assert_ne!(other, handle);
+        debug_assert_eq!(qt.get(handle).unwrap().value_ref(), &11);
+        debug_assert_eq!(qt.get(other).unwrap().value_ref(), &20);
+        debug_assert_eq!(qt.len(), 2);
+
+        let outside = AreaBuilder::default()
+            .anchor((7, 7).into())
+            .dimensions((2, 2))
+            .build()
+            .unwrap();
+        debug_assert_eq!(qt.insert_or_modify(outside, 30, |v| *v += 1), None);
+        debug_assert_eq!(qt.len(), 2);
+    }
+
+    #[test]
+    fn replace() {
+        let mut qt = Quadtree::<i32, String>::new_with_anchor((-4, -4).into(), 3);
+        let tile = AreaBuilder::default()
+            .anchor((-2, -2).into())
+            .dimensions((3, 2))
+            .build()
+            .unwrap();
+        let handle = qt.insert(tile, "a".to_string()).unwrap();
+
+        debug_assert_eq!(qt.replace(tile, "b".to_string()), Some("a".to_string()));
+        debug_assert_eq!(qt.get(handle).unwrap().value_ref(), "b");
+        debug_assert_eq!(qt.get(handle).unwrap().area(), tile);
+        debug_assert_eq!(qt.query(tile).count(), 1);
+
+        // Overlapping or enclosing regions don't count.
+        let inner = AreaBuilder::default()
+            .anchor((-2, -2).into())
+            .build()
+            .unwrap();
+        debug_assert_eq!(qt.replace(inner, "c".to_string()), None);
+        debug_assert_eq!(qt.get(handle).unwrap().value_ref(), "b");
+        debug_assert_eq!(qt.len(), 1);
+    }
+
+    #[test]
+    fn try_insert() {
+        let mut qt = Quadtree::<i8, u8>::new_with_anchor((-8, -8).into(), 4);
+        debug_assert_eq!(qt.try_insert((-8, -8).into(), (16, 16), 0), Ok(0));
+        debug_assert_eq!(qt.try_insert((7, -8).into(), (1, 1), 1), Ok(1));
+        debug_assert_eq!(
+            qt.try_insert((7, -8).into(), (2, 1), 2),
+            Err(InsertError::OutOfBounds)
+        );
+        debug_assert_eq!(
+            qt.try_insert((-9, 0).into(), (1, 1), 3),
+            Err(InsertError::OutOfBounds)
+        );
+        debug_assert_eq!(
+            qt.try_insert((0, 0).into(), (1, -1), 4),
+            Err(InsertError::ZeroSize)
+        );
+        debug_assert_eq!(
+            qt.try_insert((0, 0).into(), (0, 3), 5),
+            Err(InsertError::ZeroSize)
+        );
+        debug_assert_eq!(
+            qt.try_insert((0, 120).into(), (1, 8), 6),
+            Err(InsertError::Overflow)
+        );
+        debug_assert_eq!(qt.len(), 2);
+
+        // A refused insertion doesn't use up a handle.
+        debug_assert_eq!(qt.try_insert((0, 0).into(), (1, 1), 7), Ok(2));
+    }
 }
 
 #[test]
@@ -281,6 +738,44 @@ fn reset() {
     debug_assert_eq!(qt.len(), 0);
 }
 
+#[test]
+fn capacity() {
+    let mut qt = Quadtree::<u32, u32>::with_capacity(6, 500);
+    debug_assert!(qt.capacity() >= 500);
+    let mut ordered = Quadtree::<u32, u32>::new_ordered(6);
+    ordered.reserve(500);
+    debug_assert_eq!(ordered.capacity(), 0);
+
+    for q in [&mut qt, &mut ordered].iter_mut() {
+        for i in 0..500 {
+            q.insert_pt((i % 64, (i * 7) % 64).into(), i);
+        }
+        for handle in (0..500).filter(|handle| handle % 50 != 0) {
+            q.delete_by_handle(handle);
+        }
+        q.shrink_to_fit();
+        debug_assert_eq!(q.len(), 10);
+
+        for i in 0..500 {
+            let hits: Vec<u32> = q
+                .query_pt((i % 64, (i * 7) % 64).into())
+                .map(|e| *e.value_ref())
+                .collect();
+            debug_assert_eq!(hits.contains(&i), i % 50 == 0);
+        }
+        let region = AreaBuilder::default()
+            .anchor((0, 0).into())
+            .dimensions((64, 64))
+            .build()
+            .unwrap();
+        debug_assert_eq!(q.count_in_region(region), 10);
+        debug_assert!(q.insert_pt((3, 3).into(), 1000).is_some());
+        debug_assert_eq!(q.count_in_region(region), 11);
+    }
+    debug_assert!(qt.capacity() < 500);
+    debug_assert_eq!(ordered.capacity(), 11);
+}
+
 // We should be able to store strings.
 mod string {
     use super::*;
@@ -324,7 +819,7 @@ mod string {
                 .anchor((0, 0).into())
                 .build()
                 .unwrap(),
-            |v| *v += "world",
+            |_, v| *v += "world",
         );
 
         assert_eq!(
@@ -429,6 +924,21 @@ mod extend {
         debug_assert_eq!(entry_five.value_ref(), &5);
     }
 
+    #[test]
+    fn try_extend() {
+        let mut qt = Quadtree::<u32, i8>::new_with_anchor((4, 4).into(), 2);
+        let rejected = qt.try_extend(vec![((3, 4), 0), ((4, 4), 1), ((7, 7), 2), ((8, 7), 3)]);
+        debug_assert_eq!(rejected, vec![((3, 4), 0), ((8, 7), 3)]);
+        debug_assert_eq!(qt.len(), 2);
+        debug_assert!(qt.try_extend(vec![((5, 5), 4)]).is_empty());
+        debug_assert_eq!(qt.len(), 3);
+
+        // Nothing is rejected once the quadtree grows to fit.
+        qt.set_auto_expand(true);
+        debug_assert!(qt.try_extend(rejected).is_empty());
+        debug_assert_eq!(qt.len(), 5);
+    }
+
     #[test]
     fn extend_with_points_and_regions() {
         let mut qt = Quadtree::<u32, i8>::new(3);
@@ -483,6 +993,222 @@ mod extend {
     }
 }
 
+mod merge_and_split {
+    use {
+        super::*,
+        crate::util::{area, unordered_elements_are},
+        quadtree_rs::{area::Area, split::NeverSplit},
+    };
+
+    // A deterministic spread of @n regions within a 32x32 quadtree, of assorted sizes.
+    fn regions(n: u32, seed: u32) -> Vec<Area<u32>> {
+        (0..n)
+            .map(|i| {
+                let k = i.wrapping_mul(2_654_435_761).wrapping_add(seed);
+                let (w, h) = (1 + k % 5, 1 + (k >> 8) % 3);
+                area((k >> 12) % (32 - w), (k >> 20) % (32 - h), w, h)
+            })
+            .collect()
+    }
+
+    // Checks @merged against @expected with queries over a grid of windows, then deletes
+    // everything in it by handle, so that every entry has to be where its region says it is.
+    fn check_merged(mut merged: Quadtree<u32, u32>, expected: &Quadtree<u32, u32>) {
+        debug_assert_eq!(merged.len(), expected.len());
+        for x in (0..32).step_by(6) {
+            for y in (0..32).step_by(7) {
+                let window = area(x, y, 5, 6);
+                debug_assert!(unordered_elements_are(
+                    merged.query(window).map(|e| (e.area(), *e.value_ref())),
+                    expected
+                        .query(window)
+                        .map(|e| (e.area(), *e.value_ref()))
+                        .collect::<Vec<_>>(),
+                ));
+                debug_assert_eq!(
+                    merged.count_in_region(window),
+                    expected.count_in_region(window)
+                );
+            }
+        }
+        let handles: Vec<u64> = merged.handles().collect();
+        for handle in handles {
+            debug_assert!(merged.delete_by_handle(handle).is_some());
+        }
+        debug_assert!(merged.is_empty());
+        debug_assert_eq!(merged.query(area(0, 0, 32, 32)).count(), 0);
+    }
+
+    fn merge_and_check(mut left: Quadtree<u32, u32>, mut right: Quadtree<u32, u32>) {
+        let mut expected = Quadtree::<u32, u32>::new(5);
+        for (i, region) in regions(150, 7).into_iter().enumerate() {
+            left.insert(region, i as u32);
+            expected.insert(region, i as u32);
+        }
+        for (i, region) in regions(150, 99).into_iter().enumerate() {
+            right.insert(region, 1000 + i as u32);
+            expected.insert(region, 1000 + i as u32);
+        }
+        debug_assert!(left.merge(right).is_empty());
+        check_merged(left, &expected);
+    }
+
+    #[test]
+    fn merge_grafts() {
+        merge_and_check(Quadtree::new(5), Quadtree::new(5));
+
+        // Into an empty quadtree, and from one.
+        let mut empty = Quadtree::<u32, u32>::new(5);
+        let mut right = Quadtree::<u32, u32>::new(5);
+        right.extend(vec![((3, 3), 1), ((30, 30), 2)]);
+        debug_assert!(empty.merge(right.clone()).is_empty());
+        debug_assert!(empty.merge(Quadtree::new(5)).is_empty());
+        check_merged(empty, &right);
+    }
+
+    #[test]
+    fn merge_split_policies() {
+        let mut left = Quadtree::<u32, u32>::new(5);
+        left.set_max_items_per_node(Some(2));
+        let mut right = Quadtree::<u32, u32>::new(5);
+        right.set_max_items_per_node(Some(8));
+        merge_and_check(left, right);
+
+        let mut left = Quadtree::<u32, u32>::new(5);
+        left.set_max_items_per_node(Some(8));
+        let mut right = Quadtree::<u32, u32>::new(5);
+        right.set_max_items_per_node(Some(2));
+        merge_and_check(left, right);
+
+        // The merged entries are laid out by this quadtree's policy, not by the other's.
+        let mut left = Quadtree::<u32, u32>::new(5);
+        left.set_split_policy(NeverSplit);
+        let mut right = Quadtree::<u32, u32>::new(5);
+        right.extend(vec![((1, 1), 1), ((30, 30), 2)]);
+        debug_assert!(left.merge(right).is_empty());
+        debug_assert_eq!(left.nodes().count(), 1);
+    }
+
+    #[test]
+    fn merge_loose() {
+        let mut left = Quadtree::<u32, u32>::new(5);
+        left.set_looseness(2.0);
+        let mut right = Quadtree::<u32, u32>::new(5);
+        right.set_looseness(2.0);
+        merge_and_check(left, right);
+
+        // Trees of different looseness can't be grafted together.
+        let mut left = Quadtree::<u32, u32>::new(5);
+        left.set_looseness(1.5);
+        merge_and_check(left, Quadtree::new(5));
+    }
+
+    #[test]
+    fn merge_renumbers_handles() {
+        let mut left = Quadtree::<u32, char>::new_ordered(4);
+        left.extend(vec![((1, 1), 'a'), ((2, 2), 'b')]);
+        let mut right = Quadtree::<u32, char>::new_ordered(4);
+        right.extend(vec![((9, 9), 'c'), ((1, 1), 'd'), ((4, 4), 'e')]);
+        right.delete_by_handle(0);
+
+        debug_assert!(left.merge(right).is_empty());
+        let merged: Vec<(u64, char)> = left
+            .into_iter()
+            .map(|e| (e.handle(), *e.value_ref()))
+            .collect();
+        debug_assert_eq!(merged, vec![(0, 'a'), (1, 'b'), (2, 'd'), (3, 'e')]);
+    }
+
+    #[test]
+    fn merge_out_of_bounds() {
+        let mut right = Quadtree::<u32, char>::new(4);
+        right.extend(vec![((1, 1), 'a'), ((6, 6), 'b')]);
+        right.insert(area(2, 2, 6, 2), 'c');
+
+        // A smaller quadtree rejects what doesn't fit...
+        let mut small = Quadtree::<u32, char>::new(2);
+        let rejected = small.merge(right.clone());
+        debug_assert!(unordered_elements_are(
+            rejected,
+            vec![(area(6, 6, 1, 1), 'b'), (area(2, 2, 6, 2), 'c')],
+        ));
+        debug_assert_eq!(small.values().collect::<Vec<_>>(), vec![&'a']);
+
+        // ...or clips it, if that's its policy...
+        let mut clipped = Quadtree::<u32, char>::new(2);
+        clipped.set_bounds_policy(BoundsPolicy::Clip);
+        debug_assert_eq!(clipped.merge(right.clone()), vec![(area(6, 6, 1, 1), 'b')]);
+        debug_assert!(unordered_elements_are(
+            clipped.regions(),
+            vec![area(1, 1, 1, 1), area(2, 2, 2, 2)],
+        ));
+
+        // ...and one with a different anchor has everything reinserted.
+        let mut shifted = Quadtree::<u32, char>::new_with_anchor((1, 1).into(), 4);
+        debug_assert!(shifted.merge(right).is_empty());
+        debug_assert_eq!(shifted.len(), 3);
+        debug_assert_eq!(shifted.query_pt((6, 6).into()).count(), 1);
+    }
+
+    #[test]
+    fn split_quadrants() {
+        let mut qt = Quadtree::<u32, u32>::new_with_anchor((32, 0).into(), 5);
+        qt.set_max_items_per_node(Some(3));
+        let mut handles = vec![];
+        for (i, region) in regions(200, 3).into_iter().enumerate() {
+            let region = area(
+                region.anchor().x() + 32,
+                region.anchor().y(),
+                region.width(),
+                region.height(),
+            );
+            handles.push((qt.insert(region, i as u32).unwrap(), region, i as u32));
+        }
+        let next = qt.insert_pt((32, 0).into(), 0).unwrap() + 1;
+        qt.delete_by_handle(next - 1);
+
+        let quadrants = qt.clone().split_quadrants();
+        let anchors: Vec<_> = quadrants.iter().map(|q| q.anchor()).collect();
+        debug_assert_eq!(
+            anchors,
+            vec![
+                (32, 0).into(),
+                (48, 0).into(),
+                (32, 16).into(),
+                (48, 16).into()
+            ]
+        );
+        for q in &quadrants {
+            debug_assert_eq!(q.depth(), 4);
+            debug_assert!(!q.split_policy().should_split(1, 2));
+            debug_assert!(q.split_policy().should_split(1, 3));
+        }
+
+        // Every entry is in each quadrant it overlaps, clipped to fit, under its old handle.
+        for (handle, region, value) in handles {
+            for q in &quadrants {
+                let clipped = q.get(handle).map(|e| (e.area(), *e.value_ref()));
+                let expected = region
+                    .intersection(area(q.anchor().x(), q.anchor().y(), 16, 16))
+                    .map(|clipped| (clipped, value));
+                debug_assert_eq!(clipped, expected);
+            }
+        }
+
+        // New handles don't collide with old ones.
+        for mut q in quadrants {
+            let handle = q.insert_pt(q.anchor(), 0).unwrap();
+            debug_assert_eq!(handle, next);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn split_quadrants_of_a_point() {
+        Quadtree::<u32, u32>::new(0).split_quadrants();
+    }
+}
+
 mod delete {
     use super::*;
 
@@ -529,6 +1255,115 @@ mod delete {
             1
         );
     }
+
+    #[test]
+    fn remove() {
+        let mut qt = Quadtree::<u32, String>::new(4);
+        let region = AreaBuilder::default()
+            .anchor((1, 1).into())
+            .dimensions((5, 3))
+            .build()
+            .unwrap();
+        let handle = qt.insert(region, "foo".to_string()).unwrap();
+        qt.insert(region, "bar".to_string()).unwrap();
+
+        let (area, value) = qt.remove(handle).unwrap();
+        debug_assert_eq!(area, region);
+        debug_assert_eq!(value, "foo");
+        debug_assert_eq!(qt.len(), 1);
+
+        // The handle was purged from every node which held it.
+        debug_assert!(qt.query(region).all(|e| e.value_ref() == "bar"));
+        debug_assert!(qt.remove(handle).is_none());
+    }
+
+    #[test]
+    fn clear() {
+        let mut tight = Quadtree::<u16, u32>::new(7);
+        let mut loose = Quadtree::<u16, u32>::new(7);
+        loose.set_looseness(2.0);
+        for i in 0..200_u32 {
+            let region = AreaBuilder::default()
+                .anchor((((i * 41) % 120) as u16, ((i * 23) % 120) as u16).into())
+                .dimensions((1 + (i % 7) as u16, 1 + (i % 4) as u16))
+                .build()
+                .unwrap();
+            tight.insert(region, i);
+            loose.insert(region, i);
+        }
+
+        let whole = AreaBuilder::default()
+            .anchor((0, 0).into())
+            .dimensions((128, 128))
+            .build()
+            .unwrap();
+        let region = AreaBuilder::default()
+            .anchor((16, 10).into())
+            .dimensions((64, 70))
+            .build()
+            .unwrap();
+        for qt in [&mut tight, &mut loose].iter_mut() {
+            let mut expected = qt.clone();
+            let deleted = expected.delete(region).count();
+
+            debug_assert_eq!(qt.clear(region), deleted);
+            debug_assert_eq!(qt.len(), expected.len());
+            debug_assert_eq!(qt.count_in_region(whole), qt.len());
+            debug_assert!(!qt.intersects_any(region));
+            debug_assert!(crate::util::unordered_elements_are(
+                qt.query(whole).map(|e| e.handle()),
+                expected.query(whole).map(|e| e.handle()),
+            ));
+
+            debug_assert_eq!(qt.clear(region), 0);
+            let remaining = qt.len();
+            debug_assert_eq!(qt.clear(whole), remaining);
+            debug_assert!(qt.is_empty());
+            debug_assert_eq!(qt.count_in_region(whole), 0);
+        }
+    }
+
+    #[test]
+    fn pop_pt() {
+        let mut qt = Quadtree::<u32, String>::new(4);
+        let region = AreaBuilder::default()
+            .anchor((1, 1).into())
+            .dimensions((5, 3))
+            .build()
+            .unwrap();
+        qt.insert(region, "foo".to_string()).unwrap();
+        qt.insert_pt((8, 8).into(), "bar".to_string()).unwrap();
+
+        debug_assert_eq!(qt.pop_pt((0, 0).into()), None);
+        debug_assert_eq!(qt.pop_pt((5, 3).into()), Some((region, "foo".to_string())));
+        debug_assert_eq!(qt.pop_pt((5, 3).into()), None);
+        debug_assert_eq!(qt.len(), 1);
+        debug_assert!(qt.query(region).next().is_none());
+    }
+
+    #[test]
+    fn pop_region() {
+        let mut qt = Quadtree::<u32, String>::new(4);
+        let region = AreaBuilder::default()
+            .anchor((1, 1).into())
+            .dimensions((5, 3))
+            .build()
+            .unwrap();
+        qt.insert(region, "foo".to_string()).unwrap();
+        qt.insert_pt((2, 2).into(), "bar".to_string()).unwrap();
+        qt.insert_pt((8, 8).into(), "baz".to_string()).unwrap();
+
+        let area = AreaBuilder::default()
+            .anchor((0, 0).into())
+            .dimensions((3, 3))
+            .build()
+            .unwrap();
+        let mut popped: Vec<String> = qt.pop_region(area).into_iter().map(|(_, v)| v).collect();
+        popped.sort();
+        debug_assert_eq!(popped, vec!["bar".to_string(), "foo".to_string()]);
+        debug_assert_eq!(qt.len(), 1);
+        debug_assert!(qt.pop_region(area).is_empty());
+    }
 }
 
 #[test]
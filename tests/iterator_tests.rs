@@ -14,11 +14,12 @@
 
 mod util; // For unordered_elements_are.
 
-// For testing .iter(), .iter_mut(), .regions(), .values(), .values_mut().
+// For testing .iter(), .iter_mut(), .regions(), .values(), .values_mut(), .handles(), .nodes(),
+// iterating over them in reverse, and .iter_in(), .nodes_in(), .morton_code(), .morton_key().
 mod iterator_tests {
     use {
         crate::util::unordered_elements_are,
-        quadtree_rs::{area::AreaBuilder, entry::Entry, Quadtree},
+        quadtree_rs::{area::AreaBuilder, entry::Entry, iter::Order, Quadtree},
     };
 
     fn mk_quadtree_for_iter_tests() -> Quadtree<i32, i8> {
@@ -42,7 +43,7 @@ mod iterator_tests {
     fn iter_mut_all() {
         let mut qt = mk_quadtree_for_iter_tests();
 
-        qt.modify_all(|v| *v += 1);
+        qt.modify_all(|_, v| *v += 1);
 
         debug_assert!(unordered_elements_are(
             qt.iter().map(|e| e.value_ref()),
@@ -57,6 +58,7 @@ mod iterator_tests {
             qt.regions().map(|a| a.into()),
             vec![((0, -5), (1, 1)), ((-15, 20), (1, 1)), ((30, -35), (1, 1))],
         ));
+        debug_assert_eq!(qt.regions().size_hint(), qt.iter().size_hint());
     }
 
     #[test]
@@ -64,6 +66,301 @@ mod iterator_tests {
         let qt = mk_quadtree_for_iter_tests();
 
         debug_assert!(unordered_elements_are(qt.values(), vec![&10, &-25, &40]));
+        debug_assert_eq!(qt.values().size_hint(), qt.iter().size_hint());
+    }
+
+    #[test]
+    fn rev() {
+        let mut qt = Quadtree::<u32, u32>::new(6);
+        for i in 0..150 {
+            let region = AreaBuilder::default()
+                .anchor((i * 13 % 61, i * 29 % 59).into())
+                .dimensions((1 + i % 4, 1 + i % 3))
+                .build()
+                .unwrap();
+            qt.insert(region, i);
+        }
+        qt.delete(
+            AreaBuilder::default()
+                .anchor((8, 8).into())
+                .dimensions((16, 16))
+                .build()
+                .unwrap(),
+        );
+
+        let forwards: Vec<u64> = qt.iter().map(|e| e.handle()).collect();
+        debug_assert_eq!(forwards.len(), qt.len());
+        let mut backwards: Vec<u64> = qt.iter().rev().map(|e| e.handle()).collect();
+        backwards.reverse();
+        debug_assert_eq!(forwards, backwards);
+
+        let values: Vec<&u32> = qt.values().collect();
+        debug_assert!(qt.values().rev().eq(values.into_iter().rev()));
+        let regions: Vec<_> = qt.regions().collect();
+        debug_assert!(qt.regions().rev().eq(regions.into_iter().rev()));
+
+        // Taking from both ends meets in the middle without losing or repeating anything.
+        let mut iter = qt.iter();
+        let mut met = vec![];
+        while let Some(e) = iter.next() {
+            met.push(e.handle());
+            if let Some(e) = iter.next_back() {
+                met.push(e.handle());
+            }
+        }
+        debug_assert!(iter.next_back().is_none());
+        debug_assert!(unordered_elements_are(met, forwards));
+    }
+
+    #[test]
+    fn nodes() {
+        let mut qt = Quadtree::<u32, u32>::new(6);
+        for i in 0..150 {
+            let region = AreaBuilder::default()
+                .anchor((i * 13 % 61, i * 29 % 59).into())
+                .dimensions((1 + i % 4, 1 + i % 3))
+                .build()
+                .unwrap();
+            qt.insert(region, i);
+        }
+
+        let root = AreaBuilder::default()
+            .anchor((0, 0).into())
+            .dimensions((64, 64))
+            .build()
+            .unwrap();
+        let nodes: Vec<_> = qt.nodes().collect();
+        debug_assert_eq!(nodes[0], (root, 0, nodes[0].2));
+        debug_assert_eq!(nodes.iter().map(|n| n.2).sum::<usize>(), qt.len());
+        // A level at a time, and every level's cells are half the size of the one above.
+        debug_assert!(nodes.windows(2).all(|w| w[0].1 <= w[1].1));
+        for (region, depth, _) in &nodes {
+            debug_assert!(*depth <= qt.depth());
+            debug_assert_eq!(region.width(), 64 >> depth);
+            debug_assert!(root.contains(*region));
+        }
+        // Each level covers the whole of the cells it was split from, without overlapping.
+        for depth in 1..=qt.depth() {
+            let level: Vec<_> = nodes.iter().filter(|n| n.1 == depth).collect();
+            let parents = nodes.iter().filter(|n| {
+                n.1 == depth - 1 && { level.iter().any(|child| n.0.contains(child.0)) }
+            });
+            debug_assert_eq!(level.len(), 4 * parents.count());
+        }
+
+        let empty = Quadtree::<u32, u32>::new(3);
+        let root = AreaBuilder::default()
+            .anchor((0, 0).into())
+            .dimensions((8, 8))
+            .build()
+            .unwrap();
+        debug_assert_eq!(empty.nodes().collect::<Vec<_>>(), vec![(root, 0, 0)]);
+    }
+
+    #[test]
+    fn orders() {
+        let mut qt = Quadtree::<u32, u32>::new(6);
+        for i in 0..150 {
+            let region = AreaBuilder::default()
+                .anchor((i * 13 % 61, i * 29 % 59).into())
+                .dimensions((1 + i % 4, 1 + i % 3))
+                .build()
+                .unwrap();
+            qt.insert(region, i);
+        }
+        // Interleaves the bits of a cell's anchor, y above x.
+        let morton = |x: u32, y: u32| {
+            (0..6).fold(0u32, |code, bit| {
+                code | ((x >> bit) & 1) << (2 * bit) | ((y >> bit) & 1) << (2 * bit + 1)
+            })
+        };
+
+        // The distance along the Hilbert curve through a 64 by 64 grid of a cell at the given
+        // depth, in its units.
+        let hilbert = |x: u32, y: u32, depth: usize| {
+            let (mut x, mut y) = (x >> (6 - depth), y >> (6 - depth));
+            let n = 1u32 << depth;
+            let (mut d, mut s) = (0, n / 2);
+            while s > 0 {
+                let (rx, ry) = ((x & s > 0) as u32, (y & s > 0) as u32);
+                d += s * s * ((3 * rx) ^ ry);
+                if ry == 0 {
+                    if rx == 1 {
+                        x = s - 1 - (x & (s - 1));
+                        y = s - 1 - (y & (s - 1));
+                    }
+                    std::mem::swap(&mut x, &mut y);
+                }
+                (x, y, s) = (x & (s - 1), y & (s - 1), s / 2);
+            }
+            d
+        };
+
+        for order in [
+            Order::DepthFirst,
+            Order::BreadthFirst,
+            Order::Morton,
+            Order::Hilbert,
+        ] {
+            debug_assert!(unordered_elements_are(
+                qt.iter_in(order).map(|e| e.handle()),
+                qt.handles(),
+            ));
+            debug_assert_eq!(qt.iter_in(order).count(), qt.len());
+
+            let nodes: Vec<_> = qt.nodes_in(order).collect();
+            debug_assert!(unordered_elements_are(
+                nodes.clone(),
+                qt.nodes().collect::<Vec<_>>()
+            ));
+            // Entries come out in the order of the cells holding them.
+            let mut cells = nodes
+                .iter()
+                .flat_map(|&(region, _, count)| vec![region; count]);
+            for entry in qt.iter_in(order) {
+                debug_assert!(cells.next().unwrap().contains(entry.area()));
+            }
+
+            match order {
+                Order::BreadthFirst => {
+                    debug_assert!(nodes.windows(2).all(|w| w[0].1 <= w[1].1));
+                }
+                _ => {
+                    // Everything beneath a cell follows straight after it.
+                    for (i, (region, depth, _)) in nodes.iter().enumerate() {
+                        let below = nodes[i + 1..]
+                            .iter()
+                            .take_while(|(r, d, _)| d > depth && region.contains(*r))
+                            .count();
+                        let all_below = nodes
+                            .iter()
+                            .filter(|(r, d, _)| d > depth && region.contains(*r))
+                            .count();
+                        debug_assert_eq!(below, all_below);
+                    }
+                }
+            }
+            if order == Order::Morton {
+                for depth in 0..=qt.depth() {
+                    let codes: Vec<u32> = nodes
+                        .iter()
+                        .filter(|n| n.1 == depth)
+                        .map(|(r, _, _)| morton(r.anchor().x(), r.anchor().y()))
+                        .collect();
+                    debug_assert!(codes.windows(2).all(|w| w[0] < w[1]));
+                }
+            }
+            if order == Order::Hilbert {
+                for depth in 0..=qt.depth() {
+                    let codes: Vec<u32> = nodes
+                        .iter()
+                        .filter(|n| n.1 == depth)
+                        .map(|(r, d, _)| hilbert(r.anchor().x(), r.anchor().y(), *d))
+                        .collect();
+                    debug_assert!(codes.windows(2).all(|w| w[0] < w[1]));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn morton_keys() {
+        let mut qt = Quadtree::<u32, u32>::new_with_anchor((100, 200).into(), 6);
+        for i in 0..150 {
+            let region = AreaBuilder::default()
+                .anchor((100 + i * 13 % 61, 200 + i * 29 % 59).into())
+                .dimensions((1 + i % 4, 1 + i % 3))
+                .build()
+                .unwrap();
+            qt.insert(region, i);
+        }
+
+        let keys: Vec<(u128, usize)> = qt
+            .iter_in(Order::Morton)
+            .map(|e| qt.morton_key(e.handle()).unwrap())
+            .collect();
+        for (entry, &(code, depth)) in qt.iter_in(Order::Morton).zip(&keys) {
+            // The key's cell holds the entry, so its corners share the key as a prefix.
+            let (a, b) = (entry.anchor(), entry.area());
+            let far = (b.right_edge() - 1, b.bottom_edge() - 1).into();
+            debug_assert_eq!(qt.morton_code(a, depth), Some(code));
+            debug_assert_eq!(qt.morton_code(far, depth), Some(code));
+            debug_assert_eq!(qt.morton_code(a, 0), Some(0));
+            if depth < qt.depth() {
+                debug_assert_eq!(qt.morton_code(a, depth + 1).unwrap() >> 2, code);
+            }
+        }
+        // Widened to the full depth, ancestors first, the keys come out sorted.
+        let widened: Vec<(u128, usize)> = keys
+            .iter()
+            .map(|&(code, depth)| (code << (2 * (6 - depth)), depth))
+            .collect();
+        debug_assert!(widened.windows(2).all(|w| w[0] <= w[1]));
+
+        debug_assert_eq!(qt.morton_code((99, 200).into(), 3), None);
+        debug_assert_eq!(qt.morton_code((100, 200).into(), 7), None);
+        debug_assert_eq!(qt.morton_code((163, 263).into(), 6), Some(0xfff));
+        debug_assert_eq!(qt.morton_key(1000), None);
+    }
+
+    #[test]
+    fn iter_mut() {
+        let mut qt = mk_quadtree_for_iter_tests();
+        let regions: Vec<_> = qt.regions().collect();
+
+        for (region, v) in qt.iter_mut() {
+            if region.anchor().x() < 0 {
+                *v = 0;
+            }
+        }
+
+        debug_assert!(unordered_elements_are(qt.values(), vec![&10, &0, &40]));
+        debug_assert!(unordered_elements_are(qt.regions(), regions));
+    }
+
+    #[test]
+    fn values_mut() {
+        let mut qt = mk_quadtree_for_iter_tests();
+        debug_assert_eq!(qt.values_mut().count(), 3);
+
+        qt.values_mut().for_each(|v| *v /= 2);
+        debug_assert!(unordered_elements_are(qt.values(), vec![&5, &-12, &20]));
+
+        qt.reset();
+        debug_assert_eq!(qt.values_mut().next(), None);
+    }
+
+    #[test]
+    fn handles() {
+        let mut qt = Quadtree::<i32, i8>::new_with_anchor((-35, -35).into(), 8);
+        let handles: Vec<u64> = [((0, -5), 10), ((-15, 20), -25), ((30, -35), 40)]
+            .iter()
+            .map(|&(pt, v)| qt.insert_pt(pt.into(), v).unwrap())
+            .collect();
+        qt.delete_by_handle(handles[1]);
+
+        debug_assert!(unordered_elements_are(
+            qt.handles(),
+            vec![handles[0], handles[2]]
+        ));
+        debug_assert!(unordered_elements_are(
+            qt.iter_with_handles(),
+            vec![
+                (handles[0], qt.get(handles[0]).unwrap().area(), &10),
+                (handles[2], qt.get(handles[2]).unwrap().area(), &40),
+            ]
+        ));
+    }
+
+    #[test]
+    fn contains_value() {
+        let mut qt = mk_quadtree_for_iter_tests();
+        debug_assert!(qt.contains_value(&-25));
+        debug_assert!(!qt.contains_value(&11));
+
+        qt.modify_all(|_, v| *v += 1);
+        debug_assert!(!qt.contains_value(&-25));
+        debug_assert!(qt.contains_value(&11));
     }
 
     #[test]
@@ -147,4 +444,172 @@ mod iterator_tests {
             vec![&-25, &10,]
         ));
     }
+
+    #[test]
+    fn drain() {
+        let mut qt = mk_quadtree_for_iter_tests();
+        let capacity = qt.capacity();
+
+        debug_assert!(unordered_elements_are(
+            qt.drain().map(|e| *e.value_ref()),
+            vec![10, -25, 40]
+        ));
+        debug_assert!(qt.is_empty());
+        debug_assert!(qt.query_pt((0, -5).into()).next().is_none());
+        debug_assert_eq!(qt.capacity(), capacity);
+
+        // The quadtree can be filled again.
+        qt.extend(vec![((0, -5), 1)]);
+        debug_assert_eq!(qt.query_pt((0, -5).into()).next().unwrap().value_ref(), &1);
+    }
+
+    #[test]
+    fn drain_region() {
+        let mut qt = mk_quadtree_for_iter_tests();
+        let drained: Vec<Entry<i32, i8>> = qt
+            .drain_region(
+                AreaBuilder::default()
+                    .anchor((-15, -5).into())
+                    .dimensions((16, 26))
+                    .build()
+                    .unwrap(),
+            )
+            .collect();
+        debug_assert!(unordered_elements_are(
+            drained.iter().map(|e| e.value_ref()),
+            vec![&-25, &10]
+        ));
+        debug_assert!(unordered_elements_are(qt.values(), vec![&40]));
+    }
+
+    #[test]
+    fn drain_lazily() {
+        let mut qt = mk_quadtree_for_iter_tests();
+        let mut drain = qt.drain();
+        drain.next().unwrap();
+        debug_assert_eq!(drain.size_hint(), (0, Some(2)));
+        drop(drain);
+        // Whatever wasn't yielded went when the iterator was dropped.
+        debug_assert!(qt.is_empty());
+        debug_assert_eq!(qt.iter().count(), 0);
+
+        let mut qt = mk_quadtree_for_iter_tests();
+        let everywhere = AreaBuilder::default()
+            .anchor((-35, -35).into())
+            .dimensions((256, 256))
+            .build()
+            .unwrap();
+        let mut drain = qt.drain_region(everywhere);
+        let first = drain.next().unwrap();
+        drop(drain);
+        debug_assert!(qt.is_empty());
+        debug_assert!(qt.query(everywhere).next().is_none());
+        debug_assert!(qt.get(first.handle()).is_none());
+    }
+
+    fn mk_ordered_quadtree_for_iter_tests() -> Quadtree<i32, i8> {
+        let mut qt = Quadtree::<i32, i8>::new_ordered_with_anchor((-35, -35).into(), 8);
+        qt.extend(vec![
+            ((30, -35), 1),
+            ((0, -5), 2),
+            ((-15, 20), 3),
+            ((0, -4), 4),
+            ((-35, -35), 5),
+        ]);
+        qt
+    }
+
+    #[test]
+    fn ordered_into_iter() {
+        let qt = mk_ordered_quadtree_for_iter_tests();
+        debug_assert!(qt.is_ordered());
+        let values: Vec<i8> = qt.into_iter().map(|e| *e.value_ref()).collect();
+        debug_assert_eq!(values, vec![1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn ordered_retain() {
+        let mut qt = mk_ordered_quadtree_for_iter_tests();
+        let removed: Vec<i8> = qt.retain(|v| *v % 2 == 1).map(|e| *e.value_ref()).collect();
+        debug_assert_eq!(removed, vec![1, 3, 5]);
+        debug_assert_eq!(qt.len(), 2);
+    }
+
+    #[test]
+    fn ordered_delete() {
+        let mut qt = mk_ordered_quadtree_for_iter_tests();
+        let removed: Vec<i8> = qt
+            .delete(
+                AreaBuilder::default()
+                    .anchor((-35, -35).into())
+                    .dimensions((80, 80))
+                    .build()
+                    .unwrap(),
+            )
+            .map(|e| *e.value_ref())
+            .collect();
+        debug_assert_eq!(removed, vec![1, 2, 3, 4, 5]);
+        debug_assert!(qt.is_empty());
+    }
+
+    #[test]
+    fn ordered_drain() {
+        let mut qt = mk_ordered_quadtree_for_iter_tests();
+        let drained: Vec<i8> = qt.drain().map(|e| *e.value_ref()).collect();
+        debug_assert_eq!(drained, vec![1, 2, 3, 4, 5]);
+        debug_assert!(qt.is_empty());
+        debug_assert!(qt.is_ordered());
+    }
+
+    #[test]
+    fn map_values() {
+        let qt = mk_quadtree_for_iter_tests();
+        let before: Vec<(u64, _, i8)> = qt
+            .iter_with_handles()
+            .map(|(h, region, v)| (h, region, *v))
+            .collect();
+
+        let mut mapped = qt.map_values(|region, v| (region.anchor().x(), i32::from(v) * 2));
+        debug_assert!(unordered_elements_are(
+            mapped
+                .iter_with_handles()
+                .map(|(h, region, v)| (h, region, *v)),
+            before
+                .iter()
+                .map(|&(h, region, v)| (h, region, (region.anchor().x(), i32::from(v) * 2)))
+                .collect::<Vec<_>>(),
+        ));
+
+        // The mapped quadtree carries on issuing new handles.
+        let handle = mapped.insert_pt((1, 1).into(), (0, 0)).unwrap();
+        debug_assert!(before.iter().all(|&(h, _, _)| h < handle));
+        debug_assert_eq!(mapped.query_pt((1, 1).into()).count(), 1);
+    }
+
+    #[test]
+    fn ordered_map_values() {
+        let mut visited = vec![];
+        let qt = mk_ordered_quadtree_for_iter_tests().map_values(|_, v| {
+            visited.push(v);
+            u32::from(v.unsigned_abs())
+        });
+        debug_assert_eq!(visited, vec![1, 2, 3, 4, 5]);
+        debug_assert!(qt.is_ordered());
+        let values: Vec<u32> = qt.into_iter().map(|e| *e.value_ref()).collect();
+        debug_assert_eq!(values, vec![1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn ordered_equals_unordered() {
+        let mut unordered = Quadtree::<i32, i8>::new_with_anchor((-35, -35).into(), 8);
+        unordered.extend(vec![
+            ((30, -35), 1),
+            ((0, -5), 2),
+            ((-15, 20), 3),
+            ((0, -4), 4),
+            ((-35, -35), 5),
+        ]);
+        debug_assert!(!unordered.is_ordered());
+        debug_assert_eq!(mk_ordered_quadtree_for_iter_tests(), unordered);
+    }
 }
@@ -0,0 +1,41 @@
+// Copyright 2019 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![cfg(feature = "nalgebra")]
+
+// For testing the conversions to and from nalgebra.
+mod nalgebra_tests {
+    use {
+        nalgebra::{Point2, Vector2},
+        quadtree_rs::{point::Point, Quadtree},
+    };
+
+    #[test]
+    fn points_and_vectors() {
+        debug_assert_eq!(Point::from(Point2::new(-3_i64, 7)), Point { x: -3, y: 7 });
+        debug_assert_eq!(Point2::from(Point { x: -3_i64, y: 7 }), Point2::new(-3, 7));
+        debug_assert_eq!(Point::from(Vector2::new(4_u16, 5)), Point { x: 4, y: 5 });
+        debug_assert_eq!(Vector2::from(Point { x: 4_u16, y: 5 }), Vector2::new(4, 5));
+    }
+
+    #[test]
+    fn with_a_quadtree() {
+        let mut qt = Quadtree::<i64, &str>::new_with_anchor((-8, -8).into(), 4);
+        let handle = qt.insert_pt(Point2::new(-2, 5).into(), "crate").unwrap();
+
+        let found = qt.nearest(Point2::new(0, 4).into(), None).unwrap();
+        debug_assert_eq!(found.handle(), handle);
+        debug_assert_eq!(Point2::from(found.anchor()), Point2::new(-2, 5));
+    }
+}
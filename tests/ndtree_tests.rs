@@ -0,0 +1,117 @@
+// Copyright 2019 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![cfg(feature = "ndtree")]
+
+mod util; // For unordered_elements_are.
+
+// For testing ndtree::Tree.
+mod ndtree_tests {
+    use {
+        crate::util::unordered_elements_are,
+        quadtree_rs::{
+            area::AreaBuilder,
+            ndtree::{Region, Tree},
+            point::Point,
+            Quadtree,
+        },
+    };
+
+    #[test]
+    fn two_dimensions_agree_with_quadtree() {
+        let mut qt = Quadtree::<u16, u32>::new(5);
+        let mut tree = Tree::<u16, u32, 2>::new(5);
+        let mut value = 0;
+        for x in (0..32).step_by(3) {
+            for y in (0..32).step_by(5) {
+                let area = AreaBuilder::default()
+                    .anchor(Point { x, y })
+                    .dimensions((1 + x % 4, 1 + y % 3))
+                    .build()
+                    .unwrap();
+                assert_eq!(
+                    qt.insert(area, value).is_some(),
+                    tree.insert(area.into(), value).is_some()
+                );
+                value += 1;
+            }
+        }
+        assert_eq!(qt.len(), tree.len());
+
+        for &((x, y), (w, h)) in &[((0, 0), (32, 32)), ((7, 3), (5, 9)), ((15, 15), (2, 2))] {
+            let area = AreaBuilder::default()
+                .anchor(Point { x, y })
+                .dimensions((w, h))
+                .build()
+                .unwrap();
+            assert!(unordered_elements_are(
+                qt.query(area).map(|e| *e.value_ref()),
+                tree.query(area.into()).map(|e| *e.value_ref()),
+            ));
+        }
+
+        let pt = Point { x: 22, y: 6 };
+        assert_eq!(tree.knn(pt, 4).len(), 4);
+        assert!(tree.knn(pt, 1)[0].region().contains_pt(pt));
+    }
+
+    #[test]
+    fn four_dimensions() {
+        let mut tree = Tree::<i8, usize, 4>::new_with_anchor([-4; 4], 3);
+        assert_eq!(tree.width(), 8);
+        let mut regions = vec![];
+        for i in 0..40_i8 {
+            let anchor = [i % 7 - 4, i % 5 - 4, i % 7 - 4, i % 3 - 4];
+            let region = Region::new(anchor, [1 + i % 2, 1, 2, 1 + i % 3]).unwrap();
+            assert_eq!(
+                tree.insert(region, regions.len()),
+                Some(regions.len() as u64)
+            );
+            regions.push(region);
+        }
+        assert!(tree.insert_pt([4, 0, 0, 0], 0).is_none());
+
+        let req = Region::new([-1; 4], [3, 3, 3, 3]).unwrap();
+        assert!(unordered_elements_are(
+            tree.query(req).map(|e| *e.value_ref()),
+            (0..regions.len()).filter(|&i| regions[i].intersects(req)),
+        ));
+
+        let deleted: Vec<usize> = tree.delete(req).map(|e| *e.value_ref()).collect();
+        assert!(!deleted.is_empty());
+        assert_eq!(tree.len() + deleted.len(), regions.len());
+        assert_eq!(tree.query(req).count(), 0);
+        assert_eq!(tree.knn([0; 4], usize::MAX).len(), tree.len());
+    }
+
+    #[test]
+    fn knn_breaks_ties_by_handle() {
+        let mut tree = Tree::<u8, (), 2>::new(4);
+        // Equidistant from the origin. The later one straddles a boundary, so it's held higher
+        // up than the earlier one.
+        tree.insert_pt([0, 4], ());
+        tree.insert(Region::new([4, 0], [1, 5]).unwrap(), ());
+        tree.insert_pt([9, 9], ());
+        let handles: Vec<u64> = tree.knn([0, 0], 3).iter().map(|e| e.handle()).collect();
+        assert_eq!(handles, vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn regions_must_have_positive_extents() {
+        assert!(Region::new([0, 0, 0], [1, 1, 1]).is_ok());
+        assert!(Region::new([0, 0, 0], [1, 0, 1])
+            .unwrap_err()
+            .contains("axis 1"));
+    }
+}
@@ -0,0 +1,277 @@
+// Copyright 2019 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+mod util; // For unordered_elements_are.
+
+// For testing .knn(), .nearest(), .raycast().
+mod nearest_tests {
+    use {
+        crate::util::unordered_elements_are,
+        quadtree_rs::{area::AreaBuilder, point::Point, Quadtree},
+    };
+
+    // The squared distance from (x, y) to the nearest cell of a region.
+    fn distance_sq(pt: (i32, i32), ((ax, ay), (w, h)): ((i32, i32), (i32, i32))) -> i32 {
+        let gap = |c: i32, lo: i32, hi: i32| {
+            if c < lo {
+                lo - c
+            } else if c >= hi {
+                c - hi + 1
+            } else {
+                0
+            }
+        };
+        let dx = gap(pt.0, ax, ax + w);
+        let dy = gap(pt.1, ay, ay + h);
+        dx * dx + dy * dy
+    }
+
+    fn mk_quadtree_for_nearest_tests() -> Quadtree<i32, u32> {
+        let mut qt = Quadtree::<i32, u32>::new_with_anchor((-16, -16).into(), 5);
+        let mut value = 0;
+        for x in (-16..16).step_by(5) {
+            for y in (-16..16).step_by(7) {
+                let w = 1 + (x + 16) % 3;
+                let h = 1 + (y + 16) % 4;
+                qt.insert(
+                    AreaBuilder::default()
+                        .anchor((x, y).into())
+                        .dimensions((w, h))
+                        .build()
+                        .unwrap(),
+                    value,
+                );
+                value += 1;
+            }
+        }
+        qt
+    }
+
+    #[test]
+    fn knn_empty() {
+        let qt = Quadtree::<u32, u8>::new(3);
+        debug_assert!(qt.knn(Point { x: 1, y: 1 }, 3).is_empty());
+    }
+
+    #[test]
+    fn knn_fewer_than_k() {
+        let mut qt = Quadtree::<u32, u8>::new(3);
+        qt.insert_pt(Point { x: 1, y: 1 }, 1);
+        qt.insert_pt(Point { x: 6, y: 2 }, 2);
+        debug_assert!(unordered_elements_are(
+            qt.knn(Point { x: 0, y: 0 }, 10)
+                .iter()
+                .map(|e| e.value_ref()),
+            vec![&1, &2],
+        ));
+        debug_assert_eq!(qt.knn(Point { x: 0, y: 0 }, 0).len(), 0);
+    }
+
+    #[test]
+    fn knn_containing_region_is_nearest() {
+        let mut qt = Quadtree::<u32, char>::new(4);
+        qt.insert_pt(Point { x: 3, y: 3 }, 'a');
+        qt.insert(
+            AreaBuilder::default()
+                .anchor((0, 0).into())
+                .dimensions((16, 16))
+                .build()
+                .unwrap(),
+            'b',
+        );
+        debug_assert_eq!(qt.knn(Point { x: 12, y: 12 }, 1)[0].value_ref(), &'b');
+    }
+
+    #[test]
+    fn knn_point_outside_tree() {
+        let mut qt = Quadtree::<i32, char>::new(3);
+        qt.insert_pt(Point { x: 0, y: 0 }, 'a');
+        qt.insert_pt(Point { x: 7, y: 7 }, 'b');
+        debug_assert_eq!(qt.knn(Point { x: -5, y: -5 }, 1)[0].value_ref(), &'a');
+        debug_assert_eq!(qt.knn(Point { x: 20, y: 9 }, 1)[0].value_ref(), &'b');
+    }
+
+    #[test]
+    fn knn_at_the_ends_of_the_coordinate_type() {
+        let top = u128::MAX - 127;
+        let mut qt = Quadtree::<u128, char>::new_with_anchor(Point { x: top, y: top }, 6);
+        qt.insert_pt(Point { x: top, y: top }, 'a');
+        qt.insert_pt(
+            Point {
+                x: top + 60,
+                y: top + 2,
+            },
+            'b',
+        );
+        debug_assert_eq!(
+            qt.knn(
+                Point {
+                    x: top + 63,
+                    y: top
+                },
+                1
+            )[0]
+            .value_ref(),
+            &'b'
+        );
+        debug_assert_eq!(qt.knn(Point { x: 0, y: 0 }, 1)[0].value_ref(), &'a');
+
+        // The query point is further from the tree than an i8 can count.
+        let mut qt = Quadtree::<i8, char>::new_with_anchor(Point { x: -128, y: -128 }, 6);
+        qt.insert_pt(Point { x: -128, y: -128 }, 'a');
+        qt.insert_pt(Point { x: -66, y: -70 }, 'b');
+        debug_assert_eq!(qt.knn(Point { x: 127, y: 127 }, 1)[0].value_ref(), &'b');
+        debug_assert_eq!(qt.knn(Point { x: -128, y: 127 }, 2).len(), 2);
+    }
+
+    #[test]
+    fn knn_matches_brute_force() {
+        let qt = mk_quadtree_for_nearest_tests();
+        for &pt in &[(0, 0), (-16, -16), (15, 15), (3, -9), (-7, 12), (40, -3)] {
+            let mut expected: Vec<(i32, u64)> = qt
+                .iter()
+                .map(|e| (distance_sq(pt, e.area().into()), e.handle()))
+                .collect();
+            expected.sort();
+
+            for k in 0..8 {
+                let actual: Vec<(i32, u64)> = qt
+                    .knn(pt.into(), k)
+                    .iter()
+                    .map(|e| (distance_sq(pt, e.area().into()), e.handle()))
+                    .collect();
+                // Ties come back in order of handle.
+                debug_assert_eq!(actual, expected[..k].to_vec());
+            }
+        }
+    }
+
+    #[test]
+    fn nearest_empty() {
+        let qt = Quadtree::<u32, u8>::new(3);
+        debug_assert!(qt.nearest(Point { x: 1, y: 1 }, None).is_none());
+    }
+
+    #[test]
+    fn nearest_matches_knn() {
+        let qt = mk_quadtree_for_nearest_tests();
+        for &pt in &[(0, 0), (-16, -16), (15, 15), (3, -9), (-7, 12), (40, -3)] {
+            let nearest = qt.nearest(pt.into(), None).unwrap();
+            debug_assert_eq!(
+                distance_sq(pt, nearest.area().into()),
+                distance_sq(pt, qt.knn(pt.into(), 1)[0].area().into())
+            );
+        }
+    }
+
+    #[test]
+    fn nearest_within_max_distance() {
+        let mut qt = Quadtree::<u32, char>::new(4);
+        qt.insert_pt(Point { x: 4, y: 4 }, 'a');
+
+        // (4, 4) is exactly 3 cells from (4, 7) and 5 cells from (7, 8).
+        debug_assert!(qt.nearest(Point { x: 4, y: 7 }, Some(2)).is_none());
+        debug_assert_eq!(
+            qt.nearest(Point { x: 4, y: 7 }, Some(3))
+                .unwrap()
+                .value_ref(),
+            &'a'
+        );
+        debug_assert!(qt.nearest(Point { x: 7, y: 8 }, Some(4)).is_none());
+        debug_assert!(qt.nearest(Point { x: 7, y: 8 }, Some(5)).is_some());
+        debug_assert!(qt.nearest(Point { x: 4, y: 4 }, Some(0)).is_some());
+    }
+
+    // Marches along a ray in small steps, returning where it first finds itself in each region it
+    // passes through.
+    fn march(
+        qt: &Quadtree<i32, u32>,
+        origin: (i32, i32),
+        (dx, dy): (f64, f64),
+        max_len: f64,
+    ) -> Vec<(u64, f64)> {
+        let len = dx.hypot(dy);
+        let (ox, oy) = (f64::from(origin.0) + 0.5, f64::from(origin.1) + 0.5);
+        let mut first: Vec<(u64, f64)> = vec![];
+        for step in 0..=(max_len * 100.0) as i32 {
+            let t = f64::from(step) / 100.0;
+            let (x, y) = (ox + t * dx / len, oy + t * dy / len);
+            for e in qt.iter() {
+                let a = e.area();
+                let inside = f64::from(a.left_edge()) <= x
+                    && x <= f64::from(a.right_edge())
+                    && f64::from(a.top_edge()) <= y
+                    && y <= f64::from(a.bottom_edge());
+                if inside && first.iter().all(|(h, _)| *h != e.handle()) {
+                    first.push((e.handle(), t));
+                }
+            }
+        }
+        first
+    }
+
+    #[test]
+    fn raycast_matches_marching() {
+        let qt = mk_quadtree_for_nearest_tests();
+        let mut loose = Quadtree::<i32, u32>::new_with_anchor((-16, -16).into(), 5);
+        loose.set_looseness(2.0);
+        for e in qt.iter() {
+            loose.insert(e.area(), *e.value_ref());
+        }
+
+        let mut total = 0;
+        for (origin, direction, max_len) in [
+            ((-16, -16), (1.0, 0.83), 50.0),
+            ((0, 0), (-0.31, 1.0), 20.0),
+            ((5, -3), (-1.0, -0.47), 30.0),
+            ((-12, 9), (0.9, -0.13), 12.5),
+            ((-30, -2), (1.0, 0.05), 60.0),
+        ] {
+            let hits: Vec<(u64, f64)> = qt
+                .raycast(origin.into(), direction, max_len)
+                .map(|(t, e)| (e.handle(), t))
+                .collect();
+            debug_assert!(hits.windows(2).all(|w| w[0].1 <= w[1].1));
+
+            let expected = march(&qt, origin, direction, max_len);
+            total += hits.len();
+            debug_assert!(unordered_elements_are(
+                hits.iter().map(|(h, _)| *h),
+                expected.iter().map(|(h, _)| *h).collect::<Vec<_>>(),
+            ));
+            for (handle, t) in &hits {
+                let (_, marched) = expected.iter().find(|(h, _)| h == handle).unwrap();
+                debug_assert!(*marched - *t <= 0.011 && *marched >= *t);
+            }
+
+            debug_assert!(unordered_elements_are(
+                loose
+                    .raycast(origin.into(), direction, max_len)
+                    .map(|(_, e)| e.area()),
+                qt.raycast(origin.into(), direction, max_len)
+                    .map(|(_, e)| e.area())
+                    .collect::<Vec<_>>(),
+            ));
+        }
+        debug_assert!(total > 5);
+    }
+
+    #[test]
+    #[should_panic]
+    fn raycast_nowhere() {
+        mk_quadtree_for_nearest_tests()
+            .raycast(Point { x: 0, y: 0 }, (0.0, 0.0), 10.0)
+            .next();
+    }
+}
@@ -0,0 +1,137 @@
+// Copyright 2019 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+mod util; // For area and unordered_elements_are.
+
+// For testing .union(), .intersection(), .difference().
+mod occupancy_tests {
+    use {
+        crate::util::{area, unordered_elements_are},
+        quadtree_rs::{area::AreaBuilder, point::Point, Quadtree},
+    };
+
+    const WIDTH: u16 = 20;
+    const HEIGHT: u16 = 13;
+
+    // A mask of overlapping rectangles, some of them cut off by the bounds.
+    fn mk_mask(seed: u16) -> Quadtree<u16, ()> {
+        let mut qt = Quadtree::<u16, ()>::new_with_bounds((0, 0).into(), WIDTH, HEIGHT);
+        qt.set_bounds_policy(quadtree_rs::BoundsPolicy::Clip);
+        for i in 0..7 {
+            let (x, y) = ((seed * 7 + i * 5) % WIDTH, (seed * 3 + i * 4) % HEIGHT);
+            let region = AreaBuilder::default()
+                .anchor(Point { x, y })
+                .dimensions((1 + (i * seed) % 6, 1 + (i + seed) % 5))
+                .build()
+                .unwrap();
+            qt.insert(region, ());
+        }
+        qt
+    }
+
+    fn occupied(qt: &Quadtree<u16, ()>, x: u16, y: u16) -> bool {
+        qt.query_pt(Point { x, y }).next().is_some()
+    }
+
+    // Checks that @combine combines the masks of @a and @b cell by cell with @op.
+    fn check<F>(
+        a: &Quadtree<u16, ()>,
+        b: &Quadtree<u16, ()>,
+        combine: F,
+        op: fn(bool, bool) -> bool,
+    ) where
+        F: Fn(&Quadtree<u16, ()>, &Quadtree<u16, ()>) -> Quadtree<u16, ()>,
+    {
+        let result = combine(a, b);
+        assert_eq!((result.width(), result.height()), (a.width(), a.height()));
+        // The result is exactly the combined mask, merged just as .from_grid() merges it.
+        let expected = Quadtree::<u16, ()>::from_grid(WIDTH, HEIGHT, |x, y| {
+            op(occupied(a, x, y), occupied(b, x, y))
+        });
+        assert!(unordered_elements_are(result.regions(), expected.regions()));
+    }
+
+    #[test]
+    fn union() {
+        check(&mk_mask(1), &mk_mask(2), |a, b| a.union(b), |a, b| a || b);
+    }
+
+    #[test]
+    fn intersection() {
+        check(
+            &mk_mask(3),
+            &mk_mask(4),
+            |a, b| a.intersection(b),
+            |a, b| a && b,
+        );
+    }
+
+    #[test]
+    fn difference() {
+        check(
+            &mk_mask(5),
+            &mk_mask(6),
+            |a, b| a.difference(b),
+            |a, b| a && !b,
+        );
+    }
+
+    #[test]
+    fn with_a_loose_tree() {
+        let mut loose = mk_mask(9);
+        loose.set_looseness(2.0);
+        check(&mk_mask(8), &loose, |a, b| a.union(b), |a, b| a || b);
+        check(&loose, &mk_mask(8), |a, b| a.difference(b), |a, b| a && !b);
+    }
+
+    #[test]
+    fn with_itself() {
+        let a = mk_mask(7);
+        let merged = Quadtree::<u16, ()>::from_grid(WIDTH, HEIGHT, |x, y| occupied(&a, x, y));
+        assert!(unordered_elements_are(
+            a.union(&a).regions(),
+            merged.regions()
+        ));
+        assert!(unordered_elements_are(
+            a.intersection(&a).regions(),
+            merged.regions()
+        ));
+        assert!(a.difference(&a).is_empty());
+    }
+
+    #[test]
+    fn with_cells_too_large_to_measure() {
+        // Any cell more than 2^64 wide covers more than u128::MAX cells.
+        let half = 1_u128 << 126;
+        let mut a = Quadtree::<u128, ()>::new(127);
+        a.insert(area(0, 0, half, half), ());
+        let mut b = Quadtree::<u128, ()>::new(127);
+        b.insert(area(half, 0, half, half), ());
+        b.insert(area(0, half, 2 * half, half), ());
+
+        assert!(unordered_elements_are(
+            a.union(&b).regions(),
+            vec![area(0, 0, 2 * half, 2 * half)],
+        ));
+        assert!(a.intersection(&b).is_empty());
+        assert!(unordered_elements_are(
+            b.difference(&a).regions(),
+            vec![
+                area(half, 0, half, half),
+                area(0, half, half, half),
+                area(half, half, half, half),
+            ],
+        ));
+    }
+}
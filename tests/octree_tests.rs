@@ -0,0 +1,133 @@
+// Copyright 2019 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![cfg(feature = "octree")]
+
+mod util; // For unordered_elements_are.
+
+// For testing octree::Octree.
+mod octree_tests {
+    use {
+        crate::util::unordered_elements_are,
+        quadtree_rs::octree::{Cuboid, Octree, Point3},
+    };
+
+    // A scattering of boxes of assorted sizes, some of which straddle the octree's midplanes.
+    fn mk_octree() -> (Octree<i32, u32>, Vec<Cuboid<i32>>) {
+        let mut ot = Octree::<i32, u32>::new_with_anchor((-8, -8, -8), 4);
+        let mut regions = vec![];
+        let mut value = 0;
+        for x in (-8..8).step_by(3) {
+            for y in (-8..8).step_by(5) {
+                for z in (-8..8).step_by(4) {
+                    let dims = (1 + (x + 8) % 3, 1 + (y + 8) % 4, 1 + (z + 8) % 2);
+                    let region = Cuboid::new((x, y, z), dims).unwrap();
+                    if ot.insert(region, value).is_some() {
+                        regions.push(region);
+                        value += 1;
+                    }
+                }
+            }
+        }
+        (ot, regions)
+    }
+
+    fn distance_sq(pt: Point3<i32>, region: Cuboid<i32>) -> i32 {
+        let gap = |c: i32, lo: i32, len: i32| {
+            if c < lo {
+                lo - c
+            } else if c >= lo + len {
+                c - (lo + len) + 1
+            } else {
+                0
+            }
+        };
+        let [x, y, z] = region.anchor();
+        let dx = gap(pt.x, x, region.width());
+        let dy = gap(pt.y, y, region.height());
+        let dz = gap(pt.z, z, region.depth());
+        dx * dx + dy * dy + dz * dz
+    }
+
+    #[test]
+    fn insert_out_of_bounds() {
+        let mut ot = Octree::<u8, ()>::new(3);
+        assert!(ot.insert_pt((7, 7, 7), ()).is_some());
+        assert!(ot.insert_pt((7, 7, 8), ()).is_none());
+        assert!(ot
+            .insert(Cuboid::new((6, 0, 0), (3, 1, 1)).unwrap(), ())
+            .is_none());
+        assert_eq!(ot.len(), 1);
+    }
+
+    #[test]
+    fn query_matches_brute_force() {
+        let (ot, regions) = mk_octree();
+        assert_eq!(ot.len(), regions.len());
+        for &(anchor, dims) in &[
+            ((-8, -8, -8), (16, 16, 16)),
+            ((-1, -1, -1), (2, 2, 2)),
+            ((0, -8, 3), (1, 16, 1)),
+            ((5, 5, 5), (3, 3, 3)),
+            ((-20, -20, -20), (4, 4, 4)),
+        ] {
+            let req = Cuboid::new(anchor, dims).unwrap();
+            let expected =
+                (0..regions.len() as u32).filter(|&i| regions[i as usize].intersects(req));
+            assert!(unordered_elements_are(
+                ot.query(req).map(|e| *e.value_ref()),
+                expected,
+            ));
+        }
+    }
+
+    #[test]
+    fn knn_matches_brute_force() {
+        let (ot, regions) = mk_octree();
+        for &pt in &[(0, 0, 0), (-8, 7, -8), (7, 7, 7), (3, -5, 1)] {
+            let pt: Point3<i32> = pt.into();
+            let found: Vec<i32> = ot
+                .knn(pt, 5)
+                .iter()
+                .map(|e| distance_sq(pt, e.region()))
+                .collect();
+            let mut expected: Vec<i32> = regions.iter().map(|&r| distance_sq(pt, r)).collect();
+            expected.sort_unstable();
+            assert_eq!(found, expected[..5].to_vec());
+        }
+        assert_eq!(ot.knn((0, 0, 0), 1000).len(), regions.len());
+    }
+
+    #[test]
+    fn delete_and_iterate() {
+        let (mut ot, regions) = mk_octree();
+        let (region, value) = ot.remove(3).unwrap();
+        assert_eq!((region, value), (regions[3], 3));
+        assert!(ot.get(3).is_none());
+        assert!(ot.query(region).all(|e| e.handle() != 3));
+
+        let everything = Cuboid::new((-8, -8, -8), (16, 16, 16)).unwrap();
+        let left = Cuboid::new((-8, -8, -8), (8, 16, 16)).unwrap();
+        let deleted = ot.delete(left).count();
+        assert_eq!(ot.len() + deleted, regions.len() - 1);
+        assert_eq!(ot.query(everything).count(), ot.len());
+        assert_eq!(ot.iter().count(), ot.len());
+        assert!(ot.iter().all(|e| !e.region().intersects(left)));
+
+        ot.reset();
+        assert!(ot.is_empty());
+        assert_eq!(ot.query(everything).count(), 0);
+        assert_ne!(ot.insert_pt((0, 0, 0), 0), Some(0));
+    }
+}
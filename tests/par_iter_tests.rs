@@ -0,0 +1,118 @@
+// Copyright 2019 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![cfg(feature = "rayon")]
+
+mod util; // For unordered_elements_are.
+
+// For testing .par_iter(), .par_values(), .par_values_mut(), .par_query().
+mod par_iter_tests {
+    use {
+        crate::util::unordered_elements_are,
+        quadtree_rs::{area::AreaBuilder, Quadtree},
+        rayon::prelude::*,
+    };
+
+    fn mk_quadtree_for_par_iter_tests(ordered: bool) -> Quadtree<i32, i32> {
+        let mut qt = if ordered {
+            Quadtree::<i32, i32>::new_ordered_with_anchor((-64, -64).into(), 7)
+        } else {
+            Quadtree::<i32, i32>::new_with_anchor((-64, -64).into(), 7)
+        };
+        qt.extend((-50..50).map(|i| ((i, -i), i)));
+        qt
+    }
+
+    #[test]
+    fn par_iter() {
+        for ordered in [false, true].iter() {
+            let qt = mk_quadtree_for_par_iter_tests(*ordered);
+            let entries: Vec<(u64, i32)> = qt
+                .par_iter()
+                .map(|e| (e.handle(), *e.value_ref()))
+                .collect();
+            debug_assert!(unordered_elements_are(
+                entries,
+                qt.iter().map(|e| (e.handle(), *e.value_ref())),
+            ));
+            debug_assert_eq!((&qt).into_par_iter().count(), 100);
+        }
+    }
+
+    #[test]
+    fn par_values() {
+        for ordered in [false, true].iter() {
+            let qt = mk_quadtree_for_par_iter_tests(*ordered);
+            debug_assert_eq!(qt.par_values().sum::<i32>(), -50);
+            debug_assert_eq!(qt.par_values().max(), Some(&49));
+        }
+    }
+
+    #[test]
+    fn par_values_mut() {
+        for ordered in [false, true].iter() {
+            let mut qt = mk_quadtree_for_par_iter_tests(*ordered);
+            qt.par_values_mut().for_each(|v| *v *= 2);
+            debug_assert!(unordered_elements_are(
+                qt.values().copied(),
+                (-50..50).map(|i| i * 2),
+            ));
+        }
+    }
+
+    #[test]
+    fn par_iter_empty() {
+        let qt = Quadtree::<u32, u8>::new(4);
+        debug_assert_eq!(qt.par_iter().count(), 0);
+    }
+
+    #[test]
+    fn par_query() {
+        let mut qt = mk_quadtree_for_par_iter_tests(false);
+        // Some larger regions too, which will be held further up the tree.
+        for i in 0..10 {
+            qt.insert(
+                AreaBuilder::default()
+                    .anchor((-60 + i * 11, -60 + i * 7).into())
+                    .dimensions((1 + i * 5, 3 + i * 4))
+                    .build()
+                    .unwrap(),
+                1000 + i,
+            );
+        }
+
+        for (x, y, w, h) in [
+            (-64, -64, 128, 128),
+            (-64, -64, 64, 64),
+            (-10, -10, 20, 20),
+            (0, 0, 1, 1),
+            (3, -40, 30, 8),
+            (-64, 40, 5, 5),
+        ]
+        .iter()
+        {
+            let area = AreaBuilder::default()
+                .anchor((*x, *y).into())
+                .dimensions((*w, *h))
+                .build()
+                .unwrap();
+            let handles: Vec<u64> = qt.par_query(area).map(|e| e.handle()).collect();
+            debug_assert_eq!(handles.len(), qt.query(area).count());
+            debug_assert!(unordered_elements_are(
+                handles,
+                qt.query(area).map(|e| e.handle()),
+            ));
+        }
+    }
+}
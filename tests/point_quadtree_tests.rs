@@ -0,0 +1,177 @@
+// Copyright 2019 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+mod util; // For area and unordered_elements_are.
+
+// For testing point_quadtree::PointQuadtree.
+mod point_quadtree_tests {
+    use {
+        crate::util::{area, unordered_elements_are},
+        quadtree_rs::{point::Point, point_quadtree::PointQuadtree, Quadtree},
+    };
+
+    // A scattering of points over a 128x128 square, with a clump of them on a single cell.
+    fn points() -> Vec<Point<u32>> {
+        let mut seed = 7_u32;
+        let mut points: Vec<Point<u32>> = (0..300)
+            .map(|_| {
+                seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12345);
+                Point {
+                    x: (seed >> 8) % 128,
+                    y: (seed >> 20) % 128,
+                }
+            })
+            .collect();
+        points.extend(std::iter::repeat_n(Point { x: 40, y: 90 }, 20));
+        points
+    }
+
+    fn mk_point_quadtree() -> PointQuadtree<u32, usize> {
+        let mut pqt = PointQuadtree::<u32, usize>::new(7);
+        pqt.set_bucket_size(4);
+        for (i, &pt) in points().iter().enumerate() {
+            assert_eq!(pqt.insert(pt, i), Some(i as u64));
+        }
+        pqt
+    }
+
+    #[test]
+    fn queries_agree_with_brute_force() {
+        let pqt = mk_point_quadtree();
+        let points = points();
+        assert_eq!(pqt.len(), points.len());
+        assert!(unordered_elements_are(
+            pqt.iter().map(|e| *e.value_ref()),
+            0..points.len(),
+        ));
+
+        for &rect in &[
+            area(0, 0, 128, 128),
+            area(10, 20, 30, 70),
+            area(40, 90, 1, 1),
+            area(127, 0, 1, 128),
+        ] {
+            let found: Vec<usize> = pqt.query(rect).map(|e| *e.value_ref()).collect();
+            let expected: Vec<usize> = (0..points.len())
+                .filter(|&i| rect.contains_pt(points[i]))
+                .collect();
+            assert_eq!(found.len(), expected.len());
+            assert!(unordered_elements_are(found, expected));
+        }
+
+        // A disc covers the same cells as it would in a Quadtree.
+        let mut qt = Quadtree::<u32, usize>::new(7);
+        for (i, &pt) in points.iter().enumerate() {
+            qt.insert_pt(pt, i);
+        }
+        for &(center, radius) in &[((64, 64), 20), ((40, 90), 0), ((0, 127), 50)] {
+            let center = Point::from(center);
+            let found: Vec<usize> = pqt
+                .query_within_radius(center, radius)
+                .map(|e| *e.value_ref())
+                .collect();
+            assert!(unordered_elements_are(
+                found,
+                qt.query_within_radius(center, radius)
+                    .map(|e| *e.value_ref()),
+            ));
+        }
+    }
+
+    #[test]
+    fn knn_agrees_with_brute_force() {
+        let pqt = mk_point_quadtree();
+        let pt = Point { x: 50, y: 80 };
+        let distance_sq = |p: Point<u32>| {
+            let (dx, dy) = (
+                i64::from(p.x()) - i64::from(pt.x()),
+                i64::from(p.y()) - i64::from(pt.y()),
+            );
+            dx * dx + dy * dy
+        };
+        let mut expected: Vec<(i64, u64)> = pqt
+            .iter()
+            .map(|e| (distance_sq(e.point()), e.handle()))
+            .collect();
+        expected.sort_unstable();
+
+        let nearest: Vec<(i64, u64)> = pqt
+            .knn(pt, 30)
+            .iter()
+            .map(|e| (distance_sq(e.point()), e.handle()))
+            .collect();
+        assert_eq!(nearest, expected[..30]);
+        assert_eq!(pqt.knn(pt, usize::MAX).len(), pqt.len());
+    }
+
+    #[test]
+    fn relocate_and_delete() {
+        let mut pqt = mk_point_quadtree();
+        let n = pqt.len() as u64;
+
+        // Mirror every point. Those near the middle stay within their leaves.
+        for handle in 0..n {
+            let pt = pqt.get(handle).unwrap().point();
+            assert!(pqt.relocate(handle, Point::from((127 - pt.x(), 127 - pt.y()))));
+        }
+        assert!(!pqt.relocate(n, Point { x: 0, y: 0 }));
+        assert!(!pqt.relocate(0, Point { x: 128, y: 0 }));
+        for (handle, &pt) in points().iter().enumerate() {
+            let entry = pqt.get(handle as u64).unwrap();
+            assert_eq!(entry.point(), Point::from((127 - pt.x(), 127 - pt.y())));
+            assert_eq!(*entry.value_ref(), handle);
+        }
+        assert_eq!(pqt.query(area(0, 0, 128, 128)).count(), n as usize);
+
+        let western: Vec<u64> = pqt
+            .delete(area(0, 0, 64, 128))
+            .map(|e| e.handle())
+            .collect();
+        assert!(!western.is_empty());
+        assert_eq!(pqt.len() + western.len(), n as usize);
+        assert!(western.iter().all(|&handle| pqt.get(handle).is_none()));
+        assert_eq!(pqt.query(area(0, 0, 64, 128)).count(), 0);
+
+        // Emptying the tree merges it back into its root, and it's as good as new.
+        for handle in 0..n {
+            pqt.remove(handle);
+        }
+        assert!(pqt.is_empty());
+        assert_eq!(pqt.iter().count(), 0);
+        let handle = pqt.insert(Point { x: 3, y: 4 }, 0).unwrap();
+        assert_eq!(handle, n);
+        assert_eq!(pqt.knn(Point { x: 0, y: 0 }, 2).len(), 1);
+    }
+
+    #[test]
+    fn changing_the_bucket_size_keeps_handles() {
+        let mut pqt = mk_point_quadtree();
+        pqt.set_bucket_size(32);
+        assert_eq!(pqt.len(), points().len());
+        for (handle, &pt) in points().iter().enumerate() {
+            assert_eq!(pqt.get(handle as u64).unwrap().point(), pt);
+        }
+        assert_eq!(
+            pqt.insert(Point { x: 1, y: 1 }, 0),
+            Some(points().len() as u64)
+        );
+        assert!(pqt.insert(Point { x: 1, y: 128 }, 0).is_none());
+    }
+
+    #[test]
+    #[should_panic]
+    fn bucket_size_of_zero() {
+        PointQuadtree::<u8, ()>::new(4).set_bucket_size(0);
+    }
+}
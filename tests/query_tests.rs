@@ -14,11 +14,15 @@
 
 mod util; // For unordered_elements_are.
 
-// For testing .query(), .modify().
+// For testing .query() and its variants, .modify(), .view(), .collisions().
 mod query_tests {
     use {
         crate::util::unordered_elements_are,
-        quadtree_rs::{area::AreaBuilder, Quadtree},
+        quadtree_rs::{
+            area::{Area, AreaBuilder},
+            split::{Hybrid, NeverSplit},
+            Quadtree,
+        },
     };
 
     #[test]
@@ -631,6 +635,164 @@ mod query_tests {
         ));
     }
 
+    #[test]
+    fn query_pt() {
+        let mut qt = Quadtree::<i32, char>::new_with_anchor((-4, -4).into(), 3);
+        qt.insert(
+            AreaBuilder::default()
+                .anchor((-2, -2).into())
+                .dimensions((3, 3))
+                .build()
+                .unwrap(),
+            'a',
+        );
+        qt.insert_pt((0, 0).into(), 'b');
+
+        debug_assert!(unordered_elements_are(
+            qt.query_pt((0, 0).into()).map(|e| e.value_ref()),
+            vec![&'a', &'b'],
+        ));
+        debug_assert!(unordered_elements_are(
+            qt.query_pt((-2, 0).into()).map(|e| e.value_ref()),
+            vec![&'a'],
+        ));
+        debug_assert_eq!(qt.query_pt((1, 0).into()).count(), 0);
+    }
+
+    #[test]
+    fn query_strict_pt() {
+        let mut qt = Quadtree::<u32, char>::new(3);
+        qt.insert(
+            AreaBuilder::default()
+                .anchor((0, 0).into())
+                .dimensions((2, 2))
+                .build()
+                .unwrap(),
+            'a',
+        );
+        qt.insert_pt((1, 1).into(), 'b');
+
+        // Only the entry at exactly (1, 1)->1x1 is contained by the point.
+        let mut query = qt.query_strict_pt((1, 1).into());
+        debug_assert_eq!(query.next().unwrap().value_ref(), &'b');
+        debug_assert_eq!(query.next(), None);
+        debug_assert_eq!(qt.query_strict_pt((0, 0).into()).count(), 0);
+    }
+
+    #[test]
+    fn query_containing() {
+        let mut qt = Quadtree::<u32, u32>::new(4);
+        let mut regions = vec![];
+        for x in 0..6 {
+            for y in 0..6 {
+                let region = AreaBuilder::default()
+                    .anchor((x * 2, y * 2).into())
+                    .dimensions((1 + x + y % 3, 1 + y + x % 2))
+                    .build()
+                    .unwrap();
+                if qt.insert(region, x * 10 + y).is_some() {
+                    regions.push((region, x * 10 + y));
+                }
+            }
+        }
+
+        for &((ax, ay), (w, h)) in &[
+            ((0, 0), (1, 1)),
+            ((5, 5), (1, 1)),
+            ((4, 6), (2, 3)),
+            ((9, 2), (3, 1)),
+            ((7, 7), (4, 4)),
+        ] {
+            let query = AreaBuilder::default()
+                .anchor((ax, ay).into())
+                .dimensions((w, h))
+                .build()
+                .unwrap();
+            debug_assert!(unordered_elements_are(
+                qt.query_containing(query).map(|e| *e.value_ref()),
+                regions
+                    .iter()
+                    .filter(|(r, _)| r.contains(query))
+                    .map(|(_, v)| *v),
+            ));
+        }
+    }
+
+    #[test]
+    fn query_exact() {
+        // Tiles of sides 8, 4 and 2, stacked on top of each other, some of them twice.
+        let mut tight = Quadtree::<u32, u32>::new(4);
+        let mut loose = Quadtree::<u32, u32>::new(4);
+        loose.set_looseness(1.5);
+        let mut tiles = vec![];
+        for side in [8_u32, 4, 2].iter() {
+            for x in (0..16).step_by(*side as usize) {
+                for y in (0..16).step_by(*side as usize) {
+                    tiles.push(
+                        AreaBuilder::default()
+                            .anchor((x, y).into())
+                            .dimensions((*side, *side))
+                            .build()
+                            .unwrap(),
+                    );
+                }
+            }
+        }
+        for qt in [&mut tight, &mut loose].iter_mut() {
+            for (i, tile) in tiles.iter().enumerate() {
+                qt.insert(*tile, i as u32);
+                if i % 3 == 0 {
+                    qt.insert(*tile, 1000 + i as u32);
+                }
+            }
+        }
+
+        for qt in [&tight, &loose].iter() {
+            for tile in &tiles {
+                debug_assert!(unordered_elements_are(
+                    qt.query_exact(*tile).map(|e| e.handle()),
+                    qt.iter().filter(|e| e.area() == *tile).map(|e| e.handle()),
+                ));
+            }
+            // Straddling the tiles, so it matches none of them.
+            let region = AreaBuilder::default()
+                .anchor((3, 3).into())
+                .dimensions((2, 2))
+                .build()
+                .unwrap();
+            debug_assert_eq!(qt.query_exact(region).count(), 0);
+        }
+    }
+
+    #[test]
+    fn query_containing_pt() {
+        let mut qt = Quadtree::<u32, char>::new(3);
+        qt.insert(
+            AreaBuilder::default()
+                .anchor((0, 0).into())
+                .dimensions((8, 8))
+                .build()
+                .unwrap(),
+            'a',
+        );
+        qt.insert(
+            AreaBuilder::default()
+                .anchor((3, 1).into())
+                .dimensions((3, 5))
+                .build()
+                .unwrap(),
+            'b',
+        );
+        debug_assert!(unordered_elements_are(
+            qt.query_containing_pt((5, 5).into()).map(|e| e.value_ref()),
+            vec![&'a', &'b'],
+        ));
+        debug_assert!(unordered_elements_are(
+            qt.query_containing_pt((6, 5).into()).map(|e| e.value_ref()),
+            vec![&'a'],
+        ));
+    }
+
     #[test]
     fn query_exhibiting_collection() {
         let mut qt: Quadtree<u8, f32> = Quadtree::new(2);
@@ -655,18 +817,589 @@ mod query_tests {
         debug_assert_eq!(query_obj.next().unwrap().value_ref(), &1.234);
     }
 
+    #[test]
+    fn query_within_radius() {
+        let mut qt = Quadtree::<i16, (i16, i16)>::new_with_anchor((-8, -8).into(), 4);
+        for x in -8..8 {
+            for y in -8..8 {
+                qt.insert_pt((x, y).into(), (x, y));
+            }
+        }
+
+        for &(cx, cy, r) in &[(0, 0, 0), (0, 0, 3), (-8, -8, 5), (7, -2, 4), (20, 20, 3)] {
+            debug_assert!(unordered_elements_are(
+                qt.query_within_radius((cx, cy).into(), r)
+                    .map(|e| *e.value_ref()),
+                qt.iter()
+                    .map(|e| *e.value_ref())
+                    .filter(|(x, y)| (x - cx).pow(2) + (y - cy).pow(2) <= r * r),
+            ));
+        }
+        debug_assert_eq!(qt.query_within_radius((0, 0).into(), -1).count(), 0);
+    }
+
+    #[test]
+    fn query_within_radius_of_regions() {
+        let mut qt = Quadtree::<u8, char>::new(4);
+        // A long region to the right of the center, whose nearest cell is (6, 4).
+        qt.insert(
+            AreaBuilder::default()
+                .anchor((6, 0).into())
+                .dimensions((3, 9))
+                .build()
+                .unwrap(),
+            'a',
+        );
+        debug_assert_eq!(qt.query_within_radius((4, 4).into(), 1).count(), 0);
+        debug_assert_eq!(qt.query_within_radius((4, 4).into(), 2).count(), 1);
+        // Near the top of the coordinate range the bounding box can't be represented.
+        debug_assert_eq!(qt.query_within_radius((240, 4).into(), 240).count(), 1);
+    }
+
+    #[test]
+    fn query_annulus() {
+        let mut qt = Quadtree::<i16, (i16, i16)>::new_with_anchor((-8, -8).into(), 4);
+        for x in -8..8 {
+            for y in -8..8 {
+                qt.insert_pt((x, y).into(), (x, y));
+            }
+        }
+
+        for &(cx, cy, inner, outer) in &[
+            (0, 0, 0, 3),
+            (0, 0, 2, 5),
+            (-8, -8, 4, 9),
+            (7, -2, -1, 4),
+            (3, 3, 4, 4),
+            (20, 20, 1, 3),
+        ] {
+            debug_assert!(unordered_elements_are(
+                qt.query_annulus((cx, cy).into(), inner, outer)
+                    .map(|e| *e.value_ref()),
+                qt.iter().map(|e| *e.value_ref()).filter(|(x, y)| {
+                    let d = (x - cx).pow(2) + (y - cy).pow(2);
+                    d <= outer * outer && (inner < 0 || d > inner * inner)
+                }),
+            ));
+        }
+
+        // A region is in the ring as long as it straddles it.
+        let mut qt = Quadtree::<u8, char>::new(4);
+        let region = |(x, y), (w, h)| {
+            AreaBuilder::default()
+                .anchor((x, y).into())
+                .dimensions((w, h))
+                .build()
+                .unwrap()
+        };
+        qt.insert(region((3, 3), (3, 3)), 'a');
+        qt.insert(region((0, 4), (10, 1)), 'b');
+        qt.insert(region((12, 12), (2, 2)), 'c');
+        let ring = |inner, outer| {
+            let mut hits: Vec<char> = qt
+                .query_annulus((4, 4).into(), inner, outer)
+                .map(|e| *e.value_ref())
+                .collect();
+            hits.sort_unstable();
+            hits
+        };
+        debug_assert_eq!(ring(1, 3), vec!['a', 'b']);
+        debug_assert_eq!(ring(2, 3), vec!['b']);
+        debug_assert_eq!(ring(4, 12), vec!['b', 'c']);
+        debug_assert_eq!(ring(6, 12), vec!['c']);
+        debug_assert_eq!(ring(3, 2), Vec::<char>::new());
+    }
+
+    #[test]
+    fn query_segment() {
+        let mut qt = Quadtree::<u32, u32>::new(6);
+        let mut loose = Quadtree::<u32, u32>::new(6);
+        loose.set_looseness(2.0);
+        for i in 0..150 {
+            let region = AreaBuilder::default()
+                .anchor((i * 13 % 61, i * 29 % 59).into())
+                .dimensions((1 + i % 4, 1 + i % 3))
+                .build()
+                .unwrap();
+            qt.insert(region, i);
+            loose.insert(region, i);
+        }
+
+        let mut total = 0;
+        for (from, to) in [
+            ((0, 0), (63, 50)),
+            ((40, 3), (7, 61)),
+            ((10, 30), (60, 33)),
+            ((20, 0), (20, 63)),
+            ((5, 5), (5, 5)),
+        ] {
+            let hits: Vec<u64> = qt
+                .query_segment(from.into(), to.into())
+                .map(|e| e.handle())
+                .collect();
+
+            // Walk along the segment in small steps, between the centres of its end cells.
+            let (x0, y0) = (f64::from(from.0) + 0.5, f64::from(from.1) + 0.5);
+            let (x1, y1) = (f64::from(to.0) + 0.5, f64::from(to.1) + 0.5);
+            let expected: Vec<u64> = qt
+                .iter()
+                .filter(|e| {
+                    let a = e.area();
+                    (0..=10_000).any(|step| {
+                        let t = f64::from(step) / 10_000.0;
+                        let (x, y) = (x0 + t * (x1 - x0), y0 + t * (y1 - y0));
+                        f64::from(a.left_edge()) <= x
+                            && x <= f64::from(a.right_edge())
+                            && f64::from(a.top_edge()) <= y
+                            && y <= f64::from(a.bottom_edge())
+                    })
+                })
+                .map(|e| e.handle())
+                .collect();
+            total += hits.len();
+            debug_assert!(unordered_elements_are(hits.clone(), expected));
+            debug_assert!(unordered_elements_are(
+                loose
+                    .query_segment(from.into(), to.into())
+                    .map(|e| e.handle()),
+                hits,
+            ));
+        }
+        debug_assert!(total > 10);
+    }
+
+    #[test]
+    fn query_many() {
+        let mut qt = Quadtree::<u32, u32>::new(6);
+        let mut loose = Quadtree::<u32, u32>::new(6);
+        loose.set_looseness(2.0);
+        for i in 0..150 {
+            let region = AreaBuilder::default()
+                .anchor((i * 13 % 61, i * 29 % 59).into())
+                .dimensions((1 + i % 4, 1 + i % 3))
+                .build()
+                .unwrap();
+            qt.insert(region, i);
+            loose.insert(region, i);
+        }
+
+        let areas: Vec<Area<u32>> = (0..40)
+            .map(|i| {
+                AreaBuilder::default()
+                    .anchor((i * 7 % 60, i * 11 % 60).into())
+                    .dimensions((1 + i % 9, 1 + i % 5))
+                    .build()
+                    .unwrap()
+            })
+            // The same area twice gets the same answer twice.
+            .chain(std::iter::once(
+                AreaBuilder::default()
+                    .anchor((0, 0).into())
+                    .dimensions((1, 1))
+                    .build()
+                    .unwrap(),
+            ))
+            .collect();
+        for tree in [&qt, &loose] {
+            let batch = tree.query_many(&areas);
+            debug_assert_eq!(batch.len(), areas.len());
+            for (area, hits) in areas.iter().zip(batch) {
+                debug_assert!(unordered_elements_are(
+                    hits.iter().map(|e| e.handle()),
+                    tree.query(*area).map(|e| e.handle()),
+                ));
+            }
+        }
+        debug_assert!(qt.query_many(&[]).is_empty());
+    }
+
+    #[test]
+    fn query_outside() {
+        let mut qt = Quadtree::<u32, u32>::new(6);
+        let mut loose = Quadtree::<u32, u32>::new(6);
+        loose.set_looseness(2.0);
+        for i in 0..150 {
+            let region = AreaBuilder::default()
+                .anchor((i * 13 % 61, i * 29 % 59).into())
+                .dimensions((1 + i % 4, 1 + i % 3))
+                .build()
+                .unwrap();
+            qt.insert(region, i);
+            loose.insert(region, i);
+        }
+
+        for (anchor, size) in [
+            ((16, 16), (32, 32)),
+            ((0, 0), (64, 64)),
+            ((5, 40), (1, 1)),
+            ((60, 0), (100, 3)),
+        ] {
+            let rect = AreaBuilder::default()
+                .anchor(anchor.into())
+                .dimensions(size)
+                .build()
+                .unwrap();
+            for tree in [&qt, &loose] {
+                debug_assert!(unordered_elements_are(
+                    tree.query_outside(rect).map(|e| e.handle()),
+                    tree.iter()
+                        .filter(|e| !e.area().intersects(rect))
+                        .map(|e| e.handle()),
+                ));
+            }
+        }
+        let everything = AreaBuilder::default()
+            .anchor((0, 0).into())
+            .dimensions((64, 64))
+            .build()
+            .unwrap();
+        debug_assert_eq!(qt.query_outside(everything).count(), 0);
+    }
+
+    #[test]
+    fn query_filter() {
+        let mut qt = Quadtree::<u32, u32>::new(6);
+        for i in 0..150 {
+            let region = AreaBuilder::default()
+                .anchor((i * 13 % 61, i * 29 % 59).into())
+                .dimensions((1 + i % 4, 1 + i % 3))
+                .build()
+                .unwrap();
+            qt.insert(region, i);
+        }
+        let rect = AreaBuilder::default()
+            .anchor((10, 20).into())
+            .dimensions((30, 25))
+            .build()
+            .unwrap();
+
+        let mut calls = 0;
+        let hits: Vec<u32> = qt
+            .query_filter(rect, |v| {
+                calls += 1;
+                v % 3 == 0
+            })
+            .map(|e| *e.value_ref())
+            .collect();
+        debug_assert!(unordered_elements_are(
+            hits,
+            qt.query(rect)
+                .map(|e| *e.value_ref())
+                .filter(|v| v % 3 == 0),
+        ));
+        // The predicate is only asked about entries in the region.
+        debug_assert_eq!(calls, qt.query(rect).count());
+
+        debug_assert_eq!(qt.query_filter(rect, |_| false).count(), 0);
+    }
+
+    #[test]
+    fn query_min_overlap() {
+        let mut qt = Quadtree::<u32, u32>::new(6);
+        let mut loose = Quadtree::<u32, u32>::new(6);
+        loose.set_looseness(2.0);
+        for i in 0..150 {
+            let region = AreaBuilder::default()
+                .anchor((i * 13 % 61, i * 29 % 59).into())
+                .dimensions((1 + i % 7, 1 + i % 5))
+                .build()
+                .unwrap();
+            qt.insert(region, i);
+            loose.insert(region, i);
+        }
+        let rect = AreaBuilder::default()
+            .anchor((10, 20).into())
+            .dimensions((30, 25))
+            .build()
+            .unwrap();
+
+        for min_area in [0, 1, 3, 10, 36] {
+            debug_assert!(unordered_elements_are(
+                loose
+                    .query_min_overlap(rect, min_area)
+                    .map(|(overlap, e)| (overlap, e.handle())),
+                qt.query_min_overlap(rect, min_area)
+                    .map(|(overlap, e)| (overlap, e.handle())),
+            ));
+            debug_assert!(unordered_elements_are(
+                qt.query_min_overlap(rect, min_area)
+                    .map(|(overlap, e)| (overlap, e.handle())),
+                qt.iter().filter_map(|e| {
+                    let mut overlap = 0;
+                    for x in e.area().left_edge()..e.area().right_edge() {
+                        for y in e.area().top_edge()..e.area().bottom_edge() {
+                            if rect.contains_pt((x, y).into()) {
+                                overlap += 1;
+                            }
+                        }
+                    }
+                    (overlap > 0 && overlap >= min_area).then_some((overlap, e.handle()))
+                }),
+            ));
+        }
+    }
+
+    #[test]
+    fn query_limit() {
+        let mut qt = Quadtree::<u32, u32>::new(6);
+        for i in 0..150 {
+            let region = AreaBuilder::default()
+                .anchor((i * 13 % 61, i * 29 % 59).into())
+                .dimensions((1 + i % 4, 1 + i % 3))
+                .build()
+                .unwrap();
+            qt.insert(region, i);
+        }
+        let rect = AreaBuilder::default()
+            .anchor((10, 20).into())
+            .dimensions((30, 25))
+            .build()
+            .unwrap();
+        let all: Vec<u64> = qt.query(rect).map(|e| e.handle()).collect();
+        debug_assert!(all.len() > 10);
+
+        for n in [0, 1, 10, all.len(), all.len() + 5] {
+            let mut query = qt.query(rect).limit(n);
+            debug_assert!(query.size_hint().1.unwrap() <= n);
+            let some: Vec<u64> = query.by_ref().map(|e| e.handle()).collect();
+            // A limited query yields the first of what the full query would have.
+            debug_assert_eq!(some, all[..n.min(all.len())]);
+            debug_assert!(query.next().is_none());
+        }
+        // Limits only ever tighten.
+        debug_assert_eq!(qt.query(rect).limit(3).limit(8).count(), 3);
+        debug_assert_eq!(qt.query(rect).limit(8).limit(3).count(), 3);
+        debug_assert_eq!(qt.query_strict(rect).limit(2).count(), 2);
+    }
+
+    #[test]
+    fn query_touching() {
+        let mut qt = Quadtree::<u32, u32>::new(6);
+        for i in 0..150 {
+            let region = AreaBuilder::default()
+                .anchor((i * 13 % 61, i * 29 % 59).into())
+                .dimensions((1 + i % 4, 1 + i % 3))
+                .build()
+                .unwrap();
+            qt.insert(region, i);
+        }
+
+        for (anchor, size) in [((10, 20), (30, 25)), ((0, 0), (5, 5)), ((33, 7), (1, 1))] {
+            let rect = AreaBuilder::default()
+                .anchor(anchor.into())
+                .dimensions(size)
+                .build()
+                .unwrap();
+            // Closed rectangles which meet, even if only along an edge or at a corner.
+            let meets = |r: Area<u32>| {
+                r.left_edge() <= rect.right_edge()
+                    && rect.left_edge() <= r.right_edge()
+                    && r.top_edge() <= rect.bottom_edge()
+                    && rect.top_edge() <= r.bottom_edge()
+            };
+            debug_assert!(unordered_elements_are(
+                qt.query_touching(rect, false).map(|e| e.handle()),
+                qt.iter()
+                    .filter(|e| meets(e.area()) && !e.area().intersects(rect))
+                    .map(|e| e.handle()),
+            ));
+            debug_assert!(unordered_elements_are(
+                qt.query_touching(rect, true).map(|e| e.handle()),
+                qt.iter().filter(|e| meets(e.area())).map(|e| e.handle()),
+            ));
+        }
+    }
+
+    #[test]
+    fn neighbors() {
+        let mut qt = Quadtree::<i32, i32>::new_with_anchor((-32, -32).into(), 6);
+        let mut handles = vec![];
+        for i in 0..150 {
+            let region = AreaBuilder::default()
+                .anchor((i * 13 % 61 - 32, i * 29 % 59 - 32).into())
+                .dimensions((1 + i % 4, 1 + i % 3))
+                .build()
+                .unwrap();
+            handles.push(qt.insert(region, i).unwrap());
+        }
+
+        // The number of empty cells between two regions along one axis.
+        let gap = |lo: i32, hi: i32, other_lo: i32, other_hi: i32| {
+            (other_lo - hi).max(lo - other_hi).max(0)
+        };
+        for &handle in handles.iter().step_by(7) {
+            let region = qt.get(handle).unwrap().area();
+            for max_gap in [-3, 0, 1, 4] {
+                debug_assert!(unordered_elements_are(
+                    qt.neighbors(handle, max_gap).map(|e| e.handle()),
+                    qt.iter()
+                        .filter(|e| {
+                            let r = e.area();
+                            e.handle() != handle
+                                && gap(
+                                    region.left_edge(),
+                                    region.right_edge(),
+                                    r.left_edge(),
+                                    r.right_edge(),
+                                ) <= max_gap.max(0)
+                                && gap(
+                                    region.top_edge(),
+                                    region.bottom_edge(),
+                                    r.top_edge(),
+                                    r.bottom_edge(),
+                                ) <= max_gap.max(0)
+                        })
+                        .map(|e| e.handle()),
+                ));
+            }
+        }
+        debug_assert_eq!(qt.neighbors(1000, 5).count(), 0);
+    }
+
+    #[test]
+    fn query_size_hint() {
+        let mut qt = Quadtree::<u32, u32>::new(6);
+        let mut loose = Quadtree::<u32, u32>::new(6);
+        loose.set_looseness(2.0);
+        for i in 0..150 {
+            let region = AreaBuilder::default()
+                .anchor((i * 13 % 61, i * 29 % 59).into())
+                .dimensions((1 + i % 4, 1 + i % 3))
+                .build()
+                .unwrap();
+            qt.insert(region, i);
+            loose.insert(region, i);
+        }
+
+        for tree in [&qt, &loose] {
+            for (anchor, size) in [((10, 20), (30, 25)), ((3, 3), (2, 2)), ((0, 0), (64, 64))] {
+                let rect = AreaBuilder::default()
+                    .anchor(anchor.into())
+                    .dimensions(size)
+                    .build()
+                    .unwrap();
+                let mut query = tree.query(rect);
+                let mut left = tree.query(rect).count();
+                loop {
+                    let (lower, upper) = query.size_hint();
+                    debug_assert_eq!(lower, 0);
+                    debug_assert!(upper.unwrap() >= left);
+                    if query.next().is_none() {
+                        break;
+                    }
+                    left -= 1;
+                }
+                debug_assert_eq!(query.size_hint(), (0, Some(0)));
+            }
+        }
+
+        // Once the query has descended towards a small region, only what's beneath it counts.
+        let small = AreaBuilder::default()
+            .anchor((3, 3).into())
+            .dimensions((2, 2))
+            .build()
+            .unwrap();
+        debug_assert!(qt.query(small).size_hint().1.unwrap() < qt.len());
+    }
+
+    #[test]
+    fn query_triangle() {
+        let mut qt = Quadtree::<u32, u32>::new(6);
+        let mut loose = Quadtree::<u32, u32>::new(6);
+        loose.set_looseness(2.0);
+        for i in 0..150 {
+            let region = AreaBuilder::default()
+                .anchor((i * 13 % 61, i * 29 % 59).into())
+                .dimensions((1 + i % 4, 1 + i % 3))
+                .build()
+                .unwrap();
+            qt.insert(region, i);
+            loose.insert(region, i);
+        }
+        // One big region which swallows a whole triangle.
+        qt.insert(
+            AreaBuilder::default()
+                .anchor((40, 40).into())
+                .dimensions((20, 20))
+                .build()
+                .unwrap(),
+            150,
+        );
+
+        for (a, b, c) in [
+            ((0, 0), (63, 10), (20, 60)),
+            ((30, 5), (31, 40), (50, 20)),
+            ((45, 45), (50, 47), (46, 52)),
+            ((2, 2), (40, 40), (60, 60)),
+            ((9, 9), (9, 9), (9, 9)),
+        ] {
+            let hits: Vec<u64> = qt
+                .query_triangle(a.into(), b.into(), c.into())
+                .map(|e| e.handle())
+                .collect();
+
+            // A region overlaps the triangle if one of the triangle's edges passes through it, or
+            // if the triangle covers one of its corners.
+            let centre = |(x, y): (u32, u32)| (f64::from(x) + 0.5, f64::from(y) + 0.5);
+            let (pa, pb, pc) = (centre(a), centre(b), centre(c));
+            let covers = |(x, y): (f64, f64)| {
+                let side = |(x0, y0): (f64, f64), (x1, y1): (f64, f64)| {
+                    (x1 - x0) * (y - y0) - (y1 - y0) * (x - x0)
+                };
+                let sides = [side(pa, pb), side(pb, pc), side(pc, pa)];
+                // Flat triangles have every point on their line on each side, so check the
+                // bounding box as well.
+                let within = [pa, pb, pc].iter().any(|p| p.0 <= x)
+                    && [pa, pb, pc].iter().any(|p| p.0 >= x)
+                    && [pa, pb, pc].iter().any(|p| p.1 <= y)
+                    && [pa, pb, pc].iter().any(|p| p.1 >= y);
+                within && (sides.iter().all(|s| *s >= 0.0) || sides.iter().all(|s| *s <= 0.0))
+            };
+            let mut expected: Vec<u64> = qt
+                .iter()
+                .filter(|e| {
+                    let r = e.area();
+                    let (l, t) = (f64::from(r.left_edge()), f64::from(r.top_edge()));
+                    let (rt, bt) = (f64::from(r.right_edge()), f64::from(r.bottom_edge()));
+                    [(l, t), (rt, t), (rt, bt), (l, bt)]
+                        .iter()
+                        .any(|p| covers(*p))
+                })
+                .map(|e| e.handle())
+                .collect();
+            for (from, to) in [(a, b), (b, c), (c, a)] {
+                expected.extend(qt.query_segment(from.into(), to.into()).map(|e| e.handle()));
+            }
+            expected.sort_unstable();
+            expected.dedup();
+
+            debug_assert!(unordered_elements_are(hits.clone(), expected));
+            debug_assert!(unordered_elements_are(
+                loose
+                    .query_triangle(a.into(), b.into(), c.into())
+                    .map(|e| e.handle()),
+                hits.into_iter().filter(|h| *h != 150).collect::<Vec<_>>(),
+            ));
+        }
+        debug_assert!(qt
+            .query_triangle((45, 45).into(), (50, 47).into(), (46, 52).into())
+            .any(|e| e.handle() == 150));
+    }
+
     #[test]
     fn modify_empty() {
         // Modification shouldn't change the emptiness.
         let mut qt = Quadtree::<u32, u8>::new(2);
-        qt.modify(
+        let modified = qt.modify(
             AreaBuilder::default()
                 .anchor((0, 0).into())
                 .dimensions((4, 4))
                 .build()
                 .unwrap(),
-            |v| *v *= 2,
+            |_, v| *v *= 2,
         );
+        debug_assert_eq!(modified, 0);
+        debug_assert_eq!(qt.modify_all(|_, v| *v *= 2), 0);
         debug_assert!(qt.is_empty());
     }
 
@@ -689,7 +1422,7 @@ mod query_tests {
                 .anchor((0, 0).into())
                 .build()
                 .unwrap(),
-            |i| *i += 1,
+            |_, i| *i += 1,
         );
 
         // And verify.
@@ -721,7 +1454,7 @@ mod query_tests {
                 .dimensions((2, 2))
                 .build()
                 .unwrap(),
-            |i| *i += 1,
+            |_, i| *i += 1,
         );
         // And verify.
         let mut tmp_iter_2 = qt.query(
@@ -740,7 +1473,7 @@ mod query_tests {
                 .dimensions((6, 6))
                 .build()
                 .unwrap(),
-            |i| *i = 0,
+            |_, i| *i = 0,
         );
         // Every value is now 0.
 
@@ -754,4 +1487,825 @@ mod query_tests {
             debug_assert_eq!(entry.value_ref(), &0);
         }
     }
+    #[test]
+    fn modify_strict() {
+        let mut qt = Quadtree::<u32, u8>::new(4);
+        // A 1x1 region, a 4x4 region, and a region on the other side of the tree.
+        qt.extend(vec![((1, 1), 10), ((13, 13), 30)]);
+        let big = qt
+            .insert(
+                AreaBuilder::default()
+                    .anchor((0, 0).into())
+                    .dimensions((4, 4))
+                    .build()
+                    .unwrap(),
+                20,
+            )
+            .unwrap();
+
+        // Only the 1x1 region lies entirely within (0, 0)->2x2.
+        let modified = qt.modify_strict(
+            AreaBuilder::default()
+                .anchor((0, 0).into())
+                .dimensions((2, 2))
+                .build()
+                .unwrap(),
+            |_, i| *i += 1,
+        );
+        debug_assert_eq!(modified, 1);
+        debug_assert!(unordered_elements_are(qt.values(), vec![&11, &20, &30]));
+        debug_assert_eq!(qt.get(big).unwrap().value_ref(), &20);
+
+        // Whereas a non-strict modification reaches the 4x4 region too.
+        let modified = qt.modify(
+            AreaBuilder::default()
+                .anchor((0, 0).into())
+                .dimensions((2, 2))
+                .build()
+                .unwrap(),
+            |_, i| *i += 1,
+        );
+        debug_assert_eq!(modified, 2);
+        debug_assert!(unordered_elements_are(qt.values(), vec![&12, &21, &30]));
+    }
+
+    #[test]
+    fn modify_with_state() {
+        let mut qt = Quadtree::<u32, u32>::new(4);
+        qt.extend(vec![((1, 1), 0), ((2, 2), 0), ((3, 3), 0), ((13, 13), 0)]);
+        let region = AreaBuilder::default()
+            .anchor((0, 0).into())
+            .dimensions((4, 4))
+            .build()
+            .unwrap();
+
+        // Number the values in the region as they're visited.
+        let mut next = 1;
+        qt.modify(region, |_, v| {
+            *v = next;
+            next += 1;
+        });
+        debug_assert_eq!(next, 4);
+        debug_assert!(unordered_elements_are(qt.values(), vec![&0, &1, &2, &3]));
+
+        let mut visited = 0;
+        qt.modify_all(|_, v| {
+            visited += 1;
+            *v += visited;
+        });
+        debug_assert_eq!(visited, 4);
+        debug_assert_eq!(qt.values().sum::<u32>(), 6 + 10);
+    }
+
+    #[test]
+    fn modify_sees_regions() {
+        let mut qt = Quadtree::<u32, u32>::new(4);
+        let region = |x, y, w, h| {
+            AreaBuilder::default()
+                .anchor((x, y).into())
+                .dimensions((w, h))
+                .build()
+                .unwrap()
+        };
+        let a = qt.insert(region(0, 0, 4, 4), 0).unwrap();
+        let b = qt.insert(region(2, 0, 2, 8), 0).unwrap();
+        let c = qt.insert(region(9, 9, 1, 1), 0).unwrap();
+
+        // Store how many cells of each region lie within (0, 0)->4x4.
+        let brush = region(0, 0, 4, 4);
+        qt.modify(brush, |r, v| {
+            let overlap = r.intersection(brush).unwrap();
+            *v = overlap.width() * overlap.height();
+        });
+        debug_assert_eq!(qt.get(a).unwrap().value_ref(), &16);
+        debug_assert_eq!(qt.get(b).unwrap().value_ref(), &8);
+        debug_assert_eq!(qt.get(c).unwrap().value_ref(), &0);
+
+        qt.modify_all(|r, v| *v += r.anchor().x());
+        debug_assert_eq!(qt.get(a).unwrap().value_ref(), &16);
+        debug_assert_eq!(qt.get(b).unwrap().value_ref(), &10);
+        debug_assert_eq!(qt.get(c).unwrap().value_ref(), &9);
+    }
+
+    // Regression test: a query which has to reject a long run of candidates in a row must not
+    // recurse once per rejection.
+    #[test]
+    fn query_rejecting_many_entries() {
+        let mut qt = Quadtree::<u32, ()>::new(2);
+        let region = AreaBuilder::default()
+            .anchor((0, 0).into())
+            .dimensions((2, 2))
+            .build()
+            .unwrap();
+        for _ in 0..1_000_000 {
+            qt.insert(region, ());
+        }
+
+        // Every entry is registered in the same node, and every one of them overlaps this area
+        // without being contained by it.
+        let area = AreaBuilder::default()
+            .anchor((0, 0).into())
+            .dimensions((1, 2))
+            .build()
+            .unwrap();
+        debug_assert_eq!(qt.query_strict(area).count(), 0);
+        debug_assert_eq!(qt.query(area).count(), 1_000_000);
+    }
+    #[test]
+    fn count_in_region() {
+        let mut qt = Quadtree::<u32, u32>::new(5);
+        for i in 0..32 {
+            for j in (0..32).step_by(3) {
+                qt.insert(
+                    AreaBuilder::default()
+                        .anchor((i, j).into())
+                        .dimensions((1 + i % 4, 1 + j % 5))
+                        .build()
+                        .unwrap(),
+                    i * j,
+                );
+            }
+        }
+        // Knock a few out, so that the counts held in the tree have to be kept up to date.
+        qt.delete(
+            AreaBuilder::default()
+                .anchor((10, 10).into())
+                .dimensions((6, 3))
+                .build()
+                .unwrap(),
+        );
+
+        for x in (0..32).step_by(5) {
+            for y in (0..32).step_by(7) {
+                for (w, h) in [(1, 1), (3, 2), (8, 8), (16, 20), (32 - x, 32 - y)].iter() {
+                    let area = AreaBuilder::default()
+                        .anchor((x, y).into())
+                        .dimensions((*w, *h))
+                        .build()
+                        .unwrap();
+                    let expected = qt.iter().filter(|e| e.area().intersects(area)).count();
+                    debug_assert_eq!(qt.count_in_region(area), expected);
+                    debug_assert_eq!(qt.query(area).count(), expected);
+                    debug_assert_eq!(qt.intersects_any(area), expected > 0);
+                }
+            }
+        }
+
+        qt.reset();
+        debug_assert_eq!(
+            qt.count_in_region(
+                AreaBuilder::default()
+                    .anchor((0, 0).into())
+                    .dimensions((32, 32))
+                    .build()
+                    .unwrap()
+            ),
+            0
+        );
+    }
+
+    #[test]
+    fn density_grid() {
+        let mut qt = Quadtree::<i32, u32>::new_with_bounds((-10, -5).into(), 37, 21);
+        for i in 0..120 {
+            let region = AreaBuilder::default()
+                .anchor(((i * 7 % 37) - 10, (i * 5 % 21) - 5).into())
+                .dimensions((1 + i % 6, 1 + i % 4))
+                .build()
+                .unwrap();
+            qt.insert(region, i as u32);
+        }
+
+        for cell_size in [1, 2, 5, 8, 40] {
+            let grid = qt.density_grid(cell_size);
+            debug_assert_eq!(grid.len() as i32, (21 + cell_size - 1) / cell_size);
+            for (row, counts) in grid.iter().enumerate() {
+                debug_assert_eq!(counts.len() as i32, (37 + cell_size - 1) / cell_size);
+                for (col, count) in counts.iter().enumerate() {
+                    let (x, y) = (-10 + col as i32 * cell_size, -5 + row as i32 * cell_size);
+                    let cell = AreaBuilder::default()
+                        .anchor((x, y).into())
+                        .dimensions((cell_size.min(27 - x), cell_size.min(16 - y)))
+                        .build()
+                        .unwrap();
+                    let expected = qt.iter().filter(|e| e.area().intersects(cell)).count();
+                    debug_assert_eq!(*count, expected, "at ({}, {})", x, y);
+                }
+            }
+        }
+        debug_assert_eq!(
+            qt.density_grid(40),
+            vec![vec![qt.len()]],
+            "One cell covers everything."
+        );
+    }
+
+    #[test]
+    fn free_space() {
+        let area = |x: u32, y: u32, w: u32, h: u32| {
+            AreaBuilder::default()
+                .anchor((x, y).into())
+                .dimensions((w, h))
+                .build()
+                .unwrap()
+        };
+        let mut qt = Quadtree::<u32, u32>::new_with_bounds((0, 0).into(), 30, 27);
+        for i in 0..40 {
+            qt.insert(area(i * 7 % 26, i * 11 % 24, 1 + i % 5, 1 + i % 3), i);
+        }
+        qt.insert(area(16, 16, 8, 8), 40);
+
+        for query in [area(0, 0, 32, 32), area(3, 5, 17, 9), area(16, 16, 8, 8)] {
+            let free: Vec<_> = qt.free_space(query).collect();
+            for (i, a) in free.iter().enumerate() {
+                debug_assert!(query.contains(*a));
+                debug_assert!(!qt.intersects_any(*a));
+                debug_assert!(free[i + 1..].iter().all(|b| !a.intersects(*b)));
+            }
+            for x in 0..32 {
+                for y in 0..32 {
+                    let cell = area(x, y, 1, 1);
+                    let expected =
+                        query.contains(cell) && x < 30 && y < 27 && !qt.intersects_any(cell);
+                    debug_assert_eq!(
+                        free.iter().any(|a| a.contains(cell)),
+                        expected,
+                        "at ({}, {})",
+                        x,
+                        y
+                    );
+                }
+            }
+        }
+        debug_assert_eq!(qt.free_space(area(16, 16, 8, 8)).count(), 0);
+        debug_assert_eq!(qt.free_space(area(31, 31, 1, 1)).count(), 0);
+        debug_assert_eq!(
+            Quadtree::<u32, u32>::new(3)
+                .free_space(area(0, 0, 8, 8))
+                .collect::<Vec<_>>(),
+            vec![area(0, 0, 8, 8)]
+        );
+    }
+
+    #[test]
+    fn intersects_any() {
+        let mut qt = Quadtree::<u32, u8>::new(4);
+        let area = |x, y, w, h| {
+            AreaBuilder::default()
+                .anchor((x, y).into())
+                .dimensions((w, h))
+                .build()
+                .unwrap()
+        };
+        debug_assert!(!qt.intersects_any(area(0, 0, 16, 16)));
+
+        // A region straddling the center of the tree is held at the root.
+        let handle = qt.insert(area(6, 6, 4, 4), 1).unwrap();
+        debug_assert!(qt.intersects_any(area(0, 0, 16, 16)));
+        debug_assert!(qt.intersects_any(area(9, 9, 1, 1)));
+        debug_assert!(qt.intersects_any(area(0, 0, 7, 7)));
+        debug_assert!(!qt.intersects_any(area(0, 0, 6, 6)));
+        debug_assert!(!qt.intersects_any(area(10, 0, 6, 16)));
+
+        qt.delete_by_handle(handle);
+        debug_assert!(!qt.intersects_any(area(0, 0, 16, 16)));
+    }
+    #[test]
+    fn get_pt() {
+        // A 4x4 grid of 4x4 tiles, each holding its index.
+        let mut qt = Quadtree::<u32, u32>::new(4);
+        for i in 0..4 {
+            for j in 0..4 {
+                qt.insert(
+                    AreaBuilder::default()
+                        .anchor((i * 4, j * 4).into())
+                        .dimensions((4, 4))
+                        .build()
+                        .unwrap(),
+                    i * 4 + j,
+                );
+            }
+        }
+        // Replace the four tiles in the middle with one straddling the center of the tree.
+        for pt in [(4, 4), (8, 4), (4, 8), (8, 8)].iter() {
+            qt.delete_by_handle(qt.get_pt((*pt).into()).unwrap().handle());
+        }
+        qt.insert(
+            AreaBuilder::default()
+                .anchor((6, 6).into())
+                .dimensions((4, 4))
+                .build()
+                .unwrap(),
+            99,
+        );
+
+        for x in 0..16 {
+            for y in 0..16 {
+                let pt = (x, y).into();
+                let expected = qt.query_pt(pt).next().map(|e| e.handle());
+                debug_assert_eq!(qt.get_pt(pt).map(|e| e.handle()), expected);
+            }
+        }
+        debug_assert_eq!(qt.get_pt((7, 6).into()).unwrap().value_ref(), &99);
+        debug_assert_eq!(qt.get_pt((9, 9).into()).unwrap().value_ref(), &99);
+        debug_assert!(qt.get_pt((4, 4).into()).is_none());
+        debug_assert!(qt.get_pt((16, 0).into()).is_none());
+    }
+
+    #[test]
+    fn get_all() {
+        let area = |x, y, w, h| {
+            AreaBuilder::default()
+                .anchor((x, y).into())
+                .dimensions((w, h))
+                .build()
+                .unwrap()
+        };
+        let mut tight = Quadtree::<u32, u32>::new(5);
+        let mut loose = Quadtree::<u32, u32>::new(5);
+        loose.set_looseness(2.0);
+        for qt in [&mut tight, &mut loose].iter_mut() {
+            for i in 0..60 {
+                qt.insert(area(i % 8 * 3, i % 5 * 4, 1 + i % 3, 2), i);
+            }
+            // Two more at the same region as the entry with value 1.
+            qt.insert(area(3, 4, 2, 2), 100);
+            qt.insert(area(3, 4, 2, 2), 101);
+        }
+
+        for qt in [&tight, &loose].iter() {
+            for x in 0..24 {
+                for y in 0..20 {
+                    for (w, h) in [(1, 2), (2, 2), (3, 2), (1, 1)].iter() {
+                        let region = area(x, y, *w, *h);
+                        let mut expected: Vec<u64> = qt
+                            .iter()
+                            .filter(|e| e.area() == region)
+                            .map(|e| e.handle())
+                            .collect();
+                        expected.sort_unstable();
+                        let found: Vec<u64> =
+                            qt.get_all(region).iter().map(|e| e.handle()).collect();
+                        debug_assert_eq!(found, expected);
+                    }
+                }
+            }
+            let values: Vec<u32> = qt
+                .get_all(area(3, 4, 2, 2))
+                .iter()
+                .map(|e| *e.value_ref())
+                .collect();
+            debug_assert_eq!(values, vec![1, 100, 101]);
+            debug_assert!(qt.get_all(area(30, 30, 4, 4)).is_empty());
+        }
+    }
+
+    #[test]
+    fn max_items_per_node() {
+        // The same entries in a tree which subdivides as far as it can, and in one whose leaves
+        // hold up to three entries.
+        let mut deep = Quadtree::<u32, u32>::new(6);
+        let mut shallow = Quadtree::<u32, u32>::new(6);
+        shallow.set_max_items_per_node(Some(3));
+        for i in 0..200 {
+            let region = AreaBuilder::default()
+                .anchor(((i * 37) % 60, (i * 11) % 62).into())
+                .dimensions((1 + i % 4, 1 + i % 2))
+                .build()
+                .unwrap();
+            deep.insert(region, i);
+            shallow.insert(region, i);
+        }
+        // Deleting from a subdivided leaf leaves the rest of it intact.
+        for handle in (0..200).step_by(7) {
+            debug_assert_eq!(
+                deep.delete_by_handle(handle).map(|e| e.area()),
+                shallow.delete_by_handle(handle).map(|e| e.area())
+            );
+        }
+
+        for x in (0..64).step_by(5) {
+            for y in (0..64).step_by(3) {
+                let region = AreaBuilder::default()
+                    .anchor((x, y).into())
+                    .dimensions((1 + x % 9, 1 + y % 7))
+                    .build()
+                    .unwrap();
+                debug_assert!(unordered_elements_are(
+                    shallow.query(region).map(|e| e.handle()),
+                    deep.query(region).map(|e| e.handle()),
+                ));
+                debug_assert!(unordered_elements_are(
+                    shallow.query_strict(region).map(|e| e.handle()),
+                    deep.query_strict(region).map(|e| e.handle()),
+                ));
+                debug_assert_eq!(
+                    shallow.count_in_region(region),
+                    deep.count_in_region(region)
+                );
+                debug_assert_eq!(
+                    shallow.get_pt((x, y).into()).is_some(),
+                    deep.get_pt((x, y).into()).is_some()
+                );
+            }
+        }
+
+        // Lifting the limit rebuilds the tree without losing anything.
+        shallow.set_max_items_per_node(None);
+        debug_assert_eq!(format!("{:?}", shallow.split_policy()), "DepthLimited");
+        debug_assert!(unordered_elements_are(
+            shallow.iter().map(|e| (e.handle(), e.area())),
+            deep.iter().map(|e| (e.handle(), e.area())),
+        ));
+    }
+
+    #[test]
+    fn split_policies() {
+        let mut deep = Quadtree::<u32, u32>::new(5);
+        let mut flat = Quadtree::<u32, u32>::new(5);
+        flat.set_split_policy(NeverSplit);
+        let mut hybrid = Quadtree::<u32, u32>::new(5);
+        hybrid.set_split_policy(Hybrid {
+            max_items_per_node: 2,
+            max_level: 2,
+        });
+        for i in 0..100 {
+            let region = AreaBuilder::default()
+                .anchor(((i * 13) % 30, (i * 7) % 31).into())
+                .dimensions((1 + i % 3, 1 + i % 2))
+                .build()
+                .unwrap();
+            for qt in [&mut deep, &mut flat, &mut hybrid].iter_mut() {
+                qt.insert(region, i);
+            }
+        }
+
+        for x in (0..32).step_by(3) {
+            for y in (0..32).step_by(4) {
+                let region = AreaBuilder::default()
+                    .anchor((x, y).into())
+                    .dimensions((1 + x % 5, 1 + y % 6))
+                    .build()
+                    .unwrap();
+                for qt in [&flat, &hybrid].iter() {
+                    debug_assert!(unordered_elements_are(
+                        qt.query(region).map(|e| e.handle()),
+                        deep.query(region).map(|e| e.handle()),
+                    ));
+                    debug_assert!(unordered_elements_are(
+                        qt.query_strict(region).map(|e| e.handle()),
+                        deep.query_strict(region).map(|e| e.handle()),
+                    ));
+                    debug_assert_eq!(qt.count_in_region(region), deep.count_in_region(region));
+                }
+            }
+        }
+    }
+
+    // The squared distance from @pt to the nearest cell of @area.
+    fn distance_sq(area: quadtree_rs::area::Area<u16>, (x, y): (u16, u16)) -> u64 {
+        let gap = |lo: u16, len: u16, v: u16| {
+            let (lo, hi, v) = (
+                u64::from(lo),
+                u64::from(lo) + u64::from(len) - 1,
+                u64::from(v),
+            );
+            lo.saturating_sub(v) + v.saturating_sub(hi)
+        };
+        let dx = gap(area.anchor().x(), area.width(), x);
+        let dy = gap(area.anchor().y(), area.height(), y);
+        dx * dx + dy * dy
+    }
+
+    #[test]
+    fn loose() {
+        let mut tight = Quadtree::<u16, u32>::new_with_bounds((0, 0).into(), 250, 250);
+        let mut loose = Quadtree::<u16, u32>::new_with_bounds((0, 0).into(), 250, 250);
+        loose.set_looseness(2.0);
+        let mut loose_capped = Quadtree::<u16, u32>::new_with_bounds((0, 0).into(), 250, 250);
+        loose_capped.set_looseness(1.5);
+        loose_capped.set_max_items_per_node(Some(4));
+        debug_assert_eq!(loose_capped.looseness(), 1.5);
+
+        for i in 0..300_u32 {
+            // Small regions right up against the edges of the tree, and a few large ones.
+            let (w, h) = if i % 50 == 0 {
+                (90, 120)
+            } else {
+                (1 + i % 5, 1 + i % 3)
+            };
+            let region = AreaBuilder::default()
+                .anchor((((i * 41) % 250) as u16, ((i * 23) % 250) as u16).into())
+                .dimensions((w as u16, h as u16))
+                .build()
+                .unwrap();
+            for qt in [&mut tight, &mut loose, &mut loose_capped].iter_mut() {
+                qt.insert(region, i);
+            }
+        }
+        for handle in (0..300).step_by(9) {
+            for qt in [&mut tight, &mut loose, &mut loose_capped].iter_mut() {
+                qt.delete_by_handle(handle);
+            }
+        }
+
+        for x in (0..250).step_by(13) {
+            for y in (0..250).step_by(17) {
+                let region = AreaBuilder::default()
+                    .anchor((x, y).into())
+                    .dimensions((1 + x % 11, 1 + y % 7))
+                    .build()
+                    .unwrap();
+                for qt in [&loose, &loose_capped].iter() {
+                    debug_assert!(unordered_elements_are(
+                        qt.query(region).map(|e| e.handle()),
+                        tight.query(region).map(|e| e.handle()),
+                    ));
+                    debug_assert!(unordered_elements_are(
+                        qt.query_strict(region).map(|e| e.handle()),
+                        tight.query_strict(region).map(|e| e.handle()),
+                    ));
+                    debug_assert!(unordered_elements_are(
+                        qt.query_containing(region).map(|e| e.handle()),
+                        tight.query_containing(region).map(|e| e.handle()),
+                    ));
+                    debug_assert_eq!(qt.count_in_region(region), tight.count_in_region(region));
+                    debug_assert_eq!(qt.intersects_any(region), tight.intersects_any(region));
+                    debug_assert_eq!(
+                        qt.get_pt((x, y).into()).is_some(),
+                        tight.get_pt((x, y).into()).is_some()
+                    );
+                    debug_assert_eq!(
+                        qt.knn((x, y).into(), 3)
+                            .iter()
+                            .map(|e| distance_sq(e.area(), (x, y)))
+                            .collect::<Vec<_>>(),
+                        tight
+                            .knn((x, y).into(), 3)
+                            .iter()
+                            .map(|e| distance_sq(e.area(), (x, y)))
+                            .collect::<Vec<_>>(),
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn relocate() {
+        let mk_region = |i: u32, step: u32| {
+            AreaBuilder::default()
+                .anchor((((i * 41 + step * 3) % 60), ((i * 23 + step * 5) % 61)).into())
+                .dimensions((1 + (i + step) % 3, 1 + i % 2))
+                .build()
+                .unwrap()
+        };
+        let mut tight = Quadtree::<u32, u32>::new(6);
+        let mut loose = Quadtree::<u32, u32>::new(6);
+        loose.set_looseness(2.0);
+        let mut capped = Quadtree::<u32, u32>::new(6);
+        capped.set_max_items_per_node(Some(3));
+        for i in 0..100 {
+            for qt in [&mut tight, &mut loose, &mut capped].iter_mut() {
+                qt.insert(mk_region(i, 0), i);
+            }
+        }
+
+        for step in 1..10 {
+            // A tree built from scratch at the same positions.
+            let mut expected = Quadtree::<u32, u32>::new(6);
+            for i in 0..100 {
+                expected.insert(mk_region(i, step), i);
+            }
+            for qt in [&mut tight, &mut loose, &mut capped].iter_mut() {
+                for i in 0..100 {
+                    debug_assert!(qt.relocate(u64::from(i), mk_region(i, step)));
+                }
+                debug_assert_eq!(qt.len(), 100);
+            }
+
+            for x in (0..64).step_by(7) {
+                for y in (0..64).step_by(9) {
+                    let region = AreaBuilder::default()
+                        .anchor((x, y).into())
+                        .dimensions((1 + x % 6, 1 + y % 5))
+                        .build()
+                        .unwrap();
+                    for qt in [&tight, &loose, &capped].iter() {
+                        debug_assert!(unordered_elements_are(
+                            qt.query(region).map(|e| *e.value_ref()),
+                            expected.query(region).map(|e| *e.value_ref()),
+                        ));
+                        debug_assert_eq!(
+                            qt.count_in_region(region),
+                            expected.count_in_region(region)
+                        );
+                    }
+                }
+            }
+        }
+
+        // Relocation fails, changing nothing, for unknown handles and regions out of bounds.
+        debug_assert!(!tight.relocate(100, mk_region(0, 0)));
+        let outside = AreaBuilder::default()
+            .anchor((60, 60).into())
+            .dimensions((5, 5))
+            .build()
+            .unwrap();
+        let before = tight.get(0).unwrap().area();
+        debug_assert!(!tight.relocate(0, outside));
+        debug_assert_eq!(tight.get(0).unwrap().area(), before);
+        debug_assert_eq!(tight.count_in_region(before), tight.query(before).count());
+    }
+
+    #[test]
+    fn update_many() {
+        let mk_region = |i: u32, step: u32| {
+            AreaBuilder::default()
+                .anchor((((i * 41 + step * 3) % 60), ((i * 23 + step * 5) % 61)).into())
+                .dimensions((1 + (i + step) % 3, 1 + i % 2))
+                .build()
+                .unwrap()
+        };
+        let mut tight = Quadtree::<u32, u32>::new(6);
+        let mut loose = Quadtree::<u32, u32>::new(6);
+        loose.set_looseness(2.0);
+        let mut capped = Quadtree::<u32, u32>::new(6);
+        capped.set_max_items_per_node(Some(3));
+        for i in 0..100 {
+            for qt in [&mut tight, &mut loose, &mut capped].iter_mut() {
+                qt.insert(mk_region(i, 0), i);
+            }
+        }
+
+        let outside = AreaBuilder::default()
+            .anchor((63, 63).into())
+            .dimensions((2, 2))
+            .build()
+            .unwrap();
+        for step in 1..10 {
+            let mut expected = Quadtree::<u32, u32>::new(6);
+            for i in 0..100 {
+                expected.insert(mk_region(i, step), i);
+            }
+            for qt in [&mut tight, &mut loose, &mut capped].iter_mut() {
+                // Every entry moves, each of them twice, alongside a few moves which are skipped.
+                // Only the distinct entries which moved are counted.
+                let moves = (0..100)
+                    .map(|i| (u64::from(i), mk_region(i, step - 1)))
+                    .chain((0..100).map(|i| (u64::from(i), mk_region(i, step))))
+                    .chain(vec![(1000, mk_region(0, 0)), (0, outside)]);
+                debug_assert_eq!(qt.update_many(moves), 100);
+                debug_assert_eq!(qt.len(), 100);
+            }
+
+            for x in (0..64).step_by(7) {
+                for y in (0..64).step_by(9) {
+                    let region = AreaBuilder::default()
+                        .anchor((x, y).into())
+                        .dimensions((1 + x % 6, 1 + y % 5))
+                        .build()
+                        .unwrap();
+                    for qt in [&tight, &loose, &capped].iter() {
+                        debug_assert!(unordered_elements_are(
+                            qt.query(region).map(|e| *e.value_ref()),
+                            expected.query(region).map(|e| *e.value_ref()),
+                        ));
+                        debug_assert_eq!(
+                            qt.count_in_region(region),
+                            expected.count_in_region(region)
+                        );
+                    }
+                }
+            }
+        }
+
+        // Pruning leaves the same shape of tree as building it from scratch would.
+        let mut moved = Quadtree::<u32, u32>::new(6);
+        let handle = moved.insert(mk_region(0, 0), 0).unwrap();
+        moved.update_many(vec![(handle, mk_region(0, 1))]);
+        let mut fresh = Quadtree::<u32, u32>::new(6);
+        fresh.insert(mk_region(0, 1), 0);
+        debug_assert_eq!(format!("{:?}", moved), format!("{:?}", fresh));
+
+        // A handle given several times is moved once, to the last region given for it.
+        let mut repeated = Quadtree::<u32, u32>::new(6);
+        let handle = repeated.insert(mk_region(0, 0), 0).unwrap();
+        let other = repeated.insert(mk_region(1, 0), 1).unwrap();
+        let moves = vec![
+            (handle, mk_region(0, 1)),
+            (other, mk_region(1, 1)),
+            (handle, mk_region(0, 2)),
+            (handle, mk_region(0, 3)),
+        ];
+        debug_assert_eq!(repeated.update_many(moves), 2);
+        debug_assert_eq!(repeated.get(handle).unwrap().area(), mk_region(0, 3));
+        debug_assert_eq!(repeated.query(mk_region(0, 3)).count(), 1);
+        debug_assert_eq!(repeated.query(mk_region(0, 1)).count(), 0);
+    }
+
+    #[test]
+    fn view() {
+        let region = |x: u32, y: u32, w: u32, h: u32| {
+            AreaBuilder::default()
+                .anchor((x, y).into())
+                .dimensions((w, h))
+                .build()
+                .unwrap()
+        };
+        let mut qt = Quadtree::<u32, u32>::new(5);
+        for i in 0..120 {
+            qt.insert(region(i * 7 % 29, i * 11 % 30, 1 + i % 4, 1 + i % 3), i);
+        }
+
+        let view = qt.view(region(5, 9, 12, 10));
+        let bounds = view.region();
+        let in_view = |r| bounds.intersects(r);
+        debug_assert_eq!(view.len(), qt.regions().filter(|r| in_view(*r)).count());
+        debug_assert!(!view.is_empty());
+        debug_assert!(unordered_elements_are(
+            view.regions(),
+            qt.regions().filter(|r| in_view(*r)).collect::<Vec<_>>(),
+        ));
+
+        for (x, y, w, h) in [(0, 0, 32, 32), (10, 12, 3, 3), (0, 0, 6, 10), (20, 0, 4, 4)] {
+            let area = region(x, y, w, h);
+            let expected: Vec<_> = qt
+                .regions()
+                .filter(|r| in_view(*r) && r.intersects(area))
+                .collect();
+            debug_assert!(unordered_elements_are(
+                view.query(area).map(|e| e.area()),
+                expected.clone(),
+            ));
+            debug_assert_eq!(view.count_in_region(area), expected.len());
+            debug_assert_eq!(view.intersects_any(area), !expected.is_empty());
+            debug_assert!(unordered_elements_are(
+                view.query_strict(area).map(|e| e.area()),
+                expected
+                    .into_iter()
+                    .filter(|r| area.contains(*r))
+                    .collect::<Vec<_>>(),
+            ));
+        }
+
+        for (handle, r, _) in qt.iter_with_handles() {
+            debug_assert_eq!(view.get(handle).is_some(), in_view(r));
+        }
+        debug_assert_eq!(view.query_pt((0, 0).into()).count(), 0);
+    }
+
+    #[test]
+    fn collisions() {
+        let region = |x: u32, y: u32, w: u32, h: u32| {
+            AreaBuilder::default()
+                .anchor((x, y).into())
+                .dimensions((w, h))
+                .build()
+                .unwrap()
+        };
+        let mut deep = Quadtree::<u32, u32>::new(6);
+        let mut capped = Quadtree::<u32, u32>::new(6);
+        capped.set_max_items_per_node(Some(3));
+        let mut loose = Quadtree::<u32, u32>::new(6);
+        loose.set_looseness(2.0);
+        for i in 0..150 {
+            // Mostly small regions, and a few which straddle the middle of the tree.
+            let r = if i % 40 == 0 {
+                region(20 + i % 7, 25, 30, 12)
+            } else {
+                region(i * 13 % 61, i * 29 % 59, 1 + i % 4, 1 + i % 3)
+            };
+            for qt in [&mut deep, &mut capped, &mut loose].iter_mut() {
+                qt.insert(r, i);
+            }
+        }
+        for handle in (0..150).step_by(11) {
+            for qt in [&mut deep, &mut capped, &mut loose].iter_mut() {
+                qt.delete_by_handle(handle);
+            }
+        }
+
+        let entries: Vec<_> = deep.iter_with_handles().collect();
+        let mut expected = vec![];
+        for (i, (a, ra, _)) in entries.iter().enumerate() {
+            for (b, rb, _) in &entries[i + 1..] {
+                if ra.intersects(*rb) {
+                    expected.push((*a.min(b), *a.max(b)));
+                }
+            }
+        }
+        debug_assert!(!expected.is_empty());
+
+        for qt in [&deep, &capped, &loose].iter() {
+            debug_assert!(unordered_elements_are(
+                qt.collisions().map(|(a, b)| {
+                    debug_assert!(a.area().intersects(b.area()));
+                    (a.handle().min(b.handle()), a.handle().max(b.handle()))
+                }),
+                expected.clone(),
+            ));
+        }
+        debug_assert_eq!(Quadtree::<u32, u32>::new(2).collisions().count(), 0);
+    }
 }
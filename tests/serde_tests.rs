@@ -0,0 +1,149 @@
+// Copyright 2019 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![cfg(feature = "serde")]
+
+mod util; // For unordered_elements_are.
+
+// For testing Serialize and Deserialize.
+mod serde_tests {
+    use {
+        crate::util::unordered_elements_are,
+        quadtree_rs::{area::AreaBuilder, Quadtree},
+    };
+
+    fn mk_quadtree_for_serde_tests() -> Quadtree<i32, String> {
+        let mut qt = Quadtree::<i32, String>::new_with_anchor((-8, -8).into(), 4);
+        qt.insert(
+            AreaBuilder::default()
+                .anchor((-8, -8).into())
+                .dimensions((5, 3))
+                .build()
+                .unwrap(),
+            "a".to_string(),
+        );
+        let doomed = qt.insert_pt((0, 0).into(), "b".to_string()).unwrap();
+        qt.insert(
+            AreaBuilder::default()
+                .anchor((-1, -1).into())
+                .dimensions((4, 9))
+                .build()
+                .unwrap(),
+            "c".to_string(),
+        );
+        qt.delete_by_handle(doomed);
+        qt
+    }
+
+    #[test]
+    fn round_trip() {
+        let qt = mk_quadtree_for_serde_tests();
+        let json = serde_json::to_string(&qt).unwrap();
+        let mut qt2: Quadtree<i32, String> = serde_json::from_str(&json).unwrap();
+
+        debug_assert_eq!(qt2.anchor(), qt.anchor());
+        debug_assert_eq!(qt2.depth(), qt.depth());
+        debug_assert!(unordered_elements_are(
+            qt2.iter()
+                .map(|e| (e.handle(), e.area(), e.value_ref().clone())),
+            qt.iter()
+                .map(|e| (e.handle(), e.area(), e.value_ref().clone())),
+        ));
+
+        // The rebuilt node structure answers queries the same way.
+        let region = AreaBuilder::default()
+            .anchor((-4, -4).into())
+            .dimensions((4, 4))
+            .build()
+            .unwrap();
+        debug_assert!(unordered_elements_are(
+            qt2.query(region).map(|e| e.value_ref()),
+            qt.query(region).map(|e| e.value_ref()),
+        ));
+
+        // And handles are never re-issued, even those of deleted entries.
+        let handle = qt2.insert_pt((1, 1).into(), "d".to_string()).unwrap();
+        debug_assert_eq!(handle, 3);
+    }
+
+    #[test]
+    fn serialization_is_stable() {
+        let qt = mk_quadtree_for_serde_tests();
+        let json = serde_json::to_string(&qt).unwrap();
+        let qt2: Quadtree<i32, String> = serde_json::from_str(&json).unwrap();
+        debug_assert_eq!(serde_json::to_string(&qt2).unwrap(), json);
+    }
+
+    #[test]
+    fn bounds_round_trip() {
+        let mut qt = Quadtree::<u8, u8>::new_with_bounds((0, 0).into(), 6, 3);
+        qt.insert_pt((5, 2).into(), 1);
+        let json = serde_json::to_string(&qt).unwrap();
+        let qt2: Quadtree<u8, u8> = serde_json::from_str(&json).unwrap();
+        debug_assert_eq!(qt2, qt);
+
+        // Unclipped quadtrees leave the bounds out altogether.
+        let json = serde_json::to_string(&Quadtree::<u8, u8>::new(2)).unwrap();
+        debug_assert!(!json.contains("dimensions"));
+    }
+
+    #[test]
+    fn rejects_bounds_outside_root() {
+        let json = r#"{"anchor":{"x":0,"y":0},"depth":2,"dimensions":[5,3],
+            "next_handle":0,"entries":[]}"#;
+        debug_assert!(serde_json::from_str::<Quadtree<u8, u8>>(json).is_err());
+
+        let json = r#"{"anchor":{"x":0,"y":0},"depth":2,"dimensions":[3,3],
+            "next_handle":1,"entries":[{"region":[[3,3],[1,1]],"value":7,"handle":0}]}"#;
+        debug_assert!(serde_json::from_str::<Quadtree<u8, u8>>(json).is_err());
+    }
+
+    #[test]
+    fn rejects_entries_out_of_bounds() {
+        let json = r#"{"anchor":{"x":0,"y":0},"depth":2,"next_handle":1,
+            "entries":[{"region":[[3,3],[2,2]],"value":7,"handle":0}]}"#;
+        debug_assert!(serde_json::from_str::<Quadtree<u8, u8>>(json).is_err());
+    }
+
+    #[test]
+    fn rejects_invalid_regions() {
+        let json = r#"{"anchor":{"x":0,"y":0},"depth":2,"next_handle":1,
+            "entries":[{"region":[[0,0],[0,2]],"value":7,"handle":0}]}"#;
+        debug_assert!(serde_json::from_str::<Quadtree<u8, u8>>(json).is_err());
+    }
+
+    #[test]
+    fn rejects_duplicate_and_unissued_handles() {
+        let json = r#"{"anchor":{"x":0,"y":0},"depth":2,"next_handle":2,
+            "entries":[{"region":[[0,0],[1,1]],"value":7,"handle":1},
+                       {"region":[[1,1],[1,1]],"value":8,"handle":1}]}"#;
+        debug_assert!(serde_json::from_str::<Quadtree<u8, u8>>(json).is_err());
+
+        let json = r#"{"anchor":{"x":0,"y":0},"depth":2,"next_handle":1,
+            "entries":[{"region":[[0,0],[1,1]],"value":7,"handle":1}]}"#;
+        debug_assert!(serde_json::from_str::<Quadtree<u8, u8>>(json).is_err());
+    }
+
+    #[test]
+    fn rejects_oversized_depth() {
+        let json = r#"{"anchor":{"x":0,"y":0},"depth":8,"next_handle":0,"entries":[]}"#;
+        debug_assert!(serde_json::from_str::<Quadtree<u8, u8>>(json).is_err());
+
+        let json = r#"{"anchor":{"x":200,"y":0},"depth":7,"next_handle":0,"entries":[]}"#;
+        debug_assert!(serde_json::from_str::<Quadtree<u8, u8>>(json).is_err());
+
+        let json = r#"{"anchor":{"x":0,"y":0},"depth":7,"next_handle":0,"entries":[]}"#;
+        debug_assert!(serde_json::from_str::<Quadtree<u8, u8>>(json).is_ok());
+    }
+}
@@ -0,0 +1,178 @@
+// Copyright 2019 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+mod util; // For unordered_elements_are.
+
+// For testing .write_to(), .read_from().
+mod snapshot_tests {
+    use {
+        crate::util::unordered_elements_are,
+        quadtree_rs::{area::AreaBuilder, Quadtree},
+        std::io::ErrorKind,
+    };
+
+    fn mk_quadtree_for_snapshot_tests() -> Quadtree<i32, (String, Option<f64>)> {
+        let mut qt = Quadtree::new_with_anchor((-64, -64).into(), 7);
+        qt.insert(
+            AreaBuilder::default()
+                .anchor((-64, -64).into())
+                .dimensions((100, 3))
+                .build()
+                .unwrap(),
+            ("a".to_string(), Some(1.5)),
+        );
+        let doomed = qt
+            .insert_pt((0, 0).into(), ("b".to_string(), None))
+            .unwrap();
+        qt.insert(
+            AreaBuilder::default()
+                .anchor((-1, 17).into())
+                .dimensions((4, 9))
+                .build()
+                .unwrap(),
+            ("ç".to_string(), None),
+        );
+        qt.delete_by_handle(doomed);
+        qt
+    }
+
+    fn snapshot<U, V>(qt: &Quadtree<U, V>) -> Vec<u8>
+    where
+        U: num::PrimInt + Default,
+        V: quadtree_rs::snapshot::Record,
+    {
+        let mut buffer = vec![];
+        qt.write_to(&mut buffer).unwrap();
+        buffer
+    }
+
+    #[test]
+    fn round_trip() {
+        let qt = mk_quadtree_for_snapshot_tests();
+        let buffer = snapshot(&qt);
+        let mut qt2 = Quadtree::<i32, (String, Option<f64>)>::read_from(&buffer[..]).unwrap();
+
+        debug_assert_eq!(qt2.anchor(), qt.anchor());
+        debug_assert_eq!(qt2.depth(), qt.depth());
+        debug_assert!(unordered_elements_are(
+            qt2.iter()
+                .map(|e| (e.handle(), e.area(), e.value_ref().0.clone())),
+            qt.iter()
+                .map(|e| (e.handle(), e.area(), e.value_ref().0.clone())),
+        ));
+
+        // The encoding is stable...
+        debug_assert_eq!(snapshot(&qt2), buffer);
+
+        // ...and handles are never re-issued, even those of deleted entries.
+        debug_assert_eq!(
+            qt2.insert_pt((1, 1).into(), ("d".to_string(), None)),
+            Some(3)
+        );
+    }
+
+    #[test]
+    fn empty_round_trip() {
+        let qt = Quadtree::<u8, ()>::new(3);
+        let buffer = snapshot(&qt);
+        let qt2 = Quadtree::<u8, ()>::read_from(&buffer[..]).unwrap();
+        debug_assert!(qt2.is_empty());
+        debug_assert_eq!(qt2.depth(), 3);
+    }
+
+    #[test]
+    fn bounds_round_trip() {
+        let mut qt = Quadtree::<u16, u8>::new_with_bounds((3, 4).into(), 640, 480);
+        qt.insert_pt((642, 483).into(), 1);
+        let buffer = snapshot(&qt);
+        let qt2 = Quadtree::<u16, u8>::read_from(&buffer[..]).unwrap();
+        debug_assert_eq!(qt2, qt);
+        debug_assert_eq!((qt2.width(), qt2.height()), (640, 480));
+    }
+
+    #[test]
+    fn reads_version_1() {
+        let mut qt = Quadtree::<u8, u8>::new(3);
+        qt.insert_pt((1, 2).into(), 3);
+        let mut buffer = snapshot(&qt);
+        // Version 1 had no bounds, which come straight after the depth and anchor.
+        buffer[4] = 1;
+        debug_assert_eq!(buffer.remove(8), 0);
+        debug_assert_eq!(Quadtree::<u8, u8>::read_from(&buffer[..]).unwrap(), qt);
+    }
+
+    #[test]
+    fn is_compact() {
+        let mut qt = Quadtree::<u64, u8>::new(10);
+        for i in 0..100 {
+            qt.insert_pt((i, i).into(), i as u8);
+        }
+        // Header, plus a handful of bytes per entry rather than 8 per coordinate.
+        debug_assert!(snapshot(&qt).len() < 100 * 8);
+    }
+
+    #[test]
+    fn rejects_bad_header() {
+        let qt = Quadtree::<u8, u8>::new(3);
+        let mut buffer = snapshot(&qt);
+
+        buffer[4] = 99;
+        let err = Quadtree::<u8, u8>::read_from(&buffer[..]).unwrap_err();
+        debug_assert_eq!(err.kind(), ErrorKind::InvalidData);
+
+        buffer[0] = b'X';
+        let err = Quadtree::<u8, u8>::read_from(&buffer[..]).unwrap_err();
+        debug_assert_eq!(err.kind(), ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn rejects_truncated_input() {
+        let qt = mk_quadtree_for_snapshot_tests();
+        let buffer = snapshot(&qt);
+        for len in 0..buffer.len() {
+            debug_assert!(
+                Quadtree::<i32, (String, Option<f64>)>::read_from(&buffer[..len]).is_err()
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_coordinates_out_of_range() {
+        let mut qt = Quadtree::<i32, u8>::new_with_anchor((-8, -8).into(), 4);
+        qt.insert_pt((-3, 2).into(), 1);
+        let buffer = snapshot(&qt);
+        let err = Quadtree::<u32, u8>::read_from(&buffer[..]).unwrap_err();
+        debug_assert_eq!(err.kind(), ErrorKind::InvalidData);
+
+        // A depth too large for the coordinate type.
+        let qt = Quadtree::<u32, u8>::new(12);
+        let buffer = snapshot(&qt);
+        let err = Quadtree::<u8, u8>::read_from(&buffer[..]).unwrap_err();
+        debug_assert_eq!(err.kind(), ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn rejects_writing_coordinates_beyond_i128() {
+        let mut qt = Quadtree::<u128, u8>::new_with_anchor((1 << 127, 0).into(), 4);
+        qt.insert_pt(((1 << 127) + 1, 2).into(), 1);
+        let err = qt.write_to(&mut vec![]).unwrap_err();
+        debug_assert_eq!(err.kind(), ErrorKind::InvalidData);
+
+        // Everything below that still round-trips.
+        let mut qt = Quadtree::<u128, u8>::new_with_anchor((i128::MAX as u128 - 15, 0).into(), 4);
+        qt.insert_pt((i128::MAX as u128, 2).into(), 1);
+        let qt2 = Quadtree::<u128, u8>::read_from(&snapshot(&qt)[..]).unwrap();
+        debug_assert_eq!(qt2.iter().next().unwrap().anchor().x(), i128::MAX as u128);
+    }
+}
@@ -1,6 +1,6 @@
 use {
     num::{cast::FromPrimitive, PrimInt},
-    quadtree_rs::area::AreaBuilder,
+    quadtree_rs::area::{Area, AreaBuilder},
     std::{collections::HashSet, default::Default, fmt::Debug, hash::Hash, iter::FromIterator},
 };
 
@@ -21,6 +21,19 @@ where
     hs1 == hs2
 }
 
+// The region anchored at (@x, @y) with dimensions (@w, @h). Panics if either dimension is zero.
+#[allow(dead_code)]
+pub fn area<U>(x: U, y: U, w: U, h: U) -> Area<U>
+where
+    U: PrimInt + Default,
+{
+    AreaBuilder::default()
+        .anchor((x, y).into())
+        .dimensions((w, h))
+        .build()
+        .unwrap()
+}
+
 #[allow(dead_code)]
 pub fn print_quadtree<U, V>(qt: &quadtree_rs::Quadtree<U, V>)
 where
@@ -0,0 +1,165 @@
+// Copyright 2019 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+mod util; // For unordered_elements_are.
+
+// For testing .visit().
+mod visit_tests {
+    use {
+        crate::util::unordered_elements_are,
+        quadtree_rs::{
+            area::{Area, AreaBuilder},
+            visit::VisitControl,
+            Quadtree,
+        },
+    };
+
+    fn mk_quadtree(looseness: f64) -> Quadtree<u32, u32> {
+        let mut qt = Quadtree::<u32, u32>::new(6);
+        qt.set_looseness(looseness);
+        for i in 0..150 {
+            let region = AreaBuilder::default()
+                .anchor((i * 13 % 61, i * 29 % 59).into())
+                .dimensions((1 + i % 4, 1 + i % 3))
+                .build()
+                .unwrap();
+            qt.insert(region, i);
+        }
+        qt
+    }
+
+    #[test]
+    fn visit_everything() {
+        let qt = mk_quadtree(1.0);
+        let mut seen = vec![];
+        let mut cells = vec![];
+        qt.visit(|node, items| {
+            debug_assert!(!node.is_empty());
+            debug_assert!(items.len() <= node.len());
+            for (region, _) in items {
+                debug_assert!(node.bounds().contains(**region));
+            }
+            seen.extend(items.iter().map(|(region, value)| (**region, **value)));
+            cells.push((node.region(), node.depth()));
+            VisitControl::Continue
+        });
+        debug_assert_eq!(seen.len(), qt.len());
+        debug_assert!(unordered_elements_are(
+            seen,
+            qt.iter().map(|e| (e.area(), *e.value_ref())),
+        ));
+        // Every cell with something at or beneath it is visited, and only those.
+        let nodes: Vec<_> = qt.nodes().collect();
+        debug_assert!(unordered_elements_are(
+            cells,
+            nodes
+                .iter()
+                .filter(|(region, depth, _)| {
+                    nodes
+                        .iter()
+                        .any(|(r, d, count)| *count > 0 && d >= depth && region.contains(*r))
+                })
+                .map(|(region, depth, _)| (*region, *depth)),
+        ));
+    }
+
+    // By default every entry is held by the smallest cell which totally contains it, so a large
+    // region is held once, high up, rather than by every cell it covers.
+    #[test]
+    fn entries_are_held_by_the_smallest_containing_cell() {
+        let mut qt = mk_quadtree(1.0);
+        let big = AreaBuilder::default()
+            .anchor((3, 5).into())
+            .dimensions((58, 50))
+            .build()
+            .unwrap();
+        qt.insert(big, 1000);
+
+        let mut holders = vec![];
+        qt.visit(|node, items| {
+            let cell = node.region();
+            let half = cell.width() / 2;
+            let quadrants = [(0, 0), (half, 0), (0, half), (half, half)].map(|(dx, dy)| {
+                AreaBuilder::default()
+                    .anchor((cell.left_edge() + dx, cell.top_edge() + dy).into())
+                    .dimensions((half.max(1), half.max(1)))
+                    .build()
+                    .unwrap()
+            });
+            for (region, value) in items {
+                debug_assert!(half == 0 || !quadrants.iter().any(|q| q.contains(**region)));
+                if **value == 1000 {
+                    holders.push(cell);
+                }
+            }
+            VisitControl::Continue
+        });
+        let root = qt.nodes().next().unwrap().0;
+        debug_assert_eq!(holders, vec![root]);
+
+        debug_assert_eq!(qt.delete(big).filter(|e| *e.value_ref() == 1000).count(), 1);
+        debug_assert_eq!(qt.query(big).count(), 0);
+    }
+
+    // A query for a custom shape, here a rectangle, built out of visit().
+    #[test]
+    fn visit_as_query() {
+        for looseness in [1.0, 2.0] {
+            let qt = mk_quadtree(looseness);
+            let rect = AreaBuilder::default()
+                .anchor((10, 20).into())
+                .dimensions((30, 25))
+                .build()
+                .unwrap();
+            let mut found: Vec<Area<u32>> = vec![];
+            let mut visited = 0;
+            qt.visit(|node, items| {
+                visited += 1;
+                if !node.bounds().intersects(rect) {
+                    return VisitControl::Skip;
+                }
+                found.extend(
+                    items
+                        .iter()
+                        .filter(|(region, _)| region.intersects(rect))
+                        .map(|(region, _)| **region),
+                );
+                VisitControl::Continue
+            });
+            debug_assert!(unordered_elements_are(
+                found,
+                qt.query(rect).map(|e| e.area()),
+            ));
+            debug_assert!(visited < qt.nodes().count());
+        }
+    }
+
+    #[test]
+    fn visit_stop() {
+        let qt = mk_quadtree(1.0);
+        let mut visited = 0;
+        qt.visit(|_, _| {
+            visited += 1;
+            if visited == 3 {
+                VisitControl::Stop
+            } else {
+                VisitControl::Continue
+            }
+        });
+        debug_assert_eq!(visited, 3);
+
+        let empty = Quadtree::<u32, u32>::new(4);
+        empty.visit(|_, _| panic!("There's nothing to visit."));
+    }
+}
@@ -0,0 +1,138 @@
+// Copyright 2019 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![cfg(feature = "wkt")]
+
+mod util; // For area.
+
+// For testing Area::to_wkt(), Area::from_wkt(), .read_wkt() and .write_wkt().
+mod wkt_tests {
+    use {
+        crate::util::area,
+        quadtree_rs::{area::Area, Quadtree},
+        std::io,
+    };
+
+    #[test]
+    fn area_round_trip() {
+        for region in [
+            area(0, 0, 1, 1),
+            area(-5, 3, 1, 1),
+            area(-5, 3, 1, 2),
+            area(7, -9, 12, 1),
+            area(i32::MAX - 1, i32::MAX - 2, 1, 2),
+        ] {
+            debug_assert_eq!(Area::from_wkt(&region.to_wkt()), Ok(region));
+        }
+        debug_assert_eq!(
+            area(i32::MAX - 1, 0, 1, 2).to_wkt(),
+            "POLYGON ((2147483646 0, 2147483647 0, 2147483647 2, 2147483646 2, 2147483646 0))"
+        );
+    }
+
+    #[test]
+    fn from_wkt() {
+        let parses = |wkt: &str, expected: Area<i32>| {
+            debug_assert_eq!(Area::from_wkt(wkt), Ok(expected), "{}", wkt);
+        };
+        parses("POINT(1 2)", area(1, 2, 1, 1));
+        parses("  point ( -1   -2 )  ", area(-1, -2, 1, 1));
+        parses("POINT ZM (1 2 3 4)", area(1, 2, 1, 1));
+        parses("POINT (1.0 2.0)", area(1, 2, 1, 1));
+        parses("srid=3857; POINT (1 2)", area(1, 2, 1, 1));
+        parses("POLYGON ((0 0, 4 0, 4 3, 0 3, 0 0))", area(0, 0, 4, 3));
+        // With a hole, which lies within the outer ring.
+        parses(
+            "POLYGON ((0 0, 9 0, 9 9, 0 9, 0 0), (2 2, 3 2, 3 3, 2 2))",
+            area(0, 0, 9, 9),
+        );
+        parses(
+            "POLYGON Z ((0 0 1, 3 5 1, -2 4 1, 0 0 1))",
+            area(-2, 0, 5, 5),
+        );
+
+        for wkt in [
+            "",
+            "POINT",
+            "POINT EMPTY",
+            "POINT (1)",
+            "POINT (1 2 3 4 5)",
+            "POINT (1 2, 3 4)",
+            "POINT ((1 2))",
+            "POINT (1 2",
+            "POINT (1 2))",
+            "POINT (1 2) junk",
+            "POINT (1.5 2)",
+            "POINT (x 2)",
+            "POINT (1 3000000000)",
+            "POINT Q (1 2)",
+            "POLYGON (0 0, 1 0, 1 1, 0 0)",
+            "POLYGON ((0 0, 5 0, 0 0))",
+            "SRID=4326",
+            "MULTIPOINT ((1 2))",
+        ] {
+            debug_assert!(Area::<i32>::from_wkt(wkt).is_err(), "{}", wkt);
+        }
+        // Every corner fits in an i8, but the width doesn't.
+        debug_assert!(
+            Area::<i8>::from_wkt("POLYGON ((-100 0, 100 0, 100 5, -100 5, -100 0))").is_err()
+        );
+    }
+
+    #[test]
+    fn read_and_write() {
+        let mut qt = Quadtree::<i32, u32>::new_with_anchor((-20, -20).into(), 6);
+        for i in 0..40 {
+            qt.insert(
+                area(i * 7 % 40 - 20, i * 11 % 37 - 20, 1 + i % 5, 1 + i % 3),
+                i as u32,
+            );
+        }
+
+        let mut out: Vec<u8> = vec![];
+        qt.write_wkt(&mut out).unwrap();
+        let text = String::from_utf8(out).unwrap();
+        debug_assert_eq!(text.lines().count(), 40);
+        debug_assert_eq!(text.lines().next(), Some("POINT (-20 -20)\t0"));
+
+        let qt2 = Quadtree::<i32, u32>::read_wkt(text.as_bytes()).unwrap();
+        debug_assert_eq!(qt2.len(), qt.len());
+        for handle in 0..40 {
+            let (a, b) = (qt.get(handle).unwrap(), qt2.get(handle).unwrap());
+            debug_assert_eq!(a.area(), b.area());
+            debug_assert_eq!(a.value_ref(), b.value_ref());
+        }
+
+        // Values are optional, and blank lines are skipped.
+        let qt = Quadtree::<i32, String>::read_wkt(&b"POINT (1 1)\n\n  \nPOINT (5 5)\tfive\n"[..])
+            .unwrap();
+        debug_assert_eq!(qt.len(), 2);
+        debug_assert_eq!(qt.get(0).unwrap().value_ref(), "");
+        debug_assert_eq!(qt.get(1).unwrap().value_ref(), "five");
+    }
+
+    #[test]
+    fn read_rejects_bad_lines() {
+        let err =
+            Quadtree::<i32, u32>::read_wkt(&b"POINT (1 1)\t1\nPOINT (2 x)\t2\n"[..]).unwrap_err();
+        debug_assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        debug_assert!(err.to_string().starts_with("Line 2:"));
+
+        let err = Quadtree::<i32, u32>::read_wkt(&b"POINT (1 1)\tone\n"[..]).unwrap_err();
+        debug_assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+        let empty = Quadtree::<i32, u32>::read_wkt(&b""[..]).unwrap();
+        debug_assert!(empty.is_empty());
+    }
+}